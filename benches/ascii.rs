@@ -0,0 +1,46 @@
+//! Reads a large synthetic ascii mesh.
+//!
+//! Run with `cargo bench --bench ascii`, add `--features parallel` or `--features simd`
+//! to compare against the optimized paths.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::DefaultElement;
+use std::fmt::Write;
+use std::time::Instant;
+
+const VERTICES: u32 = 200_000;
+const FACES: u32 = 400_000;
+
+fn ascii_mesh() -> String {
+    let mut txt = format!(
+        "ply\nformat ascii 1.0\n\
+        element vertex {}\nproperty float x\nproperty float y\nproperty float z\nproperty uchar red\n\
+        element face {}\nproperty list uchar int vertex_indices\nend_header\n",
+        VERTICES, FACES
+    );
+    for i in 0..VERTICES {
+        writeln!(txt, "{} {} {} {}", i as f32 * 0.25, -0.5, i, i % 256).unwrap();
+    }
+    for i in 0..FACES {
+        let v = i % (VERTICES - 2);
+        writeln!(txt, "3 {} {} {}", v, v + 1, v + 2).unwrap();
+    }
+    txt
+}
+
+fn main() {
+    let txt = ascii_mesh();
+    let p = Parser::<DefaultElement>::new();
+    let start = Instant::now();
+    let ply = p.read_ply(&mut txt.as_bytes()).unwrap();
+    println!("read_ply: {:?}", start.elapsed());
+    assert_eq!(ply.payload["face"].len(), FACES as usize);
+
+    #[cfg(feature = "parallel")]
+    {
+        let start = Instant::now();
+        let parallel = p.read_ply_parallel(&mut txt.as_bytes()).unwrap();
+        println!("read_ply, parallel: {:?}", start.elapsed());
+        assert_eq!(parallel.payload, ply.payload);
+    }
+}
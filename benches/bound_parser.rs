@@ -0,0 +1,91 @@
+//! Reads many small files sharing one header with `Parser::read_ply()` and with a `BoundParser`.
+//!
+//! Run with `cargo bench --bench bound_parser`.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::{Property, PropertyAccess};
+use std::io::BufRead;
+use std::time::Instant;
+
+struct Vertex {
+    position: [f32; 3],
+    color: [u8; 3],
+}
+
+impl PropertyAccess for Vertex {
+    fn new() -> Self {
+        Vertex {
+            position: [0.0; 3],
+            color: [0; 3],
+        }
+    }
+    fn set_property(&mut self, key: &str, property: Property) {
+        match (key, property) {
+            ("x", Property::Float(v)) => self.position[0] = v,
+            ("y", Property::Float(v)) => self.position[1] = v,
+            ("z", Property::Float(v)) => self.position[2] = v,
+            ("red", Property::UChar(v)) => self.color[0] = v,
+            ("green", Property::UChar(v)) => self.color[1] = v,
+            ("blue", Property::UChar(v)) => self.color[2] = v,
+            _ => (),
+        }
+    }
+}
+
+const FRAMES: usize = 2_000;
+const VERTICES: u32 = 500;
+
+fn frame() -> Vec<u8> {
+    let mut bytes = format!(
+        "ply\nformat binary_little_endian 1.0\n\
+        element vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+        property uchar red\nproperty uchar green\nproperty uchar blue\nend_header\n",
+        VERTICES
+    )
+    .into_bytes();
+    for i in 0..VERTICES {
+        for v in [i as f32, 0.5, -1.0] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes.extend_from_slice(&[255, i as u8, 0]);
+    }
+    bytes
+}
+
+fn main() {
+    let bytes = frame();
+    let p = Parser::<Vertex>::new();
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        let ply = p.read_ply(&mut bytes.as_slice()).unwrap();
+        assert_eq!(ply.payload["vertex"][1].position, [1.0, 0.5, -1.0]);
+    }
+    println!("read_ply:             {:?}", start.elapsed());
+
+    let bound = p.bind(p.read_header(&mut bytes.as_slice()).unwrap());
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        let ply = bound.read_ply_checked(&mut bytes.as_slice()).unwrap();
+        assert_eq!(ply.payload["vertex"][1].position, [1.0, 0.5, -1.0]);
+    }
+    println!("read_ply_checked:     {:?}", start.elapsed());
+
+    let payload_start = {
+        let mut rest = bytes.as_slice();
+        let mut line = String::new();
+        while line != "end_header\n" {
+            line.clear();
+            rest.read_line(&mut line).unwrap();
+        }
+        bytes.len() - rest.len()
+    };
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        let payload = bound
+            .read_payload_trusted(&mut &bytes[payload_start..])
+            .unwrap();
+        assert_eq!(payload["vertex"][1].color, [255, 1, 0]);
+    }
+    println!("read_payload_trusted: {:?}", start.elapsed());
+}
@@ -0,0 +1,87 @@
+//! Reads headers with thousands of lines, like those of files with huge property lists,
+//! and looks up every property of a 200 property element by name.
+//!
+//! Run with `cargo bench --bench header`.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::DefaultElement;
+use std::time::Instant;
+
+const PROPERTIES: u32 = 5_000;
+const COMMENTS: u32 = 1_000;
+const RUNS: u32 = 20;
+const WIDE_PROPERTIES: u32 = 200;
+const WIDE_RUNS: u32 = 1_000;
+
+fn long_header() -> Vec<u8> {
+    let mut header = String::from("ply\nformat binary_little_endian 1.0\n");
+    for i in 0..COMMENTS {
+        header.push_str(&format!("comment line {} of the file description\n", i));
+    }
+    header.push_str("element vertex 1\n");
+    for i in 0..PROPERTIES {
+        header.push_str(&format!("property float f_rest_{}\n", i));
+    }
+    header.push_str("element face 0\nproperty list uchar int vertex_indices\nend_header\n");
+    header.into_bytes()
+}
+
+fn wide_header() -> Vec<u8> {
+    let mut header = String::from("ply\nformat binary_little_endian 1.0\nelement vertex 1\n");
+    for i in 0..WIDE_PROPERTIES {
+        header.push_str(&format!("property float f_{}\n", i));
+    }
+    header.push_str("end_header\n");
+    header.into_bytes()
+}
+
+fn lookups() {
+    let bytes = wide_header();
+    let p = Parser::<DefaultElement>::new();
+    let names: Vec<String> = (0..WIDE_PROPERTIES).map(|i| format!("f_{}", i)).collect();
+    let start = Instant::now();
+    for _ in 0..WIDE_RUNS {
+        p.read_header(&mut &bytes[..]).unwrap();
+    }
+    let parsed = start.elapsed();
+    let header = p.read_header(&mut &bytes[..]).unwrap();
+    let def = &header.elements[0];
+    let start = Instant::now();
+    for _ in 0..WIDE_RUNS {
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(def.property_index(name), Some(i));
+        }
+    }
+    let indexed = start.elapsed();
+    let start = Instant::now();
+    for _ in 0..WIDE_RUNS {
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(def.properties.iter().position(|p| &p.name == name), Some(i));
+        }
+    }
+    let scanned = start.elapsed();
+    println!(
+        "read_header ({} properties): {:?}, looking up all properties: {:?} indexed, {:?} scanned",
+        WIDE_PROPERTIES,
+        parsed / WIDE_RUNS,
+        indexed / WIDE_RUNS,
+        scanned / WIDE_RUNS
+    );
+}
+
+fn main() {
+    let bytes = long_header();
+    let p = Parser::<DefaultElement>::new();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        let header = p.read_header(&mut &bytes[..]).unwrap();
+        assert_eq!(header.elements[0].properties.len(), PROPERTIES as usize);
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "read_header ({} lines): {:?} per header",
+        PROPERTIES + COMMENTS + 7,
+        elapsed / RUNS
+    );
+    lookups();
+}
@@ -0,0 +1,61 @@
+//! Compares reading a face-heavy payload with and without pooled list buffers.
+//!
+//! Run with `cargo bench --bench list_pool`.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::{
+    ElementDef, Encoding, Header, Property, PropertyAccess, PropertyDef, PropertyType, ScalarType,
+};
+use std::time::Instant;
+
+struct Triangle {
+    indices: [u32; 3],
+}
+
+impl PropertyAccess for Triangle {
+    fn new() -> Self {
+        Triangle { indices: [0; 3] }
+    }
+    fn set_property(&mut self, key: &str, property: Property) {
+        self.set_property_ref(key, &property);
+    }
+    fn set_property_ref(&mut self, key: &str, property: &Property) {
+        if let ("vertex_indices", Property::ListUInt(v)) = (key, property) {
+            self.indices.copy_from_slice(&v[..3]);
+        }
+    }
+}
+
+const FACES: u32 = 1_000_000;
+
+fn main() {
+    let mut header = Header::new();
+    header.encoding = Encoding::BinaryLittleEndian;
+    let mut face = ElementDef::new("face");
+    face.count = FACES as u64;
+    face.properties.push(PropertyDef::new(
+        "vertex_indices",
+        PropertyType::List(ScalarType::UChar, ScalarType::UInt),
+    ));
+    header.elements.push(face.clone());
+    let mut bytes = Vec::new();
+    for i in 0..FACES {
+        bytes.push(3);
+        for v in [i, i + 1, i + 2] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    let p = Parser::<Triangle>::new();
+    let start = Instant::now();
+    let faces = p
+        .read_payload_for_element(&mut bytes.as_slice(), &face, &header)
+        .unwrap();
+    println!("read_payload_for_element:        {:?}", start.elapsed());
+    let start = Instant::now();
+    let pooled = p
+        .read_payload_for_element_pooled(&mut bytes.as_slice(), &face, &header)
+        .unwrap();
+    println!("read_payload_for_element_pooled: {:?}", start.elapsed());
+    assert_eq!(faces.len(), pooled.len());
+}
@@ -0,0 +1,81 @@
+//! Reads a synthetic mesh into `DefaultElement`s and `SmallElement`s and compares time and heap usage.
+//!
+//! Run with `cargo bench --bench small_element`.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::{DefaultElement, PropertyAccess, SmallElement};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const VERTICES: u32 = 200_000;
+const FACES: u32 = 400_000;
+
+fn mesh() -> Vec<u8> {
+    let mut bytes = format!(
+        "ply\nformat binary_little_endian 1.0\n\
+        element vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+        property float nx\nproperty float ny\nproperty float nz\n\
+        element face {}\nproperty list uchar int vertex_indices\nend_header\n",
+        VERTICES, FACES
+    )
+    .into_bytes();
+    for i in 0..VERTICES {
+        for v in [i as f32, 0.5, -(i as f32), 0.0, 0.0, 1.0] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    for i in 0..FACES {
+        bytes.push(3);
+        for v in [i % VERTICES, (i + 1) % VERTICES, (i + 2) % VERTICES] {
+            bytes.extend_from_slice(&(v as i32).to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn run<E: PropertyAccess + Send>(name: &str, bytes: &[u8]) -> f64 {
+    let p = Parser::<E>::new();
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let ply = p.read_ply(&mut &bytes[..]).unwrap();
+    let read = start.elapsed();
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes;
+
+    let start = Instant::now();
+    let mut sum = 0.0;
+    for v in &ply.payload["vertex"] {
+        sum += (v.get_float("x").unwrap() + v.get_float("nz").unwrap()) as f64;
+    }
+    let lookup = start.elapsed();
+
+    println!("{}:", name);
+    println!("  read_ply:          {:?}", read);
+    println!("  lookups:           {:?}", lookup);
+    println!("  allocated bytes:   {}", allocated_bytes);
+    sum
+}
+
+fn main() {
+    let bytes = mesh();
+    let default = run::<DefaultElement>("DefaultElement", &bytes);
+    let small = run::<SmallElement>("SmallElement", &bytes);
+    assert_eq!(default, small);
+}
@@ -0,0 +1,80 @@
+//! Reads a synthetic triangle mesh into `DefaultElement`s and reports time and heap usage.
+//!
+//! Compare `cargo bench --bench triangle_mesh` with `cargo bench --bench triangle_mesh --features smallvec`.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::{DefaultElement, Property};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const VERTICES: u32 = 100_000;
+const FACES: u32 = 200_000;
+
+fn triangle_mesh() -> Vec<u8> {
+    let mut bytes = format!(
+        "ply\nformat binary_little_endian 1.0\n\
+        element vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+        element face {}\nproperty list uchar int vertex_indices\nend_header\n",
+        VERTICES, FACES
+    )
+    .into_bytes();
+    for i in 0..VERTICES {
+        for v in [i as f32, 0.5, -(i as f32)] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    for i in 0..FACES {
+        bytes.push(3);
+        for v in [i % VERTICES, (i + 1) % VERTICES, (i + 2) % VERTICES] {
+            bytes.extend_from_slice(&(v as i32).to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn main() {
+    let bytes = triangle_mesh();
+    let p = Parser::<DefaultElement>::new();
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let ply = p.read_ply(&mut bytes.as_slice()).unwrap();
+    let elapsed = start.elapsed();
+
+    println!("smallvec feature:  {}", cfg!(feature = "smallvec"));
+    println!(
+        "size of Property:  {} bytes",
+        std::mem::size_of::<Property>()
+    );
+    println!("read_ply:          {:?}", elapsed);
+    println!(
+        "allocations:       {}",
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations
+    );
+    println!(
+        "allocated bytes:   {}",
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes
+    );
+    assert_eq!(ply.payload["face"].len(), FACES as usize);
+}
@@ -0,0 +1,80 @@
+//! Reads elements with many properties and compares the allocations of `DefaultElement`,
+//! which reserves its map in `new_from_def()`, with a map grown one property at a time.
+//!
+//! Run with `cargo bench --bench wide_element`.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::{DefaultElement, Property, PropertyAccess};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const VERTICES: u32 = 50_000;
+/// About as wide as the vertices of a gaussian splat.
+const PROPERTIES: u32 = 62;
+
+/// `DefaultElement` without `new_from_def()`, its map grows while the properties are set.
+struct GrowingElement(DefaultElement);
+
+impl PropertyAccess for GrowingElement {
+    fn new() -> Self {
+        GrowingElement(DefaultElement::new())
+    }
+    fn set_property(&mut self, key: &str, property: Property) {
+        self.0.set_property(key, property);
+    }
+}
+
+fn wide_mesh() -> Vec<u8> {
+    let mut header = format!(
+        "ply\nformat binary_little_endian 1.0\nelement vertex {}\n",
+        VERTICES
+    );
+    for i in 0..PROPERTIES {
+        header.push_str(&format!("property float f_{}\n", i));
+    }
+    header.push_str("end_header\n");
+    let mut bytes = header.into_bytes();
+    for i in 0..VERTICES * PROPERTIES {
+        bytes.extend_from_slice(&(i as f32).to_le_bytes());
+    }
+    bytes
+}
+
+fn run<E: PropertyAccess + Send>(name: &str, bytes: &[u8]) -> usize {
+    let p = Parser::<E>::new();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let ply = p.read_ply(&mut &bytes[..]).unwrap();
+    let read = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    println!("{}:", name);
+    println!("  read_ply:          {:?}", read);
+    println!("  allocations:       {}", allocations);
+    ply.payload["vertex"].len()
+}
+
+fn main() {
+    let bytes = wide_mesh();
+    let reserved = run::<DefaultElement>("DefaultElement", &bytes);
+    let growing = run::<GrowingElement>("growing map", &bytes);
+    assert_eq!(reserved, growing);
+}
@@ -0,0 +1,115 @@
+//! Writes a synthetic binary payload of 10M vertices through a `BufWriter`,
+//! element by element and as a whole payload.
+//! The first 100k vertices are also written to an unbuffered file,
+//! where serializing each element into a scratch buffer saves most system calls.
+//!
+//! Run with `cargo bench --bench write_binary`.
+extern crate ply_rs;
+use ply_rs::ply::{
+    ElementDef, Encoding, Header, PropertyAccess, PropertyDef, PropertyType, ScalarType,
+};
+use ply_rs::writer::Writer;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::BufWriter;
+use std::time::Instant;
+
+const VERTICES: usize = 10_000_000;
+const UNBUFFERED_VERTICES: usize = 100_000;
+
+struct Vertex {
+    pos: [f32; 3],
+    neighbors: [i32; 3],
+}
+
+impl PropertyAccess for Vertex {
+    fn new() -> Self {
+        Vertex {
+            pos: [0.0; 3],
+            neighbors: [0; 3],
+        }
+    }
+    fn get_float(&self, property_name: &str) -> Option<f32> {
+        match property_name {
+            "x" => Some(self.pos[0]),
+            "y" => Some(self.pos[1]),
+            "z" => Some(self.pos[2]),
+            _ => None,
+        }
+    }
+    fn get_list_int(&self, property_name: &str) -> Option<&[i32]> {
+        match property_name {
+            "neighbors" => Some(&self.neighbors),
+            _ => None,
+        }
+    }
+}
+
+fn hash(bytes: &[u8]) -> u64 {
+    let mut h = DefaultHasher::new();
+    bytes.hash(&mut h);
+    h.finish()
+}
+
+fn main() {
+    let mut def = ElementDef::new("vertex");
+    def.count = VERTICES as u64;
+    for name in ["x", "y", "z"] {
+        def.properties.push(PropertyDef::new(
+            name,
+            PropertyType::Scalar(ScalarType::Float),
+        ));
+    }
+    def.properties.push(PropertyDef::new(
+        "neighbors",
+        PropertyType::List(ScalarType::UChar, ScalarType::Int),
+    ));
+    let mut header = Header::new();
+    header.encoding = Encoding::BinaryLittleEndian;
+    let vertices: Vec<Vertex> = (0..VERTICES)
+        .map(|i| Vertex {
+            pos: [i as f32, 0.5, -(i as f32)],
+            neighbors: [i as i32, i as i32 + 1, i as i32 + 2],
+        })
+        .collect();
+    let w = Writer::<Vertex>::new();
+
+    let mut elementwise = BufWriter::new(Vec::new());
+    let start = Instant::now();
+    for v in &vertices {
+        w.write_little_endian_element(&mut elementwise, v, &def)
+            .unwrap();
+    }
+    println!("write_little_endian_element: {:?}", start.elapsed());
+
+    let mut payload = BufWriter::new(Vec::new());
+    let start = Instant::now();
+    w.write_payload_of_element(&mut payload, &vertices, &def, &header)
+        .unwrap();
+    println!("write_payload_of_element: {:?}", start.elapsed());
+
+    let path = std::env::temp_dir().join("ply_rs_write_binary_bench.ply");
+    let mut small = def.clone();
+    small.count = UNBUFFERED_VERTICES as u64;
+    let vertices = &vertices[..UNBUFFERED_VERTICES];
+    let mut file = File::create(&path).unwrap();
+    let start = Instant::now();
+    for v in vertices {
+        w.write_little_endian_element(&mut file, v, &small).unwrap();
+    }
+    println!(
+        "unbuffered write_little_endian_element: {:?}",
+        start.elapsed()
+    );
+    let mut file = File::create(&path).unwrap();
+    let start = Instant::now();
+    w.write_payload_of_element(&mut file, vertices, &small, &header)
+        .unwrap();
+    println!("unbuffered write_payload_of_element: {:?}", start.elapsed());
+    std::fs::remove_file(&path).unwrap();
+
+    let payload = payload.into_inner().unwrap();
+    let elementwise = elementwise.into_inner().unwrap();
+    assert_eq!(hash(&payload), hash(&elementwise));
+}
@@ -26,13 +26,25 @@ impl ply::PropertyAccess for Vertex {
             z: 0.0,
         }
     }
-    fn set_property(&mut self, key: &str, property: ply::Property) {
+    // Rejecting unexpected properties with an error rather than a panic lets the parser
+    // report them, or skip them with `ParserOptions::skip_rejected_properties()`.
+    fn try_set_property(
+        &mut self,
+        key: &str,
+        property: ply::Property,
+    ) -> Result<(), ply::PropertySetError> {
         match (key, property) {
             ("x", ply::Property::Float(v)) => self.x = v,
             ("y", ply::Property::Float(v)) => self.y = v,
             ("z", ply::Property::Float(v)) => self.z = v,
-            (k, _) => panic!("Vertex: Unexpected key/value combination: key: {}", k),
+            (k, _) => {
+                return Err(ply::PropertySetError::new(&format!(
+                    "Vertex: Unexpected key/value combination: key: {}",
+                    k
+                )))
+            }
         }
+        Ok(())
     }
 }
 
@@ -43,11 +55,21 @@ impl ply::PropertyAccess for Face {
             vertex_index: Vec::new(),
         }
     }
-    fn set_property(&mut self, key: &str, property: ply::Property) {
+    fn try_set_property(
+        &mut self,
+        key: &str,
+        property: ply::Property,
+    ) -> Result<(), ply::PropertySetError> {
         match (key, property) {
-            ("vertex_index", ply::Property::ListInt(vec)) => self.vertex_index = vec,
-            (k, _) => panic!("Face: Unexpected key/value combination: key: {}", k),
+            ("vertex_index", ply::Property::ListInt(vec)) => self.vertex_index = vec.into(),
+            (k, _) => {
+                return Err(ply::PropertySetError::new(&format!(
+                    "Face: Unexpected key/value combination: key: {}",
+                    k
+                )))
+            }
         }
+        Ok(())
     }
 }
 
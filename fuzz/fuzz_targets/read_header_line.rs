@@ -0,0 +1,10 @@
+//! Feeds arbitrary strings to `Parser::read_header_line()`, which must return instead of panicking.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use ply_rs::parser::Parser;
+use ply_rs::ply::DefaultElement;
+
+fuzz_target!(|line: &str| {
+    let p = Parser::<DefaultElement>::new();
+    let _ = p.read_header_line(line);
+});
@@ -0,0 +1,11 @@
+//! Feeds arbitrary bytes to `Parser::read_ply()`, which must return instead of panicking.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use ply_rs::parser::Parser;
+use ply_rs::ply::DefaultElement;
+
+fuzz_target!(|data: &[u8]| {
+    let p = Parser::<DefaultElement>::new();
+    let _ = p.read_ply(&mut &data[..]);
+    let _ = p.read_ply_from_bytes(&bytes::Bytes::copy_from_slice(data));
+});
@@ -0,0 +1,393 @@
+//! Converts elements to and from Apache Arrow record batches.
+//!
+//! Every property becomes a column named like the property:
+//! Scalars map to primitive arrays (`char` to `Int8`, ..., `double` to `Float64`),
+//! lists map to `ListArray`s of the corresponding primitive type.
+//! PLY has no notion of missing values, hence all arrays and fields are non-nullable.
+//!
+//! The index type of a list is kept in the field metadata under `ply:index_type`.
+//!
+//! Requires the `arrow` feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use ply_rs::arrow::{element_to_record_batch, record_batch_to_elements};
+//! # use ply_rs::parser::Parser;
+//! # use ply_rs::ply::DefaultElement;
+//! let mut f = std::fs::File::open("example_plys/house_ok_ascii.ply").unwrap();
+//! let ply = Parser::<DefaultElement>::new().read_ply(&mut f).unwrap();
+//! let batch = element_to_record_batch(&ply, "face").unwrap();
+//! assert_eq!(batch.num_rows(), 3);
+//!
+//! let (def, faces) = record_batch_to_elements(&batch, "face").unwrap();
+//! assert_eq!(def, ply.header.elements[1]);
+//! assert_eq!(faces, ply.payload["face"]);
+//! ```
+
+use crate::ply::{
+    DefaultElement, ElementDef, Ply, Property, PropertyAccess, PropertyDef, PropertyType,
+    ScalarType,
+};
+use arrow_array::cast::AsArray;
+use arrow_array::types::*;
+use arrow_array::{Array, ArrayRef, ListArray, PrimitiveArray, RecordBatch};
+use arrow_buffer::OffsetBuffer;
+use arrow_schema::{DataType, Field, Schema};
+use std::collections::HashMap;
+use std::io;
+use std::io::{ErrorKind, Result};
+use std::sync::Arc;
+
+/// Metadata key of list fields holding the PLY index type, for example `uchar`.
+pub const INDEX_TYPE_KEY: &str = "ply:index_type";
+
+/// Converts the elements called `element` of `ply` into a record batch.
+///
+/// The schema follows the element definition in the header.
+/// Fails if the element doesn't exist, has a property with a custom type,
+/// or if an element lacks one of the declared properties.
+pub fn element_to_record_batch<E: PropertyAccess>(
+    ply: &Ply<E>,
+    element: &str,
+) -> Result<RecordBatch> {
+    let def = match ply.header.elements.iter().find(|e| e.name == element) {
+        Some(d) => d,
+        None => return Err(arrow_error(format!("Unknown element '{}'.", element))),
+    };
+    let elements: &[E] = ply.payload.get(element).map_or(&[], |e| &e[..]);
+    let mut fields = Vec::with_capacity(def.properties.len());
+    let mut columns = Vec::with_capacity(def.properties.len());
+    for p in &def.properties {
+        let (field, column) = match p.data_type {
+            PropertyType::Scalar(ref s) => (
+                Field::new(p.name.as_str(), data_type(s), false),
+                scalar_column(elements, &p.name, s)?,
+            ),
+            PropertyType::List(ref index, ref s) => {
+                let metadata =
+                    HashMap::from([(INDEX_TYPE_KEY.to_string(), type_name(index).to_string())]);
+                let field = Field::new(
+                    p.name.as_str(),
+                    DataType::List(Arc::new(item_field(s))),
+                    false,
+                )
+                .with_metadata(metadata);
+                (field, list_column(elements, &p.name, s)?)
+            }
+            PropertyType::Custom { ref name, .. } => {
+                return Err(arrow_error(format!(
+                    "Property '{}' has the custom type '{}', which has no arrow equivalent.",
+                    p.name, name
+                )))
+            }
+        };
+        fields.push(field);
+        columns.push(column);
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))
+}
+
+/// Converts a record batch into elements called `element`, the inverse of `element_to_record_batch()`.
+///
+/// The element definition is inferred from the schema.
+/// Lists without `ply:index_type` metadata get the index type `uint`.
+/// Fails on columns with null values or data types that have no PLY equivalent.
+pub fn record_batch_to_elements(
+    batch: &RecordBatch,
+    element: &str,
+) -> Result<(ElementDef, Vec<DefaultElement>)> {
+    let mut def = ElementDef::new(element);
+    def.count = batch.num_rows() as u64;
+    let mut elements = vec![DefaultElement::new(); batch.num_rows()];
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let name = field.name();
+        if column.null_count() > 0 {
+            return Err(arrow_error(format!("Column '{}' contains nulls.", name)));
+        }
+        let data_type = match field.data_type() {
+            DataType::List(item) => {
+                let index = match field.metadata().get(INDEX_TYPE_KEY) {
+                    None => ScalarType::UInt,
+                    Some(n) => match scalar_type_by_name(n) {
+                        Some(s) => s,
+                        None => {
+                            return Err(arrow_error(format!(
+                                "Column '{}' has the unknown index type '{}'.",
+                                name, n
+                            )))
+                        }
+                    },
+                };
+                let s = scalar_type(item.data_type(), name)?;
+                read_list_column(&mut elements, name, column, &s)?;
+                PropertyType::List(index, s)
+            }
+            d => {
+                let s = scalar_type(d, name)?;
+                read_scalar_column(&mut elements, name, column, &s);
+                PropertyType::Scalar(s)
+            }
+        };
+        def.properties.push(PropertyDef::new(name, data_type));
+    }
+    Ok((def, elements))
+}
+
+fn arrow_error(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn missing_property(index: usize, name: &str) -> io::Error {
+    arrow_error(format!("Element #{} has no property '{}'.", index, name))
+}
+
+fn item_field(scalar_type: &ScalarType) -> Field {
+    Field::new("item", data_type(scalar_type), false)
+}
+
+fn data_type(scalar_type: &ScalarType) -> DataType {
+    match *scalar_type {
+        ScalarType::Char => DataType::Int8,
+        ScalarType::UChar => DataType::UInt8,
+        ScalarType::Short => DataType::Int16,
+        ScalarType::UShort => DataType::UInt16,
+        ScalarType::Int => DataType::Int32,
+        ScalarType::UInt => DataType::UInt32,
+        ScalarType::Float => DataType::Float32,
+        ScalarType::Double => DataType::Float64,
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => DataType::Int64,
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => DataType::UInt64,
+        #[cfg(feature = "half")]
+        ScalarType::Half => DataType::Float16,
+    }
+}
+
+fn scalar_type(data_type: &DataType, column: &str) -> Result<ScalarType> {
+    Ok(match *data_type {
+        DataType::Int8 => ScalarType::Char,
+        DataType::UInt8 => ScalarType::UChar,
+        DataType::Int16 => ScalarType::Short,
+        DataType::UInt16 => ScalarType::UShort,
+        DataType::Int32 => ScalarType::Int,
+        DataType::UInt32 => ScalarType::UInt,
+        DataType::Float32 => ScalarType::Float,
+        DataType::Float64 => ScalarType::Double,
+        #[cfg(feature = "extended-types")]
+        DataType::Int64 => ScalarType::LongLong,
+        #[cfg(feature = "extended-types")]
+        DataType::UInt64 => ScalarType::ULongLong,
+        #[cfg(feature = "half")]
+        DataType::Float16 => ScalarType::Half,
+        ref d => {
+            return Err(arrow_error(format!(
+                "Column '{}' has the data type {}, which has no PLY equivalent.",
+                column, d
+            )))
+        }
+    })
+}
+
+/// Header keyword of a scalar type.
+fn type_name(scalar_type: &ScalarType) -> &'static str {
+    match *scalar_type {
+        ScalarType::Char => "char",
+        ScalarType::UChar => "uchar",
+        ScalarType::Short => "short",
+        ScalarType::UShort => "ushort",
+        ScalarType::Int => "int",
+        ScalarType::UInt => "uint",
+        ScalarType::Float => "float",
+        ScalarType::Double => "double",
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => "int64",
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => "uint64",
+        #[cfg(feature = "half")]
+        ScalarType::Half => "half",
+    }
+}
+
+fn scalar_type_by_name(name: &str) -> Option<ScalarType> {
+    let types = [
+        ScalarType::Char,
+        ScalarType::UChar,
+        ScalarType::Short,
+        ScalarType::UShort,
+        ScalarType::Int,
+        ScalarType::UInt,
+        ScalarType::Float,
+        ScalarType::Double,
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong,
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong,
+        #[cfg(feature = "half")]
+        ScalarType::Half,
+    ];
+    types.into_iter().find(|t| type_name(t) == name)
+}
+
+macro_rules! scalar_column(
+    ($elements:expr, $name:expr, $getter:ident, $arrow:ty) => {{
+        let values = $elements
+            .iter()
+            .enumerate()
+            .map(|(i, e)| e.$getter($name).ok_or_else(|| missing_property(i, $name)))
+            .collect::<Result<Vec<_>>>()?;
+        Arc::new(PrimitiveArray::<$arrow>::from(values)) as ArrayRef
+    }}
+);
+
+fn scalar_column<E: PropertyAccess>(
+    elements: &[E],
+    name: &str,
+    scalar_type: &ScalarType,
+) -> Result<ArrayRef> {
+    Ok(match *scalar_type {
+        ScalarType::Char => scalar_column!(elements, name, get_char, Int8Type),
+        ScalarType::UChar => scalar_column!(elements, name, get_uchar, UInt8Type),
+        ScalarType::Short => scalar_column!(elements, name, get_short, Int16Type),
+        ScalarType::UShort => scalar_column!(elements, name, get_ushort, UInt16Type),
+        ScalarType::Int => scalar_column!(elements, name, get_int, Int32Type),
+        ScalarType::UInt => scalar_column!(elements, name, get_uint, UInt32Type),
+        ScalarType::Float => scalar_column!(elements, name, get_float, Float32Type),
+        ScalarType::Double => scalar_column!(elements, name, get_double, Float64Type),
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => scalar_column!(elements, name, get_longlong, Int64Type),
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => scalar_column!(elements, name, get_ulonglong, UInt64Type),
+        #[cfg(feature = "half")]
+        ScalarType::Half => scalar_column!(elements, name, get_half, Float16Type),
+    })
+}
+
+macro_rules! list_column(
+    ($elements:expr, $name:expr, $scalar_type:expr, $getter:ident, $arrow:ty) => {{
+        let mut lengths = Vec::with_capacity($elements.len());
+        let mut values = Vec::new();
+        for (i, e) in $elements.iter().enumerate() {
+            let list = e.$getter($name).ok_or_else(|| missing_property(i, $name))?;
+            lengths.push(list.len());
+            values.extend_from_slice(list);
+        }
+        if values.len() > i32::MAX as usize {
+            return Err(arrow_error(format!(
+                "Property '{}' has too many list values for an arrow list.",
+                $name
+            )));
+        }
+        let values = PrimitiveArray::<$arrow>::from(values);
+        Arc::new(ListArray::new(
+            Arc::new(item_field($scalar_type)),
+            OffsetBuffer::from_lengths(lengths),
+            Arc::new(values),
+            None,
+        )) as ArrayRef
+    }}
+);
+
+fn list_column<E: PropertyAccess>(
+    elements: &[E],
+    name: &str,
+    scalar_type: &ScalarType,
+) -> Result<ArrayRef> {
+    let s = scalar_type;
+    Ok(match *scalar_type {
+        ScalarType::Char => list_column!(elements, name, s, get_list_char, Int8Type),
+        ScalarType::UChar => list_column!(elements, name, s, get_list_uchar, UInt8Type),
+        ScalarType::Short => list_column!(elements, name, s, get_list_short, Int16Type),
+        ScalarType::UShort => list_column!(elements, name, s, get_list_ushort, UInt16Type),
+        ScalarType::Int => list_column!(elements, name, s, get_list_int, Int32Type),
+        ScalarType::UInt => list_column!(elements, name, s, get_list_uint, UInt32Type),
+        ScalarType::Float => list_column!(elements, name, s, get_list_float, Float32Type),
+        ScalarType::Double => list_column!(elements, name, s, get_list_double, Float64Type),
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => list_column!(elements, name, s, get_list_longlong, Int64Type),
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => list_column!(elements, name, s, get_list_ulonglong, UInt64Type),
+        #[cfg(feature = "half")]
+        ScalarType::Half => list_column!(elements, name, s, get_list_half, Float16Type),
+    })
+}
+
+macro_rules! read_scalar_column(
+    ($elements:expr, $name:expr, $column:expr, $variant:ident, $arrow:ty) => {
+        for (e, v) in $elements.iter_mut().zip($column.as_primitive::<$arrow>().values()) {
+            e.insert($name.to_string(), Property::$variant(*v));
+        }
+    }
+);
+
+fn read_scalar_column(
+    elements: &mut [DefaultElement],
+    name: &str,
+    column: &ArrayRef,
+    scalar_type: &ScalarType,
+) {
+    match *scalar_type {
+        ScalarType::Char => read_scalar_column!(elements, name, column, Char, Int8Type),
+        ScalarType::UChar => read_scalar_column!(elements, name, column, UChar, UInt8Type),
+        ScalarType::Short => read_scalar_column!(elements, name, column, Short, Int16Type),
+        ScalarType::UShort => read_scalar_column!(elements, name, column, UShort, UInt16Type),
+        ScalarType::Int => read_scalar_column!(elements, name, column, Int, Int32Type),
+        ScalarType::UInt => read_scalar_column!(elements, name, column, UInt, UInt32Type),
+        ScalarType::Float => read_scalar_column!(elements, name, column, Float, Float32Type),
+        ScalarType::Double => read_scalar_column!(elements, name, column, Double, Float64Type),
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => read_scalar_column!(elements, name, column, LongLong, Int64Type),
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => {
+            read_scalar_column!(elements, name, column, ULongLong, UInt64Type)
+        }
+        #[cfg(feature = "half")]
+        ScalarType::Half => read_scalar_column!(elements, name, column, Half, Float16Type),
+    }
+}
+
+macro_rules! read_list_column(
+    ($elements:expr, $name:expr, $list:expr, $variant:ident, $arrow:ty) => {{
+        let values = $list.values().as_primitive::<$arrow>();
+        if values.null_count() > 0 {
+            return Err(arrow_error(format!("Column '{}' contains nulls.", $name)));
+        }
+        let values = values.values();
+        for (e, w) in $elements.iter_mut().zip($list.value_offsets().windows(2)) {
+            let list = &values[w[0] as usize..w[1] as usize];
+            e.insert($name.to_string(), Property::$variant(list.iter().cloned().collect()));
+        }
+    }}
+);
+
+fn read_list_column(
+    elements: &mut [DefaultElement],
+    name: &str,
+    column: &ArrayRef,
+    scalar_type: &ScalarType,
+) -> Result<()> {
+    let list = column.as_list::<i32>();
+    match *scalar_type {
+        ScalarType::Char => read_list_column!(elements, name, list, ListChar, Int8Type),
+        ScalarType::UChar => read_list_column!(elements, name, list, ListUChar, UInt8Type),
+        ScalarType::Short => read_list_column!(elements, name, list, ListShort, Int16Type),
+        ScalarType::UShort => read_list_column!(elements, name, list, ListUShort, UInt16Type),
+        ScalarType::Int => read_list_column!(elements, name, list, ListInt, Int32Type),
+        ScalarType::UInt => read_list_column!(elements, name, list, ListUInt, UInt32Type),
+        ScalarType::Float => read_list_column!(elements, name, list, ListFloat, Float32Type),
+        ScalarType::Double => read_list_column!(elements, name, list, ListDouble, Float64Type),
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => {
+            read_list_column!(elements, name, list, ListLongLong, Int64Type)
+        }
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => {
+            read_list_column!(elements, name, list, ListULongLong, UInt64Type)
+        }
+        #[cfg(feature = "half")]
+        ScalarType::Half => read_list_column!(elements, name, list, ListHalf, Float16Type),
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Builds documents for common kinds of data.
+
+use crate::ply::{
+    ConsistencyError, DefaultElement, Encoding, Ply, Property, PropertyDef, PropertyType,
+    ScalarType,
+};
+
+/// Builds a point cloud with a `vertex` element, ready to be written.
+///
+/// Positions are stored as float properties `x`, `y`, `z`, normals as float `nx`, `ny`, `nz`
+/// and colors as uchar `red`, `green`, `blue`, in this order.
+/// The payload is encoded in binary little endian, change `header.encoding` for other encodings.
+///
+/// Fails if `colors` or `normals` don't hold one entry per position.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::builder::point_cloud;
+/// # use ply_rs::writer::Writer;
+/// let mut ply = point_cloud(&[[0.0, 0.0, 0.0], [1.0, 0.5, 0.0]], None, None).unwrap();
+/// let mut buf = Vec::new();
+/// Writer::new().write_ply(&mut buf, &mut ply).unwrap();
+/// ```
+pub fn point_cloud(
+    positions: &[[f32; 3]],
+    colors: Option<&[[u8; 3]]>,
+    normals: Option<&[[f32; 3]]>,
+) -> Result<Ply<DefaultElement>, ConsistencyError> {
+    check_len("colors", colors.map(<[_]>::len), positions.len())?;
+    check_len("normals", normals.map(<[_]>::len), positions.len())?;
+
+    let float = PropertyType::Scalar(ScalarType::Float);
+    let uchar = PropertyType::Scalar(ScalarType::UChar);
+    let mut properties = Vec::new();
+    let mut add = |names: [&str; 3], data_type: &PropertyType| {
+        for name in names {
+            properties.push(PropertyDef::new(name, data_type.clone()));
+        }
+    };
+    add(POSITION, &float);
+    if normals.is_some() {
+        add(NORMAL, &float);
+    }
+    if colors.is_some() {
+        add(COLOR, &uchar);
+    }
+
+    let vertices = positions
+        .iter()
+        .enumerate()
+        .map(|(i, position)| {
+            let mut vertex = DefaultElement::with_capacity(properties.len());
+            insert(&mut vertex, POSITION, position.map(Property::Float));
+            if let Some(normals) = normals {
+                insert(&mut vertex, NORMAL, normals[i].map(Property::Float));
+            }
+            if let Some(colors) = colors {
+                insert(&mut vertex, COLOR, colors[i].map(Property::UChar));
+            }
+            vertex
+        })
+        .collect();
+    Ok(Ply::from_elements(
+        "vertex",
+        properties,
+        vertices,
+        Encoding::BinaryLittleEndian,
+    ))
+}
+
+const POSITION: [&str; 3] = ["x", "y", "z"];
+const NORMAL: [&str; 3] = ["nx", "ny", "nz"];
+const COLOR: [&str; 3] = ["red", "green", "blue"];
+
+fn insert(vertex: &mut DefaultElement, names: [&str; 3], values: [Property; 3]) {
+    for (name, value) in names.into_iter().zip(values) {
+        vertex.insert(name.to_string(), value);
+    }
+}
+
+fn check_len(name: &str, len: Option<usize>, expected: usize) -> Result<(), ConsistencyError> {
+    match len {
+        Some(len) if len != expected => Err(ConsistencyError::new(&format!(
+            "Found {} {} for {} positions.",
+            len, name, expected
+        ))),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,304 @@
+//! Frames PLY documents for `tokio_util::codec`, requires the `codec` feature.
+//!
+//! `PlyDecoder` buffers incoming bytes until a complete document is available,
+//! the header and all declared elements, and parses it into a `Ply`.
+//! Documents may be split across any number of reads and may follow each other directly.
+//! `PlyEncoder` writes a `Ply` with a `Writer`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use bytes::BytesMut;
+//! # use ply_rs::codec::PlyDecoder;
+//! # use ply_rs::ply::DefaultElement;
+//! # use tokio_util::codec::Decoder;
+//! let bytes = std::fs::read("example_plys/house_ok_ascii.ply").unwrap();
+//! let mut decoder = PlyDecoder::<DefaultElement>::new();
+//! let mut buf = BytesMut::new();
+//! buf.extend_from_slice(&bytes[..100]);
+//! assert!(decoder.decode(&mut buf).unwrap().is_none());
+//! buf.extend_from_slice(&bytes[100..]);
+//! let ply = decoder.decode(&mut buf).unwrap().unwrap();
+//! assert_eq!(ply.payload["vertex"].len(), 5);
+//! ```
+
+use crate::parser::{binary_entry_len, line_len, materializable_count};
+use crate::parser::{Parser, ParserOptions};
+use crate::ply::{ElementDef, Encoding, Header, Ply, PropertyAccess, PropertyType};
+use crate::writer::{WriteOptions, Writer};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use bytes::{BufMut, BytesMut};
+use std::io;
+use std::io::{ErrorKind, Result};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Decodes a stream of bytes into `Ply` documents.
+pub struct PlyDecoder<E: PropertyAccess> {
+    parser: Parser<E>,
+    max_len: Option<usize>,
+    state: DecodeState,
+}
+
+/// Progress through the document at the start of the buffer.
+enum DecodeState {
+    /// Looking for `end_header`, `scanned` bytes are complete header lines.
+    Header { scanned: usize },
+    /// The first `scanned` bytes hold the header and complete elements.
+    Payload {
+        header: Header,
+        scanned: usize,
+        /// Index of the element being scanned and the number of its entries not scanned yet.
+        element: usize,
+        remaining: u64,
+    },
+}
+
+impl<E: PropertyAccess> Default for PlyDecoder<E> {
+    fn default() -> Self {
+        PlyDecoder::new()
+    }
+}
+
+impl<E: PropertyAccess> PlyDecoder<E> {
+    /// Creates a decoder with the default parser options.
+    pub fn new() -> Self {
+        PlyDecoder::with_options(ParserOptions::new())
+    }
+    /// Creates a decoder that parses documents with `options`.
+    pub fn with_options(options: ParserOptions) -> Self {
+        PlyDecoder {
+            parser: Parser::with_options(options),
+            max_len: None,
+            state: DecodeState::Header { scanned: 0 },
+        }
+    }
+    /// Fails with `InvalidData` once a document exceeds `max_len` bytes, unlimited by default.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Scans the new bytes of `src`, returns the length of the document once it is complete.
+    fn scan(&mut self, src: &[u8]) -> Result<Option<usize>> {
+        if let DecodeState::Header { ref mut scanned } = self.state {
+            let header_len = loop {
+                let max = self.parser.options().max_header_line_len;
+                let line_len = match line_len(&src[*scanned..], false) {
+                    Some(len) if len <= max => len,
+                    None if src.len() - *scanned < max => return Ok(None),
+                    _ => {
+                        return Err(io::Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Header line is longer than the limit of {} bytes.", max),
+                        ))
+                    }
+                };
+                let line = trim_line(&src[*scanned..*scanned + line_len]);
+                if *scanned == 0 && line != b"ply" {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        "Invalid line encountered. Expected type: 'magic number (ply)'",
+                    ));
+                }
+                *scanned += line_len;
+                if line == b"end_header" {
+                    break *scanned;
+                }
+            };
+            let header = self.parser.read_header(&mut &src[..header_len])?;
+            for e in &header.elements {
+                materializable_count(e)?;
+            }
+            let remaining = header.elements.first().map_or(0, |e| e.count);
+            self.state = DecodeState::Payload {
+                header,
+                scanned: header_len,
+                element: 0,
+                remaining,
+            };
+        }
+        let DecodeState::Payload {
+            ref header,
+            ref mut scanned,
+            ref mut element,
+            ref mut remaining,
+        } = self.state
+        else {
+            unreachable!()
+        };
+        while *element < header.elements.len() {
+            let def = &header.elements[*element];
+            let rest = &src[*scanned..];
+            let (entries, len) = match header.encoding {
+                Encoding::Ascii => scan_ascii_lines(rest, *remaining),
+                Encoding::BinaryBigEndian => {
+                    scan_binary::<BigEndian>(self.parser.options(), def, rest, *remaining)?
+                }
+                Encoding::BinaryLittleEndian => {
+                    scan_binary::<LittleEndian>(self.parser.options(), def, rest, *remaining)?
+                }
+            };
+            *scanned += len;
+            *remaining -= entries;
+            if *remaining > 0 {
+                return Ok(None);
+            }
+            *element += 1;
+            *remaining = header.elements.get(*element).map_or(0, |e| e.count);
+        }
+        Ok(Some(*scanned))
+    }
+
+    /// Parses the first `len` bytes of `src` as a document.
+    fn parse(&mut self, src: &mut BytesMut, len: usize) -> Result<Ply<E>> {
+        self.state = DecodeState::Header { scanned: 0 };
+        let document = src.split_to(len);
+        self.parser.read_ply(&mut &document[..])
+    }
+}
+
+/// Strips the line break, and leading spaces the parser accepts.
+fn trim_line(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let start = line
+        .iter()
+        .position(|&b| b != b' ' && b != b'\t')
+        .unwrap_or(line.len());
+    &line[start..]
+}
+
+/// Counts up to `max` complete lines at the start of `bytes`, returns the count and their length.
+fn scan_ascii_lines(bytes: &[u8], max: u64) -> (u64, usize) {
+    let mut lines = 0;
+    let mut len = 0;
+    while lines < max {
+        match line_len(&bytes[len..], false) {
+            Some(n) => len += n,
+            None => break,
+        }
+        lines += 1;
+    }
+    (lines, len)
+}
+
+/// Counts up to `max` complete binary entries of `def` at the start of `bytes`,
+/// returns the count and their length.
+fn scan_binary<B: ByteOrder>(
+    options: &ParserOptions,
+    def: &ElementDef,
+    bytes: &[u8],
+    max: u64,
+) -> Result<(u64, usize)> {
+    let mut stride = Some(0usize);
+    for p in &def.properties {
+        stride = match p.data_type {
+            PropertyType::Scalar(ref s) => stride.map(|n| n + s.size()),
+            PropertyType::Custom { size, .. } => stride.map(|n| n + size),
+            PropertyType::List(_, _) => None,
+        };
+    }
+    match stride {
+        // Entries without properties occupy no bytes.
+        Some(0) => return Ok((max, 0)),
+        Some(stride) => {
+            let entries = ((bytes.len() / stride) as u64).min(max);
+            return Ok((entries, entries as usize * stride));
+        }
+        None => (),
+    }
+    let mut entries = 0;
+    let mut len = 0;
+    while entries < max {
+        let rest = &bytes[len..];
+        match binary_entry_len::<B>(options, def, rest)? {
+            n if n <= rest.len() => len += n,
+            _ => break,
+        }
+        entries += 1;
+    }
+    Ok((entries, len))
+}
+
+impl<E: PropertyAccess> Decoder for PlyDecoder<E> {
+    type Item = Ply<E>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Ply<E>>> {
+        let complete = self.scan(src)?;
+        // Until the document is complete, all of `src` belongs to it.
+        let len = complete.unwrap_or(src.len());
+        if let Some(max_len) = self.max_len.filter(|&max_len| len > max_len) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("PLY document exceeds the limit of {} bytes.", max_len),
+            ));
+        }
+        match complete {
+            Some(len) => self.parse(src, len).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Ply<E>>> {
+        if let Some(ply) = self.decode(src)? {
+            return Ok(Some(ply));
+        }
+        if src.is_empty() {
+            return Ok(None);
+        }
+        // The last line of an ascii payload may lack its line break.
+        if let DecodeState::Payload {
+            ref header,
+            element,
+            remaining,
+            ..
+        } = self.state
+        {
+            let later: u64 = header.elements[element + 1..].iter().map(|e| e.count).sum();
+            if header.encoding == Encoding::Ascii && remaining + later == 1 {
+                let len = src.len();
+                return self.parse(src, len).map(Some);
+            }
+        }
+        Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "Stream ended within a PLY document.",
+        ))
+    }
+}
+
+/// Encodes `Ply` documents with a `Writer`.
+pub struct PlyEncoder<E: PropertyAccess> {
+    writer: Writer<E>,
+}
+
+impl<E: PropertyAccess> Default for PlyEncoder<E> {
+    fn default() -> Self {
+        PlyEncoder::new()
+    }
+}
+
+impl<E: PropertyAccess> PlyEncoder<E> {
+    /// Creates an encoder with the default write options.
+    pub fn new() -> Self {
+        PlyEncoder {
+            writer: Writer::new(),
+        }
+    }
+    /// Creates an encoder that writes documents with `options`.
+    pub fn with_options(options: WriteOptions) -> Self {
+        PlyEncoder {
+            writer: Writer::with_options(options),
+        }
+    }
+}
+
+impl<E: PropertyAccess> Encoder<Ply<E>> for PlyEncoder<E> {
+    type Error = io::Error;
+
+    fn encode(&mut self, mut ply: Ply<E>, dst: &mut BytesMut) -> Result<()> {
+        self.writer.write_ply(&mut dst.writer(), &mut ply)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+//! Summarizes PLY files without reading their payload.
+//!
+//! Useful for file browsers and the like, where opening hundreds of files must be fast.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use ply_rs::info;
+//! let info = info::scan("example_plys/house_2_ok_little_endian.ply").unwrap();
+//! assert_eq!(info.header.elements[0].count, 5);
+//! // The payload contains lists, hence its size can't be checked without reading it.
+//! assert_eq!(info.size_matches, None);
+//! ```
+
+use crate::parser::Parser;
+use crate::ply::{DefaultElement, Encoding, Header, PropertyType};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Summary of a PLY file, see `scan()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlyInfo {
+    /// The parsed header.
+    pub header: Header,
+    /// Number of bytes of the header, including the `end_header` line.
+    pub header_len: u64,
+    /// Number of bytes of the whole file.
+    pub file_size: u64,
+    /// Number of bytes the file should have, according to the header.
+    ///
+    /// Only known for binary files where all properties have a fixed size, that is: no lists.
+    pub expected_size: Option<u64>,
+    /// Whether `file_size` equals `expected_size`, `None` if the latter is unknown.
+    ///
+    /// `Some(false)` usually means the file is truncated.
+    pub size_matches: Option<bool>,
+}
+
+/// Reads the header of the file at `path` and checks the file size against it.
+///
+/// The payload is not read, hence its content isn't validated.
+pub fn scan<P: AsRef<Path>>(path: P) -> io::Result<PlyInfo> {
+    let file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let mut reader = CountingReader {
+        inner: BufReader::new(file),
+        consumed: 0,
+    };
+    let header = Parser::<DefaultElement>::new().read_header(&mut reader)?;
+    let header_len = reader.consumed;
+    let expected_size = payload_size(&header).and_then(|s| s.checked_add(header_len));
+    Ok(PlyInfo {
+        header,
+        header_len,
+        file_size,
+        expected_size,
+        size_matches: expected_size.map(|s| s == file_size),
+    })
+}
+
+/// Size of a binary payload without lists, `None` if it can't be computed from the header alone.
+fn payload_size(header: &Header) -> Option<u64> {
+    if header.encoding == Encoding::Ascii {
+        return None;
+    }
+    let mut size = 0u64;
+    for e in &header.elements {
+        let mut stride = 0u64;
+        for p in &e.properties {
+            stride += match p.data_type {
+                PropertyType::Scalar(ref s) => s.size() as u64,
+                PropertyType::Custom { size, .. } => size as u64,
+                PropertyType::List(_, _) => return None,
+            };
+        }
+        size = size.checked_add(stride.checked_mul(e.count)?)?;
+    }
+    Some(size)
+}
+
+/// Keeps track of how many bytes were consumed from the underlying reader.
+struct CountingReader<R> {
+    inner: BufReader<R>,
+    consumed: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.consumed += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        self.consumed += amt as u64;
+        self.inner.consume(amt)
+    }
+}
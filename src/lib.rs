@@ -17,17 +17,41 @@
 //!
 //! Simplicity is provided by giving you high level operations and ready to use data structures:
 //!
+//! - Read the points of a file with `read_vertices(path)`, or with their colors with `read_vertices_with_colors(path)`.
 //! - Read an entire PLY file with `read_ply(reader)`, see the Parser module.
 //! - Write an entire PLY with `ẁrite_ply(target, ply)`, se the Writer module.
+//! - Build a point cloud from position, normal and color slices with `builder::point_cloud()`.
+//! - Read attributes split into scalar properties, like `x`, `y`, `z`, as arrays or flat buffers with `ElementDef::group()`.
+//! - Summarize a file without reading its payload with `info::scan(path)`.
+//! - Read numbered frames like `frame_*.ply` in order with `sequence::PlySequence`.
+//! - Convert a file without holding its payload in memory with `pipeline::copy(source, target, options)`.
+//! - Exchange elements with Apache Arrow through the `arrow` module, requires the `arrow` feature.
+//! - Send documents over framed transports with the `codec` module, requires the `codec` feature.
 //! - Don't care about data types: `DefaultElement` is nothing more than a [linked HashMap](https://github.com/contain-rs/linked-hash-map) where you access elements with String keys.
 //!
 //! Performance can be achieved by using the finer granular methods and your own structs:
 //!
 //! - `Writer` and `Parser` provide you with methods down to the line/element level for nice things like streaming architectures.
 //! - `Ply`, `Writer`, and `Parser` use generics for the element-type. If HashMaps are too slow for you, define your own structs and implement the `PropertyAccess` trait. Data will then be written directly to your target format.
+//! - `MultiParser` reads each element of a file into its own struct.
+//! - `Parser::bind()` prepares a `BoundParser` for reading many files that share one header.
+//! - `RawElement` keeps the payload undecoded, copying a file without changing its encoding is nearly free.
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod builder;
+#[cfg(feature = "codec")]
+pub mod codec;
+pub mod info;
 pub mod parser;
+pub mod pipeline;
 pub mod ply;
+pub mod sequence;
+mod vertices;
+pub use vertices::{read_vertices, read_vertices_with_colors, ColoredVertices};
 pub mod writer;
 
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
 mod util;
@@ -0,0 +1,284 @@
+//! Splits ascii payload lines into values without going through the grammar.
+//!
+//! Accepts exactly the lines accepted by `grammar::data_line()`:
+//! whitespace (spaces and tabs) separated numbers, optionally surrounded by whitespace
+//! and followed by a single line break (`\n`, `\r` or `\r\n`).
+
+#[cfg(feature = "simd")]
+use memchr::memchr2;
+use std::io;
+use std::io::{BufRead, ErrorKind};
+
+/// Returns the values of `line` or `None` if the grammar would reject it.
+#[cfg(feature = "simd")]
+pub(crate) fn tokenize(line: &str) -> Option<Vec<&str>> {
+    let bytes = line.as_bytes();
+    let end = if bytes.ends_with(b"\r\n") {
+        bytes.len() - 2
+    } else if bytes.ends_with(b"\n") || bytes.ends_with(b"\r") {
+        bytes.len() - 1
+    } else {
+        bytes.len()
+    };
+    let mut values = Vec::new();
+    let mut start = 0;
+    while start < end {
+        let stop = memchr2(b' ', b'\t', &bytes[start..end]).map_or(end, |i| start + i);
+        if stop > start {
+            let value = &bytes[start..stop];
+            if !is_number(value) {
+                return None;
+            }
+            // Only ascii characters were accepted, hence the boundaries are valid.
+            values.push(&line[start..stop]);
+        }
+        start = stop + 1;
+    }
+    Some(values)
+}
+
+/// Checks `[-+]?(([0-9]+(\.[0-9]*)?|\.[0-9]+)(e[-+]?[0-9]+)?|nan|NaN|inf)`, the `any_number` rule of the grammar.
+fn is_number(s: &[u8]) -> bool {
+    let mut i = 0;
+    let skip_sign = |i: usize| match s.get(i) {
+        Some(b'-') | Some(b'+') => i + 1,
+        _ => i,
+    };
+    let skip_digits = |i: usize| {
+        let n = s[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        (i + n, n > 0)
+    };
+    i = skip_sign(i);
+    if matches!(&s[i..], b"nan" | b"NaN" | b"inf") {
+        return true;
+    }
+    let (next, integer) = skip_digits(i);
+    i = next;
+    if s.get(i) == Some(&b'.') {
+        let (next, fraction) = skip_digits(i + 1);
+        if !integer && !fraction {
+            return false;
+        }
+        i = next;
+    } else if !integer {
+        return false;
+    }
+    if s.get(i) == Some(&b'e') {
+        let (next, ok) = skip_digits(skip_sign(i + 1));
+        if !ok {
+            return false;
+        }
+        i = next;
+    }
+    i == s.len()
+}
+
+/// Longest value accepted by `LineTokens`, longer values are an error.
+const MAX_TOKEN_LEN: usize = 1024;
+
+/// Reads the values of a single line from a reader, without holding the whole line in memory.
+///
+/// Used for lines too long to be buffered, see `LONG_LINE_LEN`.
+/// Stops after the line break, the reader is left at the start of the next line.
+/// Values and line breaks are checked like `tokenize()` does,
+/// a rejected line or a failing reader ends the iteration and leaves the reason in `error`.
+pub(crate) struct LineTokens<R> {
+    reader: R,
+    done: bool,
+    pub(crate) error: Option<io::Error>,
+}
+
+impl<R: BufRead> LineTokens<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        LineTokens {
+            reader,
+            done: false,
+            error: None,
+        }
+    }
+
+    fn fail(&mut self, e: io::Error) -> Option<String> {
+        self.done = true;
+        self.error = Some(e);
+        None
+    }
+
+    fn invalid(&mut self, message: String) -> Option<String> {
+        self.fail(io::Error::new(ErrorKind::InvalidInput, message))
+    }
+
+    /// Consumes the line break starting with `first`, returns false if it isn't a valid one.
+    fn consume_line_break(&mut self, first: u8) -> io::Result<bool> {
+        self.reader.consume(1);
+        if first == b'\n' {
+            return Ok(true);
+        }
+        match super::fill_buf(&mut self.reader)?.first() {
+            None => Ok(true),
+            Some(b'\n') => {
+                self.reader.consume(1);
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for LineTokens<R> {
+    type Item = String;
+    fn next(&mut self) -> Option<String> {
+        let mut token = Vec::new();
+        while !self.done {
+            let buf = match super::fill_buf(&mut self.reader) {
+                Ok(b) => b,
+                Err(e) => return self.fail(e),
+            };
+            if buf.is_empty() {
+                self.done = true;
+                break;
+            }
+            let mut used = 0;
+            let mut stop = None;
+            for &c in buf {
+                match c {
+                    b' ' | b'\t' if token.is_empty() => (),
+                    b' ' | b'\t' | b'\n' | b'\r' => {
+                        stop = Some(c);
+                        break;
+                    }
+                    _ => token.push(c),
+                }
+                used += 1;
+            }
+            self.reader.consume(used);
+            if token.len() > MAX_TOKEN_LEN {
+                return self.invalid(format!(
+                    "Value longer than {} bytes in element line.",
+                    MAX_TOKEN_LEN
+                ));
+            }
+            match stop {
+                None => continue,
+                Some(b' ') | Some(b'\t') => break,
+                Some(c) => {
+                    self.done = true;
+                    match self.consume_line_break(c) {
+                        Ok(true) => break,
+                        Ok(false) => {
+                            return self.invalid("Line break inside element line.".to_string())
+                        }
+                        Err(e) => return self.fail(e),
+                    }
+                }
+            }
+        }
+        if token.is_empty() {
+            return None;
+        }
+        if !is_number(&token) {
+            let value = String::from_utf8_lossy(&token).into_owned();
+            return self.invalid(format!("Invalid value '{}' in element line.", value));
+        }
+        // Only ascii characters were accepted.
+        Some(token.into_iter().map(char::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::grammar;
+    use super::*;
+    use std::fs;
+
+    fn assert_same(line: &str) {
+        let expected = grammar::data_line(line).ok();
+        #[cfg(feature = "simd")]
+        {
+            let found = tokenize(line).map(|v| v.into_iter().map(String::from).collect());
+            assert_eq!(found, expected, "line: {:?}", line);
+        }
+        // `LineTokens` stops at the first `\n`, like the `read_until()` producing the lines.
+        if line.find('\n').is_none_or(|i| i + 1 == line.len()) {
+            let mut tokens = LineTokens::new(line.as_bytes());
+            let values: Vec<String> = (&mut tokens).collect();
+            let found = tokens.error.map_or(Some(values), |_| None);
+            assert_eq!(found, expected, "line: {:?}", line);
+        }
+    }
+    #[test]
+    fn tokenize_ok() {
+        for line in [
+            "",
+            "\n",
+            "\r\n",
+            "\r",
+            "1 2 3",
+            "+7 -7 7 +5.21 -5.21 5.21 +0 -0 0 \r\n",
+            "034 8e3 8e-3",
+            " \t 1\t\t2  \n",
+            "3 0 1 2\n",
+            ".5 -.25 +.5 1. -1. 1.e5 .5e-2",
+            "nan NaN -nan inf -inf +inf",
+        ] {
+            assert_same(line);
+        }
+    }
+    #[test]
+    fn tokenize_err() {
+        for line in [
+            "++3", "+-3", "five", ".", "-.", "+.", ".e5", "1e", "1e+", "1-2", "1\n\n", "1\n\r",
+            "1\r\r", "1\n ", "1,2", "1 x", "\u{a0}1", "Nan", "infinity", "inf1", "--inf",
+        ] {
+            assert_same(line);
+        }
+    }
+    #[test]
+    fn tokenize_example_files() {
+        for entry in fs::read_dir("example_plys").unwrap() {
+            let bytes = fs::read(entry.unwrap().path()).unwrap();
+            let text = match std::str::from_utf8(&bytes) {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            for line in text.split_inclusive('\n') {
+                assert_same(line);
+            }
+        }
+    }
+    #[test]
+    fn tokenize_random_lines() {
+        const PIECES: &[&str] = &[
+            "0", "12", "-3", "+4", ".5", "6.", "7e8", "e-2", "9.25", " ", "  ", "\t", "\r", "\n",
+            "\r\n", "x", ".", "+", "nan", "inf",
+        ];
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for _ in 0..20_000 {
+            let mut line = String::new();
+            for _ in 0..next() % 12 {
+                line.push_str(PIECES[next() % PIECES.len()]);
+            }
+            assert_same(&line);
+        }
+    }
+    #[test]
+    fn line_tokens_stop_at_line_break() {
+        let mut reader: &[u8] = b" 1\t2 \r\n3 4\n";
+        let first: Vec<String> = LineTokens::new(&mut reader).collect();
+        assert_eq!(first, vec!["1", "2"]);
+        assert_eq!(reader, b"3 4\n");
+        let mut tokens = LineTokens::new(&mut reader);
+        assert_eq!(tokens.by_ref().count(), 2);
+        assert!(tokens.error.is_none());
+
+        let too_long = "1".repeat(MAX_TOKEN_LEN + 1);
+        let mut tokens = LineTokens::new(too_long.as_bytes());
+        assert_eq!(tokens.next(), None);
+        assert!(tokens.error.is_some());
+    }
+}
@@ -0,0 +1,242 @@
+//! Reads many files sharing one header.
+
+use super::{
+    check_checksum, element_error, element_slot, fill_buf, materializable_count, ChecksumReader,
+    ParseError, Parser, MAX_PREALLOCATION,
+};
+use crate::ply::{
+    ElementDef, Encoding, Header, Payload, Ply, Property, PropertyAccess, PropertyType, ScalarType,
+};
+use crate::util::{Crc32, LocationTracker};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+#[cfg(feature = "half")]
+use half::f16;
+use std::io::{BufRead, BufReader, Read, Result};
+
+/// What `BoundParser` knows about an element before reading it.
+#[derive(Debug, Clone)]
+struct ElementPlan {
+    /// Number of bytes of every binary entry, `None` if the element has lists,
+    /// custom types or no properties, or if the payload is ascii.
+    stride: Option<usize>,
+    /// Offset and type of every property within an entry of `stride` bytes.
+    fields: Vec<(usize, ScalarType)>,
+}
+
+impl ElementPlan {
+    fn new(element_def: &ElementDef, encoding: Encoding) -> Self {
+        let mut stride = Some(0usize);
+        let mut fields = Vec::new();
+        for p in &element_def.properties {
+            stride = match p.data_type {
+                PropertyType::Scalar(s) => stride.map(|n| {
+                    fields.push((n, s));
+                    n + s.size()
+                }),
+                PropertyType::Custom { .. } | PropertyType::List(..) => None,
+            };
+        }
+        let stride = stride.filter(|&n| n > 0 && encoding != Encoding::Ascii);
+        if stride.is_none() {
+            fields.clear();
+        }
+        ElementPlan { stride, fields }
+    }
+}
+
+/// A `Parser` bound to a header, for reading many files with the same schema.
+///
+/// The layout of every element is worked out once by `Parser::bind()`.
+/// Binary elements with only scalar properties are then decoded from the buffer of the reader
+/// directly, using the offsets of their properties.
+///
+/// `read_ply_checked()` reads files with their header and fails if it doesn't match the
+/// bound one, `read_payload_trusted()` reads payloads whose header was already consumed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::Parser;
+/// # use ply_rs::ply::DefaultElement;
+/// let path = "example_plys/house_2_ok_little_endian.ply";
+/// let p = Parser::<DefaultElement>::new();
+/// let header = p.read_header(&mut std::io::BufReader::new(std::fs::File::open(path).unwrap())).unwrap();
+/// let bound = p.bind(header);
+/// for _ in 0..3 {
+///     let ply = bound.read_ply_checked(&mut std::fs::File::open(path).unwrap()).unwrap();
+///     assert_eq!(ply.payload["vertex"].len(), 5);
+/// }
+/// ```
+pub struct BoundParser<E: PropertyAccess> {
+    parser: Parser<E>,
+    header: Header,
+    plans: Vec<ElementPlan>,
+}
+
+// Implemented by hand, deriving would require `E: Clone`.
+impl<E: PropertyAccess> Clone for BoundParser<E> {
+    fn clone(&self) -> Self {
+        BoundParser {
+            parser: self.parser.clone(),
+            header: self.header.clone(),
+            plans: self.plans.clone(),
+        }
+    }
+}
+
+impl<E: PropertyAccess> Parser<E> {
+    /// Binds a copy of this parser to `header`, see `BoundParser`.
+    pub fn bind(&self, header: Header) -> BoundParser<E> {
+        let plans = header
+            .elements
+            .iter()
+            .map(|e| ElementPlan::new(e, header.encoding))
+            .collect();
+        BoundParser {
+            parser: self.clone(),
+            header,
+            plans,
+        }
+    }
+}
+
+impl<E: PropertyAccess> BoundParser<E> {
+    /// The header this parser is bound to.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads a complete PLY file, like `Parser::read_ply()`.
+    ///
+    /// The header of the file has to be compatible with the bound header,
+    /// see `Header::compatible_with()`, the element counts may differ.
+    /// Otherwise, an error of kind `InvalidInput` is returned before the payload is read.
+    pub fn read_ply_checked<T: Read>(&self, source: &mut T) -> Result<Ply<E>> {
+        let mut source = BufReader::new(source);
+        let mut location = LocationTracker::new();
+        let header = self.parser.__read_header(&mut source, &mut location)?;
+        self.check_schema(&header)?;
+        let payload = match self.parser.options.expected_checksum(&header) {
+            None => self.read_payload(&mut source, &mut location, &header)?,
+            Some(expected) => {
+                let mut source = ChecksumReader {
+                    inner: &mut source,
+                    crc: Crc32::new(),
+                };
+                let payload = self.read_payload(&mut source, &mut location, &header)?;
+                check_checksum(expected, source.crc.finish())?;
+                payload
+            }
+        };
+        let mut ply = Ply::new();
+        ply.header = header;
+        ply.payload = payload;
+        Ok(ply)
+    }
+
+    /// Reads a payload described by the bound header, `reader` has to be at its start.
+    ///
+    /// Nothing is checked up front, the element counts of the bound header are read.
+    pub fn read_payload_trusted<T: BufRead>(&self, reader: &mut T) -> Result<Payload<E>> {
+        let mut location = LocationTracker::payload();
+        self.read_payload(reader, &mut location, &self.header)
+    }
+
+    /// Checks that `header` has the schema of the bound header.
+    fn check_schema(&self, header: &Header) -> Result<()> {
+        match header.incompatibility(&self.header) {
+            None => Ok(()),
+            Some(description) => Err(ParseError::SchemaMismatch(description).into_io()),
+        }
+    }
+
+    fn read_payload<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        header: &Header,
+    ) -> Result<Payload<E>> {
+        let mut payload = Payload::new();
+        for (e, plan) in header.elements.iter().zip(&self.plans) {
+            let elems = match (header.encoding, plan.stride) {
+                (Encoding::BinaryBigEndian, Some(stride)) => {
+                    self.read_fixed::<T, BigEndian>(reader, location, e, plan, stride)?
+                }
+                (Encoding::BinaryLittleEndian, Some(stride)) => {
+                    self.read_fixed::<T, LittleEndian>(reader, location, e, plan, stride)?
+                }
+                _ => self
+                    .parser
+                    .__read_payload_for_element(reader, location, e, header, false)?,
+            };
+            payload.insert(e.name.clone(), elems);
+        }
+        Ok(payload)
+    }
+
+    /// Reads the entries of a binary element of `stride` bytes each.
+    ///
+    /// Entries are decoded from the buffer of `reader` if it holds them completely.
+    fn read_fixed<T: BufRead, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        plan: &ElementPlan,
+        stride: usize,
+    ) -> Result<Vec<E>> {
+        let count = materializable_count(element_def)?;
+        let mut out = Vec::with_capacity(count.min(MAX_PREALLOCATION));
+        let mut scratch = vec![0; stride];
+        for index in 0..element_def.count {
+            let element = element_slot(&mut out, element_def, index);
+            let buf = fill_buf(reader).map_err(|e| element_error(element_def, index, e))?;
+            let result = if buf.len() >= stride {
+                let result =
+                    decode_fixed::<E, B>(&self.parser, &buf[..stride], element_def, plan, element);
+                reader.consume(stride);
+                result
+            } else {
+                reader.read_exact(&mut scratch).and_then(|()| {
+                    decode_fixed::<E, B>(&self.parser, &scratch, element_def, plan, element)
+                })
+            };
+            result.map_err(|e| element_error(element_def, index, e))?;
+            location.next_line();
+        }
+        Ok(out)
+    }
+}
+
+/// Decodes an entry of `plan.stride` bytes into `element`.
+fn decode_fixed<E: PropertyAccess, B: ByteOrder>(
+    parser: &Parser<E>,
+    bytes: &[u8],
+    element_def: &ElementDef,
+    plan: &ElementPlan,
+    element: &mut E,
+) -> Result<()> {
+    for (def, &(offset, scalar_type)) in element_def.properties.iter().zip(&plan.fields) {
+        let b = &bytes[offset..];
+        let property = match scalar_type {
+            ScalarType::Char => Property::Char(b[0] as i8),
+            ScalarType::UChar => Property::UChar(b[0]),
+            ScalarType::Short => Property::Short(B::read_i16(b)),
+            ScalarType::UShort => Property::UShort(B::read_u16(b)),
+            ScalarType::Int => Property::Int(B::read_i32(b)),
+            ScalarType::UInt => Property::UInt(B::read_u32(b)),
+            ScalarType::Float => Property::Float(B::read_f32(b)),
+            ScalarType::Double => Property::Double(B::read_f64(b)),
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => Property::LongLong(B::read_i64(b)),
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => Property::ULongLong(B::read_u64(b)),
+            #[cfg(feature = "half")]
+            ScalarType::Half => Property::Half(f16::from_bits(B::read_u16(b))),
+        };
+        if parser.options.keeps_property(&element_def.name, &def.name) {
+            parser.store_property(element, &def.name, property)?;
+        }
+    }
+    element.finalize(element_def)
+}
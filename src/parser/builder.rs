@@ -0,0 +1,174 @@
+//! Configures a `Parser` setting by setting.
+
+use super::{
+    CustomTypeHandler, NameCharacters, NameNormalization, Parser, ParserOptions, ProgressEvent,
+};
+use crate::ply::PropertyAccess;
+use std::marker::PhantomData;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// Builds a configured `Parser`, created by `Parser::builder()`.
+///
+/// Each setter forwards to the `ParserOptions` method of the same name, see there for details.
+/// A built parser can't be changed anymore, build another one for other settings.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::Parser;
+/// # use ply_rs::ply::DefaultElement;
+/// let p = Parser::<DefaultElement>::builder()
+///     .allow_indented_header(false)
+///     .skip_elements(["face"])
+///     .build();
+/// let mut f = std::fs::File::open("example_plys/house_ok_ascii.ply").unwrap();
+/// let ply = p.read_ply(&mut f).unwrap();
+/// assert!(!ply.payload.contains_key("face"));
+/// ```
+pub struct ParserBuilder<E: PropertyAccess> {
+    options: ParserOptions,
+    phantom: PhantomData<fn() -> E>,
+}
+
+impl<E: PropertyAccess> Parser<E> {
+    /// Starts configuring a parser, `Parser::new()` is the same as `Parser::builder().build()`.
+    pub fn builder() -> ParserBuilder<E> {
+        ParserBuilder::new()
+    }
+    /// The options the parser was configured with.
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+}
+
+impl<E: PropertyAccess> Default for ParserBuilder<E> {
+    fn default() -> Self {
+        ParserBuilder::new()
+    }
+}
+
+// Implemented by hand, deriving would require `E: Clone`.
+impl<E: PropertyAccess> Clone for ParserBuilder<E> {
+    fn clone(&self) -> Self {
+        ParserBuilder {
+            options: self.options.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: PropertyAccess> ParserBuilder<E> {
+    /// Starts from the default options.
+    pub fn new() -> Self {
+        ParserBuilder::from_options(ParserOptions::new())
+    }
+    /// Starts from `options`.
+    pub fn from_options(options: ParserOptions) -> Self {
+        ParserBuilder {
+            options,
+            phantom: PhantomData,
+        }
+    }
+    /// Creates the parser.
+    pub fn build(self) -> Parser<E> {
+        Parser::with_options(self.options)
+    }
+
+    /// See `ParserOptions::allow_indented_header()`.
+    pub fn allow_indented_header(self, allow: bool) -> Self {
+        self.map(|o| o.allow_indented_header(allow))
+    }
+    /// See `ParserOptions::verify_checksum()`.
+    pub fn verify_checksum(self, verify: bool) -> Self {
+        self.map(|o| o.verify_checksum(verify))
+    }
+    /// See `ParserOptions::normalize_property_names()`.
+    pub fn normalize_property_names(self, normalization: NameNormalization) -> Self {
+        self.map(|o| o.normalize_property_names(normalization))
+    }
+    /// See `ParserOptions::name_characters()`.
+    pub fn name_characters(self, characters: NameCharacters) -> Self {
+        self.map(|o| o.name_characters(characters))
+    }
+    /// See `ParserOptions::expect_fixed_list()`.
+    pub fn expect_fixed_list(self, element: &str, property: &str, len: usize) -> Self {
+        self.map(|o| o.expect_fixed_list(element, property, len))
+    }
+    /// See `ParserOptions::skip_rejected_properties()`.
+    pub fn skip_rejected_properties(self, skip: bool) -> Self {
+        self.map(|o| o.skip_rejected_properties(skip))
+    }
+    /// See `ParserOptions::multiline_ascii_elements()`.
+    pub fn multiline_ascii_elements(self, multiline: bool) -> Self {
+        self.map(|o| o.multiline_ascii_elements(multiline))
+    }
+    /// See `ParserOptions::skip_elements()`.
+    pub fn skip_elements<I, S>(self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.map(|o| o.skip_elements(names))
+    }
+    /// See `ParserOptions::keep_properties()`.
+    pub fn keep_properties<I, S>(self, element: &str, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.map(|o| o.keep_properties(element, names))
+    }
+    /// See `ParserOptions::max_element_count()`.
+    pub fn max_element_count(self, max: u64) -> Self {
+        self.map(|o| o.max_element_count(max))
+    }
+    /// See `ParserOptions::max_list_len()`.
+    pub fn max_list_len(self, max: usize) -> Self {
+        self.map(|o| o.max_list_len(max))
+    }
+    /// See `ParserOptions::max_total_properties()`.
+    pub fn max_total_properties(self, max: u64) -> Self {
+        self.map(|o| o.max_total_properties(max))
+    }
+    /// See `ParserOptions::max_header_line_len()`.
+    pub fn max_header_line_len(self, max: usize) -> Self {
+        self.map(|o| o.max_header_line_len(max))
+    }
+    /// See `ParserOptions::on_progress()`.
+    pub fn on_progress<F>(self, every: u64, hook: F) -> Self
+    where
+        F: Fn(&ProgressEvent) + Send + Sync + 'static,
+    {
+        self.map(|o| o.on_progress(every, hook))
+    }
+    /// See `ParserOptions::cancel_flag()`.
+    pub fn cancel_flag(self, flag: Arc<AtomicBool>) -> Self {
+        self.map(|o| o.cancel_flag(flag))
+    }
+    /// See `ParserOptions::on_unknown_type()`.
+    pub fn on_unknown_type<F>(self, hook: F) -> Self
+    where
+        F: Fn(&str) -> Option<CustomTypeHandler> + Send + Sync + 'static,
+    {
+        self.map(|o| o.on_unknown_type(hook))
+    }
+    /// See `ParserOptions::parallel_threshold()`.
+    #[cfg(feature = "parallel")]
+    pub fn parallel_threshold(self, threshold: u64) -> Self {
+        self.map(|o| o.parallel_threshold(threshold))
+    }
+
+    fn map(mut self, f: impl FnOnce(ParserOptions) -> ParserOptions) -> Self {
+        self.options = f(self.options);
+        self
+    }
+}
+
+impl<E: PropertyAccess> std::fmt::Debug for ParserBuilder<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ParserBuilder")
+            .field("options", &self.options)
+            .finish()
+    }
+}
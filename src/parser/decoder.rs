@@ -0,0 +1,629 @@
+//! Decodes PLY documents from bytes pushed by the caller, without doing any IO.
+//!
+//! `decode_entry()` decodes single entries from byte slices and is the only place entries
+//! are decoded. The blocking readers drive it over `BufRead::fill_buf()`, `ElementStream` and
+//! the `*_async` methods over the buffer of their asynchronous reader, `ElementDecoder` with
+//! the bytes it is fed, and the codec frames documents with the same entry lengths. The drivers only add
+//! what depends on how the bytes arrive, like checksums, progress and skipped elements.
+
+use super::{binary_list_count, element_error, parse_ascii_rethrow, unexpected_eof};
+use super::{HeaderAccumulator, Parser, ParserOptions, WrappedTokens};
+use crate::ply::{ElementDef, Encoding, Header, Property, PropertyAccess, PropertyType};
+use crate::util::LocationTracker;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::io;
+use std::io::{ErrorKind, Result};
+
+/// Produced by `ElementDecoder::decode()`, in the order they appear in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeEvent<E> {
+    /// The header is complete, produced once before any element.
+    Header(Header),
+    /// Entry `index` of the element at position `element` in `Header::elements`.
+    Element {
+        element: usize,
+        index: u64,
+        value: E,
+    },
+    /// All elements declared by the header were decoded, produced once.
+    End,
+}
+
+/// Decodes a document from byte slices, for sources the `read_*` methods can't use.
+///
+/// The caller fetches bytes however it likes, hands them over with `feed()`,
+/// and takes the header and the elements out with `decode()` as soon as they are complete.
+/// Chunks may be split anywhere. The grammar and the decoding are the ones of `Parser`.
+///
+/// Bytes after the last element are left undecoded.
+/// After an error, the decoder is in an unspecified state and should be dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::{DecodeEvent, ElementDecoder};
+/// # use ply_rs::ply::DefaultElement;
+/// let bytes = std::fs::read("example_plys/house_ok_ascii.ply").unwrap();
+/// let mut decoder = ElementDecoder::<DefaultElement>::new();
+/// let mut vertices = 0;
+/// let mut ended = false;
+/// for chunk in bytes.chunks(7) {
+///     decoder.feed(chunk);
+///     while let Some(event) = decoder.decode().unwrap() {
+///         match event {
+///             DecodeEvent::Element { element: 0, .. } => vertices += 1,
+///             DecodeEvent::End => ended = true,
+///             _ => (),
+///         }
+///     }
+/// }
+/// assert!(ended);
+/// assert_eq!(vertices, 5);
+/// ```
+pub struct ElementDecoder<E: PropertyAccess> {
+    parser: Parser<E>,
+    buf: Vec<u8>,
+    /// Start of the bytes in `buf` that weren't decoded yet.
+    start: usize,
+    /// Length the undecoded bytes need before decoding the next entry is retried.
+    needed: usize,
+    eof: bool,
+    location: LocationTracker,
+    state: DecodeState,
+}
+
+enum DecodeState {
+    Header(HeaderAccumulator),
+    /// Decoding entry `index` of the element at position `element`.
+    Payload {
+        header: Header,
+        element: usize,
+        index: u64,
+        ended: bool,
+    },
+}
+
+impl<E: PropertyAccess> Default for ElementDecoder<E> {
+    fn default() -> Self {
+        ElementDecoder::new()
+    }
+}
+
+impl<E: PropertyAccess> ElementDecoder<E> {
+    /// Creates a decoder with the default parser options.
+    pub fn new() -> Self {
+        ElementDecoder::with_options(ParserOptions::new())
+    }
+    /// Creates a decoder that decodes the header and the entries like a `Parser` with `options`.
+    ///
+    /// The options about reading a document as a whole are ignored: every element is decoded
+    /// despite `ParserOptions::skip_elements()`, checksums aren't verified, and
+    /// `ParserOptions::on_progress()` and `ParserOptions::cancel_flag()` have no effect,
+    /// the caller sees each entry as it is decoded anyway.
+    pub fn with_options(options: ParserOptions) -> Self {
+        ElementDecoder {
+            parser: Parser::with_options(options),
+            buf: Vec::new(),
+            start: 0,
+            needed: 0,
+            eof: false,
+            location: LocationTracker::new(),
+            state: DecodeState::Header(HeaderAccumulator::new()),
+        }
+    }
+    /// Appends the next bytes of the document.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+    /// Marks the end of the document, an incomplete header or element is an error from now on.
+    ///
+    /// Like the `read_*` methods, the last ascii line doesn't need a line break.
+    pub fn finish(&mut self) {
+        self.eof = true;
+    }
+    /// The header, once it was decoded.
+    pub fn header(&self) -> Option<&Header> {
+        match self.state {
+            DecodeState::Header(_) => None,
+            DecodeState::Payload { ref header, .. } => Some(header),
+        }
+    }
+    /// Bytes that were fed but not decoded yet.
+    pub fn remaining(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    /// Decodes the next event, `None` if more bytes are needed or `End` was produced already.
+    pub fn decode(&mut self) -> Result<Option<DecodeEvent<E>>> {
+        match self.state {
+            DecodeState::Header(ref mut accumulator) => {
+                while !accumulator.is_complete() {
+                    let rest = &self.buf[self.start..];
+                    let max = self.parser.options().max_header_line_len;
+                    let (len, complete) = match line_len(rest, self.eof) {
+                        Some(len) => (len, len <= max),
+                        None if rest.len() >= max => (rest.len(), false),
+                        None => return Ok(None),
+                    };
+                    self.location.next_line();
+                    let line =
+                        self.parser
+                            .header_line_str(&rest[..len], complete, &self.location)?;
+                    self.parser
+                        .push_header_line(accumulator, &self.location, line)?;
+                    self.start += len;
+                }
+                let accumulator = std::mem::take(accumulator);
+                let header = self.parser.finish_header(accumulator, &mut self.location)?;
+                self.state = DecodeState::Payload {
+                    header: header.clone(),
+                    element: 0,
+                    index: 0,
+                    ended: false,
+                };
+                Ok(Some(DecodeEvent::Header(header)))
+            }
+            DecodeState::Payload {
+                ref header,
+                ref mut element,
+                ref mut index,
+                ref mut ended,
+            } => {
+                while header
+                    .elements
+                    .get(*element)
+                    .is_some_and(|def| *index >= def.count)
+                {
+                    *element += 1;
+                    *index = 0;
+                }
+                let Some(def) = header.elements.get(*element) else {
+                    if *ended {
+                        return Ok(None);
+                    }
+                    *ended = true;
+                    return Ok(Some(DecodeEvent::End));
+                };
+                let rest = &self.buf[self.start..];
+                if rest.len() < self.needed && !self.eof {
+                    return Ok(None);
+                }
+                let entry = decode_entry(
+                    &self.parser,
+                    header.encoding,
+                    def,
+                    *index,
+                    &mut self.location,
+                    rest,
+                    self.eof,
+                )?;
+                let (value, len) = match entry {
+                    Entry::Complete(value, len) => (value, len),
+                    Entry::Incomplete(needed) => {
+                        self.needed = needed;
+                        return Ok(None);
+                    }
+                };
+                self.start += len;
+                self.needed = 0;
+                let event = DecodeEvent::Element {
+                    element: *element,
+                    index: *index,
+                    value,
+                };
+                *index += 1;
+                Ok(Some(event))
+            }
+        }
+    }
+}
+
+/// An entry decoded by `decode_entry()` from the start of a byte slice.
+pub(crate) enum Entry<E> {
+    /// The entry and the number of bytes it occupied.
+    Complete(E, usize),
+    /// The slice ends within the entry, which is at least this many bytes long.
+    Incomplete(usize),
+}
+
+impl<T> Entry<T> {
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> Entry<U> {
+        match self {
+            Entry::Complete(value, len) => Entry::Complete(f(value), len),
+            Entry::Incomplete(needed) => Entry::Incomplete(needed),
+        }
+    }
+}
+
+/// Decodes entry `index` of `def` at the start of `bytes`, `eof` if nothing follows them.
+///
+/// This is the decoding without IO that all readers drive, see `decode_entry_into()`.
+pub(crate) fn decode_entry<E: PropertyAccess>(
+    parser: &Parser<E>,
+    encoding: Encoding,
+    def: &ElementDef,
+    index: u64,
+    location: &mut LocationTracker,
+    bytes: &[u8],
+    eof: bool,
+) -> Result<Entry<E>> {
+    let mut value = E::new_from_def(def);
+    let entry = decode_entry_into(
+        parser, encoding, def, index, location, bytes, eof, None, &mut value,
+    )?;
+    Ok(entry.map(|()| value))
+}
+
+/// Same as `decode_entry()`, but decodes into `element`, overwriting its properties,
+/// and reuses the list buffers of `pool` if given.
+///
+/// Binary entries are framed before they are decoded,
+/// so list limits apply before the bytes of a list are waited for.
+/// With `ParserOptions::multiline_ascii_elements()`, an ascii entry takes as many lines as
+/// its values need, `location` is left after the last of them.
+/// At `eof`, an incomplete entry is an error instead.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn decode_entry_into<E: PropertyAccess>(
+    parser: &Parser<E>,
+    encoding: Encoding,
+    def: &ElementDef,
+    index: u64,
+    location: &mut LocationTracker,
+    bytes: &[u8],
+    eof: bool,
+    pool: Option<&mut [Property]>,
+    element: &mut E,
+) -> Result<Entry<()>> {
+    let (len, entry) = match encoding {
+        Encoding::Ascii => {
+            let len = match line_len(bytes, eof) {
+                Some(0) if !def.properties.is_empty() => {
+                    return Err(unexpected_eof(def, index, None))
+                }
+                Some(len) => len,
+                None => return Ok(Entry::Incomplete(bytes.len() + 1)),
+            };
+            if parser.options.multiline_ascii_elements && element.raw_mut().is_none() {
+                return decode_wrapped(
+                    parser, def, index, location, bytes, len, eof, pool, element,
+                );
+            }
+            let line = &bytes[..len];
+            (
+                len,
+                parser.read_ascii_line(line, location, def, index, pool, element),
+            )
+        }
+        Encoding::BinaryBigEndian => {
+            let len = binary_len::<BigEndian>(parser, def, index, bytes, eof)?;
+            if len > bytes.len() {
+                return Ok(Entry::Incomplete(len));
+            }
+            let mut entry = &bytes[..len];
+            let result =
+                parser.read_binary_element_into::<_, BigEndian>(&mut entry, def, pool, element);
+            (len, result.map_err(|e| element_error(def, index, e)))
+        }
+        Encoding::BinaryLittleEndian => {
+            let len = binary_len::<LittleEndian>(parser, def, index, bytes, eof)?;
+            if len > bytes.len() {
+                return Ok(Entry::Incomplete(len));
+            }
+            let mut entry = &bytes[..len];
+            let result =
+                parser.read_binary_element_into::<_, LittleEndian>(&mut entry, def, pool, element);
+            (len, result.map_err(|e| element_error(def, index, e)))
+        }
+    };
+    entry?;
+    location.next_line();
+    Ok(Entry::Complete((), len))
+}
+
+/// Same as `decode_entry()`, but only finds the length of the entry, for skipped elements.
+///
+/// Ascii entries that may wrap across lines are decoded to find their end.
+#[cfg(feature = "futures-io")]
+pub(crate) fn skip_entry<E: PropertyAccess>(
+    parser: &Parser<E>,
+    encoding: Encoding,
+    def: &ElementDef,
+    index: u64,
+    location: &mut LocationTracker,
+    bytes: &[u8],
+    eof: bool,
+) -> Result<Entry<()>> {
+    let len = match encoding {
+        Encoding::Ascii if parser.options.multiline_ascii_elements => {
+            let mut scratch = E::new_from_def(def);
+            let entry = decode_entry_into(
+                parser,
+                encoding,
+                def,
+                index,
+                location,
+                bytes,
+                eof,
+                None,
+                &mut scratch,
+            );
+            return entry;
+        }
+        Encoding::Ascii => match line_len(bytes, eof) {
+            Some(0) if !def.properties.is_empty() => return Err(unexpected_eof(def, index, None)),
+            Some(len) => len,
+            None => return Ok(Entry::Incomplete(bytes.len() + 1)),
+        },
+        Encoding::BinaryBigEndian => binary_len::<BigEndian>(parser, def, index, bytes, eof)?,
+        Encoding::BinaryLittleEndian => binary_len::<LittleEndian>(parser, def, index, bytes, eof)?,
+    };
+    if len > bytes.len() {
+        return Ok(Entry::Incomplete(len));
+    }
+    location.next_line();
+    Ok(Entry::Complete((), len))
+}
+
+/// Length of the binary entry at the start of `bytes` as framed by `binary_entry_len()`,
+/// an error if `bytes` end within it at `eof`.
+fn binary_len<B: ByteOrder>(
+    parser: &Parser<impl PropertyAccess>,
+    def: &ElementDef,
+    index: u64,
+    bytes: &[u8],
+    eof: bool,
+) -> Result<usize> {
+    let len = binary_entry_len::<B>(parser.options(), def, bytes)
+        .map_err(|e| element_error(def, index, e))?;
+    if len > bytes.len() && eof {
+        let e = io::Error::new(ErrorKind::UnexpectedEof, "Payload ends within the entry.");
+        return Err(element_error(def, index, e));
+    }
+    Ok(len)
+}
+
+/// Ascii part of `decode_entry_into()` for entries that may wrap across lines,
+/// the first line of the entry is `first` bytes long.
+#[allow(clippy::too_many_arguments)]
+fn decode_wrapped<E: PropertyAccess>(
+    parser: &Parser<E>,
+    def: &ElementDef,
+    index: u64,
+    location: &mut LocationTracker,
+    bytes: &[u8],
+    first: usize,
+    eof: bool,
+    pool: Option<&mut [Property]>,
+    element: &mut E,
+) -> Result<Entry<()>> {
+    // A line without a line break may continue in the bytes still to come.
+    let end = match bytes.iter().rposition(|&b| b == b'\n') {
+        _ if eof => bytes.len(),
+        Some(pos) => pos + 1,
+        None => first,
+    };
+    let mut rest = &bytes[first..end];
+    let mut line = bytes[..first].to_vec();
+    let mut last = *location;
+    let mut tokens = WrappedTokens {
+        reader: &mut rest,
+        location: &mut last,
+        line: &mut line,
+        pending: true,
+        values: Vec::new().into_iter(),
+        error: None,
+    };
+    let result = parser.read_ascii_tokens(&mut tokens, def, pool, element);
+    let error = tokens.error.take();
+    if result.is_err() && error.is_none() && rest.is_empty() && !eof {
+        // The values ran out with the lines, the next ones may complete the entry.
+        return Ok(Entry::Incomplete(bytes.len() + 1));
+    }
+    let result = match error {
+        Some(e) => Err(e),
+        None => result,
+    };
+    result.or_else(|e| {
+        let line_str = String::from_utf8_lossy(&line);
+        parse_ascii_rethrow(&last, &line_str, e, "Couln't read element line.")
+            .map_err(|e| element_error(def, index, e))
+    })?;
+    *location = last;
+    location.next_line();
+    Ok(Entry::Complete((), end - rest.len()))
+}
+
+/// Length of the bytes at the start of `next` that continue an incomplete entry
+/// missing at least `missing` bytes, and whether they may complete it.
+///
+/// For drivers that must not take bytes of the following entries from their source.
+pub(crate) fn continuation_len(encoding: Encoding, next: &[u8], missing: usize) -> (usize, bool) {
+    match encoding {
+        Encoding::Ascii => match next.iter().position(|&b| b == b'\n') {
+            Some(pos) => (pos + 1, true),
+            None => (next.len(), false),
+        },
+        _ => (missing.min(next.len()), missing <= next.len()),
+    }
+}
+
+/// Length of the line at the start of `bytes` including its line break, `None` if it isn't complete yet.
+pub(crate) fn line_len(bytes: &[u8], eof: bool) -> Option<usize> {
+    match bytes.iter().position(|&b| b == b'\n') {
+        Some(pos) => Some(pos + 1),
+        None if eof => Some(bytes.len()),
+        None => None,
+    }
+}
+
+/// Length of the binary entry of `def` at the start of `bytes`.
+///
+/// If `bytes` ends within the entry, the length is only known up to the next list count,
+/// the returned length is then larger than `bytes` and a lower bound.
+/// List counts are checked against `ParserOptions::max_list_len()` before the list is waited for.
+pub(crate) fn binary_entry_len<B: ByteOrder>(
+    options: &ParserOptions,
+    def: &ElementDef,
+    bytes: &[u8],
+) -> Result<usize> {
+    let mut len = 0usize;
+    for p in &def.properties {
+        len += match p.data_type {
+            PropertyType::Scalar(ref s) => s.size(),
+            PropertyType::Custom { size, .. } => size,
+            PropertyType::List(ref index_type, ref s) => {
+                let count = match bytes.get(len..len + index_type.size()) {
+                    Some(b) => binary_list_count::<B>(index_type, b)?,
+                    None => return Ok(len + index_type.size()),
+                };
+                options
+                    .check_list_limit(&def.name, &p.name, count)
+                    .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+                count
+                    .checked_mul(s.size())
+                    .and_then(|n| n.checked_add(index_type.size()))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("List '{}' is too long: {} entries.", p.name, count),
+                        )
+                    })?
+            }
+        };
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ply::{DefaultElement, Payload};
+    use std::fs;
+
+    /// Decodes `bytes` fed in chunks of the given lengths, repeated until the end.
+    fn decode_chunked(bytes: &[u8], lens: &[usize]) -> Result<(Header, Payload<DefaultElement>)> {
+        let mut decoder = ElementDecoder::<DefaultElement>::new();
+        let mut header = None;
+        let mut payload = Payload::new();
+        let mut chunks = Vec::new();
+        let mut rest = bytes;
+        for &len in lens.iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (chunk, tail) = rest.split_at(len.min(rest.len()));
+            chunks.push(chunk);
+            rest = tail;
+        }
+        let mut chunks = chunks.into_iter();
+        loop {
+            match decoder.decode()? {
+                Some(DecodeEvent::Header(h)) => {
+                    for e in &h.elements {
+                        payload.insert(e.name.clone(), Vec::new());
+                    }
+                    header = Some(h);
+                }
+                Some(DecodeEvent::Element {
+                    element,
+                    index,
+                    value,
+                }) => {
+                    let name = &header.as_ref().unwrap().elements[element].name;
+                    let elements = payload.get_mut(name).unwrap();
+                    assert_eq!(elements.len() as u64, index);
+                    elements.push(value);
+                }
+                Some(DecodeEvent::End) => return Ok((header.unwrap(), payload)),
+                None => match chunks.next() {
+                    Some(chunk) => decoder.feed(chunk),
+                    None => decoder.finish(),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn decode_chunks_ok() {
+        let p = Parser::<DefaultElement>::new();
+        for entry in fs::read_dir("example_plys").unwrap() {
+            let path = entry.unwrap().path();
+            let path = path.to_str().unwrap();
+            let bytes = fs::read(path).unwrap();
+            let expected = p.read_ply(&mut &bytes[..]);
+            for lens in [&[1][..], &[2, 5, 3], &[7, 64, 1], &[4096]] {
+                match (decode_chunked(&bytes, lens), &expected) {
+                    (Ok((header, payload)), Ok(expected)) => {
+                        assert_eq!(header, expected.header, "{}", path);
+                        assert_eq!(payload, expected.payload, "{}", path);
+                    }
+                    (Err(_), Err(_)) => (),
+                    (decoded, expected) => panic!(
+                        "{} {:?}: {:?} != {:?}",
+                        path,
+                        lens,
+                        decoded.err(),
+                        expected.as_ref().err()
+                    ),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decode_wrapped_ok() {
+        let bytes = fs::read("example_plys/triangles_wrapped_ascii.ply").unwrap();
+        let options = ParserOptions::new().multiline_ascii_elements(true);
+        let expected = Parser::<DefaultElement>::with_options(options.clone())
+            .read_ply(&mut &bytes[..])
+            .unwrap();
+        let mut decoder = ElementDecoder::<DefaultElement>::with_options(options);
+        let mut faces = Vec::new();
+        for chunk in bytes.chunks(3) {
+            decoder.feed(chunk);
+            while let Some(event) = decoder.decode().unwrap() {
+                if let DecodeEvent::Element {
+                    element: 1, value, ..
+                } = event
+                {
+                    faces.push(value);
+                }
+            }
+        }
+        decoder.finish();
+        while decoder.decode().unwrap().is_some() {}
+        assert_eq!(faces, expected.payload["face"]);
+    }
+
+    #[test]
+    fn decode_trailing_bytes_ok() {
+        let mut bytes = fs::read("example_plys/house_2_ok_little_endian.ply").unwrap();
+        bytes.extend_from_slice(b"trailing");
+        let mut decoder = ElementDecoder::<DefaultElement>::new();
+        decoder.feed(&bytes);
+        while decoder.decode().unwrap() != Some(DecodeEvent::End) {}
+        assert_eq!(decoder.decode().unwrap(), None);
+        assert_eq!(decoder.remaining(), b"trailing");
+    }
+
+    #[test]
+    fn decode_truncated_err() {
+        let bytes = fs::read("example_plys/house_ok_ascii.ply").unwrap();
+        let mut decoder = ElementDecoder::<DefaultElement>::new();
+        // Cut after a complete line, two faces are missing.
+        decoder.feed(&bytes[..bytes.len() - 16]);
+        decoder.finish();
+        let err = loop {
+            match decoder.decode() {
+                Ok(Some(DecodeEvent::End)) | Ok(None) => panic!("truncated input decoded"),
+                Ok(Some(_)) => (),
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,292 @@
+use super::Line;
+use crate::ply::{Comment, ElementDef, Encoding, Header, ObjInfo, PropertyDef, Version};
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// Describes why a sequence of header lines doesn't form a valid header.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderError {
+    /// The first line wasn't the magic number `ply`.
+    MissingMagicNumber(Line),
+    /// The magic number `ply` appeared a second time.
+    UnexpectedMagicNumber,
+    /// A format line contradicts a previous one.
+    ContradictingFormat {
+        previous: (Encoding, Version),
+        found: (Encoding, Version),
+    },
+    /// A property was declared before any element.
+    PropertyWithoutElement(PropertyDef),
+    /// A line was pushed after `end_header`.
+    LineAfterEndHeader(Line),
+    /// The header has no format line.
+    MissingFormat,
+    /// The header wasn't terminated with `end_header`.
+    MissingEndHeader,
+}
+
+impl Display for HeaderError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            HeaderError::MissingMagicNumber(ref l) => {
+                write!(f, "Expected magic number 'ply', but saw '{:?}'.", l)
+            }
+            HeaderError::UnexpectedMagicNumber => f.write_str("Unexpected 'ply' found."),
+            HeaderError::ContradictingFormat { previous, found } => write!(
+                f,
+                "Found contradicting format definition:\n\
+                \tEncoding: {:?}, Version: {:?}\n\
+                previous definition:\n\
+                \tEncoding: {:?}, Version: {:?}",
+                found.0, found.1, previous.0, previous.1
+            ),
+            HeaderError::PropertyWithoutElement(ref p) => {
+                write!(f, "Property '{:?}' found without preceding element.", p)
+            }
+            HeaderError::LineAfterEndHeader(ref l) => {
+                write!(f, "Line '{:?}' found after 'end_header'.", l)
+            }
+            HeaderError::MissingFormat => f.write_str("No format line found."),
+            HeaderError::MissingEndHeader => f.write_str("No 'end_header' line found."),
+        }
+    }
+}
+
+impl error::Error for HeaderError {}
+
+/// Builds a `Header` from a sequence of `Line`s.
+///
+/// Performs the same validation as `Parser::read_header()`, which is built on top of it.
+/// Useful if the header lines don't come from a `BufRead`, see `Parser::read_header_line()`.
+///
+/// Once the first element is declared, comments directly preceding an element or property line
+/// are attached to its definition. All other comments end up in `Header::comments`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::{HeaderAccumulator, Parser};
+/// # use ply_rs::ply::DefaultElement;
+/// let p = Parser::<DefaultElement>::new();
+/// let mut acc = HeaderAccumulator::new();
+/// for l in &["ply", "format ascii 1.0", "element vertex 3", "property float x", "end_header"] {
+///     acc.push(p.read_header_line(l).unwrap()).unwrap();
+/// }
+/// let header = acc.finish().unwrap();
+/// assert_eq!(header.elements[0].count, 3);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct HeaderAccumulator {
+    seen_magic_number: bool,
+    seen_end_header: bool,
+    format: Option<(Encoding, Version)>,
+    obj_infos: Vec<ObjInfo>,
+    elements: Vec<ElementDef>,
+    comments: Vec<Comment>,
+    pending_comments: Vec<Comment>,
+    /// Positions of the properties of the last element by name, finds repeated declarations
+    /// without scanning the properties, which are thousands for some files.
+    property_indices: HashMap<String, usize>,
+}
+
+impl HeaderAccumulator {
+    /// Creates an accumulator expecting the magic number as first line.
+    pub fn new() -> Self {
+        HeaderAccumulator {
+            seen_magic_number: false,
+            seen_end_header: false,
+            format: None,
+            obj_infos: Vec::new(),
+            elements: Vec::new(),
+            comments: Vec::new(),
+            pending_comments: Vec::new(),
+            property_indices: HashMap::new(),
+        }
+    }
+    /// Adds the next header line.
+    pub fn push(&mut self, line: Line) -> Result<(), HeaderError> {
+        if self.seen_end_header {
+            return Err(HeaderError::LineAfterEndHeader(line));
+        }
+        if !self.seen_magic_number {
+            return match line {
+                Line::MagicNumber => {
+                    self.seen_magic_number = true;
+                    Ok(())
+                }
+                l => Err(HeaderError::MissingMagicNumber(l)),
+            };
+        }
+        match line {
+            Line::Comment(c) if !self.elements.is_empty() => {
+                self.pending_comments.push(c);
+                return Ok(());
+            }
+            Line::Element(_) | Line::Property(_) => (),
+            _ => self.comments.append(&mut self.pending_comments),
+        }
+        match line {
+            Line::MagicNumber => return Err(HeaderError::UnexpectedMagicNumber),
+            Line::Format(t) => match self.format {
+                None => self.format = Some(t),
+                Some(f) => {
+                    if f != t {
+                        return Err(HeaderError::ContradictingFormat {
+                            previous: f,
+                            found: t,
+                        });
+                    }
+                }
+            },
+            Line::ObjInfo(o) => self.obj_infos.push(o),
+            Line::Comment(c) => self.comments.push(c),
+            Line::Element(mut e) => {
+                e.comments.append(&mut self.pending_comments);
+                self.property_indices.clear();
+                self.elements.push(e);
+            }
+            Line::Property(mut p) => match self.elements.last_mut() {
+                None => return Err(HeaderError::PropertyWithoutElement(p)),
+                Some(e) => {
+                    p.comments.append(&mut self.pending_comments);
+                    match self.property_indices.get(&p.name) {
+                        Some(&i) if e.properties[i].data_type == p.data_type => {
+                            e.properties[i].comments.append(&mut p.comments)
+                        }
+                        Some(_) => e.properties.push(p),
+                        None => {
+                            self.property_indices
+                                .insert(p.name.clone(), e.properties.len());
+                            e.properties.push(p);
+                        }
+                    }
+                }
+            },
+            Line::EndHeader => self.seen_end_header = true,
+        }
+        Ok(())
+    }
+    /// Returns true once `end_header` has been pushed.
+    pub fn is_complete(&self) -> bool {
+        self.seen_end_header
+    }
+    /// Builds the header from all pushed lines.
+    pub fn finish(self) -> Result<Header, HeaderError> {
+        if !self.seen_end_header {
+            return Err(HeaderError::MissingEndHeader);
+        }
+        let (encoding, version) = match self.format {
+            None => return Err(HeaderError::MissingFormat),
+            Some(f) => f,
+        };
+        Ok(Header {
+            encoding,
+            version,
+            obj_infos: self.obj_infos,
+            comments: self.comments,
+            elements: self.elements,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ply::{PropertyType, ScalarType};
+
+    fn accumulate(lines: Vec<Line>) -> Result<Header, HeaderError> {
+        let mut acc = HeaderAccumulator::new();
+        for l in lines {
+            acc.push(l)?;
+        }
+        acc.finish()
+    }
+    fn format() -> Line {
+        Line::Format((Encoding::Ascii, Version { major: 1, minor: 0 }))
+    }
+    fn property() -> Line {
+        Line::Property(PropertyDef::new(
+            "x",
+            PropertyType::Scalar(ScalarType::Float),
+        ))
+    }
+    #[test]
+    fn accumulate_ok() {
+        let h = accumulate(vec![
+            Line::MagicNumber,
+            format(),
+            Line::Comment("hi".to_string()),
+            Line::Element(ElementDef::new("vertex")),
+            property(),
+            property(),
+            format(),
+            Line::EndHeader,
+        ])
+        .unwrap();
+        assert_eq!(h.encoding, Encoding::Ascii);
+        assert_eq!(h.comments, vec!["hi".to_string()]);
+        assert_eq!(h.elements[0].properties.len(), 1);
+    }
+    #[test]
+    fn accumulate_comments_ok() {
+        let comment = |c: &str| Line::Comment(c.to_string());
+        let h = accumulate(vec![
+            Line::MagicNumber,
+            format(),
+            comment("file"),
+            Line::Element(ElementDef::new("vertex")),
+            comment("x is in millimeters"),
+            comment("measured"),
+            property(),
+            comment("faces"),
+            Line::Element(ElementDef::new("face")),
+            comment("trailing"),
+            Line::EndHeader,
+        ])
+        .unwrap();
+        assert_eq!(h.comments, vec!["file".to_string(), "trailing".to_string()]);
+        assert!(h.elements[0].comments.is_empty());
+        assert_eq!(
+            h.elements[0].properties[0].comments,
+            vec!["x is in millimeters".to_string(), "measured".to_string()]
+        );
+        assert_eq!(h.elements[1].comments, vec!["faces".to_string()]);
+    }
+    #[test]
+    fn accumulate_err() {
+        assert_eq!(
+            accumulate(vec![format()]),
+            Err(HeaderError::MissingMagicNumber(format()))
+        );
+        assert_eq!(
+            accumulate(vec![Line::MagicNumber, Line::MagicNumber]),
+            Err(HeaderError::UnexpectedMagicNumber)
+        );
+        assert_eq!(
+            accumulate(vec![Line::MagicNumber, format(), property()]),
+            Err(HeaderError::PropertyWithoutElement(PropertyDef::new(
+                "x",
+                PropertyType::Scalar(ScalarType::Float)
+            )))
+        );
+        let binary = (Encoding::BinaryLittleEndian, Version { major: 1, minor: 0 });
+        assert!(matches!(
+            accumulate(vec![Line::MagicNumber, format(), Line::Format(binary)]),
+            Err(HeaderError::ContradictingFormat { .. })
+        ));
+        assert_eq!(
+            accumulate(vec![Line::MagicNumber, Line::EndHeader]),
+            Err(HeaderError::MissingFormat)
+        );
+        assert_eq!(
+            accumulate(vec![Line::MagicNumber, format()]),
+            Err(HeaderError::MissingEndHeader)
+        );
+        assert_eq!(
+            accumulate(vec![Line::MagicNumber, format(), Line::EndHeader, format()]),
+            Err(HeaderError::LineAfterEndHeader(format()))
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Reads ranges of elements from seekable asynchronous sources.
+
+use super::{element_error, BigEndian, LittleEndian, Parser, MAX_PREALLOCATION};
+use crate::ply::{ElementDef, Encoding, Header, PropertyAccess, PropertyType};
+use futures_io::{AsyncRead, AsyncSeek};
+use std::io;
+use std::io::{ErrorKind, Result, SeekFrom};
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::{ready, Poll};
+
+/// Number of bytes requested per read while looking for the end of the header.
+const HEADER_CHUNK: usize = 1024;
+
+/// A PLY file whose elements are read range by range, see `Parser::open_indexed()`.
+///
+/// Only the bytes of the requested entries are read, the source is seeked to them.
+/// With a source issuing HTTP range requests, a viewer fetches the header and the entries
+/// it needs without downloading the rest of the file.
+///
+/// Requires the `futures-io` feature.
+pub struct IndexedPly<E: PropertyAccess, R> {
+    parser: Parser<E>,
+    reader: R,
+    header: Header,
+    /// Offset of the first entry and size of an entry of each element, in header order.
+    layout: Vec<(u64, usize)>,
+}
+
+impl<E: PropertyAccess> Parser<E> {
+    /// Reads the header of `reader` and computes where the entries of each element are stored.
+    ///
+    /// The payload has to be binary and all properties need a fixed size, that is: no lists.
+    /// Otherwise an error of kind `Unsupported` is returned, before any payload is read.
+    /// The header is read in chunks of 1 KiB, bytes after it are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use futures::executor::block_on;
+    /// # use futures::io::Cursor;
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::{DefaultElement, Property};
+    /// let bytes = std::fs::read("example_plys/points_ok_little_endian.ply").unwrap();
+    /// let p = Parser::<DefaultElement>::new();
+    /// let mut ply = block_on(p.open_indexed(Cursor::new(bytes))).unwrap();
+    /// let vertices = block_on(ply.read_range("vertex", 1..3)).unwrap();
+    /// assert_eq!(vertices.len(), 2);
+    /// ```
+    pub async fn open_indexed<R: AsyncRead + AsyncSeek + Unpin>(
+        &self,
+        mut reader: R,
+    ) -> Result<IndexedPly<E, R>> {
+        seek_async(&mut reader, SeekFrom::Start(0)).await?;
+        let (header, header_len) = self.read_header_chunked(&mut reader).await?;
+        if header.encoding == Encoding::Ascii {
+            return Err(unsupported("ascii payloads can't be indexed"));
+        }
+        let mut layout = Vec::with_capacity(header.elements.len());
+        let mut offset = header_len;
+        for e in &header.elements {
+            let stride = fixed_size(e)?;
+            layout.push((offset, stride));
+            offset = (stride as u64)
+                .checked_mul(e.count)
+                .and_then(|size| size.checked_add(offset))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Element '{}' is too large to be indexed.", e.name),
+                    )
+                })?;
+        }
+        Ok(IndexedPly {
+            parser: self.clone(),
+            reader,
+            header,
+            layout,
+        })
+    }
+
+    /// Reads chunks of `reader` until the `end_header` line, returns the header and its length.
+    async fn read_header_chunked<R: AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+    ) -> Result<(Header, u64)> {
+        let mut bytes = Vec::new();
+        let mut line_start = 0;
+        loop {
+            let scanned = bytes.len();
+            bytes.resize(scanned + HEADER_CHUNK, 0);
+            let n = read_async(reader, &mut bytes[scanned..]).await?;
+            bytes.truncate(scanned + n);
+            if n == 0 {
+                // Let the parser describe what is missing.
+                self.read_header(&mut &bytes[..])?;
+                return Err(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "The file ends within the header.",
+                ));
+            }
+            while let Some(i) = bytes[line_start..].iter().position(|&b| b == b'\n') {
+                let line = &bytes[line_start..line_start + i];
+                line_start += i + 1;
+                if line.trim_ascii() == b"end_header" {
+                    let header = self.read_header(&mut &bytes[..line_start])?;
+                    return Ok((header, line_start as u64));
+                }
+            }
+        }
+    }
+}
+
+impl<E: PropertyAccess, R: AsyncRead + AsyncSeek + Unpin> IndexedPly<E, R> {
+    /// The header of the file.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the entries `range` of the element `element`.
+    ///
+    /// Seeks to the first entry and reads exactly the bytes of the range.
+    /// Fails with an error of kind `InvalidInput` if there is no such element
+    /// or the range exceeds its count.
+    pub async fn read_range(&mut self, element: &str, range: Range<usize>) -> Result<Vec<E>> {
+        let index = match self.header.elements.iter().position(|e| e.name == element) {
+            Some(index) => index,
+            None => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Element '{}' isn't defined in the header.", element),
+                ))
+            }
+        };
+        let element_def = &self.header.elements[index];
+        if range.start > range.end || range.end as u64 > element_def.count {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Range {:?} is out of the {} entries of element '{}'.",
+                    range, element_def.count, element
+                ),
+            ));
+        }
+        let (offset, stride) = self.layout[index];
+        let mut elements = Vec::with_capacity(range.len().min(MAX_PREALLOCATION));
+        if range.is_empty() || stride == 0 {
+            // Entries of elements without properties are empty.
+            for index in range {
+                let element = self
+                    .decode_entry(element_def, &[])
+                    .map_err(|e| element_error(element_def, index as u64, e))?;
+                elements.push(element);
+            }
+            return Ok(elements);
+        }
+        let start = offset + range.start as u64 * stride as u64;
+        seek_async(&mut self.reader, SeekFrom::Start(start)).await?;
+        // The range may be as large as the count in the header, the bytes are fetched in batches
+        // so the buffer only grows with entries that are actually there.
+        let mut bytes = Vec::new();
+        let mut first = range.start;
+        while first < range.end {
+            let len = MAX_PREALLOCATION.min(range.end - first);
+            bytes.resize(len * stride, 0);
+            read_exact_async(&mut self.reader, &mut bytes)
+                .await
+                .map_err(|e| element_error(element_def, first as u64, e))?;
+            for (i, chunk) in bytes.chunks_exact(stride).enumerate() {
+                let element = self
+                    .decode_entry(element_def, chunk)
+                    .map_err(|e| element_error(element_def, (first + i) as u64, e))?;
+                elements.push(element);
+            }
+            first += len;
+        }
+        Ok(elements)
+    }
+
+    fn decode_entry(&self, element_def: &ElementDef, chunk: &[u8]) -> Result<E> {
+        let (element, _) = match self.header.encoding {
+            Encoding::BinaryBigEndian => self
+                .parser
+                .read_binary_element_from_slice::<BigEndian>(chunk, element_def),
+            _ => self
+                .parser
+                .read_binary_element_from_slice::<LittleEndian>(chunk, element_def),
+        }?;
+        Ok(element)
+    }
+
+    /// Returns the source, at an unspecified position.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Size of every entry of `element_def`, fails for elements with lists.
+fn fixed_size(element_def: &ElementDef) -> Result<usize> {
+    element_def
+        .properties
+        .iter()
+        .map(|p| match p.data_type {
+            PropertyType::Scalar(ref s) => Ok(s.size()),
+            PropertyType::Custom { size, .. } => Ok(size),
+            PropertyType::List(_, _) => Err(unsupported(&format!(
+                "property '{}' of element '{}' is a list, its entries don't have a fixed size",
+                p.name, element_def.name
+            ))),
+        })
+        .sum()
+}
+
+fn unsupported(reason: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::Unsupported,
+        format!("The file can't be opened indexed: {}.", reason),
+    )
+}
+
+async fn seek_async<R: AsyncSeek + Unpin>(reader: &mut R, pos: SeekFrom) -> Result<u64> {
+    std::future::poll_fn(|cx| Pin::new(&mut *reader).poll_seek(cx, pos)).await
+}
+
+/// Reads once into `buf`, retrying interrupted reads.
+async fn read_async<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    std::future::poll_fn(|cx| loop {
+        match ready!(Pin::new(&mut *reader).poll_read(cx, buf)) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            r => return Poll::Ready(r),
+        }
+    })
+    .await
+}
+
+/// Fills `buf` completely, like `Read::read_exact()`.
+async fn read_exact_async<R: AsyncRead + Unpin>(reader: &mut R, mut buf: &mut [u8]) -> Result<()> {
+    while !buf.is_empty() {
+        match read_async(reader, buf).await? {
+            0 => {
+                return Err(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ))
+            }
+            n => buf = &mut buf[n..],
+        }
+    }
+    Ok(())
+}
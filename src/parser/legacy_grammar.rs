@@ -0,0 +1,152 @@
+//! The peg grammar the header parser was generated from, kept to test the hand-written one against.
+
+use super::Line;
+use crate::ply::{
+    Comment, ElementDef, Encoding, ObjInfo, PropertyDef, PropertyType, ScalarType, Version,
+};
+
+/// Maps the nonstandard 64 bit integer keywords, only accepted with the `extended-types` feature.
+#[cfg(feature = "extended-types")]
+fn extended_scalar(s: &str) -> Result<ScalarType, &'static str> {
+    match s {
+        "int64" | "long" => Ok(ScalarType::LongLong),
+        _ => Ok(ScalarType::ULongLong),
+    }
+}
+#[cfg(not(feature = "extended-types"))]
+fn extended_scalar(_: &str) -> Result<ScalarType, &'static str> {
+    Err("64 bit integer types require the `extended-types` feature")
+}
+
+/// Maps the nonstandard 16 bit float keywords, only accepted with the `half` feature.
+#[cfg(feature = "half")]
+fn half_scalar() -> Result<ScalarType, &'static str> {
+    Ok(ScalarType::Half)
+}
+#[cfg(not(feature = "half"))]
+fn half_scalar() -> Result<ScalarType, &'static str> {
+    Err("16 bit float types require the `half` feature")
+}
+
+peg::parser! {pub grammar grammar() for str {
+
+/// Grammar for PLY header
+
+rule space() = [' '|'\t']+
+
+rule uint() -> u64
+    = n:$(['0'..='9']+) {? n.parse().or(Err("unsigned integer")) }
+
+rule ident() -> String
+    = s:$(['a'..='z'|'A'..='Z'|'_']['a'..='z'|'A'..='Z'|'0'..='9'|'_'|'-']*) { s.to_string() }
+
+rule name() -> String
+    = s:$(['a'..='z'|'A'..='Z'|'0'..='9'|'_']['a'..='z'|'A'..='Z'|'0'..='9'|'_'|'-'|'.']*) { s.to_string() }
+
+rule text() -> String
+    = s:$((!['\n'|'\r'][_])+) { s.to_string() }
+
+rule line_break()
+    = "\r\n" / ['\n'|'\r']
+
+rule scalar() -> ScalarType
+    = s:$("int64" / "uint64" / "long" / "ulong") {? extended_scalar(s) }
+    / ("float16" / "half") {? half_scalar() }
+    / "char"    { ScalarType::Char }
+    / "int8"    { ScalarType::Char }
+    / "uchar"   { ScalarType::UChar }
+    / "uint8"   { ScalarType::UChar }
+    / "short"   { ScalarType::Short }
+    / "int16"   { ScalarType::Short }
+    / "uint16"  { ScalarType::UShort }
+    / "ushort"  { ScalarType::UShort }
+    / "int32"   { ScalarType::Int }
+    / "int"     { ScalarType::Int }
+    / "uint32"  { ScalarType::UInt }
+    / "uint"    { ScalarType::UInt }
+    / "float32" { ScalarType::Float }
+    / "float64" { ScalarType::Double }
+    / "float"   { ScalarType::Float }
+    / "double"  { ScalarType::Double }
+
+rule data_type() -> PropertyType
+    = s:scalar()   { PropertyType::Scalar(s) }
+    / "list" space() it:scalar() space() t:scalar() {
+        PropertyType::List(it, t)
+    }
+
+pub rule magic_number()
+    = "ply"
+
+pub rule format() -> (Encoding, Version)
+    = "format" space() "ascii" space() v:version() { (Encoding::Ascii, v) }
+    / "format" space() "binary_big_endian" space() v:version() { (Encoding::BinaryBigEndian, v) }
+    / "format" space() "binary_little_endian" space() v:version() { (Encoding::BinaryLittleEndian, v) }
+
+rule version() -> Version
+    = maj:uint() "." min:uint() {?
+        Ok(Version {
+            major: maj.try_into().or(Err("major version"))?,
+            minor: min.try_into().or(Err("minor version"))?,
+        })
+    }
+
+pub rule comment() -> Comment
+    = "comment" [' '|'\t'] c:text() {
+        c
+    }
+    / "comment" [' '|'\t']? {
+        String::new()
+    }
+
+pub rule obj_info() -> ObjInfo
+    = "obj_info" [' '|'\t'] c:text() {
+        c
+    }
+    / "obj_info" [' '|'\t']? {
+        String::new()
+    }
+
+pub rule element() -> ElementDef
+    = "element" space() id:$(name()) space() n:uint() {
+        let mut e = ElementDef::new(id);
+        e.count = n;
+        e
+    }
+
+pub rule property() -> PropertyDef
+    = "property" space() data_type:data_type() space() id:name() {
+        PropertyDef::new(&id, data_type)
+    }
+
+/// Property with a type keyword unknown to the grammar: (type keyword, property name)
+pub rule custom_property() -> (String, String)
+    = "property" space() t:ident() space() id:name() space()? line_break()? { (t, id) }
+
+pub rule end_header()
+    = "end_header"
+
+pub rule line() -> Line
+    = l:trimmed_line() space()? line_break()? { l }
+
+rule trimmed_line() -> Line
+    = magic_number() { Line::MagicNumber }
+    / end_header() { Line::EndHeader }
+    / v:format() { Line::Format(v) }
+    / v:obj_info() { Line::ObjInfo(v) }
+    / v:comment() { Line::Comment(v) }
+    / v:element() { Line::Element(v) }
+    / v:property() { Line::Property(v) }
+
+/// Either part around the decimal point may be missing (`1.`, `.5`), but not both.
+/// `nan`, `NaN` and `inf` are accepted for non-finite floats.
+rule any_number() -> String
+    = s:$(['-'|'+']? ((['0'..='9']+("."['0'..='9']*)? / "."['0'..='9']+) ("e"['-'|'+']?['0'..='9']+)? / "nan" / "NaN" / "inf")) { s.to_string() }
+
+rule trimmed_data_line() -> Vec<String>
+    = any_number() ** space()
+
+pub rule data_line() -> Vec<String>
+    = space()? l:trimmed_data_line() space()? line_break()? {l}
+
+}}
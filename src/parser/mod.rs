@@ -1,40 +1,413 @@
 //! Reads ascii or binary data into a `Ply`.
 
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::fmt::Debug;
+use std::fs::File;
 use std::io;
 use std::io::{BufReader, Read};
+use std::ops::Range;
+use std::path::Path;
 use std::result;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use std::io::{BufRead, ErrorKind, Result};
 
+#[cfg(test)]
+mod legacy_grammar;
 mod ply_grammar;
 
-use self::ply_grammar::grammar;
-use self::ply_grammar::Line;
-use crate::util::LocationTracker;
+mod options;
+pub use self::options::*;
+
+mod builder;
+pub use self::builder::*;
+
+mod header_accumulator;
+pub use self::header_accumulator::*;
+
+mod multi_parser;
+pub use self::multi_parser::*;
+
+mod bound_parser;
+pub use self::bound_parser::*;
+
+mod decoder;
+pub use self::decoder::*;
+
+mod payload_index;
+pub use self::payload_index::*;
+
+mod resume;
+pub use self::resume::*;
 
-fn parse_ascii_rethrow<T, E: Debug>(
+#[cfg(feature = "futures-io")]
+mod indexed;
+#[cfg(feature = "futures-io")]
+pub use self::indexed::*;
+
+#[cfg(feature = "futures-io")]
+mod stream;
+#[cfg(feature = "futures-io")]
+pub use self::stream::*;
+
+mod ascii_tokenizer;
+use self::ascii_tokenizer::LineTokens;
+mod parse_error;
+use self::parse_error::ParseError;
+pub use self::parse_error::{Cancelled, ChecksumMismatch, ElementError, HeaderSyntaxError};
+
+use self::ply_grammar as grammar;
+use self::ply_grammar::GrammarError;
+pub use self::ply_grammar::Line;
+use crate::util::{Crc32, LocationTracker};
+
+fn parse_ascii_rethrow<T, E: Debug + Send + Sync + 'static>(
     location: &LocationTracker,
     line_str: &str,
     e: E,
-    message: &str,
+    message: impl Into<Cow<'static, str>>,
 ) -> Result<T> {
-    Err(io::Error::new(
-        ErrorKind::InvalidInput,
-        format!(
-            "Line {}: {}\n\tString: '{}'\n\tError: {:?}",
-            location.line_index, message, line_str, e
-        ),
-    ))
+    Err(ParseError::Line {
+        location: *location,
+        message: message.into(),
+        line: line_str.to_string(),
+        cause: Some(Box::new(e)),
+    }
+    .into_io())
+}
+fn parse_ascii_error<T>(
+    location: &LocationTracker,
+    line_str: &str,
+    message: impl Into<Cow<'static, str>>,
+) -> Result<T> {
+    Err(ParseError::Line {
+        location: *location,
+        message: message.into(),
+        line: line_str.to_string(),
+        cause: None,
+    }
+    .into_io())
+}
+
+/// Upper bound for the number of entries reserved up front from a count found in the file.
+///
+/// Counts can't be trusted, collections with more entries grow while the entries are read.
+const MAX_PREALLOCATION: usize = 1 << 16;
+
+/// Bounds for the read buffer of `Parser::read_ply_from_path()`, which is sized like the file.
+const MIN_PATH_BUFFER: usize = 1 << 13;
+const MAX_PATH_BUFFER: usize = 1 << 20;
+
+/// Length from which ascii lines are parsed while reading instead of being buffered first.
+///
+/// Some scanners write a single element with hundreds of megabytes of values on one line.
+const LONG_LINE_LEN: usize = 1 << 20;
+
+/// Appends the next line of `reader` to `line` like `read_until(b'\n')`, but stops after `limit` bytes.
+///
+/// Returns whether the whole line was read.
+fn read_line_limited<T: BufRead>(reader: &mut T, line: &mut Vec<u8>, limit: usize) -> Result<bool> {
+    loop {
+        let buf = fill_buf(reader)?;
+        if buf.is_empty() {
+            return Ok(true);
+        }
+        let available = &buf[..buf.len().min(limit - line.len())];
+        let (n, done) = match available.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (available.len(), false),
+        };
+        line.extend_from_slice(&available[..n]);
+        reader.consume(n);
+        if done {
+            return Ok(true);
+        }
+        if line.len() >= limit {
+            return Ok(false);
+        }
+    }
+}
+
+/// Values of an ascii element that may wrap across lines, see `ParserOptions::multiline_ascii_elements()`.
+///
+/// Starts with the values of `line` and only reads the next line of `reader` into it once
+/// the values are used up, advancing `location`. The end of the file ends the iteration,
+/// a rejected line or a failing reader ends it and leaves the reason in `error`.
+struct WrappedTokens<'a, T> {
+    reader: &'a mut T,
+    location: &'a mut LocationTracker,
+    line: &'a mut Vec<u8>,
+    /// Whether `line` still has to be split into `values`.
+    pending: bool,
+    values: std::vec::IntoIter<String>,
+    error: Option<io::Error>,
+}
+
+impl<T: BufRead> Iterator for WrappedTokens<'_, T> {
+    type Item = String;
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if let Some(value) = self.values.next() {
+                return Some(value);
+            }
+            if self.error.is_some() {
+                return None;
+            }
+            if !self.pending {
+                self.line.clear();
+                match self.reader.read_until(b'\n', self.line) {
+                    Ok(0) => return None,
+                    Ok(_) => self.location.next_line(),
+                    Err(e) => {
+                        self.error = Some(e);
+                        return None;
+                    }
+                }
+            }
+            self.pending = false;
+            let values = match std::str::from_utf8(self.line) {
+                Ok(line) => grammar::data_line(line).map_err(|cause| {
+                    ParseError::ElementLine {
+                        line: line.to_string(),
+                        cause,
+                    }
+                    .into_io()
+                }),
+                Err(e) => Err(io::Error::new(ErrorKind::InvalidData, e)),
+            };
+            match values {
+                Ok(values) => self.values = values.into_iter(),
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Consumes the next line of `reader`, returns false at the end of the file.
+fn skip_line<T: BufRead>(reader: &mut T) -> Result<bool> {
+    let mut found = false;
+    loop {
+        let buf = fill_buf(reader)?;
+        if buf.is_empty() {
+            return Ok(found);
+        }
+        found = true;
+        match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => {
+                reader.consume(i + 1);
+                return Ok(true);
+            }
+            None => {
+                let n = buf.len();
+                reader.consume(n);
+            }
+        }
+    }
+}
+
+/// Consumes up to `len` bytes of `reader`, returns how many there were.
+fn skip_bytes<T: BufRead>(reader: &mut T, len: u64) -> Result<u64> {
+    let mut skipped = 0;
+    while skipped < len {
+        let buf = fill_buf(reader)?;
+        if buf.is_empty() {
+            break;
+        }
+        let n = buf
+            .len()
+            .min(usize::try_from(len - skipped).unwrap_or(usize::MAX));
+        reader.consume(n);
+        skipped += n as u64;
+    }
+    Ok(skipped)
+}
+
+/// Size of every binary entry of `element_def`, `None` if it has lists.
+fn binary_stride(element_def: &ElementDef) -> Option<u64> {
+    let mut stride = 0u64;
+    for p in &element_def.properties {
+        stride += match p.data_type {
+            PropertyType::Scalar(ref s) => s.size() as u64,
+            PropertyType::Custom { size, .. } => size as u64,
+            PropertyType::List(_, _) => return None,
+        };
+    }
+    Some(stride)
+}
+
+/// Reads past the first `count` binary elements of `element_def` without decoding them.
+///
+/// Elements of fixed size are skipped in one go, otherwise only the list counts are read.
+fn skip_binary_elements<T: BufRead, B: ByteOrder>(
+    reader: &mut T,
+    element_def: &ElementDef,
+    count: u64,
+) -> Result<()> {
+    if let Some(stride) = binary_stride(element_def) {
+        let len = stride.checked_mul(count).ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Element '{}' is too large to skip.", element_def.name),
+            )
+        })?;
+        let skipped = skip_bytes(reader, len)?;
+        if skipped < len {
+            // Only elements of some size can be missing.
+            return Err(unexpected_eof(element_def, skipped / stride, None));
+        }
+        return Ok(());
+    }
+    for index in 0..count {
+        skip_binary_element::<T, B>(reader, element_def, index)?;
+    }
+    Ok(())
+}
+
+/// Reads past the binary element with the zero-based `index`, returns its length in bytes.
+fn skip_binary_element<T: BufRead, B: ByteOrder>(
+    reader: &mut T,
+    element_def: &ElementDef,
+    index: u64,
+) -> Result<u64> {
+    let mut count_bytes = [0u8; 8];
+    let mut element_len = 0;
+    for p in &element_def.properties {
+        let len = match p.data_type {
+            PropertyType::Scalar(ref s) => s.size(),
+            PropertyType::Custom { size, .. } => size,
+            PropertyType::List(ref index_type, ref s) => {
+                let bytes = &mut count_bytes[..index_type.size()];
+                let count = reader
+                    .read_exact(bytes)
+                    .and_then(|()| binary_list_count::<B>(index_type, bytes))
+                    .map_err(|e| element_error(element_def, index, e))?;
+                element_len += index_type.size() as u64;
+                count.checked_mul(s.size()).ok_or_else(|| {
+                    io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("List '{}' is too long: {} entries.", p.name, count),
+                    )
+                })?
+            }
+        };
+        if skip_bytes(reader, len as u64)? < len as u64 {
+            return Err(unexpected_eof(element_def, index, None));
+        }
+        element_len += len as u64;
+    }
+    Ok(element_len)
+}
+
+/// Converts a binary list count into a `usize`, negative counts are an error.
+pub(crate) fn list_count<C: TryInto<usize> + Copy + std::fmt::Display>(count: C) -> Result<usize> {
+    count.try_into().map_err(|_| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid list count {}.", count),
+        )
+    })
+}
+
+/// Decodes a list count stored as `index_type`, `bytes` holds exactly its value.
+pub(crate) fn binary_list_count<B: ByteOrder>(
+    index_type: &ScalarType,
+    bytes: &[u8],
+) -> Result<usize> {
+    match *index_type {
+        ScalarType::Char => list_count(bytes[0] as i8),
+        ScalarType::UChar => list_count(bytes[0]),
+        ScalarType::Short => list_count(B::read_i16(bytes)),
+        ScalarType::UShort => list_count(B::read_u16(bytes)),
+        ScalarType::Int => list_count(B::read_i32(bytes)),
+        ScalarType::UInt => list_count(B::read_u32(bytes)),
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => list_count(B::read_i64(bytes)),
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => list_count(B::read_u64(bytes)),
+        ScalarType::Float | ScalarType::Double => Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "List index type must be an integer type.",
+        )),
+        #[cfg(feature = "half")]
+        ScalarType::Half => Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "List index type must be an integer type.",
+        )),
+    }
+}
+
+/// Converts the declared count of `element_def` into a `usize`.
+///
+/// Fails if the elements couldn't be held in memory on this platform.
+pub(crate) fn materializable_count(element_def: &ElementDef) -> Result<usize> {
+    usize::try_from(element_def.count).map_err(|_| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Element '{}' declares {} entries, more than can be held in memory on this platform.",
+                element_def.name, element_def.count
+            ),
+        )
+    })
+}
+
+/// Guesses whether an ascii payload line actually contains binary data.
+fn looks_like_binary(line: &[u8]) -> bool {
+    let non_printable = line
+        .iter()
+        .filter(|b| !(b.is_ascii_graphic() || b.is_ascii_whitespace()))
+        .count();
+    line.contains(&0) || non_printable * 10 > line.len()
+}
+
+/// Guesses whether the start of a binary payload actually contains ascii numbers.
+fn looks_like_ascii(bytes: &[u8]) -> bool {
+    !bytes.is_empty()
+        && bytes
+            .iter()
+            .all(|b| b.is_ascii_digit() || b" \t\r\n+-.eE".contains(b))
 }
-fn parse_ascii_error<T>(location: &LocationTracker, line_str: &str, message: &str) -> Result<T> {
-    Err(io::Error::new(
-        ErrorKind::InvalidInput,
+
+/// Adds to the error of a binary element that the payload looks like ascii.
+fn looks_ascii_error(element_def: &ElementDef, index: u64, e: io::Error) -> io::Error {
+    let cause = match e.get_ref().and_then(|e| e.downcast_ref::<ElementError>()) {
+        Some(e) => e.cause(),
+        None => return e,
+    };
+    let e = io::Error::new(
+        cause.kind(),
         format!(
-            "Line {}: {}\n\tString: '{}'",
-            location.line_index, message, line_str
+            "{}\nPayload of element '{}' looks like ascii, \
+            the format line may be wrong (did you mean ascii?)",
+            cause, element_def.name
         ),
-    ))
+    );
+    element_error(element_def, index, e)
+}
+
+/// `BufRead::fill_buf()`, but retries on `Interrupted` like the other read methods do.
+fn fill_buf<T: BufRead>(reader: &mut T) -> Result<&[u8]> {
+    loop {
+        match reader.fill_buf() {
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    reader.fill_buf()
+}
+
+/// Adds the name and index of the element that couldn't be read to `e`.
+fn element_error(element_def: &ElementDef, index: u64, e: io::Error) -> io::Error {
+    // Cancellation names the entry itself and stays a `Cancelled` for callers to downcast to.
+    if e.get_ref().is_some_and(|inner| inner.is::<Cancelled>()) {
+        return e;
+    }
+    io::Error::new(e.kind(), ElementError::new(&element_def.name, index, e))
 }
 
 use std::marker::PhantomData;
@@ -101,15 +474,53 @@ use std::marker::PhantomData;
 /// println!("Ply: {:#?}", ply);
 /// ```
 ///
+/// # Errors
+///
+/// Reads interrupted with `ErrorKind::Interrupted` are retried.
+/// Any other error of the underlying reader is returned with its kind,
+/// so `WouldBlock` or `TimedOut` can be told apart from malformed data.
+///
+/// An error leaves the reader at an unspecified position, usually somewhere within an element.
+/// The bytes consumed so far are lost, reading can't be resumed with the same reader.
+/// To retry, start over from a known position, for example by seeking back to the start of the file.
+///
+/// # Blocking and async
+///
+/// All `read_*` methods block on `std::io::Read` or `BufRead` sources, no runtime is needed.
+/// The `futures-io` feature adds `read_ply_async()` and friends for `futures::io::AsyncBufRead`,
+/// they only differ in how bytes are fetched and share the grammar and decoding with the blocking path.
+///
+/// # Threads
+///
+/// A `Parser` is `Send + Sync + Clone` for every element type,
+/// so one parser can be shared between threads or cloned into each of them.
 #[derive(Default)]
 pub struct Parser<E: PropertyAccess> {
-    phantom: PhantomData<E>,
+    options: ParserOptions,
+    phantom: PhantomData<fn() -> E>,
+}
+
+// Implemented by hand, deriving would require `E: Clone`.
+impl<E: PropertyAccess> Clone for Parser<E> {
+    fn clone(&self) -> Self {
+        Parser {
+            options: self.options.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Fails to compile if `Parser` stops being `Send + Sync + Clone` for some element type.
+#[allow(dead_code)]
+fn assert_parser_send_sync_clone<E: PropertyAccess>() {
+    fn check<T: Send + Sync + Clone>() {}
+    check::<Parser<E>>();
 }
 
 //use std::marker::PhantomData;
 //use std::io::{ Read, BufReader };
 use crate::ply::Ply;
-use crate::ply::{Encoding, Header, Payload};
+use crate::ply::{Encoding, Header, KeyMap, Payload, PayloadStore, PlyVisitor, PropertySetError};
 
 impl<E: PropertyAccess> Parser<E> {
     /// Creates a new `Parser<E>`, where `E` is the type to store the element data in.
@@ -117,6 +528,17 @@ impl<E: PropertyAccess> Parser<E> {
     /// To get started quickly try `DefaultElement` from the `ply` module.
     pub fn new() -> Self {
         Parser {
+            options: ParserOptions::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a new `Parser<E>` configured by `options`.
+    ///
+    /// `Parser::builder()` sets the options one by one instead.
+    pub fn with_options(options: ParserOptions) -> Self {
+        Parser {
+            options,
             phantom: PhantomData,
         }
     }
@@ -125,11 +547,151 @@ impl<E: PropertyAccess> Parser<E> {
     ///
     /// A PLY file starts with "ply\n". `read_ply` reads until all elements have been read as
     /// defined in the header of the PLY file.
+    ///
+    /// `source` is buffered internally, so bytes after the payload may be consumed as well.
+    /// Use `read_ply_buffered()` to continue reading after the document.
     pub fn read_ply<T: Read>(&self, source: &mut T) -> Result<Ply<E>> {
         let mut source = BufReader::new(source);
+        self.__read_ply(&mut source, Sequential)
+    }
+
+    /// Reads the PLY file at `path`, errors are prefixed with the path.
+    ///
+    /// The read buffer is sized from the length of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// let p = Parser::<DefaultElement>::new();
+    /// let ply = p.read_ply_from_path("example_plys/house_ok_ascii.ply").unwrap();
+    /// assert_eq!(ply.payload["vertex"].len(), 5);
+    /// let err = p.read_ply_from_path("example_plys/missing.ply").unwrap_err();
+    /// assert!(err.to_string().starts_with("example_plys/missing.ply: "));
+    /// ```
+    pub fn read_ply_from_path<P: AsRef<Path>>(&self, path: P) -> Result<Ply<E>> {
+        let path = path.as_ref();
+        let read = || {
+            let file = File::open(path)?;
+            let len = file.metadata().map_or(0, |m| m.len());
+            let capacity = len.clamp(MIN_PATH_BUFFER as u64, MAX_PATH_BUFFER as u64) as usize;
+            self.__read_ply(&mut BufReader::with_capacity(capacity, file), Sequential)
+        };
+        read().map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    }
+
+    /// Reads an entire PLY file held in `bytes`, without copying it into a read buffer.
+    ///
+    /// Errors are prefixed with the byte offset within `bytes` at which reading stopped.
+    pub fn read_ply_from_slice(&self, bytes: &[u8]) -> Result<Ply<E>> {
+        let mut cursor = bytes;
+        self.__read_ply(&mut cursor, Sequential).map_err(|e| {
+            let offset = bytes.len() - cursor.len();
+            io::Error::new(e.kind(), format!("Byte offset {}: {}", offset, e))
+        })
+    }
+
+    /// Same as `read_ply()`, but consumes exactly the document from the buffered `reader`.
+    ///
+    /// Whatever follows the payload remains in `reader`, for PLY data embedded in other files.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// let mut bytes = std::fs::read("example_plys/house_2_ok_little_endian.ply").unwrap();
+    /// bytes.extend_from_slice(b"more data");
+    /// let mut reader = &bytes[..];
+    /// let ply = Parser::<DefaultElement>::new().read_ply_buffered(&mut reader).unwrap();
+    /// assert_eq!(reader, b"more data");
+    /// ```
+    pub fn read_ply_buffered<T: BufRead>(&self, reader: &mut T) -> Result<Ply<E>> {
+        self.__read_ply(reader, Sequential)
+    }
+
+    /// Reads all PLY documents stored back to back in `source`, until its end.
+    ///
+    /// Each document has to end with its last payload byte, the next one starts with "ply\n",
+    /// as written by `Writer::write_ply_multi()`. Errors are prefixed with the index of the document.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// # use std::io::Read;
+    /// let mut twice = Vec::new();
+    /// for _ in 0..2 {
+    ///     let mut f = std::fs::File::open("example_plys/house_2_ok_ascii.ply").unwrap();
+    ///     f.read_to_end(&mut twice).unwrap();
+    /// }
+    /// let plys = Parser::<DefaultElement>::new().read_ply_multi(&mut twice.as_slice()).unwrap();
+    /// assert_eq!(plys.len(), 2);
+    /// ```
+    pub fn read_ply_multi<T: Read>(&self, source: &mut T) -> Result<Vec<Ply<E>>> {
+        let mut source = BufReader::new(source);
+        let mut plys = Vec::new();
+        while !fill_buf(&mut source)?.is_empty() {
+            let ply = self.__read_ply(&mut source, Sequential).map_err(|e| {
+                io::Error::new(e.kind(), format!("Document #{}: {}", plys.len(), e))
+            })?;
+            plys.push(ply);
+        }
+        Ok(plys)
+    }
+
+    /// Sets a property of `element`, a rejected property fails or is skipped as configured.
+    pub(crate) fn store_property(
+        &self,
+        element: &mut E,
+        key: &str,
+        property: Property,
+    ) -> Result<()> {
+        let result = element.try_set_property(key, property);
+        self.check_rejected(key, result)
+    }
+    /// Same as `store_property()` for a borrowed property.
+    fn store_property_ref(&self, element: &mut E, key: &str, property: &Property) -> Result<()> {
+        let result = element.try_set_property_ref(key, property);
+        self.check_rejected(key, result)
+    }
+    fn check_rejected(
+        &self,
+        key: &str,
+        result: result::Result<(), PropertySetError>,
+    ) -> Result<()> {
+        match result {
+            Err(e) if !self.options.skip_rejected_properties => Err(ParseError::RejectedProperty {
+                key: key.to_string(),
+                reason: e.reason().to_string(),
+            }
+            .into_io()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads one document and leaves `source` right after its payload.
+    fn __read_ply<T: BufRead>(
+        &self,
+        source: &mut T,
+        strategy: impl ReadElements<E>,
+    ) -> Result<Ply<E>> {
         let mut location = LocationTracker::new();
-        let header = self.__read_header(&mut source, &mut location)?;
-        let payload = self.__read_payload(&mut source, &mut location, &header)?;
+        let header = self.__read_header(source, &mut location)?;
+        let payload = match self.options.expected_checksum(&header) {
+            None => self.__read_payload(source, &mut location, &header, strategy)?,
+            Some(expected) => {
+                let mut source = ChecksumReader {
+                    inner: source,
+                    crc: Crc32::new(),
+                };
+                let payload = self.__read_payload(&mut source, &mut location, &header, strategy)?;
+                check_checksum(expected, source.crc.finish())?;
+                payload
+            }
+        };
         let mut ply = Ply::new();
         ply.header = header;
         ply.payload = payload;
@@ -137,8 +699,98 @@ impl<E: PropertyAccess> Parser<E> {
     }
 }
 
+/// How `Parser::__read_payload()` reads the elements it doesn't skip.
+trait ReadElements<E: PropertyAccess>: Copy {
+    fn read_elements<T: BufRead>(
+        self,
+        parser: &Parser<E>,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+    ) -> Result<Vec<E>>;
+}
+
+/// Reads the elements one after another on the calling thread.
+#[derive(Clone, Copy)]
+struct Sequential;
+
+impl<E: PropertyAccess> ReadElements<E> for Sequential {
+    fn read_elements<T: BufRead>(
+        self,
+        parser: &Parser<E>,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+    ) -> Result<Vec<E>> {
+        parser.__read_payload_for_element(reader, location, element_def, header, false)
+    }
+}
+
+/// Computes the checksum of all bytes consumed from `inner`.
+struct ChecksumReader<'a, R> {
+    inner: &'a mut R,
+    crc: Crc32,
+}
+
+impl<R: BufRead> Read for ChecksumReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for ChecksumReader<'_, R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.crc.update(&buf[..amt]);
+        }
+        self.inner.consume(amt)
+    }
+}
+
+/// Counts the bytes consumed from `inner`.
+struct CountingReader<'a, R> {
+    inner: &'a mut R,
+    count: &'a Cell<u64>,
+}
+
+impl<R: BufRead> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<'_, R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        self.count.set(self.count.get() + amt as u64);
+        self.inner.consume(amt)
+    }
+}
+
+fn check_checksum(expected: u32, found: u32) -> Result<()> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            ErrorKind::InvalidData,
+            ChecksumMismatch { expected, found },
+        ))
+    }
+}
+
 // use ply::{ Header, Encoding };
-use crate::ply::{Comment, ElementDef, ObjInfo, PropertyAccess, Version};
+use crate::ply::{ElementDef, PropertyAccess, PropertyDef};
 /*
 use util::LocationTracker;
 use super::Parser;
@@ -163,134 +815,132 @@ impl<E: PropertyAccess> Parser<E> {
         self.__read_header(reader, &mut line)
     }
     pub fn read_header_line(&self, line: &str) -> Result<Line> {
-        match self.__read_header_line(line) {
-            Ok(l) => Ok(l),
-            Err(e) => Err(io::Error::new(
+        self.__read_header_line(line).map_err(|e| {
+            io::Error::new(
                 ErrorKind::InvalidInput,
                 format!("Couldn't parse line.\n\tString: {}\n\tError: {:?}", line, e),
-            )),
-        }
+            )
+        })
     }
 
     // private
-    fn __read_header_line(
-        &self,
-        line_str: &str,
-    ) -> result::Result<Line, peg::error::ParseError<peg::str::LineCol>> {
-        grammar::line(line_str)
+    fn __read_header_line(&self, line_str: &str) -> result::Result<Line, GrammarError> {
+        let line_str = self.options.unindent(line_str);
+        let e = match grammar::line_with_names(line_str, self.options.name_characters) {
+            Ok(l) => return Ok(l),
+            Err(e) => e,
+        };
+        // Maybe the property type is unknown to us, but not to the user.
+        if let Ok((type_name, name)) =
+            grammar::custom_property(line_str, self.options.name_characters)
+        {
+            if let Some(handler) = self.options.custom_type(&type_name) {
+                let data_type = PropertyType::Custom {
+                    name: type_name,
+                    size: handler.size,
+                };
+                return Ok(Line::Property(PropertyDef::new(&name, data_type)));
+            }
+        }
+        Err(e)
     }
 
-    fn __read_header<T: BufRead>(
+    pub(crate) fn __read_header<T: BufRead>(
         &self,
         reader: &mut T,
         location: &mut LocationTracker,
     ) -> Result<Header> {
-        location.next_line();
-        let mut line_str = String::new();
-        reader.read_line(&mut line_str)?;
-        match self.__read_header_line(&line_str) {
-            Ok(Line::MagicNumber) => (),
-            Ok(l) => {
-                return parse_ascii_error(
-                    location,
-                    &line_str,
-                    &format!("Expected magic number 'ply', but saw '{:?}'.", l),
-                )
-            }
-            Err(e) => {
-                return parse_ascii_rethrow(location, &line_str, e, "Expected magic number 'ply'.")
-            }
-        }
-        match grammar::line(&line_str) {
-            Err(e) => return Err(io::Error::new(ErrorKind::InvalidInput, e)),
-            Ok(l @ Line::MagicNumber) => l,
-            Ok(ob) => {
-                return Err(io::Error::new(
-                    ErrorKind::InvalidInput,
-                    format!(
-                    "Invalid line encountered. Expected type: 'Line::MagicNumber', found: '{:?}'",
-                    ob
-                ),
-                ))
-            }
-        };
-
-        let mut header_form_ver: Option<(Encoding, Version)> = None;
-        let mut header_obj_infos = Vec::<ObjInfo>::new();
-        let mut header_elements = Vec::<ElementDef>::new();
-        let mut header_comments = Vec::<Comment>::new();
-        location.next_line();
-        'readlines: loop {
-            line_str.clear();
-            reader.read_line(&mut line_str)?;
-            let line = self.__read_header_line(&line_str);
-
-            match line {
-                Err(e) => {
-                    return parse_ascii_rethrow(location, &line_str, e, "Couldn't parse line.")
-                }
-                Ok(Line::MagicNumber) => {
-                    return parse_ascii_error(location, &line_str, "Unexpected 'ply' found.")
-                }
-                Ok(Line::Format(ref t)) => {
-                    if header_form_ver.is_none() {
-                        header_form_ver = Some(*t);
-                    } else {
-                        let f = header_form_ver.unwrap();
-                        if f != *t {
-                            return parse_ascii_error(
-                                location,
-                                &line_str,
-                                &format!(
-                                    "Found contradicting format definition:\n\
-                                    \tEncoding: {:?}, Version: {:?}\n\
-                                    previous definition:\n\
-                                    \tEncoding: {:?}, Version: {:?}",
-                                    t.0, t.1, f.0, f.1
-                                ),
-                            );
-                        }
-                    }
-                }
-                Ok(Line::ObjInfo(ref o)) => header_obj_infos.push(o.clone()),
-                Ok(Line::Comment(ref c)) => header_comments.push(c.clone()),
-                Ok(Line::Element(ref e)) => header_elements.push(e.clone()),
-                Ok(Line::Property(p)) => {
-                    if header_elements.is_empty() {
-                        return parse_ascii_error(
-                            location,
-                            &line_str,
-                            &format!("Property '{:?}' found without preceding element.", p),
-                        );
-                    } else {
-                        let mut e = header_elements.pop().unwrap();
-                        if !e.properties.contains(&p) {
-                            e.properties.push(p);
-                        }
-                        header_elements.push(e);
-                    }
-                }
-                Ok(Line::EndHeader) => {
-                    location.next_line();
-                    break 'readlines;
-                }
-            };
+        let mut accumulator = HeaderAccumulator::new();
+        let mut line = Vec::new();
+        while !accumulator.is_complete() {
             location.next_line();
+            line.clear();
+            let complete = read_line_limited(reader, &mut line, self.options.max_header_line_len)?;
+            let line_str = self.header_line_str(&line, complete, location)?;
+            self.push_header_line(&mut accumulator, location, line_str)?;
         }
-        if header_form_ver.is_none() {
+        self.finish_header(accumulator, location)
+    }
+
+    /// The header line at `location` read into `line`, `complete` if its end was found.
+    ///
+    /// Fails if the line is longer than `max_header_line_len()` or the input ended before it.
+    pub(crate) fn header_line_str<'l>(
+        &self,
+        line: &'l [u8],
+        complete: bool,
+        location: &LocationTracker,
+    ) -> Result<&'l str> {
+        if !complete {
             return Err(io::Error::new(
                 ErrorKind::InvalidInput,
-                "No format line found.",
+                format!(
+                    "Header line {} is longer than the limit of {} bytes.",
+                    location.line_index, self.options.max_header_line_len
+                ),
             ));
         }
-        let (encoding, version) = header_form_ver.unwrap();
-        Ok(Header {
-            encoding,
-            version,
-            obj_infos: header_obj_infos,
-            comments: header_comments,
-            elements: header_elements,
-        })
+        if line.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "Input ends within the header.",
+            ));
+        }
+        std::str::from_utf8(line).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Parses the header line at `location` and adds it to `accumulator`.
+    pub(crate) fn push_header_line(
+        &self,
+        accumulator: &mut HeaderAccumulator,
+        location: &LocationTracker,
+        line_str: &str,
+    ) -> Result<()> {
+        let line = match self.__read_header_line(line_str) {
+            Ok(l) => l,
+            Err(e) => {
+                let message = if location.line_index == 1 {
+                    "Expected magic number 'ply'."
+                } else {
+                    "Couldn't parse line."
+                };
+                let text = line_str.trim_end_matches(['\r', '\n']);
+                // The grammar saw the line without its indentation.
+                let indentation =
+                    text.chars().count() - self.options.unindent(text).chars().count();
+                let error = HeaderSyntaxError {
+                    line: location.line_index,
+                    column: indentation + e.column,
+                    text: text.to_string(),
+                    message: message.to_string(),
+                    expected: e.expected(),
+                };
+                return Err(io::Error::new(ErrorKind::InvalidInput, error));
+            }
+        };
+        if let Err(e) = accumulator.push(line) {
+            return parse_ascii_error(location, line_str, e.to_string());
+        }
+        Ok(())
+    }
+
+    /// Builds the header once `end_header` was pushed, `location` moves on to the payload.
+    pub(crate) fn finish_header(
+        &self,
+        accumulator: HeaderAccumulator,
+        location: &mut LocationTracker,
+    ) -> Result<Header> {
+        location.start_payload();
+        let mut header = accumulator
+            .finish()
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        self.options
+            .normalize_header(&mut header)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        self.options
+            .check_header_limits(&header)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        Ok(header)
     }
 }
 
@@ -300,31 +950,282 @@ impl<E: PropertyAccess> Parser<E> {
 impl<E: PropertyAccess> Parser<E> {
     /// Reads payload. Encoding is chosen according to the encoding field in `header`.
     pub fn read_payload<T: BufRead>(&self, reader: &mut T, header: &Header) -> Result<Payload<E>> {
-        let mut location = LocationTracker::new();
-        self.__read_payload(reader, &mut location, header)
+        let mut location = LocationTracker::payload();
+        self.__read_payload(reader, &mut location, header, Sequential)
     }
 
     /// Reads entire list of elements from payload. Encoding is chosen according to `header`.
     ///
     /// Make sure to read the elements in the order as they are defined in the header.
+    /// `element_def` has to be one of `header.elements`, with the same count and properties,
+    /// otherwise an error of kind `InvalidInput` is returned before anything is read.
     pub fn read_payload_for_element<T: BufRead>(
         &self,
         reader: &mut T,
         element_def: &ElementDef,
         header: &Header,
     ) -> Result<Vec<E>> {
+        check_element_def(element_def, header)?;
+        let mut location = LocationTracker::payload();
+        self.__read_payload_for_element(reader, &mut location, element_def, header, false)
+    }
+
+    /// Same as `read_payload_for_element()`, but reuses the buffers of list properties across elements.
+    ///
+    /// List properties are passed to `PropertyAccess::set_property_ref()` instead of `set_property()`.
+    /// If your element type only copies what it needs from the list,
+    /// no allocation is done per list property.
+    pub fn read_payload_for_element_pooled<T: BufRead>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        header: &Header,
+    ) -> Result<Vec<E>> {
+        check_element_def(element_def, header)?;
+        let mut location = LocationTracker::payload();
+        self.__read_payload_for_element(reader, &mut location, element_def, header, true)
+    }
+
+    /// Same as `read_payload_for_element_pooled()`, but reads the elements into `out`, reusing its storage.
+    ///
+    /// `out` ends up with one entry per element. Existing entries are overwritten in place
+    /// instead of being replaced by `PropertyAccess::new()`, so properties that aren't set again
+    /// keep their previous values. `DefaultElement` and `SmallElement` overwrite their
+    /// properties and list buffers in place.
+    /// Reading frames of the same layout over and over is thus free of per-element allocations
+    /// once `out` is warmed up, for binary payloads. Parsing ascii lines still allocates.
+    ///
+    /// On error, the contents of `out` are unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// let p = Parser::<DefaultElement>::new();
+    /// let mut vertices = Vec::new();
+    /// for _ in 0..2 {
+    ///     let mut f = std::io::BufReader::new(
+    ///         std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap(),
+    ///     );
+    ///     let header = p.read_header(&mut f).unwrap();
+    ///     let vertex_def = &header.elements[0];
+    ///     p.read_payload_for_element_into(&mut f, vertex_def, &header, &mut vertices).unwrap();
+    ///     assert_eq!(vertices.len(), 8);
+    /// }
+    /// ```
+    pub fn read_payload_for_element_into<T: BufRead>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        header: &Header,
+        out: &mut Vec<E>,
+    ) -> Result<()> {
+        check_element_def(element_def, header)?;
+        let mut location = LocationTracker::payload();
+        let mut pool = list_pool(element_def);
+        self.__read_payload_for_element_into(
+            reader,
+            &mut location,
+            element_def,
+            header,
+            Some(&mut pool),
+            out,
+        )
+    }
+
+    /// Reads the payload into stores created by `new_store`, one per element of `header`.
+    ///
+    /// Use it to decode into your own storage, like an arena, without going through a `Vec`.
+    /// Stores are keyed by element name like `Payload`.
+    pub fn read_payload_into_store<T: BufRead, S: PayloadStore<E>>(
+        &self,
+        reader: &mut T,
+        header: &Header,
+        mut new_store: impl FnMut(&ElementDef) -> S,
+    ) -> Result<KeyMap<S>> {
+        let mut location = LocationTracker::payload();
+        let mut stores = KeyMap::new();
+        for e in &header.elements {
+            let mut store = new_store(e);
+            self.__read_payload_for_element_into_store(
+                reader,
+                &mut location,
+                e,
+                header,
+                &mut store,
+            )?;
+            stores.insert(e.name.clone(), store);
+        }
+        Ok(stores)
+    }
+
+    /// Reads an entire PLY file like `read_ply()`, but hands the elements to `visitor` instead of keeping them.
+    ///
+    /// A single element per element type is allocated and overwritten in place,
+    /// list buffers are reused like in `read_payload_for_element_pooled()`.
+    /// Returns the header.
+    pub fn read_ply_with_visitor<T: Read, V: PlyVisitor<E>>(
+        &self,
+        source: &mut T,
+        visitor: &mut V,
+    ) -> Result<Header> {
+        let mut source = BufReader::new(source);
         let mut location = LocationTracker::new();
-        match header.encoding {
-            Encoding::Ascii => {
-                self.read_ascii_payload_for_element(reader, &mut location, element_def)
-            }
-            Encoding::BinaryBigEndian => {
-                self.read_big_endian_payload_for_element(reader, &mut location, element_def)
+        let header = self.__read_header(&mut source, &mut location)?;
+        visitor.header(&header)?;
+        match self.options.expected_checksum(&header) {
+            None => self.__visit_payload(&mut source, &mut location, &header, visitor)?,
+            Some(expected) => {
+                let mut source = ChecksumReader {
+                    inner: &mut source,
+                    crc: Crc32::new(),
+                };
+                self.__visit_payload(&mut source, &mut location, &header, visitor)?;
+                check_checksum(expected, source.crc.finish())?;
             }
-            Encoding::BinaryLittleEndian => {
-                self.read_little_endian_payload_for_element(reader, &mut location, element_def)
+        }
+        Ok(header)
+    }
+
+    fn __visit_payload<T: BufRead, V: PlyVisitor<E>>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        header: &Header,
+        visitor: &mut V,
+    ) -> Result<()> {
+        for e in &header.elements {
+            if self.options.skips_element(&e.name) {
+                self.skip_payload_for_element(reader, location, e, header.encoding, e.count)?;
+                continue;
             }
+            visitor.begin_element(e)?;
+            let mut pool = list_pool(e);
+            let mut sink = VisitorSink {
+                visitor: &mut *visitor,
+                element: None,
+            };
+            self.__read_payload_for_element_into(
+                reader,
+                location,
+                e,
+                header,
+                Some(&mut pool),
+                &mut sink,
+            )?;
+            visitor.end_element(e)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `read_payload_for_element()`, but pushes the elements to `store`.
+    ///
+    /// `PayloadStore::finish()` is called once all elements were read.
+    /// On error, `store` holds the elements read so far.
+    pub fn read_payload_for_element_into_store<T: BufRead, S: PayloadStore<E>>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        header: &Header,
+        store: &mut S,
+    ) -> Result<()> {
+        check_element_def(element_def, header)?;
+        let mut location = LocationTracker::payload();
+        self.__read_payload_for_element_into_store(
+            reader,
+            &mut location,
+            element_def,
+            header,
+            store,
+        )
+    }
+
+    fn __read_payload_for_element_into_store<T: BufRead, S: PayloadStore<E>>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+        store: &mut S,
+    ) -> Result<()> {
+        let mut sink = StoreSink(store);
+        self.__read_payload_for_element_into(
+            reader,
+            location,
+            element_def,
+            header,
+            None,
+            &mut sink,
+        )?;
+        sink.0.finish();
+        Ok(())
+    }
+
+    /// Reads only the elements `range` of `element_def`, e.g. to page through a large element.
+    ///
+    /// The elements before the range are skipped without decoding them:
+    /// binary elements of fixed size in one go, ascii elements line by line.
+    /// Afterwards, `reader` is left right after the last element of the range.
+    /// For seekable async sources, `Parser::open_indexed()` seeks to the range instead.
+    ///
+    /// `element_def` has to be one of `header.elements` like for `read_payload_for_element()`,
+    /// and `reader` has to be at its first element.
+    /// Fails with an error of kind `InvalidInput` before anything is read
+    /// if the range exceeds the count of `element_def`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// let p = Parser::<DefaultElement>::new();
+    /// let mut f = std::io::BufReader::new(
+    ///     std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap(),
+    /// );
+    /// let header = p.read_header(&mut f).unwrap();
+    /// let vertex_def = &header.elements[0];
+    /// let vertices = p.read_payload_for_element_range(&mut f, vertex_def, &header, 2..5).unwrap();
+    /// assert_eq!(vertices.len(), 3);
+    /// ```
+    pub fn read_payload_for_element_range<T: BufRead>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        header: &Header,
+        range: Range<usize>,
+    ) -> Result<Vec<E>> {
+        check_element_def(element_def, header)?;
+        if range.start > range.end || range.end as u64 > element_def.count {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Range {:?} is out of the {} entries of element '{}'.",
+                    range, element_def.count, element_def.name
+                ),
+            ));
         }
+        let mut location = LocationTracker::payload();
+        self.skip_payload_for_element(
+            reader,
+            &mut location,
+            element_def,
+            header.encoding,
+            range.start as u64,
+        )?;
+        let mut line = Vec::new();
+        let mut elements = Vec::with_capacity(range.len().min(MAX_PREALLOCATION));
+        for index in range {
+            elements.push(self.read_element(
+                reader,
+                &mut location,
+                element_def,
+                header.encoding,
+                index as u64,
+                &mut line,
+            )?);
+        }
+        Ok(elements)
     }
 
     /// internal dispatcher based on the encoding
@@ -333,39 +1234,357 @@ impl<E: PropertyAccess> Parser<E> {
         reader: &mut T,
         location: &mut LocationTracker,
         header: &Header,
+        strategy: impl ReadElements<E>,
     ) -> Result<Payload<E>> {
+        if self.options.progress_hook().is_some() || self.options.cancellation().is_some() {
+            return self.__read_payload_watched(reader, location, header);
+        }
         let mut payload = Payload::new();
-        match header.encoding {
-            Encoding::Ascii => {
-                for e in &header.elements {
-                    let elems = self.read_ascii_payload_for_element(reader, location, e)?;
-                    payload.insert(e.name.clone(), elems);
-                }
-            }
-            Encoding::BinaryBigEndian => {
-                for e in &header.elements {
-                    let elems = self.read_big_endian_payload_for_element(reader, location, e)?;
-                    payload.insert(e.name.clone(), elems);
-                }
+        for e in &header.elements {
+            if self.options.skips_element(&e.name) {
+                self.skip_payload_for_element(reader, location, e, header.encoding, e.count)?;
+                continue;
             }
-            Encoding::BinaryLittleEndian => {
-                for e in &header.elements {
-                    let elems = self.read_little_endian_payload_for_element(reader, location, e)?;
-                    payload.insert(e.name.clone(), elems);
+            let elems = strategy.read_elements(self, reader, location, e, header)?;
+            payload.insert(e.name.clone(), elems);
+        }
+        Ok(payload)
+    }
+
+    /// Same as `__read_payload()`, reporting progress and checking for cancellation
+    /// as set up by `ParserOptions::on_progress()` and `ParserOptions::cancel_flag()`.
+    fn __read_payload_watched<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        header: &Header,
+    ) -> Result<Payload<E>> {
+        let bytes = Cell::new(0);
+        let mut reader = CountingReader {
+            inner: reader,
+            count: &bytes,
+        };
+        let hook = self.options.progress_hook();
+        let cancel = self.options.cancellation();
+        let mut payload = Payload::new();
+        for e in &header.elements {
+            let report = |read| {
+                if let Some((hook, _)) = hook {
+                    hook(&ProgressEvent {
+                        element: &e.name,
+                        read,
+                        total: e.count,
+                        bytes: bytes.get(),
+                    });
                 }
+            };
+            check_cancelled(cancel, e, 0)?;
+            report(0);
+            if self.options.skips_element(&e.name) {
+                self.skip_payload_for_element(&mut reader, location, e, header.encoding, e.count)?;
+            } else {
+                let mut elems = Vec::new();
+                let mut sink = WatchedSink {
+                    elements: &mut elems,
+                    hook,
+                    cancel,
+                    bytes: &bytes,
+                };
+                self.__read_payload_for_element_into(
+                    &mut reader,
+                    location,
+                    e,
+                    header,
+                    None,
+                    &mut sink,
+                )?;
+                payload.insert(e.name.clone(), elems);
             }
+            report(e.count);
         }
         Ok(payload)
     }
+
+    fn __read_payload_for_element<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+        pooled: bool,
+    ) -> Result<Vec<E>> {
+        let mut pool = if pooled {
+            Some(list_pool(element_def))
+        } else {
+            None
+        };
+        let mut elems = Vec::new();
+        self.__read_payload_for_element_into(
+            reader,
+            location,
+            element_def,
+            header,
+            pool.as_deref_mut(),
+            &mut elems,
+        )?;
+        Ok(elems)
+    }
+
+    fn __read_payload_for_element_into<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+        pool: Option<&mut [Property]>,
+        out: &mut impl ElementSink<E>,
+    ) -> Result<()> {
+        match header.encoding {
+            Encoding::Ascii => {
+                self.read_ascii_payload_for_element(reader, location, element_def, pool, out)
+            }
+            Encoding::BinaryBigEndian => self.read_binary_payload_for_element::<T, BigEndian>(
+                reader,
+                location,
+                element_def,
+                pool,
+                out,
+            ),
+            Encoding::BinaryLittleEndian => self
+                .read_binary_payload_for_element::<T, LittleEndian>(
+                    reader,
+                    location,
+                    element_def,
+                    pool,
+                    out,
+                ),
+        }
+    }
+
+    /// Checks `count` against `ParserOptions::expect_fixed_list()` and `ParserOptions::max_list_len()`,
+    /// returns whether a length was declared.
+    fn check_list_len(
+        &self,
+        element_def: &ElementDef,
+        def: &PropertyDef,
+        count: usize,
+    ) -> Result<bool> {
+        self.options
+            .check_list_limit(&element_def.name, &def.name, count)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        match self.options.fixed_list_len(&element_def.name, &def.name) {
+            Some(expected) if expected != count => Err(ParseError::FixedListLength {
+                property: def.name.clone(),
+                found: count,
+                expected,
+            }
+            .into_io()),
+            expected => Ok(expected.is_some()),
+        }
+    }
+}
+
+/// Checks that `element_def` is part of `header`, comments aren't compared.
+fn check_element_def(element_def: &ElementDef, header: &Header) -> Result<()> {
+    let defined = match header.elements.iter().find(|e| e.name == element_def.name) {
+        Some(e) if e.count == element_def.count && e.properties == element_def.properties => {
+            return Ok(())
+        }
+        found => found.is_some(),
+    };
+    Err(ParseError::ForeignElementDef {
+        name: element_def.name.clone(),
+        defined,
+    }
+    .into_io())
+}
+
+/// Scratch buffers for the list properties of an element, one entry per property.
+fn list_pool(element_def: &ElementDef) -> Vec<Property> {
+    // The variants are adjusted to the property types on first use.
+    vec![Property::ListUChar(PropertyList::new()); element_def.properties.len()]
+}
+
+/// Returns the entry `index` of `out` to read an element into, `out` grows by at most one entry.
+fn element_slot<'a, E: PropertyAccess>(
+    out: &'a mut Vec<E>,
+    element_def: &ElementDef,
+    index: u64,
+) -> &'a mut E {
+    let index = index as usize;
+    if index == out.len() {
+        out.push(E::new_from_def(element_def));
+    }
+    &mut out[index]
+}
+
+/// Where the payload readers put the elements they read.
+trait ElementSink<E> {
+    /// Called before the `count` elements are read.
+    fn prepare(&mut self, count: usize);
+    /// Reads the element with the zero-based `index` with `read` and keeps it.
+    fn read_into(
+        &mut self,
+        element_def: &ElementDef,
+        index: u64,
+        read: impl FnOnce(&mut E) -> Result<()>,
+    ) -> Result<()>;
+}
+
+/// Overwrites the existing entries in place, see `element_slot()`.
+impl<E: PropertyAccess> ElementSink<E> for Vec<E> {
+    fn prepare(&mut self, count: usize) {
+        self.truncate(count);
+        self.reserve(count.min(MAX_PREALLOCATION).saturating_sub(self.len()));
+    }
+    fn read_into(
+        &mut self,
+        element_def: &ElementDef,
+        index: u64,
+        read: impl FnOnce(&mut E) -> Result<()>,
+    ) -> Result<()> {
+        read(element_slot(self, element_def, index))
+    }
+}
+
+/// Pushes fresh elements to a `PayloadStore`.
+struct StoreSink<'a, S>(&'a mut S);
+
+impl<E: PropertyAccess, S: PayloadStore<E>> ElementSink<E> for StoreSink<'_, S> {
+    fn prepare(&mut self, count: usize) {
+        self.0.reserve(count.min(MAX_PREALLOCATION));
+    }
+    fn read_into(
+        &mut self,
+        element_def: &ElementDef,
+        _index: u64,
+        read: impl FnOnce(&mut E) -> Result<()>,
+    ) -> Result<()> {
+        let mut element = E::new_from_def(element_def);
+        read(&mut element)?;
+        self.0.push(element);
+        Ok(())
+    }
+}
+
+/// Reads into a `Vec` like its own sink, calling the progress hook every so many entries
+/// and checking the cancel flag before each entry.
+struct WatchedSink<'a, E> {
+    elements: &'a mut Vec<E>,
+    /// (hook, number of entries between two events)
+    hook: Option<(&'a ProgressHook, u64)>,
+    cancel: Option<&'a AtomicBool>,
+    bytes: &'a Cell<u64>,
+}
+
+impl<E: PropertyAccess> ElementSink<E> for WatchedSink<'_, E> {
+    fn prepare(&mut self, count: usize) {
+        self.elements.prepare(count);
+    }
+    fn read_into(
+        &mut self,
+        element_def: &ElementDef,
+        index: u64,
+        read: impl FnOnce(&mut E) -> Result<()>,
+    ) -> Result<()> {
+        check_cancelled(self.cancel, element_def, index)?;
+        self.elements.read_into(element_def, index, read)?;
+        self.report(element_def, index);
+        Ok(())
+    }
+}
+
+impl<E> WatchedSink<'_, E> {
+    /// Same as `read_into()` for an element that was read already.
+    #[cfg(feature = "futures-io")]
+    fn push(&mut self, element_def: &ElementDef, index: u64, element: E) -> Result<()> {
+        check_cancelled(self.cancel, element_def, index)?;
+        match self.elements.get_mut(index as usize) {
+            Some(slot) => *slot = element,
+            None => self.elements.push(element),
+        }
+        self.report(element_def, index);
+        Ok(())
+    }
+    /// Calls the hook if entry `index` completes a batch of entries.
+    fn report(&self, element_def: &ElementDef, index: u64) {
+        let Some((hook, every)) = self.hook else {
+            return;
+        };
+        let read = index + 1;
+        // The last entry is reported once the element is complete.
+        if read.is_multiple_of(every) && read < element_def.count {
+            hook(&ProgressEvent {
+                element: &element_def.name,
+                read,
+                total: element_def.count,
+                bytes: self.bytes.get(),
+            });
+        }
+    }
+}
+
+/// Fails with a `Cancelled` error if `cancel` is set, before entry `index` is read.
+///
+/// Not of kind `Interrupted`, which readers and retry loops take as a reason to try again.
+fn check_cancelled(
+    cancel: Option<&AtomicBool>,
+    element_def: &ElementDef,
+    index: u64,
+) -> Result<()> {
+    match cancel {
+        Some(flag) if flag.load(Ordering::Relaxed) => Err(io::Error::other(Cancelled {
+            element: element_def.name.clone(),
+            index,
+        })),
+        _ => Ok(()),
+    }
+}
+
+/// Hands the elements to a `PlyVisitor`, reading all of them into the same element.
+struct VisitorSink<'a, V, E> {
+    visitor: &'a mut V,
+    element: Option<E>,
+}
+
+impl<E: PropertyAccess, V: PlyVisitor<E>> ElementSink<E> for VisitorSink<'_, V, E> {
+    fn prepare(&mut self, _count: usize) {}
+    fn read_into(
+        &mut self,
+        element_def: &ElementDef,
+        _index: u64,
+        read: impl FnOnce(&mut E) -> Result<()>,
+    ) -> Result<()> {
+        let element = self
+            .element
+            .get_or_insert_with(|| E::new_from_def(element_def));
+        read(element)?;
+        self.visitor.element(element_def, element)
+    }
+}
+
+/// Turns `$scratch` into an empty `Property::$variant`, keeping its buffer if it already is one.
+macro_rules! scratch_list {
+    ($scratch:expr, $variant:ident) => {{
+        if !matches!(*$scratch, Property::$variant(_)) {
+            *$scratch = Property::$variant(PropertyList::new());
+        }
+        match *$scratch {
+            Property::$variant(ref mut list) => {
+                list.clear();
+                list
+            }
+            _ => unreachable!(),
+        }
+    }};
 }
 
 // ////////////////////////////////////////////////////////////////
 // # Ascii
 // ////////////////////////////////////////////////////////////////
-use std::slice::Iter;
 use std::str::FromStr;
 
-use crate::ply::{Property, PropertyType, ScalarType};
+use crate::ply::{Property, PropertyList, PropertyType, ScalarType};
 use std::error;
 use std::marker;
 
@@ -376,73 +1595,343 @@ impl<E: PropertyAccess> Parser<E> {
         reader: &mut T,
         location: &mut LocationTracker,
         element_def: &ElementDef,
-    ) -> Result<Vec<E>> {
-        let mut elems = Vec::<E>::new();
-        let mut line_str = String::new();
-        for _ in 0..element_def.count {
-            line_str.clear();
-            reader.read_line(&mut line_str)?;
-            match self.read_ascii_element(&line_str, element_def) {
-                Ok(e) => elems.push(e),
-                Err(e) => {
-                    return parse_ascii_rethrow(
-                        location,
-                        &line_str,
-                        e,
-                        "Couln't read element line.",
-                    )
+        mut pool: Option<&mut [Property]>,
+        out: &mut impl ElementSink<E>,
+    ) -> Result<()> {
+        out.prepare(materializable_count(element_def)?);
+        let mut partial = Vec::new();
+        let values_per_line = scalar_value_count(element_def);
+        let mut first_mismatch = None;
+        for index in 0..element_def.count {
+            if first_mismatch.is_some()
+                && !element_def.properties.is_empty()
+                && fill_buf(reader)
+                    .map_err(|e| element_error(element_def, index, e))?
+                    .is_empty()
+            {
+                return Err(unexpected_eof(element_def, index, first_mismatch));
+            }
+            let mut mismatched = false;
+            out.read_into(element_def, index, |element| {
+                self.read_entry(
+                    reader,
+                    Encoding::Ascii,
+                    location,
+                    element_def,
+                    index,
+                    &mut partial,
+                    pool.as_deref_mut(),
+                    element,
+                    |line| mismatched = values_per_line.is_some_and(|n| n != count_values(line)),
+                )
+            })?;
+            if first_mismatch.is_none() && mismatched {
+                first_mismatch = Some(index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drives `decode_entry_into()` with the buffer of `reader`, consuming exactly the bytes
+    /// of the entry with the zero-based `index`. `inspect` is called with the bytes of the entry.
+    ///
+    /// An entry that doesn't fit into the buffer is collected in `partial`, except for ascii lines
+    /// longer than `LONG_LINE_LEN`, whose values are parsed while reading.
+    #[allow(clippy::too_many_arguments)]
+    fn read_entry<T: BufRead>(
+        &self,
+        reader: &mut T,
+        encoding: Encoding,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        index: u64,
+        partial: &mut Vec<u8>,
+        mut pool: Option<&mut [Property]>,
+        element: &mut E,
+        inspect: impl FnOnce(&[u8]),
+    ) -> Result<()> {
+        partial.clear();
+        let mut needed = 0;
+        loop {
+            let buf = fill_buf(reader).map_err(|e| element_error(element_def, index, e))?;
+            let eof = buf.is_empty();
+            if partial.is_empty() {
+                let entry = decode_entry_into(
+                    self,
+                    encoding,
+                    element_def,
+                    index,
+                    location,
+                    buf,
+                    eof,
+                    pool.as_deref_mut(),
+                    element,
+                )?;
+                match entry {
+                    Entry::Complete((), len) => {
+                        inspect(&buf[..len]);
+                        reader.consume(len);
+                        return Ok(());
+                    }
+                    Entry::Incomplete(n) => needed = n,
                 }
-            };
-            location.next_line();
+            }
+            // Take only bytes known to belong to this entry, the rest stays in `reader`.
+            let missing = needed.saturating_sub(partial.len());
+            let (len, complete) = continuation_len(encoding, buf, missing);
+            partial.extend_from_slice(&buf[..len]);
+            reader.consume(len);
+            if encoding == Encoding::Ascii
+                && !complete
+                && partial.len() >= LONG_LINE_LEN
+                && !self.options.multiline_ascii_elements
+                && element.raw_mut().is_none()
+            {
+                inspect(partial);
+                return self.read_long_ascii_line(
+                    reader,
+                    location,
+                    element_def,
+                    index,
+                    partial,
+                    pool,
+                    element,
+                );
+            }
+            if complete || eof {
+                let entry = decode_entry_into(
+                    self,
+                    encoding,
+                    element_def,
+                    index,
+                    location,
+                    partial,
+                    eof,
+                    pool.as_deref_mut(),
+                    element,
+                )?;
+                match entry {
+                    Entry::Complete((), _) => {
+                        inspect(partial);
+                        return Ok(());
+                    }
+                    Entry::Incomplete(n) => needed = n,
+                }
+            }
         }
-        Ok(elems)
+    }
+
+    /// Reads the element with the zero-based `index` from the line starting with `line`,
+    /// parsing the values of the rest of the line while reading it from `reader`.
+    #[allow(clippy::too_many_arguments)]
+    fn read_long_ascii_line<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        index: u64,
+        line: &[u8],
+        pool: Option<&mut [Property]>,
+        element: &mut E,
+    ) -> Result<()> {
+        let mut tokens = LineTokens::new(line.chain(reader));
+        let result = self.read_ascii_tokens(&mut tokens, element_def, pool, element);
+        // Consume and check the rest of the line.
+        tokens.by_ref().for_each(drop);
+        let result = match tokens.error {
+            Some(e) => Err(e),
+            None => result,
+        };
+        result.or_else(|e| {
+            let start = String::from_utf8_lossy(&line[..line.len().min(64)]);
+            let line_str = format!("{}... (more than {} bytes)", start, LONG_LINE_LEN);
+            parse_ascii_rethrow(location, &line_str, e, "Couln't read element line.")
+                .map_err(|e| element_error(element_def, index, e))
+        })?;
+        location.next_line();
+        Ok(())
+    }
+
+    /// Reads the element with the zero-based `index` of `element_def`, used to stream elements.
+    ///
+    /// `partial` is a buffer for entries that don't fit into the buffer of `reader`, reused between calls.
+    pub(crate) fn read_element<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        encoding: Encoding,
+        index: u64,
+        partial: &mut Vec<u8>,
+    ) -> Result<E> {
+        let mut element = E::new_from_def(element_def);
+        self.read_entry(
+            reader,
+            encoding,
+            location,
+            element_def,
+            index,
+            partial,
+            None,
+            &mut element,
+            |_| (),
+        )?;
+        Ok(element)
+    }
+
+    /// Reads the element with the zero-based `index` from its line in the payload into `element`.
+    fn read_ascii_line(
+        &self,
+        line_bytes: &[u8],
+        location: &LocationTracker,
+        element_def: &ElementDef,
+        index: u64,
+        pool: Option<&mut [Property]>,
+        element: &mut E,
+    ) -> Result<()> {
+        let result = match std::str::from_utf8(line_bytes) {
+            Ok(line_str) => self.__read_ascii_element(line_str, element_def, pool, element),
+            Err(e) => Err(io::Error::new(ErrorKind::InvalidData, e)),
+        };
+        result.or_else(|e| {
+            let line_str = String::from_utf8_lossy(line_bytes);
+            let message = if looks_like_binary(line_bytes) {
+                "Payload does not look like ascii, the format line may be wrong \
+                (did you mean binary_little_endian?)"
+            } else {
+                "Couln't read element line."
+            };
+            parse_ascii_rethrow(location, &line_str, e, message)
+                .map_err(|e| element_error(element_def, index, e))
+        })
     }
 
     /// Read a single element. Assume it is encoded in ascii.
     ///
     /// Make sure all elements are parsed in the order they are defined in the header.
     pub fn read_ascii_element(&self, line: &str, element_def: &ElementDef) -> Result<E> {
-        let elems = match grammar::data_line(line) {
-            Ok(e) => e,
-            Err(ref e) => {
-                return Err(io::Error::new(
-                    ErrorKind::InvalidInput,
-                    format!(
-                        "Couldn't parse element line.\n\tString: '{}'\n\tError: {}",
-                        line, e
-                    ),
-                ))
-            }
-        };
-
-        let mut elem_it: Iter<String> = elems.iter();
-        let mut vals = E::new();
-        for def in &element_def.properties {
-            let new_p: Property = self.read_ascii_property(&mut elem_it, &def.data_type)?;
-            vals.set_property(&def.name, new_p);
-        }
-        Ok(vals)
+        let mut element = E::new_from_def(element_def);
+        self.__read_ascii_element(line, element_def, None, &mut element)?;
+        Ok(element)
     }
 
-    fn read_ascii_property(
+    fn __read_ascii_element(
         &self,
-        elem_iter: &mut Iter<String>,
-        data_type: &PropertyType,
-    ) -> Result<Property> {
-        let s: &str = match elem_iter.next() {
-            None => {
-                return Err(io::Error::new(
-                    ErrorKind::InvalidInput,
-                    format!(
-                        "Expected element of type '{:?}', but found nothing.",
-                        data_type
-                    ),
-                ))
-            }
-            Some(x) => x,
+        line: &str,
+        element_def: &ElementDef,
+        pool: Option<&mut [Property]>,
+        element: &mut E,
+    ) -> Result<()> {
+        if let Some(raw) = element.raw_mut() {
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            raw.reset(Encoding::Ascii)
+                .extend_from_slice(line.as_bytes());
+            return Ok(());
+        }
+        #[cfg(feature = "simd")]
+        let elems = match ascii_tokenizer::tokenize(line) {
+            Some(e) => e,
+            // Let the grammar describe the error.
+            None => return Err(data_line_error(line)),
         };
+        #[cfg(not(feature = "simd"))]
+        let elems = grammar::data_line(line).map_err(|cause| {
+            ParseError::ElementLine {
+                line: line.to_string(),
+                cause,
+            }
+            .into_io()
+        })?;
 
-        let result = match *data_type {
+        self.read_ascii_tokens(&mut elems.iter(), element_def, pool, element)
+    }
+
+    /// Reads an element from the values of its line into `vals`.
+    fn read_ascii_tokens<I>(
+        &self,
+        elem_it: &mut I,
+        element_def: &ElementDef,
+        mut pool: Option<&mut [Property]>,
+        vals: &mut E,
+    ) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        for (i, def) in element_def.properties.iter().enumerate() {
+            if !self.options.keeps_property(&element_def.name, &def.name) {
+                self.skip_ascii_property(elem_it, element_def, def)?;
+                continue;
+            }
+            let scalar_type = match def.data_type {
+                PropertyType::List(_, scalar_type) => scalar_type,
+                _ => {
+                    let new_p: Property = self.read_ascii_property(elem_it, def)?;
+                    self.store_property(vals, &def.name, new_p)?;
+                    continue;
+                }
+            };
+            let s = next_ascii_value(elem_it, &def.data_type)?;
+            let count = ascii_list_count(s.as_ref(), def)?;
+            self.check_list_len(element_def, def, count)?;
+            match pool.as_deref_mut() {
+                Some(pool) => {
+                    self.read_ascii_list_into(elem_it, count, scalar_type, &mut pool[i])?;
+                    self.store_property_ref(vals, &def.name, &pool[i])?;
+                }
+                None => {
+                    let mut list = Property::ListUChar(PropertyList::new());
+                    self.read_ascii_list_into(elem_it, count, scalar_type, &mut list)?;
+                    self.store_property(vals, &def.name, list)?;
+                }
+            }
+        }
+        vals.finalize(element_def)
+    }
+
+    /// Checks the values of a property that isn't kept, see `ParserOptions::keep_properties()`.
+    fn skip_ascii_property<I>(
+        &self,
+        elem_it: &mut I,
+        element_def: &ElementDef,
+        def: &PropertyDef,
+    ) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        if let PropertyType::List(_, scalar_type) = def.data_type {
+            let s = next_ascii_value(elem_it, &def.data_type)?;
+            let count = ascii_list_count(s.as_ref(), def)?;
+            self.check_list_len(element_def, def, count)?;
+            let property = PropertyDef::new(&def.name, PropertyType::Scalar(scalar_type));
+            for index in 0..count {
+                self.read_ascii_property(elem_it, &property)
+                    .map_err(|e| ParseError::MissingListValue {
+                        index,
+                        cause: Some(e),
+                    })
+                    .map_err(ParseError::into_io)?;
+            }
+            return Ok(());
+        }
+        self.read_ascii_property(elem_it, def).map(drop)
+    }
+
+    fn read_ascii_property<I>(
+        &self,
+        elem_iter: &mut I,
+        property_def: &PropertyDef,
+    ) -> Result<Property>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        let s = next_ascii_value(elem_iter, &property_def.data_type)?;
+        let s = s.as_ref();
+
+        let result = match property_def.data_type {
             PropertyType::Scalar(ref scalar_type) => match *scalar_type {
                 ScalarType::Char => Property::Char(self.parse(s)?),
                 ScalarType::UChar => Property::UChar(self.parse(s)?),
@@ -452,29 +1941,27 @@ impl<E: PropertyAccess> Parser<E> {
                 ScalarType::UInt => Property::UInt(self.parse(s)?),
                 ScalarType::Float => Property::Float(self.parse(s)?),
                 ScalarType::Double => Property::Double(self.parse(s)?),
+                #[cfg(feature = "extended-types")]
+                ScalarType::LongLong => Property::LongLong(self.parse(s)?),
+                #[cfg(feature = "extended-types")]
+                ScalarType::ULongLong => Property::ULongLong(self.parse(s)?),
+                #[cfg(feature = "half")]
+                ScalarType::Half => Property::Half(self.parse(s)?),
             },
-            PropertyType::List(_, ref scalar_type) => {
-                let count: usize = self.parse(s)?;
-                match *scalar_type {
-                    ScalarType::Char => Property::ListChar(self.read_ascii_list(elem_iter, count)?),
-                    ScalarType::UChar => {
-                        Property::ListUChar(self.read_ascii_list(elem_iter, count)?)
-                    }
-                    ScalarType::Short => {
-                        Property::ListShort(self.read_ascii_list(elem_iter, count)?)
-                    }
-                    ScalarType::UShort => {
-                        Property::ListUShort(self.read_ascii_list(elem_iter, count)?)
-                    }
-                    ScalarType::Int => Property::ListInt(self.read_ascii_list(elem_iter, count)?),
-                    ScalarType::UInt => Property::ListUInt(self.read_ascii_list(elem_iter, count)?),
-                    ScalarType::Float => {
-                        Property::ListFloat(self.read_ascii_list(elem_iter, count)?)
-                    }
-                    ScalarType::Double => {
-                        Property::ListDouble(self.read_ascii_list(elem_iter, count)?)
-                    }
-                }
+            PropertyType::List(_, scalar_type) => {
+                let count = ascii_list_count(s, property_def)?;
+                let mut list = Property::ListUChar(PropertyList::new());
+                self.read_ascii_list_into(elem_iter, count, scalar_type, &mut list)?;
+                list
+            }
+            PropertyType::Custom { ref name, .. } => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Custom property type '{}' is only supported in binary payloads.",
+                        name
+                    ),
+                ))
             }
         };
         Ok(result)
@@ -487,239 +1974,1344 @@ impl<E: PropertyAccess> Parser<E> {
         let v = s.parse();
         match v {
             Ok(r) => Ok(r),
-            Err(e) => Err(io::Error::new(
-                ErrorKind::InvalidInput,
-                format!("Parse error.\n\tValue: '{}'\n\tError: {:?}, ", s, e),
+            Err(e) => Err(ParseError::Value {
+                value: s.to_string(),
+                cause: Box::new(e),
+            }
+            .into_io()),
+        }
+    }
+
+    /// Reads `count` values into `list`, which becomes a list of `scalar_type`.
+    fn read_ascii_list_into<I>(
+        &self,
+        elem_iter: &mut I,
+        count: usize,
+        scalar_type: ScalarType,
+        list: &mut Property,
+    ) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match scalar_type {
+            ScalarType::Char => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListChar))
+            }
+            ScalarType::UChar => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListUChar))
+            }
+            ScalarType::Short => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListShort))
+            }
+            ScalarType::UShort => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListUShort))
+            }
+            ScalarType::Int => self.read_ascii_list(elem_iter, count, scratch_list!(list, ListInt)),
+            ScalarType::UInt => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListUInt))
+            }
+            ScalarType::Float => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListFloat))
+            }
+            ScalarType::Double => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListDouble))
+            }
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListLongLong))
+            }
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListULongLong))
+            }
+            #[cfg(feature = "half")]
+            ScalarType::Half => {
+                self.read_ascii_list(elem_iter, count, scratch_list!(list, ListHalf))
+            }
+        }
+    }
+
+    fn read_ascii_list<I, D: FromStr>(
+        &self,
+        elem_iter: &mut I,
+        count: usize,
+        list: &mut PropertyList<D>,
+    ) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+        <D as FromStr>::Err: error::Error + marker::Send + marker::Sync + 'static,
+    {
+        for i in 0..count {
+            let s = match elem_iter.next() {
+                None => {
+                    return Err(ParseError::MissingListValue {
+                        index: i,
+                        cause: None,
+                    }
+                    .into_io())
+                }
+                Some(x) => x,
+            };
+            let value: D = self.parse(s.as_ref())?;
+            list.push(value);
+        }
+        Ok(())
+    }
+}
+
+/// Takes the next value of an ascii element line, expected to be of type `data_type`.
+fn next_ascii_value<I: Iterator>(elem_iter: &mut I, data_type: &PropertyType) -> Result<I::Item> {
+    match elem_iter.next() {
+        None => Err(ParseError::MissingValue(data_type.clone()).into_io()),
+        Some(x) => Ok(x),
+    }
+}
+
+/// Parses the ascii list count `s` of `property_def`, telling negative counts apart from garbage.
+fn ascii_list_count(s: &str, property_def: &PropertyDef) -> Result<usize> {
+    match s.parse::<usize>() {
+        Ok(count) => Ok(count),
+        Err(_) => {
+            let negative = matches!(s.parse::<i128>(), Ok(v) if v < 0);
+            Err(ParseError::ListCount {
+                property: property_def.name.clone(),
+                value: s.to_string(),
+                negative,
+            }
+            .into_io())
+        }
+    }
+}
+
+/// Number of values on each line of `element_def`, `None` if it depends on list lengths.
+fn scalar_value_count(element_def: &ElementDef) -> Option<usize> {
+    element_def
+        .properties
+        .iter()
+        .all(|p| matches!(p.data_type, PropertyType::Scalar(_)))
+        .then_some(element_def.properties.len())
+}
+
+/// Counts the whitespace separated values of an ascii line.
+fn count_values(line: &[u8]) -> usize {
+    let mut count = 0;
+    let mut in_value = false;
+    for &c in line {
+        let separator = matches!(c, b' ' | b'\t' | b'\r' | b'\n');
+        if !separator && !in_value {
+            count += 1;
+        }
+        in_value = !separator;
+    }
+    count
+}
+
+/// Describes an ascii payload that ended after `found` lines of `element_def`.
+///
+/// If known, `first_mismatch` is the first of these lines that didn't look like this element.
+/// Then the lines from there on likely belong to the next element and the count is too big.
+fn unexpected_eof(element_def: &ElementDef, found: u64, first_mismatch: Option<u64>) -> io::Error {
+    let (found, mismatched) = match first_mismatch {
+        Some(m) => (m, found - m),
+        None => (found, 0),
+    };
+    io::Error::new(
+        ErrorKind::UnexpectedEof,
+        ParseError::UnexpectedEof {
+            element: element_def.name.clone(),
+            declared: element_def.count,
+            found,
+            mismatched,
+        },
+    )
+}
+
+/// Parses `line` with the grammar to describe why it isn't a valid data line.
+#[cfg(feature = "simd")]
+fn data_line_error(line: &str) -> io::Error {
+    match grammar::data_line(line) {
+        Err(cause) => ParseError::ElementLine {
+            line: line.to_string(),
+            cause,
+        }
+        .into_io(),
+        Ok(_) => io::Error::new(ErrorKind::InvalidInput, "Couldn't parse element line."),
+    }
+}
+
+// //////////////////////////////////////
+// # Parallel ascii
+// //////////////////////////////////////
+#[cfg(feature = "parallel")]
+use std::thread;
+
+/// # Parallel ascii
+#[cfg(feature = "parallel")]
+impl<E: PropertyAccess + Send> Parser<E> {
+    /// Same as `read_ply()`, but parses large ascii elements on multiple threads.
+    ///
+    /// Elements with at least `ParserOptions::parallel_threshold()` entries are read by
+    /// `read_ascii_payload_parallel()`, unless `ParserOptions::multiline_ascii_elements()`,
+    /// `ParserOptions::on_progress()` or `ParserOptions::cancel_flag()` is set.
+    ///
+    /// Requires the `parallel` feature.
+    pub fn read_ply_parallel<T: Read>(&self, source: &mut T) -> Result<Ply<E>> {
+        let mut source = BufReader::new(source);
+        self.__read_ply(&mut source, Parallel)
+    }
+
+    /// Same as `read_payload()`, but parses large ascii elements on multiple threads,
+    /// see `read_ply_parallel()`.
+    ///
+    /// Requires the `parallel` feature.
+    pub fn read_payload_parallel<T: BufRead>(
+        &self,
+        reader: &mut T,
+        header: &Header,
+    ) -> Result<Payload<E>> {
+        let mut location = LocationTracker::payload();
+        self.__read_payload(reader, &mut location, header, Parallel)
+    }
+
+    /// Reads all elements of `element_def` from an ascii payload, parsing the lines on multiple threads.
+    ///
+    /// All lines of the element are read into memory first, the elements are returned in file order.
+    ///
+    /// Requires the `parallel` feature.
+    pub fn read_ascii_payload_parallel<T: BufRead>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+    ) -> Result<Vec<E>> {
+        let mut location = LocationTracker::payload();
+        self.__read_ascii_payload_parallel(reader, &mut location, element_def)
+    }
+
+    fn __read_ascii_payload_parallel<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+    ) -> Result<Vec<E>> {
+        let count = materializable_count(element_def)?;
+        let mut text = Vec::new();
+        let mut line_ends = Vec::new();
+        let values_per_line = scalar_value_count(element_def);
+        let mut first_mismatch = None;
+        for _ in 0..count {
+            let n = reader
+                .read_until(b'\n', &mut text)
+                .map_err(|e| element_error(element_def, line_ends.len() as u64, e))?;
+            if n == 0 && !element_def.properties.is_empty() {
+                let found = line_ends.len() as u64;
+                return Err(unexpected_eof(element_def, found, first_mismatch));
+            }
+            let line_start = line_ends.last().map_or(0, |&e| e);
+            line_ends.push(text.len());
+            if first_mismatch.is_none()
+                && values_per_line.is_some_and(|n| n != count_values(&text[line_start..]))
+            {
+                first_mismatch = Some(line_ends.len() as u64 - 1);
+            }
+            if n == 0 {
+                // Reached the end, elements without properties continue like the sequential reader.
+                break;
+            }
+        }
+        let lines = line_ends.len();
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_len = lines.div_ceil(threads).max(1);
+        let first = *location;
+        let (text, line_ends) = (&text, &line_ends);
+        let chunks: Vec<Result<Vec<E>>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..lines)
+                .step_by(chunk_len)
+                .map(|start| {
+                    scope.spawn(move || {
+                        let end = (start + chunk_len).min(lines);
+                        let mut elems = Vec::with_capacity(end - start);
+                        let mut location = LocationTracker {
+                            line_index: first.line_index + start,
+                            ..first
+                        };
+                        for i in start..end {
+                            let line_start = if i == 0 { 0 } else { line_ends[i - 1] };
+                            let line = &text[line_start..line_ends[i]];
+                            let mut element = E::new_from_def(element_def);
+                            self.read_ascii_line(
+                                line,
+                                &location,
+                                element_def,
+                                i as u64,
+                                None,
+                                &mut element,
+                            )?;
+                            elems.push(element);
+                            location.next_line();
+                        }
+                        Ok(elems)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        });
+        // Chunks are in file order, hence the first error is the first failing line.
+        let mut elems = Vec::with_capacity(lines);
+        for chunk in chunks {
+            elems.extend(chunk?);
+        }
+        location.line_index += lines;
+        // Elements without properties parse from empty lines, continue like the sequential reader.
+        let mut line_bytes = Vec::new();
+        for index in lines..count {
+            line_bytes.clear();
+            reader.read_until(b'\n', &mut line_bytes)?;
+            let mut element = E::new_from_def(element_def);
+            self.read_ascii_line(
+                &line_bytes,
+                location,
+                element_def,
+                index as u64,
+                None,
+                &mut element,
+            )?;
+            elems.push(element);
+            location.next_line();
+        }
+        Ok(elems)
+    }
+}
+
+/// Reads large ascii elements with `Parser::read_ascii_payload_parallel()`.
+#[cfg(feature = "parallel")]
+#[derive(Clone, Copy)]
+struct Parallel;
+
+#[cfg(feature = "parallel")]
+impl<E: PropertyAccess + Send> ReadElements<E> for Parallel {
+    fn read_elements<T: BufRead>(
+        self,
+        parser: &Parser<E>,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+    ) -> Result<Vec<E>> {
+        if header.encoding == Encoding::Ascii
+            && element_def.count >= parser.options.parallel_threshold
+            && !parser.options.multiline_ascii_elements
+        {
+            return parser.__read_ascii_payload_parallel(reader, location, element_def);
+        }
+        Sequential.read_elements(parser, reader, location, element_def, header)
+    }
+}
+
+// //////////////////////////////////////
+// # Binary
+// //////////////////////////////////////
+/*
+use std::io;
+use std::io::{ Read, Result, ErrorKind };
+use std::str::FromStr;
+use std::error;
+use std::marker;
+use ply::{ PropertyAccess, ElementDef, PropertyType, Property, ScalarType };
+use util::LocationTracker;
+use super::Parser;
+*/
+use byteorder::ReadBytesExt;
+pub use byteorder::{BigEndian, ByteOrder, LittleEndian};
+#[cfg(feature = "half")]
+use half::f16;
+
+/// Reads from a slice and remembers whether a read asked for more bytes than were left.
+struct SliceReader<'a> {
+    rest: &'a [u8],
+    short: bool,
+}
+
+impl Read for SliceReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        // Values are read with exact lengths, a longer request means the slice is too short.
+        self.short |= buf.len() > self.rest.len();
+        self.rest.read(buf)
+    }
+}
+
+/// Appends the next `len` bytes of `reader` to `bytes`.
+fn read_raw<T: Read>(reader: &mut T, len: usize, bytes: &mut Vec<u8>) -> Result<()> {
+    let start = bytes.len();
+    // Reading through `take()` doesn't allocate more than `reader` holds.
+    reader.take(len as u64).read_to_end(bytes)?;
+    if bytes.len() - start < len {
+        return Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("Expected {} bytes, found {}.", len, bytes.len() - start),
+        ));
+    }
+    Ok(())
+}
+
+/// The encoding of binary payloads in the byte order `B`.
+pub(crate) fn binary_encoding<B: ByteOrder>() -> Encoding {
+    if B::read_u16(&[1, 0]) == 1 {
+        Encoding::BinaryLittleEndian
+    } else {
+        Encoding::BinaryBigEndian
+    }
+}
+
+#[cfg(feature = "half")]
+fn read_f16<T: Read, B: ByteOrder>(reader: &mut T) -> Result<f16> {
+    Ok(f16::from_bits(reader.read_u16::<B>()?))
+}
+
+/// # Binary
+impl<E: PropertyAccess> Parser<E> {
+    /// Reads a single element as declared in èlement_def. Assumes big endian encoding.
+    ///
+    /// Make sure all elements are parsed in the order they are defined in the header.
+    pub fn read_big_endian_element<T: Read>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+    ) -> Result<E> {
+        // Reduce coupling with ByteOrder
+        self.read_binary_element::<T, BigEndian>(reader, element_def, None)
+    }
+
+    /// Reads a single element as declared in èlement_def. Assumes big endian encoding.
+    ///
+    /// Make sure all elements are parsed in the order they are defined in the header.
+    pub fn read_little_endian_element<T: Read>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+    ) -> Result<E> {
+        // Reduce coupling with ByteOrder
+        self.read_binary_element::<T, LittleEndian>(reader, element_def, None)
+    }
+
+    /// Reads a single binary element from the start of `bytes`.
+    ///
+    /// Returns the element and the number of bytes it occupied,
+    /// which varies with the length of its lists.
+    /// If `bytes` ends before the element does, an error of kind `UnexpectedEof` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::{LittleEndian, Parser};
+    /// # use ply_rs::ply::{DefaultElement, ElementDef, Property, PropertyDef, PropertyType, ScalarType};
+    /// let mut def = ElementDef::new("face");
+    /// def.properties.push(PropertyDef::new(
+    ///     "vertex_indices",
+    ///     PropertyType::List(ScalarType::UChar, ScalarType::UChar),
+    /// ));
+    /// let p = Parser::<DefaultElement>::new();
+    /// let (face, len) = p
+    ///     .read_binary_element_from_slice::<LittleEndian>(&[3, 0, 1, 2, 3], &def)
+    ///     .unwrap();
+    /// assert_eq!(len, 4);
+    /// assert_eq!(face["vertex_indices"], Property::ListUChar(vec![0, 1, 2].into()));
+    /// ```
+    pub fn read_binary_element_from_slice<B: ByteOrder>(
+        &self,
+        bytes: &[u8],
+        element_def: &ElementDef,
+    ) -> Result<(E, usize)> {
+        let mut reader = SliceReader {
+            rest: bytes,
+            short: false,
+        };
+        let mut element = E::new_from_def(element_def);
+        match self.read_binary_element_into::<_, B>(&mut reader, element_def, None, &mut element) {
+            Ok(()) => Ok((element, bytes.len() - reader.rest.len())),
+            Err(e) if reader.short => Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "Slice of {} bytes ends within element '{}': {}",
+                    bytes.len(),
+                    element_def.name,
+                    e
+                ),
             )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Same as `read_binary_element_from_slice()`, assumes big endian encoding.
+    pub fn read_big_endian_element_from_slice(
+        &self,
+        bytes: &[u8],
+        element_def: &ElementDef,
+    ) -> Result<(E, usize)> {
+        self.read_binary_element_from_slice::<BigEndian>(bytes, element_def)
+    }
+
+    /// Same as `read_binary_element_from_slice()`, assumes little endian encoding.
+    pub fn read_little_endian_element_from_slice(
+        &self,
+        bytes: &[u8],
+        element_def: &ElementDef,
+    ) -> Result<(E, usize)> {
+        self.read_binary_element_from_slice::<LittleEndian>(bytes, element_def)
+    }
+
+    fn read_binary_payload_for_element<T: BufRead, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        mut pool: Option<&mut [Property]>,
+        out: &mut impl ElementSink<E>,
+    ) -> Result<()> {
+        out.prepare(materializable_count(element_def)?);
+        let looks_ascii = element_def.count > 0 && looks_like_ascii(fill_buf(reader)?);
+        let mut partial = Vec::new();
+        for index in 0..element_def.count {
+            let result = out.read_into(element_def, index, |element| {
+                self.read_entry(
+                    reader,
+                    binary_encoding::<B>(),
+                    location,
+                    element_def,
+                    index,
+                    &mut partial,
+                    pool.as_deref_mut(),
+                    element,
+                    |_| (),
+                )
+            });
+            match result {
+                Ok(()) => (),
+                Err(e) if looks_ascii => return Err(looks_ascii_error(element_def, index, e)),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads past the first `count` elements of `element_def` without decoding them,
+    /// see `ParserOptions::skip_elements()`.
+    fn skip_payload_for_element<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        encoding: Encoding,
+        count: u64,
+    ) -> Result<()> {
+        match encoding {
+            Encoding::Ascii if self.options.multiline_ascii_elements => {
+                // Only parsing finds the end of elements spanning lines.
+                let mut partial = Vec::new();
+                let mut element = E::new_from_def(element_def);
+                for index in 0..count {
+                    self.read_entry(
+                        reader,
+                        encoding,
+                        location,
+                        element_def,
+                        index,
+                        &mut partial,
+                        None,
+                        &mut element,
+                        |_| (),
+                    )?;
+                }
+                Ok(())
+            }
+            Encoding::Ascii => {
+                for index in 0..count {
+                    let found =
+                        skip_line(reader).map_err(|e| element_error(element_def, index, e))?;
+                    if !found && !element_def.properties.is_empty() {
+                        return Err(unexpected_eof(element_def, index, None));
+                    }
+                    location.next_line();
+                }
+                Ok(())
+            }
+            Encoding::BinaryBigEndian => {
+                skip_binary_elements::<T, BigEndian>(reader, element_def, count)
+            }
+            Encoding::BinaryLittleEndian => {
+                skip_binary_elements::<T, LittleEndian>(reader, element_def, count)
+            }
+        }
+    }
+
+    fn read_binary_element<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        pool: Option<&mut [Property]>,
+    ) -> Result<E> {
+        let mut raw_element = E::new_from_def(element_def);
+        self.read_binary_element_into::<T, B>(reader, element_def, pool, &mut raw_element)?;
+        Ok(raw_element)
+    }
+
+    /// Reads an element into `raw_element`, overwriting its properties.
+    fn read_binary_element_into<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        mut pool: Option<&mut [Property]>,
+        raw_element: &mut E,
+    ) -> Result<()> {
+        if let Some(raw) = raw_element.raw_mut() {
+            let encoding = binary_encoding::<B>();
+            return self.read_binary_raw::<T, B>(reader, element_def, raw.reset(encoding));
+        }
+        for (i, def) in element_def.properties.iter().enumerate() {
+            if !self.options.keeps_property(&element_def.name, &def.name) {
+                self.skip_binary_property::<T, B>(reader, element_def, def)?;
+                continue;
+            }
+            let (index_type, property_type) = match def.data_type {
+                PropertyType::List(index_type, property_type) => (index_type, property_type),
+                _ => {
+                    let property = self.read_binary_property::<T, B>(reader, &def.data_type)?;
+                    self.store_property(raw_element, &def.name, property)?;
+                    continue;
+                }
+            };
+            let count = self.read_binary_list_count::<T, B>(reader, index_type)?;
+            let fixed = self.check_list_len(element_def, def, count)?;
+            match pool.as_deref_mut() {
+                Some(pool) => {
+                    self.read_binary_list_checked::<T, B>(
+                        reader,
+                        count,
+                        property_type,
+                        fixed,
+                        &mut pool[i],
+                    )?;
+                    self.store_property_ref(raw_element, &def.name, &pool[i])?;
+                }
+                None => {
+                    let mut list = Property::ListUChar(PropertyList::new());
+                    self.read_binary_list_checked::<T, B>(
+                        reader,
+                        count,
+                        property_type,
+                        fixed,
+                        &mut list,
+                    )?;
+                    self.store_property(raw_element, &def.name, list)?;
+                }
+            }
+        }
+        raw_element.finalize(element_def)
+    }
+
+    /// Reads past a property that isn't kept, see `ParserOptions::keep_properties()`.
+    fn skip_binary_property<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        def: &PropertyDef,
+    ) -> Result<()> {
+        let len = match def.data_type {
+            PropertyType::Scalar(scalar_type) => scalar_type.size(),
+            PropertyType::Custom { size, .. } => size,
+            PropertyType::List(index_type, property_type) => {
+                let count = self.read_binary_list_count::<T, B>(reader, index_type)?;
+                self.check_list_len(element_def, def, count)?;
+                count.checked_mul(property_type.size()).ok_or_else(|| {
+                    io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("List '{}' is too long: {} entries.", def.name, count),
+                    )
+                })?
+            }
+        };
+        let mut buffer = [0u8; 64];
+        match buffer.get_mut(..len) {
+            Some(bytes) => reader.read_exact(bytes),
+            None => {
+                let skipped = io::copy(&mut reader.take(len as u64), &mut io::sink())?;
+                if skipped < len as u64 {
+                    return Err(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        format!("Payload ends within property '{}'.", def.name),
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Appends the encoded values of an element to `bytes` without decoding them, see `RawElement`.
+    fn read_binary_raw<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        bytes: &mut Vec<u8>,
+    ) -> Result<()> {
+        for def in &element_def.properties {
+            match def.data_type {
+                PropertyType::Scalar(scalar_type) => read_raw(reader, scalar_type.size(), bytes)?,
+                PropertyType::Custom { size, .. } => read_raw(reader, size, bytes)?,
+                PropertyType::List(index_type, property_type) => {
+                    let start = bytes.len();
+                    read_raw(reader, index_type.size(), bytes)?;
+                    let count = binary_list_count::<B>(&index_type, &bytes[start..])?;
+                    self.check_list_len(element_def, def, count)?;
+                    let len = count.checked_mul(property_type.size()).ok_or_else(|| {
+                        io::Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("List '{}' is too long: {} entries.", def.name, count),
+                        )
+                    })?;
+                    read_raw(reader, len, bytes)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a list of `count` values, fixed lists of up to 64 bytes are read in one go.
+    fn read_binary_list_checked<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        count: usize,
+        property_type: ScalarType,
+        fixed: bool,
+        list: &mut Property,
+    ) -> Result<()> {
+        let mut buffer = [0u8; 64];
+        let len = count.saturating_mul(property_type.size());
+        match buffer.get_mut(..len) {
+            Some(bytes) if fixed => {
+                reader.read_exact(bytes)?;
+                self.read_binary_list_into::<&[u8], B>(&mut &bytes[..], count, property_type, list)
+            }
+            _ => self.read_binary_list_into::<T, B>(reader, count, property_type, list),
+        }
+    }
+
+    fn read_binary_property<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        data_type: &PropertyType,
+    ) -> Result<Property> {
+        let result = match *data_type {
+            PropertyType::Scalar(scalar_type) => match scalar_type {
+                ScalarType::Char => Property::Char(reader.read_i8()?),
+                ScalarType::UChar => Property::UChar(reader.read_u8()?),
+                ScalarType::Short => Property::Short(reader.read_i16::<B>()?),
+                ScalarType::UShort => Property::UShort(reader.read_u16::<B>()?),
+                ScalarType::Int => Property::Int(reader.read_i32::<B>()?),
+                ScalarType::UInt => Property::UInt(reader.read_u32::<B>()?),
+                ScalarType::Float => Property::Float(reader.read_f32::<B>()?),
+                ScalarType::Double => Property::Double(reader.read_f64::<B>()?),
+                #[cfg(feature = "extended-types")]
+                ScalarType::LongLong => Property::LongLong(reader.read_i64::<B>()?),
+                #[cfg(feature = "extended-types")]
+                ScalarType::ULongLong => Property::ULongLong(reader.read_u64::<B>()?),
+                #[cfg(feature = "half")]
+                ScalarType::Half => Property::Half(read_f16::<T, B>(reader)?),
+            },
+            PropertyType::List(index_type, property_type) => {
+                let count = self.read_binary_list_count::<T, B>(reader, index_type)?;
+                let mut list = Property::ListUChar(PropertyList::new());
+                self.read_binary_list_into::<T, B>(reader, count, property_type, &mut list)?;
+                list
+            }
+            PropertyType::Custom { ref name, size } => {
+                let mut bytes = vec![0; size];
+                reader.read_exact(&mut bytes)?;
+                match self.options.custom_type(name).and_then(|h| h.decode) {
+                    Some(decode) => decode(&bytes),
+                    None => Property::Custom(bytes.into()),
+                }
+            }
+        };
+        Ok(result)
+    }
+
+    /// Reads the number of entries of a list.
+    fn read_binary_list_count<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        index_type: ScalarType,
+    ) -> Result<usize> {
+        let count = match index_type {
+            ScalarType::Char => list_count(reader.read_i8()?)?,
+            ScalarType::UChar => list_count(reader.read_u8()?)?,
+            ScalarType::Short => list_count(reader.read_i16::<B>()?)?,
+            ScalarType::UShort => list_count(reader.read_u16::<B>()?)?,
+            ScalarType::Int => list_count(reader.read_i32::<B>()?)?,
+            ScalarType::UInt => list_count(reader.read_u32::<B>()?)?,
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => list_count(reader.read_i64::<B>()?)?,
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => list_count(reader.read_u64::<B>()?)?,
+            #[cfg(feature = "half")]
+            ScalarType::Half => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "Index of list must be an integer type, half declared in ScalarType.",
+                ))
+            }
+            ScalarType::Float => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "Index of list must be an integer type, float declared in ScalarType.",
+                ))
+            }
+            ScalarType::Double => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "Index of list must be an integer type, double declared in ScalarType.",
+                ))
+            }
+        };
+        Ok(count)
+    }
+
+    /// Reads `count` values into `list`, which becomes a list of `property_type`.
+    fn read_binary_list_into<T: Read, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        count: usize,
+        property_type: ScalarType,
+        list: &mut Property,
+    ) -> Result<()> {
+        match property_type {
+            ScalarType::Char => {
+                self.read_binary_list(reader, T::read_i8, count, scratch_list!(list, ListChar))
+            }
+            ScalarType::UChar => {
+                self.read_binary_list(reader, T::read_u8, count, scratch_list!(list, ListUChar))
+            }
+            ScalarType::Short => self.read_binary_list(
+                reader,
+                T::read_i16::<B>,
+                count,
+                scratch_list!(list, ListShort),
+            ),
+            ScalarType::UShort => self.read_binary_list(
+                reader,
+                T::read_u16::<B>,
+                count,
+                scratch_list!(list, ListUShort),
+            ),
+            ScalarType::Int => self.read_binary_list(
+                reader,
+                T::read_i32::<B>,
+                count,
+                scratch_list!(list, ListInt),
+            ),
+            ScalarType::UInt => self.read_binary_list(
+                reader,
+                T::read_u32::<B>,
+                count,
+                scratch_list!(list, ListUInt),
+            ),
+            ScalarType::Float => self.read_binary_list(
+                reader,
+                T::read_f32::<B>,
+                count,
+                scratch_list!(list, ListFloat),
+            ),
+            ScalarType::Double => self.read_binary_list(
+                reader,
+                T::read_f64::<B>,
+                count,
+                scratch_list!(list, ListDouble),
+            ),
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => self.read_binary_list(
+                reader,
+                T::read_i64::<B>,
+                count,
+                scratch_list!(list, ListLongLong),
+            ),
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => self.read_binary_list(
+                reader,
+                T::read_u64::<B>,
+                count,
+                scratch_list!(list, ListULongLong),
+            ),
+            #[cfg(feature = "half")]
+            ScalarType::Half => self.read_binary_list(
+                reader,
+                read_f16::<T, B>,
+                count,
+                scratch_list!(list, ListHalf),
+            ),
+        }
+    }
+
+    fn read_binary_list<T: Read, D>(
+        &self,
+        reader: &mut T,
+        read_from: fn(&mut T) -> Result<D>,
+        count: usize,
+        list: &mut PropertyList<D>,
+    ) -> Result<()> {
+        list.reserve(count.min(MAX_PREALLOCATION));
+        for i in 0..count {
+            let value: D = match read_from(reader) {
+                // The reader failed, rather than ran out of data: keep the kind, e.g. `WouldBlock`.
+                Err(e) if e.kind() != ErrorKind::UnexpectedEof => {
+                    let kind = e.kind();
+                    let e = ParseError::MissingListValue {
+                        index: i,
+                        cause: Some(e),
+                    };
+                    return Err(io::Error::new(kind, e));
+                }
+                Err(e) => {
+                    return Err(ParseError::MissingListValue {
+                        index: i,
+                        cause: Some(e),
+                    }
+                    .into_io())
+                }
+                Ok(x) => x,
+            };
+            list.push(value);
+        }
+        Ok(())
+    }
+}
+
+// //////////////////////////////////////
+// # Bytes
+// //////////////////////////////////////
+#[cfg(feature = "bytes")]
+use bytes::Bytes;
+
+/// # Bytes
+#[cfg(feature = "bytes")]
+impl<E: PropertyAccess> Parser<E> {
+    /// Reads an entire PLY file held in memory, for example a memory mapped file.
+    ///
+    /// Works like `read_ply()`, except that uchar lists of binary payloads are not copied:
+    /// They are passed to the element as `Property::ListBytes`, referencing `source`.
+    ///
+    /// Requires the `bytes` feature.
+    pub fn read_ply_from_bytes(&self, source: &Bytes) -> Result<Ply<E>> {
+        let mut cursor: &[u8] = source;
+        let mut location = LocationTracker::new();
+        let header = self.__read_header(&mut cursor, &mut location)?;
+        let payload_start = source.len() - cursor.len();
+        let payload = match header.encoding {
+            Encoding::Ascii => {
+                self.__read_payload(&mut cursor, &mut location, &header, Sequential)?
+            }
+            Encoding::BinaryBigEndian => {
+                self.read_bytes_payload::<BigEndian>(source, &mut cursor, &header)?
+            }
+            Encoding::BinaryLittleEndian => {
+                self.read_bytes_payload::<LittleEndian>(source, &mut cursor, &header)?
+            }
+        };
+        if let Some(expected) = self.options.expected_checksum(&header) {
+            let mut crc = Crc32::new();
+            crc.update(&source[payload_start..source.len() - cursor.len()]);
+            check_checksum(expected, crc.finish())?;
+        }
+        let mut ply = Ply::new();
+        ply.header = header;
+        ply.payload = payload;
+        Ok(ply)
+    }
+
+    fn read_bytes_payload<B: ByteOrder>(
+        &self,
+        source: &Bytes,
+        cursor: &mut &[u8],
+        header: &Header,
+    ) -> Result<Payload<E>> {
+        let mut payload = Payload::new();
+        for e in &header.elements {
+            if self.options.skips_element(&e.name) {
+                skip_binary_elements::<_, B>(cursor, e, e.count)?;
+                continue;
+            }
+            let mut elems =
+                Vec::<E>::with_capacity(materializable_count(e)?.min(MAX_PREALLOCATION));
+            for index in 0..e.count {
+                let element = self
+                    .read_bytes_element::<B>(source, cursor, e)
+                    .map_err(|err| element_error(e, index, err))?;
+                elems.push(element);
+            }
+            payload.insert(e.name.clone(), elems);
         }
+        Ok(payload)
     }
 
-    fn read_ascii_list<D: FromStr>(
+    /// Reads a binary element, `cursor` is the part of `source` not read yet.
+    fn read_bytes_element<B: ByteOrder>(
         &self,
-        elem_iter: &mut Iter<String>,
-        count: usize,
-    ) -> Result<Vec<D>>
-    where
-        <D as FromStr>::Err: error::Error + marker::Send + marker::Sync + 'static,
-    {
-        let mut list = Vec::<D>::new();
-        for i in 0..count {
-            let s: &str = match elem_iter.next() {
-                None => {
-                    return Err(io::Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("Couldn't find a list element at index {}.", i),
-                    ))
+        source: &Bytes,
+        cursor: &mut &[u8],
+        element_def: &ElementDef,
+    ) -> Result<E> {
+        let mut raw_element = E::new_from_def(element_def);
+        if raw_element.raw_mut().is_some() {
+            self.read_binary_element_into::<&[u8], B>(cursor, element_def, None, &mut raw_element)?;
+            return Ok(raw_element);
+        }
+        for def in &element_def.properties {
+            let property = match def.data_type {
+                PropertyType::List(index_type, ScalarType::UChar) => {
+                    let count = self.read_binary_list_count::<&[u8], B>(cursor, index_type)?;
+                    self.check_list_len(element_def, def, count)?;
+                    if cursor.len() < count {
+                        return Err(ParseError::MissingListValue {
+                            index: cursor.len(),
+                            cause: None,
+                        }
+                        .into_io());
+                    }
+                    let start = source.len() - cursor.len();
+                    *cursor = &cursor[count..];
+                    Property::ListBytes(Box::new(source.slice(start..start + count)))
                 }
-                Some(x) => x,
+                PropertyType::List(index_type, property_type) => {
+                    let count = self.read_binary_list_count::<&[u8], B>(cursor, index_type)?;
+                    let fixed = self.check_list_len(element_def, def, count)?;
+                    let mut list = Property::ListUChar(PropertyList::new());
+                    self.read_binary_list_checked::<&[u8], B>(
+                        cursor,
+                        count,
+                        property_type,
+                        fixed,
+                        &mut list,
+                    )?;
+                    list
+                }
+                _ => self.read_binary_property::<&[u8], B>(cursor, &def.data_type)?,
             };
-            let value: D = self.parse(s)?;
-            list.push(value);
+            if self.options.keeps_property(&element_def.name, &def.name) {
+                self.store_property(&mut raw_element, &def.name, property)?;
+            }
         }
-        Ok(list)
+        raw_element.finalize(element_def)?;
+        Ok(raw_element)
     }
 }
 
 // //////////////////////////////////////
-// # Binary
+// # Timeout
 // //////////////////////////////////////
-/*
-use std::io;
-use std::io::{ Read, Result, ErrorKind };
-use std::str::FromStr;
-use std::error;
-use std::marker;
-use ply::{ PropertyAccess, ElementDef, PropertyType, Property, ScalarType };
-use util::LocationTracker;
-use super::Parser;
-*/
-use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
-use peg;
+use std::net::TcpStream;
+use std::time::Duration;
 
-/// # Binary
-impl<E: PropertyAccess> Parser<E> {
-    /// Reads a single element as declared in èlement_def. Assumes big endian encoding.
-    ///
-    /// Make sure all elements are parsed in the order they are defined in the header.
-    pub fn read_big_endian_element<T: Read>(
-        &self,
-        reader: &mut T,
-        element_def: &ElementDef,
-    ) -> Result<E> {
-        // Reduce coupling with ByteOrder
-        self.read_binary_element::<T, BigEndian>(reader, element_def)
+/// Sources that can time out a read themselves, for `Parser::read_ply_with_timeout()`.
+pub trait ReadTimeout: Read {
+    /// The current timeout of a single read, `None` if reads may block forever.
+    fn read_timeout(&self) -> Result<Option<Duration>>;
+    /// Sets the timeout of a single read, `None` lets reads block forever.
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<()>;
+}
+
+impl ReadTimeout for TcpStream {
+    fn read_timeout(&self) -> Result<Option<Duration>> {
+        TcpStream::read_timeout(self)
+    }
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
     }
+}
 
-    /// Reads a single element as declared in èlement_def. Assumes big endian encoding.
+#[cfg(unix)]
+impl ReadTimeout for std::os::unix::net::UnixStream {
+    fn read_timeout(&self) -> Result<Option<Duration>> {
+        std::os::unix::net::UnixStream::read_timeout(self)
+    }
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<()> {
+        std::os::unix::net::UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+/// # Timeout
+impl<E: PropertyAccess> Parser<E> {
+    /// Like `read_ply()`, but fails if a single read of `source` takes longer than `per_read_timeout`.
     ///
-    /// Make sure all elements are parsed in the order they are defined in the header.
-    pub fn read_little_endian_element<T: Read>(
+    /// Meant for sources that may stall forever, like a dead network connection.
+    /// The timeout applies to each read rather than the whole file, so large files don't need a generous limit.
+    /// On expiry, an error of kind `TimedOut` is returned, naming the element and the index being read.
+    ///
+    /// The timeout is set on `source` while reading and restored afterwards,
+    /// no thread is left waiting for a stalled source.
+    pub fn read_ply_with_timeout<T: ReadTimeout>(
         &self,
-        reader: &mut T,
-        element_def: &ElementDef,
-    ) -> Result<E> {
-        // Reduce coupling with ByteOrder
-        self.read_binary_element::<T, LittleEndian>(reader, element_def)
+        source: &mut T,
+        per_read_timeout: Duration,
+    ) -> Result<Ply<E>> {
+        let previous = source.read_timeout()?;
+        source.set_read_timeout(Some(per_read_timeout))?;
+        let result = self.read_ply(&mut TimeoutReader {
+            source: &mut *source,
+            timeout: per_read_timeout,
+        });
+        source.set_read_timeout(previous)?;
+        result
     }
+}
 
-    /// internal wrapper
-    fn read_big_endian_payload_for_element<T: Read>(
-        &self,
-        reader: &mut T,
-        location: &mut LocationTracker,
-        element_def: &ElementDef,
-    ) -> Result<Vec<E>> {
-        self.read_binary_payload_for_element::<T, BigEndian>(reader, location, element_def)
+/// Reports the expired read timeout of `source` as `TimedOut`.
+struct TimeoutReader<'a, T> {
+    source: &'a mut T,
+    timeout: Duration,
+}
+
+impl<T: Read> Read for TimeoutReader<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self.source.read(buf) {
+            // Unix reports an expired socket timeout as `WouldBlock`.
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                Err(io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!("No data received within {:?}.", self.timeout),
+                ))
+            }
+            r => r,
+        }
     }
+}
 
-    fn read_little_endian_payload_for_element<T: Read>(
-        &self,
-        reader: &mut T,
-        location: &mut LocationTracker,
-        element_def: &ElementDef,
-    ) -> Result<Vec<E>> {
-        self.read_binary_payload_for_element::<T, LittleEndian>(reader, location, element_def)
+// //////////////////////////////////////
+// # Futures IO
+// //////////////////////////////////////
+#[cfg(feature = "futures-io")]
+use futures_io::AsyncBufRead;
+#[cfg(feature = "futures-io")]
+use std::pin::Pin;
+#[cfg(feature = "futures-io")]
+use std::task::{ready, Poll};
+
+/// # Futures IO
+///
+/// Entry points for readers implementing `futures::io::AsyncBufRead`,
+/// usable with any runtime built on the `futures` traits.
+///
+/// The header is read line by line and the payload entry by entry, as their bytes arrive.
+/// Only the bytes of the document are consumed, the results are the same as with the blocking methods.
+/// Sources that only implement `futures::io::AsyncRead` can be wrapped in `futures::io::BufReader`.
+///
+/// Requires the `futures-io` feature.
+#[cfg(feature = "futures-io")]
+impl<E: PropertyAccess> Parser<E> {
+    /// Asynchronous version of `read_ply()`.
+    ///
+    /// Consumes exactly the document, `source` is left right after the payload.
+    pub async fn read_ply_async<T: AsyncBufRead + Unpin>(&self, source: &mut T) -> Result<Ply<E>> {
+        let mut location = LocationTracker::new();
+        let header = self.__read_header_async(source, &mut location).await?;
+        let expected = self.options.expected_checksum(&header);
+        let mut crc = expected.map(|_| Crc32::new());
+        let payload = self
+            .__read_payload_async(source, &mut location, &header, crc.as_mut())
+            .await?;
+        if let (Some(expected), Some(crc)) = (expected, crc) {
+            check_checksum(expected, crc.finish())?;
+        }
+        let mut ply = Ply::new();
+        ply.header = header;
+        ply.payload = payload;
+        Ok(ply)
     }
 
-    fn read_binary_payload_for_element<T: Read, B: ByteOrder>(
+    /// Asynchronous version of `read_header()`.
+    ///
+    /// Consumes exactly the header, `reader` is left at the start of the payload.
+    pub async fn read_header_async<T: AsyncBufRead + Unpin>(
         &self,
         reader: &mut T,
-        location: &mut LocationTracker,
-        element_def: &ElementDef,
-    ) -> Result<Vec<E>> {
-        let mut elems = Vec::<E>::with_capacity(element_def.count);
-        for _ in 0..element_def.count {
-            let element = self.read_binary_element::<T, B>(reader, element_def)?;
-            elems.push(element);
-            location.next_line();
-        }
-        Ok(elems)
+    ) -> Result<Header> {
+        let mut line = LocationTracker::new();
+        self.__read_header_async(reader, &mut line).await
     }
 
-    fn read_binary_element<T: Read, B: ByteOrder>(
+    /// Asynchronous version of `read_payload()`.
+    ///
+    /// Consumes exactly the payload, `reader` is left right after it.
+    pub async fn read_payload_async<T: AsyncBufRead + Unpin>(
         &self,
         reader: &mut T,
-        element_def: &ElementDef,
-    ) -> Result<E> {
-        let mut raw_element = E::new();
-        for def in &element_def.properties {
-            let property = self.read_binary_property::<T, B>(reader, def.data_type)?;
-            raw_element.set_property(&def.name, property);
-        }
-        Ok(raw_element)
+        header: &Header,
+    ) -> Result<Payload<E>> {
+        let mut location = LocationTracker::payload();
+        self.__read_payload_async(reader, &mut location, header, None)
+            .await
     }
 
-    fn read_binary_property<T: Read, B: ByteOrder>(
+    /// Asynchronous version of `__read_payload_watched()`, adding the payload bytes to `crc`.
+    async fn __read_payload_async<T: AsyncBufRead + Unpin>(
         &self,
         reader: &mut T,
-        data_type: PropertyType,
-    ) -> Result<Property> {
-        let result = match data_type {
-            PropertyType::Scalar(scalar_type) => match scalar_type {
-                ScalarType::Char => Property::Char(reader.read_i8()?),
-                ScalarType::UChar => Property::UChar(reader.read_u8()?),
-                ScalarType::Short => Property::Short(reader.read_i16::<B>()?),
-                ScalarType::UShort => Property::UShort(reader.read_u16::<B>()?),
-                ScalarType::Int => Property::Int(reader.read_i32::<B>()?),
-                ScalarType::UInt => Property::UInt(reader.read_u32::<B>()?),
-                ScalarType::Float => Property::Float(reader.read_f32::<B>()?),
-                ScalarType::Double => Property::Double(reader.read_f64::<B>()?),
-            },
-            PropertyType::List(ref index_type, ref property_type) => {
-                let count: usize =
-                    match *index_type {
-                        ScalarType::Char => reader.read_i8()? as usize,
-                        ScalarType::UChar => reader.read_u8()? as usize,
-                        ScalarType::Short => reader.read_i16::<B>()? as usize,
-                        ScalarType::UShort => reader.read_u16::<B>()? as usize,
-                        ScalarType::Int => reader.read_i32::<B>()? as usize,
-                        ScalarType::UInt => reader.read_u32::<B>()? as usize,
-                        ScalarType::Float => return Err(io::Error::new(
-                            ErrorKind::InvalidInput,
-                            "Index of list must be an integer type, float declared in ScalarType.",
-                        )),
-                        ScalarType::Double => return Err(io::Error::new(
-                            ErrorKind::InvalidInput,
-                            "Index of list must be an integer type, double declared in ScalarType.",
-                        )),
-                    };
-                match *property_type {
-                    ScalarType::Char => {
-                        Property::ListChar(self.read_binary_list(reader, T::read_i8, count)?)
+        location: &mut LocationTracker,
+        header: &Header,
+        mut crc: Option<&mut Crc32>,
+    ) -> Result<Payload<E>> {
+        let bytes = Cell::new(0);
+        let hook = self.options.progress_hook();
+        let cancel = self.options.cancellation();
+        let mut entries = AsyncEntries::default();
+        let mut payload = Payload::new();
+        for e in &header.elements {
+            let report = |read| {
+                if let Some((hook, _)) = hook {
+                    hook(&ProgressEvent {
+                        element: &e.name,
+                        read,
+                        total: e.count,
+                        bytes: bytes.get(),
+                    });
+                }
+            };
+            check_cancelled(cancel, e, 0)?;
+            report(0);
+            let skipped = self.options.skips_element(&e.name);
+            let mut elems = Vec::new();
+            let mut sink = WatchedSink {
+                elements: &mut elems,
+                hook,
+                cancel,
+                bytes: &bytes,
+            };
+            if !skipped {
+                sink.prepare(materializable_count(e)?);
+            }
+            let values_per_line = match header.encoding {
+                Encoding::Ascii => scalar_value_count(e),
+                _ => None,
+            };
+            let mut first_mismatch = None;
+            let mut looks_ascii = None;
+            for index in 0..e.count {
+                let mut decode = |buf: &[u8], eof, location: &mut LocationTracker| {
+                    if header.encoding != Encoding::Ascii && looks_ascii.is_none() {
+                        looks_ascii = Some(looks_like_ascii(buf));
                     }
-                    ScalarType::UChar => {
-                        Property::ListUChar(self.read_binary_list(reader, T::read_u8, count)?)
+                    if skipped {
+                        let entry = skip_entry(self, header.encoding, e, index, location, buf, eof);
+                        return Ok(entry?.map(|()| None));
                     }
-                    ScalarType::Short => Property::ListShort(self.read_binary_list(
-                        reader,
-                        T::read_i16::<B>,
-                        count,
-                    )?),
-                    ScalarType::UShort => Property::ListUShort(self.read_binary_list(
-                        reader,
-                        T::read_u16::<B>,
-                        count,
-                    )?),
-                    ScalarType::Int => {
-                        Property::ListInt(self.read_binary_list(reader, T::read_i32::<B>, count)?)
+                    if first_mismatch.is_some() && buf.is_empty() && !e.properties.is_empty() {
+                        return Err(unexpected_eof(e, index, first_mismatch));
                     }
-                    ScalarType::UInt => Property::ListUInt(self.read_binary_list(
-                        reader,
-                        T::read_u32::<B>,
-                        count,
-                    )?),
-                    ScalarType::Float => Property::ListFloat(self.read_binary_list(
-                        reader,
-                        T::read_f32::<B>,
-                        count,
-                    )?),
-                    ScalarType::Double => Property::ListDouble(self.read_binary_list(
+                    let entry = decode_entry(self, header.encoding, e, index, location, buf, eof)?;
+                    if let Entry::Complete(_, len) = entry {
+                        if first_mismatch.is_none()
+                            && values_per_line.is_some_and(|n| n != count_values(&buf[..len]))
+                        {
+                            first_mismatch = Some(index);
+                        }
+                    }
+                    Ok(entry.map(Some))
+                };
+                let value = std::future::poll_fn(|cx| {
+                    entries.poll_entry(
                         reader,
-                        T::read_f64::<B>,
-                        count,
-                    )?),
+                        cx,
+                        header.encoding,
+                        |buf, eof| decode(buf, eof, location),
+                        |err| element_error(e, index, err),
+                        |buf| {
+                            bytes.set(bytes.get() + buf.len() as u64);
+                            if let Some(ref mut crc) = crc {
+                                crc.update(buf);
+                            }
+                        },
+                    )
+                })
+                .await
+                .map_err(|err| match looks_ascii {
+                    Some(true) if !skipped => looks_ascii_error(e, index, err),
+                    _ => err,
+                })?;
+                match value {
+                    Some(value) => sink.push(e, index, value)?,
+                    None => check_cancelled(cancel, e, index)?,
                 }
             }
-        };
-        Ok(result)
+            if !skipped {
+                payload.insert(e.name.clone(), elems);
+            }
+            report(e.count);
+        }
+        Ok(payload)
     }
 
-    fn read_binary_list<T: Read, D: FromStr>(
+    async fn __read_header_async<T: AsyncBufRead + Unpin>(
         &self,
         reader: &mut T,
-        read_from: fn(&mut T) -> Result<D>,
-        count: usize,
-    ) -> Result<Vec<D>>
-    where
-        <D as FromStr>::Err: error::Error + marker::Send + marker::Sync + 'static,
-    {
-        let mut list = Vec::<D>::with_capacity(count);
-        for i in 0..count {
-            let value: D = match read_from(reader) {
-                Err(e) => {
-                    return Err(io::Error::new(
-                        ErrorKind::InvalidInput,
-                        format!(
-                            "Couldn't find a list element at index {}.\n\tError: {:?}",
-                            i, e
-                        ),
-                    ))
-                }
-                Ok(x) => x,
-            };
-            list.push(value);
+        location: &mut LocationTracker,
+    ) -> Result<Header> {
+        let mut accumulator = HeaderAccumulator::new();
+        let mut line = Vec::new();
+        while !accumulator.is_complete() {
+            location.next_line();
+            line.clear();
+            let complete =
+                read_line_async(reader, &mut line, self.options.max_header_line_len).await?;
+            let line_str = self.header_line_str(&line, complete, location)?;
+            self.push_header_line(&mut accumulator, location, line_str)?;
         }
-        Ok(list)
+        self.finish_header(accumulator, location)
     }
 }
 
+/// Asynchronous version of `read_line_limited()`.
+#[cfg(feature = "futures-io")]
+async fn read_line_async<T: AsyncBufRead + Unpin>(
+    reader: &mut T,
+    line: &mut Vec<u8>,
+    limit: usize,
+) -> Result<bool> {
+    std::future::poll_fn(|cx| -> Poll<Result<bool>> {
+        loop {
+            let buf = match ready!(Pin::new(&mut *reader).poll_fill_buf(cx)) {
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                r => r?,
+            };
+            if buf.is_empty() {
+                return Poll::Ready(Ok(true));
+            }
+            let available = &buf[..buf.len().min(limit - line.len())];
+            let (n, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (available.len(), false),
+            };
+            line.extend_from_slice(&available[..n]);
+            Pin::new(&mut *reader).consume(n);
+            if done {
+                return Poll::Ready(Ok(true));
+            }
+            if line.len() >= limit {
+                return Poll::Ready(Ok(false));
+            }
+        }
+    })
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::grammar as g;
     use super::Line;
-    use crate::parser::Parser;
+    use crate::parser::{CustomTypeHandler, Parser, ParserOptions, MAX_PREALLOCATION};
     use crate::ply::{
-        DefaultElement, ElementDef, Encoding, PropertyDef, PropertyType, ScalarType, Version,
+        DefaultElement, ElementDef, Encoding, Property, PropertyDef, PropertyType, ScalarType,
+        Version,
     };
     macro_rules! assert_ok {
         ($e:expr) => {
@@ -758,6 +3350,69 @@ mod tests {
         assert_ok!(p.read_header(&mut bytes));
     }
     #[test]
+    fn parser_indented_header_ok() {
+        let txt = "ply\n\
+        format ascii 1.0\n\
+        element vertex 8\n\
+        \tcomment indented\n\
+        \x20   property float x\n\
+        end_header\n";
+        let p = Parser::<DefaultElement>::new();
+        let header = assert_ok!(p.read_header(&mut txt.as_bytes()));
+        assert_eq!(header.elements[0].properties[0].name, "x");
+        assert_eq!(
+            header.elements[0].properties[0].comments,
+            vec!["indented".to_string()]
+        );
+
+        let strict = ParserOptions::new().allow_indented_header(false);
+        let p = Parser::<DefaultElement>::with_options(strict);
+        assert_err!(p.read_header(&mut txt.as_bytes()));
+    }
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parser_parallel_ok() {
+        let mut txt = String::from(
+            "ply\nformat ascii 1.0\nelement face 100\n\
+            property list uchar int vertex_indices\nproperty float q\nend_header\n",
+        );
+        for i in 0..100 {
+            txt.push_str(&format!("3 {} {} {} {}\n", i, i + 1, i + 2, i as f32 * 0.5));
+        }
+        let expected = assert_ok!(Parser::<DefaultElement>::new().read_ply(&mut txt.as_bytes()));
+        let p = Parser::<DefaultElement>::with_options(ParserOptions::new().parallel_threshold(1));
+        let ply = assert_ok!(p.read_ply_parallel(&mut txt.as_bytes()));
+        assert_eq!(ply.payload, expected.payload);
+    }
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parser_parallel_err() {
+        let mut txt = String::from(
+            "ply\nformat ascii 1.0\nelement vertex 100\n\
+            property float x\nend_header\n",
+        );
+        for i in 0..100 {
+            if i == 73 || i == 91 {
+                txt.push_str("oops\n");
+            } else {
+                txt.push_str(&format!("{}\n", i));
+            }
+        }
+        let p = Parser::<DefaultElement>::with_options(ParserOptions::new().parallel_threshold(1));
+        let expected = p.read_ply(&mut txt.as_bytes()).unwrap_err().to_string();
+        let message = p
+            .read_ply_parallel(&mut txt.as_bytes())
+            .unwrap_err()
+            .to_string();
+        // 5 header lines, then 73 elements.
+        assert!(
+            message.starts_with("Element 'vertex' #73: File line 79, payload line 74:"),
+            "{}",
+            message
+        );
+        assert_eq!(message, expected);
+    }
+    #[test]
     fn parser_demo_ok() {
         let txt = "ply\nformat ascii 1.0\nend_header\n";
         let mut bytes = txt.as_bytes();
@@ -790,6 +3445,135 @@ mod tests {
         assert_ok!(p.read_ply(&mut bytes));
     }
     #[test]
+    fn parser_huge_count_ok() {
+        let p = Parser::<DefaultElement>::new();
+        let txt = "ply\n\
+        format ascii 1.0\n\
+        element vertex 5000000000\n\
+        property float x\n\
+        end_header\n\
+        1.0\n\
+        2.0\n";
+        let mut bytes = txt.as_bytes();
+        let header = assert_ok!(p.read_header(&mut bytes));
+        assert_eq!(header.elements[0].count, 5_000_000_000);
+        // the file ends long before the declared count is reached
+        assert_err!(p.read_payload(&mut bytes, &header));
+    }
+    #[test]
+    fn parser_huge_count_preallocation_ok() {
+        let p = Parser::<DefaultElement>::new();
+        let bytes = std::fs::read("fuzz/regressions/huge_element_count.ply").unwrap();
+        let mut reader = &bytes[..];
+        let header = assert_ok!(p.read_header(&mut reader));
+        let vertex = &header.elements[0];
+        let mut vertices = Vec::new();
+        assert_err!(p.read_payload_for_element_into(&mut reader, vertex, &header, &mut vertices));
+        // the declared count only reserves up to the cap
+        assert!(vertices.capacity() <= MAX_PREALLOCATION);
+    }
+    fn custom_type_ply() -> Vec<u8> {
+        let mut bytes = b"ply\n\
+        format binary_little_endian 1.0\n\
+        element point 2\n\
+        property uchar a\n\
+        property int24 b\n\
+        property ushort c\n\
+        end_header\n"
+            .to_vec();
+        bytes.extend_from_slice(&[7, 1, 2, 3, 0x34, 0x12]);
+        bytes.extend_from_slice(&[8, 0xff, 0xff, 0xff, 0x78, 0x56]);
+        bytes
+    }
+    fn decode_int24(b: &[u8]) -> Property {
+        let sign = if b[2] & 0x80 != 0 { 0xff } else { 0 };
+        Property::Int(i32::from_le_bytes([b[0], b[1], b[2], sign]))
+    }
+    #[test]
+    fn parser_custom_type_ok() {
+        let options = ParserOptions::new().on_unknown_type(|t| match t {
+            "int24" => Some(CustomTypeHandler::with_decoder(3, decode_int24)),
+            _ => None,
+        });
+        let p = Parser::<DefaultElement>::with_options(options);
+        let ply = assert_ok!(p.read_ply(&mut custom_type_ply().as_slice()));
+        assert_eq!(
+            ply.header.elements[0].properties[1].data_type,
+            PropertyType::Custom {
+                name: "int24".to_string(),
+                size: 3
+            }
+        );
+        let points = &ply.payload["point"];
+        assert_eq!(points[0]["a"], Property::UChar(7));
+        assert_eq!(points[0]["b"], Property::Int(0x030201));
+        assert_eq!(points[0]["c"], Property::UShort(0x1234));
+        assert_eq!(points[1]["a"], Property::UChar(8));
+        assert_eq!(points[1]["b"], Property::Int(-1));
+        assert_eq!(points[1]["c"], Property::UShort(0x5678));
+    }
+    #[test]
+    fn parser_custom_type_raw_ok() {
+        let options = ParserOptions::new().on_unknown_type(|_| Some(CustomTypeHandler::raw(3)));
+        let p = Parser::<DefaultElement>::with_options(options);
+        let ply = assert_ok!(p.read_ply(&mut custom_type_ply().as_slice()));
+        let points = &ply.payload["point"];
+        assert_eq!(points[0]["b"], Property::Custom(vec![1, 2, 3].into()));
+        assert_eq!(points[1]["c"], Property::UShort(0x5678));
+    }
+    #[test]
+    fn parser_custom_type_err() {
+        let p = Parser::<DefaultElement>::new();
+        assert_err!(p.read_ply(&mut custom_type_ply().as_slice()));
+        let options = ParserOptions::new().on_unknown_type(|_| None);
+        let p = Parser::<DefaultElement>::with_options(options);
+        assert_err!(p.read_ply(&mut custom_type_ply().as_slice()));
+    }
+    #[test]
+    fn parser_pooled_ok() {
+        let p = Parser::<DefaultElement>::new();
+        for path in &[
+            "example_plys/house_ok_ascii.ply",
+            "example_plys/house_2_ok_little_endian.ply",
+        ] {
+            let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+            let header = assert_ok!(p.read_header(&mut f));
+            let mut payload = Vec::new();
+            for e in &header.elements {
+                payload.push(assert_ok!(
+                    p.read_payload_for_element_pooled(&mut f, e, &header)
+                ));
+            }
+            let mut f = std::fs::File::open(path).unwrap();
+            let ply = assert_ok!(p.read_ply(&mut f));
+            assert_eq!(payload[0], ply.payload["vertex"]);
+            assert_eq!(payload[1], ply.payload["face"]);
+        }
+    }
+    #[test]
+    fn parser_into_ok() {
+        let p = Parser::<DefaultElement>::new();
+        for path in &[
+            "example_plys/house_ok_ascii.ply",
+            "example_plys/house_2_ok_little_endian.ply",
+        ] {
+            let mut f = std::fs::File::open(path).unwrap();
+            let ply = assert_ok!(p.read_ply(&mut f));
+            // Stale entries are overwritten, the vectors shrink or grow to the element count.
+            let mut vertices = ply.payload["vertex"].clone();
+            vertices.reverse();
+            vertices.push(DefaultElement::new());
+            let mut faces = ply.payload["face"][..1].to_vec();
+            let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+            let header = assert_ok!(p.read_header(&mut f));
+            let (vertex, face) = (&header.elements[0], &header.elements[1]);
+            assert_ok!(p.read_payload_for_element_into(&mut f, vertex, &header, &mut vertices));
+            assert_ok!(p.read_payload_for_element_into(&mut f, face, &header, &mut faces));
+            assert_eq!(vertices, ply.payload["vertex"]);
+            assert_eq!(faces, ply.payload["face"]);
+        }
+    }
+    #[test]
     fn read_property_ok() {
         let p = Parser::<DefaultElement>::new();
         let txt = "0 1 2 3";
@@ -844,15 +3628,21 @@ mod tests {
     #[test]
     fn comment_ok() {
         assert_ok!(g::comment("comment hi"), "hi");
+        // Only the first separator is dropped, the rest is kept as written.
         assert_ok!(
             g::comment("comment   hi, I'm a comment!"),
-            "hi, I'm a comment!"
+            "  hi, I'm a comment!"
+        );
+        assert_ok!(
+            g::comment("comment    ASCII art  here "),
+            "   ASCII art  here "
         );
         assert_ok!(g::comment("comment "), "");
         assert_ok!(g::comment("comment\t"), "");
         assert_ok!(g::comment("comment"), "");
-        assert_ok!(g::comment("comment\t"), "");
+        assert_ok!(g::comment("comment\t\t"), "\t");
         assert_ok!(g::comment("comment\thi"), "hi");
+        assert_ok!(g::comment("comment \thi"), "\thi");
     }
     #[test]
     fn comment_err() {
@@ -868,6 +3658,7 @@ mod tests {
         assert_ok!(g::obj_info("obj_info"), "");
         assert_ok!(g::obj_info("obj_info "), "");
         assert_ok!(g::obj_info("obj_info\t"), "");
+        assert_ok!(g::obj_info("obj_info  x  y"), " x  y");
     }
     #[test]
     fn obj_info_err() {
@@ -897,6 +3688,52 @@ mod tests {
             PropertyDef::new("c", PropertyType::List(ScalarType::UChar, ScalarType::Int))
         );
     }
+    #[cfg(feature = "extended-types")]
+    #[test]
+    fn property_extended_ok() {
+        assert_ok!(
+            g::property("property int64 t"),
+            PropertyDef::new("t", PropertyType::Scalar(ScalarType::LongLong))
+        );
+        assert_ok!(
+            g::property("property ulong id"),
+            PropertyDef::new("id", PropertyType::Scalar(ScalarType::ULongLong))
+        );
+        assert_ok!(
+            g::property("property list uint8 uint64 ids"),
+            PropertyDef::new(
+                "ids",
+                PropertyType::List(ScalarType::UChar, ScalarType::ULongLong)
+            )
+        );
+    }
+    #[cfg(not(feature = "extended-types"))]
+    #[test]
+    fn property_extended_err() {
+        assert_err!(g::property("property int64 t"));
+        assert_err!(g::property("property ulong id"));
+    }
+    #[cfg(feature = "half")]
+    #[test]
+    fn property_half_ok() {
+        assert_ok!(
+            g::property("property float16 f_dc_0"),
+            PropertyDef::new("f_dc_0", PropertyType::Scalar(ScalarType::Half))
+        );
+        assert_ok!(
+            g::property("property list uchar half f_rest"),
+            PropertyDef::new(
+                "f_rest",
+                PropertyType::List(ScalarType::UChar, ScalarType::Half)
+            )
+        );
+    }
+    #[cfg(not(feature = "half"))]
+    #[test]
+    fn property_half_err() {
+        assert_err!(g::property("property float16 f_dc_0"));
+        assert_err!(g::property("property half f_dc_0"));
+    }
     #[test]
     fn line_ok() {
         assert_ok!(g::line("ply "), Line::MagicNumber);
@@ -925,11 +3762,18 @@ mod tests {
         );
         assert_ok!(g::data_line("034 8e3 8e-3"), vec!["034", "8e3", "8e-3"]);
         assert_ok!(g::data_line(""), Vec::<String>::new());
+        assert_ok!(
+            g::data_line(".5 -.25 1. +.5 1.e5"),
+            vec![".5", "-.25", "1.", "+.5", "1.e5"]
+        );
     }
     #[test]
     fn data_line_err() {
         assert_err!(g::data_line("++3"));
         assert_err!(g::data_line("+-3"));
         assert_err!(g::data_line("five"));
+        assert_err!(g::data_line("."));
+        assert_err!(g::data_line("-."));
+        assert_err!(g::data_line("1 . 2"));
     }
 }
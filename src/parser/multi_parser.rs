@@ -0,0 +1,221 @@
+//! Reads every element of a file into its own type.
+
+use super::{check_checksum, ChecksumReader, ParseError, Parser, ParserOptions};
+use crate::ply::{ElementDef, Header, KeyMap, Property, PropertyAccess};
+use crate::util::{Crc32, LocationTracker};
+use std::any::Any;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Result};
+
+/// What `MultiParser` does with elements of the file that weren't registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnregisteredElements {
+    /// Read and discard them.
+    #[default]
+    Skip,
+    /// Fail with an error of kind `InvalidInput` before reading the payload.
+    Error,
+}
+
+/// Reads the payload of one element into a type chosen at registration.
+trait ElementReader: Send + Sync {
+    fn read(
+        &self,
+        reader: &mut dyn BufRead,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+    ) -> Result<Box<dyn Any>>;
+}
+
+impl<E: PropertyAccess + 'static> ElementReader for Parser<E> {
+    fn read(
+        &self,
+        mut reader: &mut dyn BufRead,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        header: &Header,
+    ) -> Result<Box<dyn Any>> {
+        let elements =
+            self.__read_payload_for_element(&mut reader, location, element_def, header, false)?;
+        Ok(Box::new(elements))
+    }
+}
+
+/// Element type of skipped elements, drops all properties.
+struct Ignored;
+
+impl PropertyAccess for Ignored {
+    fn new() -> Self {
+        Ignored
+    }
+    fn set_property(&mut self, _key: &str, _property: Property) {}
+    fn set_property_ref(&mut self, _key: &str, _property: &Property) {}
+}
+
+/// Reads each element of a file into the type registered for its name.
+///
+/// Replaces a `Parser` per element type and the loop over `Header::elements`.
+/// Registered elements missing from the file yield empty vectors.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::MultiParser;
+/// # use ply_rs::ply::{Property, PropertyAccess};
+/// struct Vertex {
+///     x: f32,
+/// }
+/// impl PropertyAccess for Vertex {
+///     fn new() -> Self {
+///         Vertex { x: 0.0 }
+///     }
+///     fn set_property(&mut self, key: &str, property: Property) {
+///         if let ("x", Property::Float(v)) = (key, property) {
+///             self.x = v;
+///         }
+///     }
+/// }
+/// let mut f = std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap();
+/// let mut ply = MultiParser::new()
+///     .element::<Vertex>("vertex")
+///     .read(&mut f)
+///     .unwrap();
+/// let vertices = ply.take::<Vertex>("vertex");
+/// assert_eq!(vertices[4].x, 1.0);
+/// ```
+pub struct MultiParser {
+    options: ParserOptions,
+    readers: KeyMap<Box<dyn ElementReader>>,
+    unregistered: UnregisteredElements,
+}
+
+impl Default for MultiParser {
+    fn default() -> Self {
+        MultiParser::new()
+    }
+}
+
+impl fmt::Debug for MultiParser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MultiParser")
+            .field("elements", &self.readers.keys().collect::<Vec<_>>())
+            .field("unregistered", &self.unregistered)
+            .finish()
+    }
+}
+
+impl MultiParser {
+    /// Creates a parser without registered elements.
+    pub fn new() -> Self {
+        MultiParser::with_options(ParserOptions::new())
+    }
+    /// Creates a parser configured by `options`, used for all elements.
+    pub fn with_options(options: ParserOptions) -> Self {
+        MultiParser {
+            options,
+            readers: KeyMap::new(),
+            unregistered: UnregisteredElements::Skip,
+        }
+    }
+    /// Reads the elements called `name` into `E`, replaces an earlier registration of `name`.
+    pub fn element<E: PropertyAccess + 'static>(mut self, name: &str) -> Self {
+        let parser = Parser::<E>::with_options(self.options.clone());
+        self.readers.insert(name.to_string(), Box::new(parser));
+        self
+    }
+    /// What to do with elements that weren't registered, `UnregisteredElements::Skip` by default.
+    pub fn on_unregistered(mut self, unregistered: UnregisteredElements) -> Self {
+        self.unregistered = unregistered;
+        self
+    }
+    /// Reads a complete PLY file, see `Parser::read_ply()`.
+    pub fn read<T: Read>(&self, source: &mut T) -> Result<TypedPly> {
+        let mut source = BufReader::new(source);
+        let mut location = LocationTracker::new();
+        let skipper = Parser::<Ignored>::with_options(self.options.clone());
+        let header = skipper.__read_header(&mut source, &mut location)?;
+        if self.unregistered == UnregisteredElements::Error {
+            if let Some(e) = header
+                .elements
+                .iter()
+                .find(|e| !self.readers.contains_key(&e.name))
+            {
+                return Err(ParseError::UnregisteredElement(e.name.clone()).into_io());
+            }
+        }
+        let elements = match self.options.expected_checksum(&header) {
+            None => self.read_elements(&skipper, &mut source, &mut location, &header)?,
+            Some(expected) => {
+                let mut source = ChecksumReader {
+                    inner: &mut source,
+                    crc: Crc32::new(),
+                };
+                let elements = self.read_elements(&skipper, &mut source, &mut location, &header)?;
+                check_checksum(expected, source.crc.finish())?;
+                elements
+            }
+        };
+        Ok(TypedPly { header, elements })
+    }
+
+    fn read_elements<T: BufRead>(
+        &self,
+        skipper: &Parser<Ignored>,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        header: &Header,
+    ) -> Result<KeyMap<Box<dyn Any>>> {
+        let mut elements = KeyMap::new();
+        for e in &header.elements {
+            match self.readers.get(&e.name) {
+                Some(r) => {
+                    elements.insert(e.name.clone(), r.read(reader, location, e, header)?);
+                }
+                None => {
+                    skipper.__read_payload_for_element(reader, location, e, header, true)?;
+                }
+            }
+        }
+        Ok(elements)
+    }
+}
+
+/// Elements read by `MultiParser`, each in the type registered for its name.
+pub struct TypedPly {
+    /// Header of the file.
+    pub header: Header,
+    elements: KeyMap<Box<dyn Any>>,
+}
+
+impl fmt::Debug for TypedPly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TypedPly")
+            .field("header", &self.header)
+            .field("elements", &self.elements.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl TypedPly {
+    /// Moves the elements called `name` out, leaving nothing behind.
+    ///
+    /// Returns an empty vector if the file has no such elements or they were already taken.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` was registered with another type than `E`.
+    pub fn take<E: 'static>(&mut self, name: &str) -> Vec<E> {
+        match self.elements.remove(name) {
+            None => Vec::new(),
+            Some(elements) => match elements.downcast::<Vec<E>>() {
+                Ok(elements) => *elements,
+                Err(_) => panic!(
+                    "Element '{}' was registered with another type than {}",
+                    name,
+                    std::any::type_name::<E>()
+                ),
+            },
+        }
+    }
+}
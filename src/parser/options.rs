@@ -0,0 +1,545 @@
+use crate::ply::{Header, Property};
+use crate::util::parse_checksum_comment;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// Describes how the parser deals with a property type it doesn't know.
+///
+/// Returned by the hook registered with `ParserOptions::on_unknown_type()`.
+#[derive(Debug, Clone, Copy)]
+pub struct CustomTypeHandler {
+    /// Number of bytes a single value occupies in a binary payload.
+    pub size: usize,
+    /// Converts the raw bytes of a value into a `Property`.
+    ///
+    /// The bytes are passed as they appear in the file, no byte order conversion is done.
+    /// If not set, the value is stored as `Property::Custom` containing the raw bytes.
+    pub decode: Option<fn(&[u8]) -> Property>,
+}
+
+impl CustomTypeHandler {
+    /// Reads `size` bytes per value and stores them as `Property::Custom`.
+    pub fn raw(size: usize) -> Self {
+        CustomTypeHandler { size, decode: None }
+    }
+    /// Reads `size` bytes per value and converts them with `decode`.
+    pub fn with_decoder(size: usize, decode: fn(&[u8]) -> Property) -> Self {
+        CustomTypeHandler {
+            size,
+            decode: Some(decode),
+        }
+    }
+}
+
+/// How the parser rewrites property names, see `ParserOptions::normalize_property_names()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameNormalization {
+    /// Keeps names as written in the header.
+    #[default]
+    Preserve,
+    /// Converts names to lowercase, for example `Red` becomes `red`.
+    Lowercase,
+}
+
+impl NameNormalization {
+    /// Applies the normalization to `name`.
+    pub fn apply(&self, name: &str) -> String {
+        match *self {
+            NameNormalization::Preserve => name.to_string(),
+            NameNormalization::Lowercase => name.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Which characters element and property names may contain, see `ParserOptions::name_characters()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameCharacters {
+    /// Ascii letters, digits, `_`, `-` and `.`, `[A-Za-z0-9_][A-Za-z0-9_.-]*`,
+    /// like `scale.x`, `pos-x` or `3dpoints`.
+    ///
+    /// The count of an element line is its last token, hence names may start with a digit.
+    #[default]
+    Identifier,
+    /// Any printable character except whitespace, like `rot(x)` or `näme`.
+    Printable,
+}
+
+impl NameCharacters {
+    /// Whether `c` may appear in a name, `first` tells whether it starts the name.
+    pub fn accepts(&self, c: char, first: bool) -> bool {
+        match *self {
+            NameCharacters::Printable => !c.is_whitespace() && !c.is_control(),
+            NameCharacters::Identifier if first => c.is_ascii_alphanumeric() || c == '_',
+            NameCharacters::Identifier => {
+                c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'
+            }
+        }
+    }
+}
+
+/// Reading progress, passed to the hook registered with `ParserOptions::on_progress()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent<'a> {
+    /// Name of the element being read.
+    pub element: &'a str,
+    /// Number of entries of the element read so far.
+    pub read: u64,
+    /// Number of entries of the element declared by the header.
+    pub total: u64,
+    /// Number of payload bytes consumed so far, for all elements.
+    pub bytes: u64,
+}
+
+type UnknownTypeHook = dyn Fn(&str) -> Option<CustomTypeHandler> + Send + Sync;
+pub(crate) type ProgressHook = dyn Fn(&ProgressEvent) + Send + Sync;
+
+/// Configures the behaviour of a `Parser`.
+///
+/// The default options parse files as described by the PLY format.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::{CustomTypeHandler, Parser, ParserOptions};
+/// # use ply_rs::ply::DefaultElement;
+/// let options = ParserOptions::new().on_unknown_type(|type_name| match type_name {
+///     "int24" => Some(CustomTypeHandler::raw(3)),
+///     _ => None,
+/// });
+/// let p = Parser::<DefaultElement>::with_options(options);
+/// ```
+#[derive(Clone)]
+pub struct ParserOptions {
+    unknown_type_hook: Option<Arc<UnknownTypeHook>>,
+    /// (hook, number of entries between two events)
+    progress_hook: Option<(Arc<ProgressHook>, u64)>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    allow_indented_header: bool,
+    verify_checksum: bool,
+    property_names: NameNormalization,
+    pub(crate) name_characters: NameCharacters,
+    /// (element name, property name, length)
+    fixed_lists: Vec<(String, String, usize)>,
+    pub(crate) skip_rejected_properties: bool,
+    pub(crate) multiline_ascii_elements: bool,
+    skip_elements: Vec<String>,
+    /// (element name, property names)
+    kept_properties: Vec<(String, Vec<String>)>,
+    max_element_count: Option<u64>,
+    max_list_len: Option<usize>,
+    max_total_properties: Option<u64>,
+    pub(crate) max_header_line_len: usize,
+    #[cfg(feature = "parallel")]
+    pub(crate) parallel_threshold: u64,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions::new()
+    }
+}
+
+impl ParserOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        ParserOptions {
+            unknown_type_hook: None,
+            progress_hook: None,
+            cancel_flag: None,
+            allow_indented_header: true,
+            verify_checksum: false,
+            property_names: NameNormalization::Preserve,
+            name_characters: NameCharacters::Identifier,
+            fixed_lists: Vec::new(),
+            skip_rejected_properties: false,
+            multiline_ascii_elements: false,
+            skip_elements: Vec::new(),
+            kept_properties: Vec::new(),
+            max_element_count: None,
+            max_list_len: None,
+            max_total_properties: None,
+            max_header_line_len: 1 << 20,
+            #[cfg(feature = "parallel")]
+            parallel_threshold: 10_000,
+        }
+    }
+    /// Whether header lines may start with spaces or tabs, `true` by default.
+    ///
+    /// Some writers indent the properties under their element.
+    /// Set to `false` to reject such headers.
+    pub fn allow_indented_header(mut self, allow: bool) -> Self {
+        self.allow_indented_header = allow;
+        self
+    }
+    /// Whether to verify the payload checksum written by `WriteOptions::checksum()`, `false` by default.
+    ///
+    /// Only files with a checksum comment in their header are checked.
+    /// A mismatch fails reading with an error of kind `InvalidData`.
+    pub fn verify_checksum(mut self, verify: bool) -> Self {
+        self.verify_checksum = verify;
+        self
+    }
+    /// How property names are rewritten, `NameNormalization::Preserve` by default.
+    ///
+    /// Exporters disagree on the case of names like `Red` and `red`.
+    /// The normalized names end up in the returned header and are passed to `set_property()`,
+    /// hence writing the header back out is consistent.
+    /// Names other options refer to, like in `expect_fixed_list()`, must be given normalized.
+    ///
+    /// Reading fails if two properties of an element have the same normalized name, like `X` and `x`.
+    /// Applies to `read_header()`, `read_ply()` and the like, not to `read_header_line()`.
+    pub fn normalize_property_names(mut self, normalization: NameNormalization) -> Self {
+        self.property_names = normalization;
+        self
+    }
+    /// Which characters element and property names may contain, `NameCharacters::Identifier` by default.
+    ///
+    /// Names like `scale.x` and `pos-x` are accepted by default,
+    /// `NameCharacters::Printable` accepts any name without whitespace.
+    pub fn name_characters(mut self, characters: NameCharacters) -> Self {
+        self.name_characters = characters;
+        self
+    }
+    /// Declares that every list of `property` in `element` has exactly `len` entries.
+    ///
+    /// For example all faces of a triangle mesh: `expect_fixed_list("face", "vertex_indices", 3)`.
+    /// Reading fails at the first list of a different length, naming the element index.
+    /// Binary payloads read the values of such lists in one go.
+    pub fn expect_fixed_list(mut self, element: &str, property: &str, len: usize) -> Self {
+        self.fixed_lists
+            .retain(|(e, p, _)| e != element || p != property);
+        self.fixed_lists
+            .push((element.to_string(), property.to_string(), len));
+        self
+    }
+    /// Whether properties rejected by `PropertyAccess::try_set_property()` are skipped, `false` by default.
+    ///
+    /// By default, a rejected property fails reading with an error of kind `InvalidInput`
+    /// naming the element index and the key. Skipping lets files gain properties
+    /// the element type doesn't know about.
+    pub fn skip_rejected_properties(mut self, skip: bool) -> Self {
+        self.skip_rejected_properties = skip;
+        self
+    }
+    /// Whether the values of an ascii element may wrap across several lines, `false` by default.
+    ///
+    /// Some writers break long faces over multiple lines. When enabled, an element that
+    /// runs out of values at the end of a line continues on the next one, errors still report
+    /// the line the value was missing on. Disables parallel parsing of ascii payloads.
+    pub fn multiline_ascii_elements(mut self, multiline: bool) -> Self {
+        self.multiline_ascii_elements = multiline;
+        self
+    }
+    /// Elements left out of the payload, none by default.
+    ///
+    /// `read_ply()`, `read_payload()` and their variants read past these elements without decoding them,
+    /// the header still declares them. Binary elements of fixed size are skipped in one go,
+    /// for elements with lists only the list counts are read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::{Parser, ParserOptions};
+    /// # use ply_rs::ply::DefaultElement;
+    /// let p = Parser::<DefaultElement>::with_options(ParserOptions::new().skip_elements(["face"]));
+    /// let mut f = std::fs::File::open("example_plys/house_2_ok_little_endian.ply").unwrap();
+    /// let ply = p.read_ply(&mut f).unwrap();
+    /// assert!(!ply.payload.contains_key("face"));
+    /// assert_eq!(ply.payload["vertex"].len(), 5);
+    /// ```
+    pub fn skip_elements<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.skip_elements = names.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Keeps only the properties called `names` of `element`, all properties are kept by default.
+    ///
+    /// The values of the other properties are checked but not passed to the element:
+    /// ascii values are parsed and dropped, binary values and lists are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::{Parser, ParserOptions};
+    /// # use ply_rs::ply::DefaultElement;
+    /// let options = ParserOptions::new().keep_properties("vertex", ["x", "y"]);
+    /// let p = Parser::<DefaultElement>::with_options(options);
+    /// let mut f = std::fs::File::open("example_plys/house_ok_ascii.ply").unwrap();
+    /// let ply = p.read_ply(&mut f).unwrap();
+    /// let keys: Vec<_> = ply.payload["vertex"][0].keys().collect();
+    /// assert_eq!(keys, ["x", "y"]);
+    /// ```
+    pub fn keep_properties<I, S>(mut self, element: &str, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.kept_properties.retain(|(e, _)| e != element);
+        self.kept_properties.push((
+            element.to_string(),
+            names.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+    /// Largest count an element may declare in the header, unlimited by default.
+    ///
+    /// Meant for untrusted input: a larger count fails reading the header
+    /// with an error of kind `InvalidInput`, naming the element and the limit.
+    pub fn max_element_count(mut self, max: u64) -> Self {
+        self.max_element_count = Some(max);
+        self
+    }
+    /// Largest number of entries a list property may have, unlimited by default.
+    ///
+    /// Meant for untrusted input: a longer list fails reading with an error of kind `InvalidInput`,
+    /// naming the element, the property and the limit, before any of its entries is read.
+    pub fn max_list_len(mut self, max: usize) -> Self {
+        self.max_list_len = Some(max);
+        self
+    }
+    /// Largest number of property values the header may declare, unlimited by default.
+    ///
+    /// The number is the sum of count times number of properties over all elements,
+    /// a list counts as one value. Meant for untrusted input: a header declaring more
+    /// fails reading with an error of kind `InvalidInput`, naming the element reaching the limit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::{Parser, ParserOptions};
+    /// # use ply_rs::ply::DefaultElement;
+    /// let options = ParserOptions::new()
+    ///     .max_element_count(1_000_000)
+    ///     .max_list_len(16)
+    ///     .max_total_properties(10);
+    /// let p = Parser::<DefaultElement>::with_options(options);
+    /// let f = std::fs::File::open("example_plys/house_ok_ascii.ply").unwrap();
+    /// // 5 vertices with 3 properties each are too many.
+    /// assert!(p.read_header(&mut std::io::BufReader::new(f)).is_err());
+    /// ```
+    pub fn max_total_properties(mut self, max: u64) -> Self {
+        self.max_total_properties = Some(max);
+        self
+    }
+    /// Longest header line in bytes, including its line break, 1 MiB by default.
+    ///
+    /// Header lines are read up to this length only, a longer line fails reading the header
+    /// with an error of kind `InvalidInput`. Guards against input without line breaks,
+    /// like binary data passed in by mistake, which would otherwise be buffered whole.
+    pub fn max_header_line_len(mut self, max: usize) -> Self {
+        self.max_header_line_len = max;
+        self
+    }
+    /// Registers a hook that is consulted when a header declares a scalar property of unknown type.
+    ///
+    /// The hook receives the type keyword as written in the header.
+    /// If it returns a handler, the property is recorded as `PropertyType::Custom`,
+    /// otherwise the header is rejected as before.
+    ///
+    /// Custom types can only be read from binary payloads and can not be written.
+    pub fn on_unknown_type<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) -> Option<CustomTypeHandler> + Send + Sync + 'static,
+    {
+        self.unknown_type_hook = Some(Arc::new(hook));
+        self
+    }
+    /// Registers a hook that is told how far `read_ply()` and `read_payload()` got.
+    ///
+    /// The hook is called when an element starts, after every `every` entries, and when it is complete.
+    /// Payload bytes are only counted while a hook is registered,
+    /// without one reading costs the same as before. Disables parallel parsing of ascii payloads.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::{Parser, ParserOptions};
+    /// # use ply_rs::ply::DefaultElement;
+    /// let options = ParserOptions::new().on_progress(100_000, |event| {
+    ///     println!("{}: {}/{} ({} bytes)", event.element, event.read, event.total, event.bytes);
+    /// });
+    /// let p = Parser::<DefaultElement>::with_options(options);
+    /// ```
+    pub fn on_progress<F>(mut self, every: u64, hook: F) -> Self
+    where
+        F: Fn(&ProgressEvent) + Send + Sync + 'static,
+    {
+        self.progress_hook = Some((Arc::new(hook), every.max(1)));
+        self
+    }
+    /// Flag to stop `read_ply()` and `read_payload()` from another thread or task, none by default.
+    ///
+    /// Once the flag is set, reading fails before the next entry with a `Cancelled` error
+    /// naming the element and entry it stopped at, of kind `Other`. The elements read so far are dropped.
+    /// Disables parallel parsing of ascii payloads.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::{Cancelled, Parser, ParserOptions};
+    /// # use ply_rs::ply::DefaultElement;
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use std::sync::Arc;
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// let p = Parser::<DefaultElement>::with_options(ParserOptions::new().cancel_flag(cancel.clone()));
+    /// // The client went away.
+    /// cancel.store(true, Ordering::Relaxed);
+    /// let mut f = std::fs::File::open("example_plys/house_ok_ascii.ply").unwrap();
+    /// let err = p.read_ply(&mut f).unwrap_err();
+    /// let cancelled = err.get_ref().unwrap().downcast_ref::<Cancelled>().unwrap();
+    /// assert_eq!((cancelled.element.as_str(), cancelled.index), ("vertex", 0));
+    /// ```
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel_flag = Some(flag);
+        self
+    }
+    /// Minimal number of entries of an ascii element to parse it on multiple threads, 10000 by default.
+    ///
+    /// Only used by `Parser::read_ply_parallel()` and `Parser::read_payload_parallel()`.
+    /// Requires the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn parallel_threshold(mut self, threshold: u64) -> Self {
+        self.parallel_threshold = threshold;
+        self
+    }
+    /// Removes the indentation of a header line, if allowed.
+    pub(crate) fn unindent<'a>(&self, line: &'a str) -> &'a str {
+        if self.allow_indented_header {
+            line.trim_start_matches([' ', '\t'])
+        } else {
+            line
+        }
+    }
+    /// Expected length of the lists of `property` in `element`, see `expect_fixed_list()`.
+    pub(crate) fn fixed_list_len(&self, element: &str, property: &str) -> Option<usize> {
+        self.fixed_lists
+            .iter()
+            .find(|(e, p, _)| e == element && p == property)
+            .map(|&(_, _, len)| len)
+    }
+    /// The checksum to verify the payload of `header` against, if any.
+    pub(crate) fn expected_checksum(&self, header: &Header) -> Option<u32> {
+        if !self.verify_checksum {
+            return None;
+        }
+        header
+            .comments
+            .iter()
+            .find_map(|c| parse_checksum_comment(c))
+    }
+    /// Renames the properties of `header` according to `normalize_property_names()`.
+    pub(crate) fn normalize_header(&self, header: &mut Header) -> Result<(), String> {
+        if self.property_names == NameNormalization::Preserve {
+            return Ok(());
+        }
+        for e in &mut header.elements {
+            let names: Vec<String> = e
+                .properties
+                .iter()
+                .map(|p| self.property_names.apply(&p.name))
+                .collect();
+            let mut seen: HashMap<&str, usize> = HashMap::with_capacity(names.len());
+            for (i, name) in names.iter().enumerate() {
+                if let Some(&j) = seen.get(name.as_str()) {
+                    return Err(format!(
+                        "Properties '{}' and '{}' of element '{}' have the same normalized name '{}'.",
+                        e.properties[j].name, e.properties[i].name, e.name, name
+                    ));
+                }
+                seen.insert(name.as_str(), i);
+            }
+            for (p, name) in e.properties.iter_mut().zip(names) {
+                p.name = name;
+            }
+        }
+        Ok(())
+    }
+    /// Checks the counts of `header` against `max_element_count()` and `max_total_properties()`.
+    pub(crate) fn check_header_limits(&self, header: &Header) -> Result<(), String> {
+        let mut total = 0u64;
+        for e in &header.elements {
+            if let Some(max) = self.max_element_count.filter(|&max| e.count > max) {
+                return Err(format!(
+                    "Element '{}' declares {} entries, more than the limit of {}.",
+                    e.name, e.count, max
+                ));
+            }
+            total = total.saturating_add(e.count.saturating_mul(e.properties.len() as u64));
+            if let Some(max) = self.max_total_properties.filter(|&max| total > max) {
+                return Err(format!(
+                    "Element '{}' brings the number of property values to {}, more than the limit of {}.",
+                    e.name, total, max
+                ));
+            }
+        }
+        Ok(())
+    }
+    /// Checks the length of a list of `property` in `element` against `max_list_len()`.
+    pub(crate) fn check_list_limit(
+        &self,
+        element: &str,
+        property: &str,
+        len: usize,
+    ) -> Result<(), String> {
+        match self.max_list_len {
+            Some(max) if len > max => Err(format!(
+                "List '{}' of element '{}' has {} entries, more than the limit of {}.",
+                property, element, len, max
+            )),
+            _ => Ok(()),
+        }
+    }
+    /// Whether `property` of `element` is passed to the element, see `keep_properties()`.
+    pub(crate) fn keeps_property(&self, element: &str, property: &str) -> bool {
+        self.kept_properties
+            .iter()
+            .find(|(e, _)| e == element)
+            .is_none_or(|(_, names)| names.iter().any(|n| n == property))
+    }
+    /// Whether the elements called `name` are left out, see `skip_elements()`.
+    pub(crate) fn skips_element(&self, name: &str) -> bool {
+        self.skip_elements.iter().any(|e| e == name)
+    }
+    /// The hook registered with `on_progress()` and its interval.
+    pub(crate) fn progress_hook(&self) -> Option<(&ProgressHook, u64)> {
+        self.progress_hook
+            .as_ref()
+            .map(|(hook, every)| (&**hook, *every))
+    }
+    /// The flag set with `cancel_flag()`.
+    pub(crate) fn cancellation(&self) -> Option<&AtomicBool> {
+        self.cancel_flag.as_deref()
+    }
+    /// Asks the registered hook, how to handle `type_name`.
+    pub(crate) fn custom_type(&self, type_name: &str) -> Option<CustomTypeHandler> {
+        self.unknown_type_hook.as_ref().and_then(|h| h(type_name))
+    }
+}
+
+impl fmt::Debug for ParserOptions {
+    /// The hooks and the cancellation flag can't be shown and are left out.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut d = f.debug_struct("ParserOptions");
+        d.field("allow_indented_header", &self.allow_indented_header)
+            .field("verify_checksum", &self.verify_checksum)
+            .field("property_names", &self.property_names)
+            .field("name_characters", &self.name_characters)
+            .field("fixed_lists", &self.fixed_lists)
+            .field("skip_rejected_properties", &self.skip_rejected_properties)
+            .field("multiline_ascii_elements", &self.multiline_ascii_elements)
+            .field("skip_elements", &self.skip_elements)
+            .field("kept_properties", &self.kept_properties)
+            .field("max_element_count", &self.max_element_count)
+            .field("max_list_len", &self.max_list_len)
+            .field("max_total_properties", &self.max_total_properties)
+            .field("max_header_line_len", &self.max_header_line_len);
+        #[cfg(feature = "parallel")]
+        d.field("parallel_threshold", &self.parallel_threshold);
+        d.finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,366 @@
+use super::GrammarError;
+use crate::ply::PropertyType;
+use crate::util::LocationTracker;
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::io;
+
+/// A header line that doesn't follow the PLY grammar, carried inside the returned `io::Error`.
+///
+/// Get it with `err.get_ref().and_then(|e| e.downcast_ref::<HeaderSyntaxError>())`.
+/// Displayed with the offending line and a caret under `column`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::{HeaderSyntaxError, Parser};
+/// # use ply_rs::ply::DefaultElement;
+/// let txt = "ply\nformat ascii 1.0\nelement vertex 1\nproperty flaot x\nend_header\n";
+/// let err = Parser::<DefaultElement>::new().read_ply(&mut txt.as_bytes()).unwrap_err();
+/// let syntax = err.get_ref().unwrap().downcast_ref::<HeaderSyntaxError>().unwrap();
+/// assert_eq!((syntax.line, syntax.column), (4, 10));
+/// assert_eq!(syntax.text, "property flaot x");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderSyntaxError {
+    /// Line of the file, starting at 1.
+    pub line: usize,
+    /// Column of the offending character within the line, starting at 1.
+    pub column: usize,
+    /// The line without its line break.
+    pub text: String,
+    /// Describes the problem, for example "Couldn't parse line."
+    pub message: String,
+    /// What the grammar expected at `column`.
+    pub expected: String,
+}
+
+impl Display for HeaderSyntaxError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        // Keep tabs, so the caret lines up with the text above it.
+        let indent: String = self
+            .text
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(
+            f,
+            "Line {}, column {}: {}\n\tString: '{}'\n\t         {}^\n\tError: expected {}",
+            self.line, self.column, self.message, self.text, indent, self.expected
+        )
+    }
+}
+
+impl error::Error for HeaderSyntaxError {}
+
+/// The payload doesn't match the checksum comment of its header, carried inside the returned `io::Error`.
+///
+/// Returned with kind `InvalidData` if `ParserOptions::verify_checksum()` is set.
+/// Get it with `err.get_ref().and_then(|e| e.downcast_ref::<ChecksumMismatch>())`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    /// CRC-32 given by the header comment.
+    pub expected: u32,
+    /// CRC-32 of the payload that was read.
+    pub found: u32,
+}
+
+impl Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "Payload checksum mismatch, the header says {:08x} but the payload has {:08x}.",
+            self.expected, self.found
+        )
+    }
+}
+
+impl error::Error for ChecksumMismatch {}
+
+/// Reading was stopped with `ParserOptions::cancel_flag()`, carried inside the returned `io::Error`.
+///
+/// Returned with kind `Other`, get it with `err.get_ref().and_then(|e| e.downcast_ref::<Cancelled>())`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancelled {
+    /// Name of the element being read.
+    pub element: String,
+    /// Zero-based index of the entry that wasn't read anymore.
+    pub index: u64,
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "Reading was cancelled before entry {} of element '{}'.",
+            self.index, self.element
+        )
+    }
+}
+
+impl error::Error for Cancelled {}
+
+/// An entry of the payload that couldn't be read, carried inside the returned `io::Error`.
+///
+/// Get it with `err.get_ref().and_then(|e| e.downcast_ref::<ElementError>())`.
+/// The `io::Error` keeps the kind of the underlying error, which is the `source()` of this one.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::{ElementError, Parser};
+/// # use ply_rs::ply::DefaultElement;
+/// let txt = "ply\nformat ascii 1.0\nelement vertex 2\nproperty uchar x\nend_header\n1\n300\n";
+/// let err = Parser::<DefaultElement>::new().read_ply(&mut txt.as_bytes()).unwrap_err();
+/// let element = err.get_ref().unwrap().downcast_ref::<ElementError>().unwrap();
+/// assert_eq!((element.element(), element.index()), ("vertex", 1));
+/// ```
+#[derive(Debug)]
+pub struct ElementError {
+    element: String,
+    index: u64,
+    cause: io::Error,
+}
+
+impl ElementError {
+    pub(crate) fn new(element: &str, index: u64, cause: io::Error) -> Self {
+        ElementError {
+            element: element.to_string(),
+            index,
+            cause,
+        }
+    }
+    /// Name of the element that couldn't be read.
+    pub fn element(&self) -> &str {
+        &self.element
+    }
+    /// Zero-based index of the entry within its element.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+    /// Why the entry couldn't be read.
+    pub fn cause(&self) -> &io::Error {
+        &self.cause
+    }
+}
+
+impl Display for ElementError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "Element '{}' #{}: {}",
+            self.element, self.index, self.cause
+        )
+    }
+}
+
+impl error::Error for ElementError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+/// Errors raised while parsing, carried inside an `io::Error`.
+///
+/// Only the data needed to describe the problem is captured,
+/// the message is built when the error is displayed.
+pub(crate) enum ParseError {
+    /// A line of the file couldn't be processed.
+    Line {
+        location: LocationTracker,
+        message: Cow<'static, str>,
+        line: String,
+        cause: Option<Box<dyn Debug + Send + Sync>>,
+    },
+    /// The grammar rejected an element line.
+    ElementLine { line: String, cause: GrammarError },
+    /// An ascii value couldn't be converted to its declared type.
+    Value {
+        value: String,
+        cause: Box<dyn error::Error + Send + Sync>,
+    },
+    /// An ascii element line ended before all properties were read.
+    MissingValue(PropertyType),
+    /// A list doesn't have the length given to `ParserOptions::expect_fixed_list()`.
+    FixedListLength {
+        property: String,
+        found: usize,
+        expected: usize,
+    },
+    /// An ascii list count isn't a non-negative integer.
+    ListCount {
+        property: String,
+        value: String,
+        negative: bool,
+    },
+    /// An ascii payload ended before all entries of `element` were read.
+    ///
+    /// `found` lines looked like the element, followed by `mismatched` lines that didn't.
+    UnexpectedEof {
+        element: String,
+        declared: u64,
+        found: u64,
+        mismatched: u64,
+    },
+    /// An element definition passed along with a header isn't part of it.
+    ///
+    /// `defined` tells whether the header has an element of the same name.
+    ForeignElementDef { name: String, defined: bool },
+    /// The header of a file doesn't match the header of a `BoundParser`.
+    SchemaMismatch(String),
+    /// The file has an element that wasn't registered with `MultiParser::element()`.
+    UnregisteredElement(String),
+    /// The element type rejected a property in `PropertyAccess::try_set_property()`.
+    RejectedProperty { key: String, reason: String },
+    /// A list ended before its declared length.
+    MissingListValue {
+        index: usize,
+        cause: Option<io::Error>,
+    },
+}
+
+impl ParseError {
+    /// Wraps `self` into an `io::Error` of kind `InvalidInput`.
+    pub(crate) fn into_io(self) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, self)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            ParseError::Line {
+                location,
+                ref message,
+                ref line,
+                cause: None,
+            } => write!(f, "{}: {}\n\tString: '{}'", location, message, line),
+            ParseError::Line {
+                location,
+                ref message,
+                ref line,
+                cause: Some(ref cause),
+            } => write!(
+                f,
+                "{}: {}\n\tString: '{}'\n\tError: {:?}",
+                location, message, line, cause
+            ),
+            ParseError::ElementLine {
+                ref line,
+                ref cause,
+            } => write!(
+                f,
+                "Couldn't parse element line.\n\tString: '{}'\n\tError: {}",
+                line, cause
+            ),
+            ParseError::Value {
+                ref value,
+                ref cause,
+            } => write!(
+                f,
+                "Parse error.\n\tValue: '{}'\n\tError: {:?}, ",
+                value, cause
+            ),
+            ParseError::MissingValue(ref data_type) => write!(
+                f,
+                "Expected element of type '{:?}', but found nothing.",
+                data_type
+            ),
+            ParseError::FixedListLength {
+                ref property,
+                found,
+                expected,
+            } => write!(
+                f,
+                "List '{}' has {} entries, expected {}.",
+                property, found, expected
+            ),
+            ParseError::ListCount {
+                ref property,
+                ref value,
+                negative: true,
+            } => write!(
+                f,
+                "List count for property '{}' is negative ({}).",
+                property, value
+            ),
+            ParseError::ListCount {
+                ref property,
+                ref value,
+                negative: false,
+            } => write!(
+                f,
+                "List count for property '{}' is not a valid count: '{}'.",
+                property, value
+            ),
+            ParseError::UnexpectedEof {
+                ref element,
+                declared,
+                found,
+                mismatched,
+            } => {
+                write!(
+                    f,
+                    "Unexpected end of file: element '{}' declared {} entries but only {} data lines were found.",
+                    element, declared, found
+                )?;
+                if mismatched > 0 {
+                    write!(
+                        f,
+                        " The {} lines after them don't match the properties of '{}', \
+                        they likely belong to the next element.",
+                        mismatched, element
+                    )?;
+                }
+                Ok(())
+            }
+            ParseError::ForeignElementDef {
+                ref name,
+                defined: false,
+            } => write!(f, "Element '{}' isn't defined in the header.", name),
+            ParseError::ForeignElementDef {
+                ref name,
+                defined: true,
+            } => write!(
+                f,
+                "Element '{}' doesn't match its definition in the header.",
+                name
+            ),
+            ParseError::SchemaMismatch(ref description) => {
+                write!(f, "Header doesn't match the bound schema: {}.", description)
+            }
+            ParseError::UnregisteredElement(ref name) => write!(
+                f,
+                "Element '{}' isn't registered with the MultiParser.",
+                name
+            ),
+            ParseError::RejectedProperty {
+                ref key,
+                ref reason,
+            } => write!(f, "Property '{}' was rejected: {}", key, reason),
+            ParseError::MissingListValue { index, cause: None } => {
+                write!(f, "Couldn't find a list element at index {}.", index)
+            }
+            ParseError::MissingListValue {
+                index,
+                cause: Some(ref cause),
+            } => write!(
+                f,
+                "Couldn't find a list element at index {}.\n\tError: {:?}",
+                index, cause
+            ),
+        }
+    }
+}
+
+// Nested errors are printed with `{:?}`, show the message like a `String` error would.
+impl Debug for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl error::Error for ParseError {}
@@ -0,0 +1,292 @@
+//! Byte offsets of binary payloads, to read single elements without parsing everything before them.
+
+use super::{binary_stride, element_error, skip_binary_element, skip_binary_elements, Parser};
+use super::{BigEndian, ByteOrder, LittleEndian, MAX_PREALLOCATION};
+use crate::ply::{ElementDef, Encoding, Header, PropertyAccess};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::io;
+use std::io::{BufRead, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+
+/// Number of elements between two checkpoints of elements with lists, used by `Parser::build_index()`.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 1024;
+
+/// Identifies data written by `PayloadIndex::write_to()`.
+const MAGIC: &[u8; 8] = b"plyidx01";
+
+/// Where the elements of a binary PLY file are stored, created by `Parser::build_index()`.
+///
+/// Holds the offset of each element type. Entries of elements with fixed size are found
+/// by their offset alone. For elements with lists, the offset of every `interval`-th entry
+/// is kept as a checkpoint, at most `interval - 1` entries are skipped to reach an entry.
+///
+/// Build it once and keep it with `write_to()`, it only depends on the file it was built for.
+/// Use `Parser::read_element_at()` to read single elements with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadIndex {
+    header: Header,
+    interval: u64,
+    /// Per element type, in header order.
+    elements: Vec<ElementIndex>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ElementIndex {
+    /// Offset of the first entry.
+    offset: u64,
+    /// Offset of every `interval`-th entry, empty for entries of fixed size.
+    checkpoints: Vec<u64>,
+}
+
+impl PayloadIndex {
+    /// The header of the indexed file.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+    /// Byte offset of the first entry of the element called `name`, from the start of the file.
+    pub fn element_offset(&self, name: &str) -> Option<u64> {
+        let pos = self.header.elements.iter().position(|e| e.name == name)?;
+        Some(self.elements[pos].offset)
+    }
+
+    /// Writes the index to `out`, read it back with `PayloadIndex::read_from()`.
+    ///
+    /// The header isn't written, it is read from the PLY file again.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_u64::<LittleEndian>(self.interval)?;
+        out.write_u64::<LittleEndian>(self.elements.len() as u64)?;
+        for (def, element) in self.header.elements.iter().zip(&self.elements) {
+            out.write_u64::<LittleEndian>(def.name.len() as u64)?;
+            out.write_all(def.name.as_bytes())?;
+            out.write_u64::<LittleEndian>(def.count)?;
+            out.write_u64::<LittleEndian>(element.offset)?;
+            out.write_u64::<LittleEndian>(element.checkpoints.len() as u64)?;
+            for &checkpoint in &element.checkpoints {
+                out.write_u64::<LittleEndian>(checkpoint)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads an index written by `write_to()`, `header` is the one of the indexed file.
+    ///
+    /// Consumes exactly the bytes of the index.
+    /// Fails with an error of kind `InvalidData` if the index doesn't match the elements of `header`.
+    pub fn read_from<R: Read>(reader: &mut R, header: &Header) -> Result<PayloadIndex> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(mismatch("it isn't a payload index"));
+        }
+        if header.encoding == Encoding::Ascii {
+            return Err(mismatch("ascii payloads aren't indexed"));
+        }
+        let interval = reader.read_u64::<LittleEndian>()?;
+        if interval == 0 {
+            return Err(mismatch("the checkpoint interval is 0"));
+        }
+        if reader.read_u64::<LittleEndian>()? != header.elements.len() as u64 {
+            return Err(mismatch("the number of elements differs"));
+        }
+        let mut elements = Vec::with_capacity(header.elements.len());
+        for def in &header.elements {
+            let name_len = reader.read_u64::<LittleEndian>()?;
+            let mut name = Vec::new();
+            reader.take(name_len).read_to_end(&mut name)?;
+            if name != def.name.as_bytes() || reader.read_u64::<LittleEndian>()? != def.count {
+                return Err(mismatch(&format!("element '{}' differs", def.name)));
+            }
+            let offset = reader.read_u64::<LittleEndian>()?;
+            let len = reader.read_u64::<LittleEndian>()?;
+            if len != checkpoint_count(def, interval) {
+                return Err(mismatch(&format!(
+                    "element '{}' has {} checkpoints",
+                    def.name, len
+                )));
+            }
+            let mut checkpoints = Vec::with_capacity((len as usize).min(MAX_PREALLOCATION));
+            for _ in 0..len {
+                checkpoints.push(reader.read_u64::<LittleEndian>()?);
+            }
+            elements.push(ElementIndex {
+                offset,
+                checkpoints,
+            });
+        }
+        Ok(PayloadIndex {
+            header: header.clone(),
+            interval,
+            elements,
+        })
+    }
+}
+
+impl<E: PropertyAccess> Parser<E> {
+    /// Reads past the payload of `reader` once and records where its elements are stored.
+    ///
+    /// `reader` has to be right after the header, as left by `read_header()`.
+    /// Elements with lists get a checkpoint every `DEFAULT_CHECKPOINT_INTERVAL` entries.
+    /// The payload has to be binary, otherwise an error of kind `Unsupported` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// let p = Parser::<DefaultElement>::new();
+    /// let mut f = std::io::BufReader::new(
+    ///     std::fs::File::open("example_plys/house_2_ok_little_endian.ply").unwrap(),
+    /// );
+    /// let header = p.read_header(&mut f).unwrap();
+    /// let index = p.build_index(&mut f, &header).unwrap();
+    /// p.read_element_at(&mut f, &index, "vertex", 3).unwrap();
+    /// ```
+    pub fn build_index<T: BufRead + Seek>(
+        &self,
+        reader: &mut T,
+        header: &Header,
+    ) -> Result<PayloadIndex> {
+        self.build_index_with_interval(reader, header, DEFAULT_CHECKPOINT_INTERVAL)
+    }
+
+    /// Same as `build_index()`, with a checkpoint every `interval` entries.
+    ///
+    /// Smaller intervals make reads of elements with lists faster and the index larger.
+    pub fn build_index_with_interval<T: BufRead + Seek>(
+        &self,
+        reader: &mut T,
+        header: &Header,
+        interval: u64,
+    ) -> Result<PayloadIndex> {
+        if interval == 0 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "The checkpoint interval has to be at least 1.",
+            ));
+        }
+        match header.encoding {
+            Encoding::Ascii => Err(io::Error::new(
+                ErrorKind::Unsupported,
+                "Ascii payloads can't be indexed.",
+            )),
+            Encoding::BinaryBigEndian => build::<T, BigEndian>(reader, header, interval),
+            Encoding::BinaryLittleEndian => build::<T, LittleEndian>(reader, header, interval),
+        }
+    }
+
+    /// Reads entry `index` of the element called `element`, using the offsets of `payload_index`.
+    ///
+    /// `reader` has to be the file `payload_index` was built for, its position doesn't matter.
+    /// Fails with an error of kind `InvalidInput` if there is no such element or entry.
+    pub fn read_element_at<T: BufRead + Seek>(
+        &self,
+        reader: &mut T,
+        payload_index: &PayloadIndex,
+        element: &str,
+        index: u64,
+    ) -> Result<E> {
+        let header = &payload_index.header;
+        let pos = match header.elements.iter().position(|e| e.name == element) {
+            Some(pos) => pos,
+            None => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Element '{}' isn't defined in the header.", element),
+                ))
+            }
+        };
+        let element_def = &header.elements[pos];
+        if index >= element_def.count {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Entry {} is out of the {} entries of element '{}'.",
+                    index, element_def.count, element
+                ),
+            ));
+        }
+        let entry = &payload_index.elements[pos];
+        let interval = payload_index.interval;
+        match header.encoding {
+            Encoding::BinaryBigEndian => {
+                self.read_indexed::<T, BigEndian>(reader, element_def, entry, interval, index)
+            }
+            _ => self.read_indexed::<T, LittleEndian>(reader, element_def, entry, interval, index),
+        }
+    }
+
+    fn read_indexed<T: BufRead + Seek, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        entry: &ElementIndex,
+        interval: u64,
+        index: u64,
+    ) -> Result<E> {
+        match binary_stride(element_def) {
+            Some(stride) => {
+                reader.seek(SeekFrom::Start(entry.offset + stride * index))?;
+            }
+            None => {
+                let checkpoint = index / interval;
+                reader.seek(SeekFrom::Start(entry.checkpoints[checkpoint as usize]))?;
+                for skipped in checkpoint * interval..index {
+                    skip_binary_element::<T, B>(reader, element_def, skipped)?;
+                }
+            }
+        }
+        self.read_binary_element::<T, B>(reader, element_def, None)
+            .map_err(|e| element_error(element_def, index, e))
+    }
+}
+
+fn build<T: BufRead + Seek, B: ByteOrder>(
+    reader: &mut T,
+    header: &Header,
+    interval: u64,
+) -> Result<PayloadIndex> {
+    let mut offset = reader.stream_position()?;
+    let mut elements = Vec::with_capacity(header.elements.len());
+    for def in &header.elements {
+        let start = offset;
+        let mut checkpoints = Vec::new();
+        match binary_stride(def) {
+            Some(stride) => {
+                skip_binary_elements::<T, B>(reader, def, def.count)?;
+                offset += stride * def.count;
+            }
+            None => {
+                for index in 0..def.count {
+                    if index % interval == 0 {
+                        checkpoints.push(offset);
+                    }
+                    offset += skip_binary_element::<T, B>(reader, def, index)?;
+                }
+            }
+        }
+        elements.push(ElementIndex {
+            offset: start,
+            checkpoints,
+        });
+    }
+    Ok(PayloadIndex {
+        header: header.clone(),
+        interval,
+        elements,
+    })
+}
+
+/// Number of checkpoints `build()` records for `def`.
+fn checkpoint_count(def: &ElementDef, interval: u64) -> u64 {
+    match binary_stride(def) {
+        Some(_) => 0,
+        None => def.count.div_ceil(interval),
+    }
+}
+
+fn mismatch(reason: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("The payload index doesn't match the file: {}.", reason),
+    )
+}
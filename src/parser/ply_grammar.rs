@@ -1,6 +1,16 @@
+//! Parses header lines and ascii element lines.
+//!
+//! Every rule has to match its whole input. On failure, the error points at the farthest
+//! position the parser got to and lists what it expected there.
+
+use super::NameCharacters;
 use crate::ply::{
     Comment, ElementDef, Encoding, ObjInfo, PropertyDef, PropertyType, ScalarType, Version,
 };
+use std::error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Line {
     MagicNumber,
@@ -12,117 +22,746 @@ pub enum Line {
     EndHeader,
 }
 
-peg::parser! {pub grammar grammar() for str {
+/// A line rejected by the grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GrammarError {
+    /// Line within the input, starting at 1.
+    pub line: usize,
+    /// Column of the offending character, starting at 1.
+    pub column: usize,
+    /// What was expected at the offending character, sorted.
+    pub expected: Vec<&'static str>,
+}
 
-/// Grammar for PLY header
+impl GrammarError {
+    /// Describes what was expected, like "one of \"ascii\", \"binary_big_endian\"".
+    pub fn expected(&self) -> String {
+        match self.expected[..] {
+            [single] => single.to_string(),
+            _ => format!("one of {}", self.expected.join(", ")),
+        }
+    }
+}
 
-pub rule number() -> String
-    = n:$(['0'..='9']+) { n.to_string() }
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "error at {}:{}: expected {}",
+            self.line,
+            self.column,
+            self.expected()
+        )
+    }
+}
 
-rule space() = [' '|'\t']+
+impl error::Error for GrammarError {}
 
-rule uint() -> u64
-    = n:$(['0'..='9']+) { n.parse().unwrap() }
+/// Position within the input, remembering the farthest failure.
+struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+    fail_pos: usize,
+    expected: Vec<&'static str>,
+    /// Whether failures are recorded, only needed to describe an error.
+    track: bool,
+    names: NameCharacters,
+}
 
-rule ident() -> String
-    = s:$(['a'..='z'|'A'..='Z'|'_']['a'..='z'|'A'..='Z'|'0'..='9'|'_'|'-']*) { s.to_string() }
+impl<'a> Cursor<'a> {
+    fn new(input: &'a str, track: bool, names: NameCharacters) -> Self {
+        Cursor {
+            input,
+            pos: 0,
+            fail_pos: 0,
+            expected: Vec::new(),
+            track,
+            names,
+        }
+    }
 
-rule text() -> String
-    = s:$((!['\n'|'\r'][_])+) { s.to_string() }
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
 
-rule line_break()
-    = "\r\n" / ['\n'|'\r']
+    /// Records that `what` was expected at `pos`.
+    fn expect(&mut self, pos: usize, what: &'static str) {
+        if !self.track {
+            return;
+        }
+        if pos > self.fail_pos {
+            self.fail_pos = pos;
+            self.expected.clear();
+        }
+        if pos == self.fail_pos && !self.expected.contains(&what) {
+            self.expected.push(what);
+        }
+    }
 
-rule scalar() -> ScalarType
-    = "char"    { ScalarType::Char }
-    / "int8"    { ScalarType::Char }
-    / "uchar"   { ScalarType::UChar }
-    / "uint8"   { ScalarType::UChar }
-    / "short"   { ScalarType::Short }
-    / "int16"   { ScalarType::Short }
-    / "uint16"  { ScalarType::UShort }
-    / "ushort"  { ScalarType::UShort }
-    / "int32"   { ScalarType::Int }
-    / "int"     { ScalarType::Int }
-    / "uint32"  { ScalarType::UInt }
-    / "uint"    { ScalarType::UInt }
-    / "float32" { ScalarType::Float }
-    / "float64" { ScalarType::Double }
-    / "float"   { ScalarType::Float }
-    / "double"  { ScalarType::Double }
+    /// Consumes `literal`, `quoted` is how it is shown in errors.
+    fn literal(&mut self, literal: &str, quoted: &'static str) -> bool {
+        if self.rest().starts_with(literal) {
+            self.pos += literal.len();
+            true
+        } else {
+            self.expect(self.pos, quoted);
+            false
+        }
+    }
 
-rule data_type() -> PropertyType
-    = s:scalar()   { PropertyType::Scalar(s) }
-    / "list" space() it:scalar() space() t:scalar() {
-        PropertyType::List(it, t)
+    /// Consumes characters while `accept` holds, returns them.
+    fn take_while(&mut self, accept: impl Fn(u8) -> bool, class: &'static str) -> &'a str {
+        let start = self.pos;
+        let n = self.rest().bytes().take_while(|&b| accept(b)).count();
+        self.pos += n;
+        // The failing character is the one after the taken ones.
+        self.expect(self.pos, class);
+        &self.input[start..self.pos]
     }
 
-pub rule magic_number()
-    = "ply"
+    /// One or more spaces or tabs.
+    fn space(&mut self) -> bool {
+        !self
+            .take_while(|b| b == b' ' || b == b'\t', "[' '|'\\t']")
+            .is_empty()
+    }
 
-pub rule format() -> (Encoding, Version)
-    = "format" space() "ascii" space() v:version() { (Encoding::Ascii, v) }
-    / "format" space() "binary_big_endian" space() v:version() { (Encoding::BinaryBigEndian, v) }
-    / "format" space() "binary_little_endian" space() v:version() { (Encoding::BinaryLittleEndian, v) }
+    /// An optional `\r\n`, `\n` or `\r`.
+    fn line_break(&mut self) {
+        if !self.literal("\r\n", "\"\\r\\n\"") {
+            self.take_while_one(|b| b == b'\n' || b == b'\r', "['\\n'|'\\r']");
+        }
+    }
 
-rule version() -> Version
-    = maj:uint() "." min:uint() {
-        Version {
-            major: maj as u16,
-            minor: min as u8,
+    /// Consumes a single character if `accept` holds for it.
+    fn take_while_one(&mut self, accept: impl Fn(u8) -> bool, class: &'static str) -> bool {
+        match self.rest().bytes().next() {
+            Some(b) if accept(b) => {
+                self.pos += 1;
+                true
+            }
+            _ => {
+                self.expect(self.pos, class);
+                false
+            }
         }
     }
 
-pub rule comment() -> Comment
-    = "comment" space() c:text() {
-        c
+    fn digits(&mut self) -> Option<&'a str> {
+        Some(self.take_while(|b| b.is_ascii_digit(), "['0'..='9']")).filter(|d| !d.is_empty())
     }
-    / "comment" space()? {
-        String::new()
+
+    /// `[A-Za-z_][A-Za-z0-9_-]*`
+    fn ident(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        if !self.take_while_one(
+            |b| b.is_ascii_alphabetic() || b == b'_',
+            "['a'..='z'|'A'..='Z'|'_']",
+        ) {
+            return None;
+        }
+        self.take_while(
+            |b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-',
+            "['a'..='z'|'A'..='Z'|'0'..='9'|'_'|'-']",
+        );
+        Some(&self.input[start..self.pos])
     }
 
-pub rule obj_info() -> ObjInfo
-    = "obj_info" space() c:text() {
-        c
+    /// `[A-Za-z0-9_][A-Za-z0-9_.-]*`
+    fn dotted_ident(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        if !self.take_while_one(
+            |b| b.is_ascii_alphanumeric() || b == b'_',
+            "['a'..='z'|'A'..='Z'|'0'..='9'|'_']",
+        ) {
+            return None;
+        }
+        self.take_while(
+            |b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'.',
+            "['a'..='z'|'A'..='Z'|'0'..='9'|'_'|'-'|'.']",
+        );
+        Some(&self.input[start..self.pos])
     }
-    / "obj_info" space()? {
-        String::new()
+
+    /// An element or property name, made of the characters accepted by `names`.
+    fn name(&mut self) -> Option<&'a str> {
+        let names = self.names;
+        if names == NameCharacters::Identifier {
+            return self.dotted_ident();
+        }
+        let start = self.pos;
+        let n: usize = self
+            .rest()
+            .chars()
+            .take_while(|&c| names.accepts(c, false))
+            .map(char::len_utf8)
+            .sum();
+        self.pos += n;
+        self.expect(self.pos, "[c if !c.is_whitespace() && !c.is_control()]");
+        Some(&self.input[start..self.pos]).filter(|name| !name.is_empty())
     }
 
-pub rule element() -> ElementDef
-    = "element" space() id:$(ident()) space() n:uint() {
-        let mut e = ElementDef::new(id);
-        e.count = n as usize;
-        e
+    fn uint(&mut self) -> Option<u64> {
+        let start = self.pos;
+        let n = self.digits()?.parse().ok();
+        if n.is_none() {
+            // Rejected values are reported after their end.
+            self.expect(self.pos, "unsigned integer");
+            self.pos = start;
+        }
+        n
     }
 
-pub rule property() -> PropertyDef
-    = "property" space() data_type:data_type() space() id:ident() {
-        PropertyDef::new(&id, data_type)
+    /// The rest of the line, up to a line break.
+    fn text(&mut self) -> &'a str {
+        let start = self.pos;
+        let n = self
+            .rest()
+            .bytes()
+            .take_while(|&b| b != b'\n' && b != b'\r')
+            .count();
+        self.pos += n;
+        // Any character is fine, the line break isn't expected.
+        if self.pos == self.input.len() {
+            self.expect(self.pos, "[_]");
+        }
+        &self.input[start..self.pos]
     }
 
-pub rule end_header()
-    = "end_header"
+    /// Runs `rule`, going back to the current position if it fails.
+    fn attempt<T>(&mut self, rule: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        let start = self.pos;
+        let result = rule(self);
+        if result.is_none() {
+            self.pos = start;
+        }
+        result
+    }
 
-pub rule line() -> Line
-    = l:trimmed_line() space()? line_break()? { l }
+    /// Succeeds if the whole input was consumed.
+    fn finish<T>(mut self, value: Option<T>) -> Result<T, GrammarError> {
+        match value {
+            Some(v) if self.pos == self.input.len() => return Ok(v),
+            Some(_) => self.expect(self.pos, "EOF"),
+            None => (),
+        }
+        let before = &self.input[..self.fail_pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        self.expected.sort_unstable();
+        Err(GrammarError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected: self.expected,
+        })
+    }
+}
 
-rule trimmed_line() -> Line
-    = magic_number() { Line::MagicNumber }
-    / end_header() { Line::EndHeader }
-    / v:format() { Line::Format(v) }
-    / v:obj_info() { Line::ObjInfo(v) }
-    / v:comment() { Line::Comment(v) }
-    / v:element() { Line::Element(v) }
-    / v:property() { Line::Property(v) }
+/// Applies `rule` to all of `input`.
+///
+/// Failures are only recorded when `rule` is applied a second time to describe an error,
+/// most lines are fine and shouldn't pay for that.
+fn parse<'a, T>(
+    input: &'a str,
+    rule: impl Fn(&mut Cursor<'a>) -> Option<T>,
+) -> Result<T, GrammarError> {
+    parse_names(input, NameCharacters::default(), rule)
+}
+
+/// Like `parse()`, element and property names are made of the characters accepted by `names`.
+fn parse_names<'a, T>(
+    input: &'a str,
+    names: NameCharacters,
+    rule: impl Fn(&mut Cursor<'a>) -> Option<T>,
+) -> Result<T, GrammarError> {
+    let mut c = Cursor::new(input, false, names);
+    if let Some(value) = rule(&mut c).filter(|_| c.pos == input.len()) {
+        return Ok(value);
+    }
+    let mut c = Cursor::new(input, true, names);
+    let value = rule(&mut c);
+    c.finish(value)
+}
+
+/// Maps the nonstandard 64 bit integer keywords, only accepted with the `extended-types` feature.
+#[cfg(feature = "extended-types")]
+fn extended_scalar(s: &str) -> Result<ScalarType, &'static str> {
+    match s {
+        "int64" | "long" => Ok(ScalarType::LongLong),
+        _ => Ok(ScalarType::ULongLong),
+    }
+}
+#[cfg(not(feature = "extended-types"))]
+fn extended_scalar(_: &str) -> Result<ScalarType, &'static str> {
+    Err("64 bit integer types require the `extended-types` feature")
+}
+
+/// Maps the nonstandard 16 bit float keywords, only accepted with the `half` feature.
+#[cfg(feature = "half")]
+fn half_scalar() -> Result<ScalarType, &'static str> {
+    Ok(ScalarType::Half)
+}
+#[cfg(not(feature = "half"))]
+fn half_scalar() -> Result<ScalarType, &'static str> {
+    Err("16 bit float types require the `half` feature")
+}
+
+/// Type keywords with their quoted form, in the order they are tried.
+///
+/// A keyword matching the start of a longer one has to come after it.
+const SCALAR_KEYWORDS: &[(&str, &str)] = &[
+    ("int64", "\"int64\""),
+    ("uint64", "\"uint64\""),
+    ("long", "\"long\""),
+    ("ulong", "\"ulong\""),
+    ("float16", "\"float16\""),
+    ("half", "\"half\""),
+    ("char", "\"char\""),
+    ("int8", "\"int8\""),
+    ("uchar", "\"uchar\""),
+    ("uint8", "\"uint8\""),
+    ("short", "\"short\""),
+    ("int16", "\"int16\""),
+    ("uint16", "\"uint16\""),
+    ("ushort", "\"ushort\""),
+    ("int32", "\"int32\""),
+    ("int", "\"int\""),
+    ("uint32", "\"uint32\""),
+    ("uint", "\"uint\""),
+    ("float32", "\"float32\""),
+    ("float64", "\"float64\""),
+    ("float", "\"float\""),
+    ("double", "\"double\""),
+];
+
+fn scalar_type(keyword: &str) -> Result<ScalarType, &'static str> {
+    match keyword {
+        "int64" | "uint64" | "long" | "ulong" => extended_scalar(keyword),
+        "float16" | "half" => half_scalar(),
+        "char" | "int8" => Ok(ScalarType::Char),
+        "uchar" | "uint8" => Ok(ScalarType::UChar),
+        "short" | "int16" => Ok(ScalarType::Short),
+        "ushort" | "uint16" => Ok(ScalarType::UShort),
+        "int" | "int32" => Ok(ScalarType::Int),
+        "uint" | "uint32" => Ok(ScalarType::UInt),
+        "float" | "float32" => Ok(ScalarType::Float),
+        _ => Ok(ScalarType::Double),
+    }
+}
+
+fn scalar(c: &mut Cursor) -> Option<ScalarType> {
+    let start = c.pos;
+    for &(keyword, quoted) in SCALAR_KEYWORDS {
+        if !c.literal(keyword, quoted) {
+            continue;
+        }
+        match scalar_type(keyword) {
+            Ok(t) => return Some(t),
+            Err(reason) => {
+                c.expect(c.pos, reason);
+                c.pos = start;
+            }
+        }
+    }
+    None
+}
+
+fn data_type(c: &mut Cursor) -> Option<PropertyType> {
+    if let Some(s) = c.attempt(scalar) {
+        return Some(PropertyType::Scalar(s));
+    }
+    c.attempt(|c| {
+        if !(c.literal("list", "\"list\"") && c.space()) {
+            return None;
+        }
+        let index_type = scalar(c)?;
+        if !c.space() {
+            return None;
+        }
+        Some(PropertyType::List(index_type, scalar(c)?))
+    })
+}
+
+fn version(c: &mut Cursor) -> Option<Version> {
+    let major = c.uint()?;
+    if !c.literal(".", "\".\"") {
+        return None;
+    }
+    let minor = c.uint()?;
+    let major = major.try_into().ok().or_else(|| {
+        c.expect(c.pos, "major version");
+        None
+    })?;
+    let minor = minor.try_into().ok().or_else(|| {
+        c.expect(c.pos, "minor version");
+        None
+    })?;
+    Some(Version { major, minor })
+}
 
-rule any_number() -> String
-    = s:$(['-'|'+']? ['0'..='9']+("."['0'..='9']+)?("e"['-'|'+']?['0'..='9']+)?) { s.to_string() }
+/// `keyword`, optionally followed by a single space or tab and text.
+///
+/// The text is kept as written, including further leading spaces.
+fn text_line(c: &mut Cursor, keyword: &str, quoted: &'static str) -> Option<String> {
+    if !c.literal(keyword, quoted) {
+        return None;
+    }
+    let separated = c.take_while_one(|b| b == b' ' || b == b'\t', "[' '|'\\t']");
+    let text = if separated { c.text() } else { "" };
+    Some(text.to_string())
+}
 
-rule trimmed_data_line() -> Vec<String>
-    = any_number() ** space()
+fn magic_number_rule(c: &mut Cursor) -> Option<()> {
+    c.literal("ply", "\"ply\"").then_some(())
+}
 
-pub rule data_line() -> Vec<String>
-    = space()? l:trimmed_data_line() space()? line_break()? {l}
+fn end_header_rule(c: &mut Cursor) -> Option<()> {
+    c.literal("end_header", "\"end_header\"").then_some(())
+}
 
-}}
+fn format_rule(c: &mut Cursor) -> Option<(Encoding, Version)> {
+    if !(c.literal("format", "\"format\"") && c.space()) {
+        return None;
+    }
+    let encoding = if c.literal("ascii", "\"ascii\"") {
+        Encoding::Ascii
+    } else if c.literal("binary_big_endian", "\"binary_big_endian\"") {
+        Encoding::BinaryBigEndian
+    } else if c.literal("binary_little_endian", "\"binary_little_endian\"") {
+        Encoding::BinaryLittleEndian
+    } else {
+        return None;
+    };
+    if !c.space() {
+        return None;
+    }
+    Some((encoding, version(c)?))
+}
+
+fn comment_rule(c: &mut Cursor) -> Option<Comment> {
+    text_line(c, "comment", "\"comment\"")
+}
+
+fn obj_info_rule(c: &mut Cursor) -> Option<ObjInfo> {
+    text_line(c, "obj_info", "\"obj_info\"")
+}
+
+fn element_rule(c: &mut Cursor) -> Option<ElementDef> {
+    if !(c.literal("element", "\"element\"") && c.space()) {
+        return None;
+    }
+    let name = c.name()?;
+    if !c.space() {
+        return None;
+    }
+    let mut e = ElementDef::new(name);
+    e.count = c.uint()?;
+    Some(e)
+}
+
+fn property_rule(c: &mut Cursor) -> Option<PropertyDef> {
+    if !(c.literal("property", "\"property\"") && c.space()) {
+        return None;
+    }
+    let data_type = data_type(c)?;
+    if !c.space() {
+        return None;
+    }
+    Some(PropertyDef::new(c.name()?, data_type))
+}
+
+fn line_rule(c: &mut Cursor) -> Option<Line> {
+    let line = c
+        .attempt(|c| magic_number_rule(c).map(|()| Line::MagicNumber))
+        .or_else(|| c.attempt(|c| end_header_rule(c).map(|()| Line::EndHeader)))
+        .or_else(|| c.attempt(|c| format_rule(c).map(Line::Format)))
+        .or_else(|| c.attempt(|c| obj_info_rule(c).map(Line::ObjInfo)))
+        .or_else(|| c.attempt(|c| comment_rule(c).map(Line::Comment)))
+        .or_else(|| c.attempt(|c| element_rule(c).map(Line::Element)))
+        .or_else(|| c.attempt(|c| property_rule(c).map(Line::Property)))?;
+    c.space();
+    c.line_break();
+    Some(line)
+}
+
+/// `[-+]?(([0-9]+(\.[0-9]*)?|\.[0-9]+)(e[-+]?[0-9]+)?|nan|NaN|inf)`
+///
+/// Either part around the decimal point may be missing (`1.`, `.5`), but not both.
+fn any_number<'a>(c: &mut Cursor<'a>) -> Option<&'a str> {
+    let start = c.pos;
+    let sign = |c: &mut Cursor| c.take_while_one(|b| b == b'-' || b == b'+', "['-'|'+']");
+    sign(c);
+    let mantissa = c
+        .attempt(|c| {
+            c.digits()?;
+            c.attempt(|c| c.literal(".", "\".\"").then(|| c.digits()));
+            Some(())
+        })
+        .or_else(|| {
+            c.attempt(|c| {
+                c.literal(".", "\".\"")
+                    .then(|| c.digits())
+                    .flatten()
+                    .map(|_| ())
+            })
+        });
+    match mantissa {
+        Some(()) => {
+            c.attempt(|c| {
+                if !c.literal("e", "\"e\"") {
+                    return None;
+                }
+                sign(c);
+                c.digits()
+            });
+        }
+        None => {
+            let special = ["nan", "NaN", "inf"];
+            let quoted = ["\"nan\"", "\"NaN\"", "\"inf\""];
+            if !special.iter().zip(quoted).any(|(s, q)| c.literal(s, q)) {
+                c.pos = start;
+                return None;
+            }
+        }
+    }
+    Some(&c.input[start..c.pos])
+}
+
+fn data_line_rule(c: &mut Cursor) -> Option<Vec<String>> {
+    c.space();
+    let mut values = Vec::new();
+    if let Some(first) = c.attempt(any_number) {
+        values.push(first.to_string());
+        while let Some(value) = c.attempt(|c| if c.space() { any_number(c) } else { None }) {
+            values.push(value.to_string());
+        }
+    }
+    c.space();
+    c.line_break();
+    Some(values)
+}
+
+// Single rules, the parser only needs whole lines.
+
+#[cfg(test)]
+pub fn magic_number(input: &str) -> Result<(), GrammarError> {
+    parse(input, magic_number_rule)
+}
+
+#[cfg(test)]
+pub fn format(input: &str) -> Result<(Encoding, Version), GrammarError> {
+    parse(input, format_rule)
+}
+
+#[cfg(test)]
+pub fn comment(input: &str) -> Result<Comment, GrammarError> {
+    parse(input, comment_rule)
+}
+
+#[cfg(test)]
+pub fn obj_info(input: &str) -> Result<ObjInfo, GrammarError> {
+    parse(input, obj_info_rule)
+}
+
+#[cfg(test)]
+pub fn element(input: &str) -> Result<ElementDef, GrammarError> {
+    parse(input, element_rule)
+}
+
+#[cfg(test)]
+pub fn property(input: &str) -> Result<PropertyDef, GrammarError> {
+    parse(input, property_rule)
+}
+
+/// Property with a type keyword unknown to the grammar: (type keyword, property name)
+pub fn custom_property(
+    input: &str,
+    names: NameCharacters,
+) -> Result<(String, String), GrammarError> {
+    parse_names(input, names, |c| {
+        if !(c.literal("property", "\"property\"") && c.space()) {
+            return None;
+        }
+        let type_name = c.ident()?;
+        if !c.space() {
+            return None;
+        }
+        let name = c.name()?;
+        c.space();
+        c.line_break();
+        Some((type_name.to_string(), name.to_string()))
+    })
+}
+
+#[cfg(test)]
+pub fn end_header(input: &str) -> Result<(), GrammarError> {
+    parse(input, end_header_rule)
+}
+
+/// A header line, optionally followed by whitespace and a line break.
+#[cfg(test)]
+pub fn line(input: &str) -> Result<Line, GrammarError> {
+    parse(input, line_rule)
+}
+
+/// A header line, optionally followed by whitespace and a line break.
+/// Element and property names are made of the characters accepted by `names`.
+pub fn line_with_names(input: &str, names: NameCharacters) -> Result<Line, GrammarError> {
+    parse_names(input, names, line_rule)
+}
+
+/// Whitespace separated numbers, optionally surrounded by whitespace and followed by a line break.
+pub fn data_line(input: &str) -> Result<Vec<String>, GrammarError> {
+    parse(input, data_line_rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::legacy_grammar::grammar as legacy;
+    use super::*;
+    use std::fs;
+
+    fn custom_property(input: &str) -> Result<(String, String), GrammarError> {
+        super::custom_property(input, NameCharacters::default())
+    }
+
+    /// Checks that both grammars accept `input` with the same value or reject it with the same error.
+    macro_rules! assert_same {
+        ($rule:ident, $input:expr) => {
+            let input = $input;
+            let found = $rule(input).map_err(|e| (e.line, e.column, e.expected()));
+            let expected = legacy::$rule(input)
+                .map_err(|e| (e.location.line, e.location.column, e.expected.to_string()));
+            assert_eq!(found, expected, "{}: {:?}", stringify!($rule), input);
+        };
+    }
+    fn assert_all_same(input: &str) {
+        assert_same!(magic_number, input);
+        assert_same!(format, input);
+        assert_same!(comment, input);
+        assert_same!(obj_info, input);
+        assert_same!(element, input);
+        assert_same!(property, input);
+        assert_same!(custom_property, input);
+        assert_same!(end_header, input);
+        assert_same!(line, input);
+        assert_same!(data_line, input);
+    }
+    #[test]
+    fn same_as_legacy_example_files() {
+        for entry in fs::read_dir("example_plys").unwrap() {
+            let bytes = fs::read(entry.unwrap().path()).unwrap();
+            // Binary payloads aren't utf-8, the header is ascii.
+            let text = String::from_utf8_lossy(&bytes);
+            for line in text.split_inclusive('\n').take(200) {
+                assert_all_same(line);
+                assert_all_same(line.trim_end());
+            }
+        }
+    }
+    #[test]
+    fn same_as_legacy_edge_cases() {
+        for line in [
+            "",
+            "ply",
+            "ply \r\n",
+            "plyx",
+            "format ascii 1.0",
+            "format\tbinary_big_endian  2.1 \r",
+            "format ascii 1.0.3",
+            "format ascii 1,0",
+            "format ascii 65536.0",
+            "format ascii 1.256",
+            "format ascii 99999999999999999999.0",
+            "comment",
+            "comment  trailing space  \n",
+            "commentt",
+            "comment hi\n\n",
+            "comment hi\ra comment",
+            "obj_info\t\tx",
+            "element vertex 8",
+            "element vertex-2 8\n",
+            "element 8 vertex",
+            "element vertex 99999999999999999999",
+            "element v\u{e9}rtex 8",
+            "property float x",
+            "property flaot x",
+            "property float x.y",
+            "property float rot(x)",
+            "element 3d.points 2",
+            "property list uchar int vertex_indices",
+            "property list uchar vertex_indices",
+            "property listx y",
+            "property int64 t",
+            "property uint8 a",
+            "property int8x y",
+            "property half h",
+            "property float16 h",
+            "property int24 b",
+            "property int24 b c",
+            "end_header",
+            "end_header  \n",
+            "1 2 3",
+            " \t1\t-2.5e3 .5 1. nan -inf \r\n",
+            "1e",
+            "1e+ 2",
+            "inf1",
+            "1 . 2",
+            "\u{a0}1",
+        ] {
+            assert_all_same(line);
+        }
+    }
+    #[test]
+    fn same_as_legacy_random_lines() {
+        const PIECES: &[&str] = &[
+            "ply",
+            "format",
+            "ascii",
+            "binary_little_endian",
+            "comment",
+            "obj_info",
+            "element",
+            "property",
+            "list",
+            "end_header",
+            "uchar",
+            "int",
+            "int8",
+            "float",
+            "int64",
+            "half",
+            "vertex",
+            "x_1",
+            "-",
+            "0",
+            "12",
+            ".",
+            "1.0",
+            "e5",
+            "nan",
+            "inf",
+            " ",
+            "  ",
+            "\t",
+            "\r",
+            "\n",
+            "\r\n",
+            "+",
+            "é",
+        ];
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for _ in 0..20_000 {
+            let mut line = String::new();
+            for _ in 0..next() % 8 {
+                line.push_str(PIECES[next() % PIECES.len()]);
+            }
+            assert_all_same(&line);
+        }
+    }
+}
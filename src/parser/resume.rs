@@ -0,0 +1,204 @@
+//! Reads payloads element by element, so reading can continue after the source broke off.
+
+use super::{element_error, skip_binary_element, unexpected_eof, CountingReader, Parser};
+use super::{BigEndian, ByteOrder, LittleEndian};
+use crate::ply::{ElementDef, Encoding, Header, Payload, PropertyAccess};
+use crate::util::LocationTracker;
+use std::cell::Cell;
+use std::io::{BufRead, Result};
+
+/// Progress of `Parser::read_payload_resumable()`, holds the elements read so far.
+///
+/// After an error, it describes the payload up to the last complete element.
+/// Reopen the source `offset()` bytes after the start of the payload and pass the state
+/// again to continue where reading stopped.
+#[derive(Debug, Clone)]
+pub struct ParseState<E: PropertyAccess> {
+    header: Header,
+    /// Position in `header.elements` of the element being read.
+    element: usize,
+    /// Zero-based index of the next entry of `element`.
+    index: u64,
+    /// Length of the complete entries, from the start of the payload.
+    offset: u64,
+    location: LocationTracker,
+    payload: Payload<E>,
+}
+
+impl<E: PropertyAccess> ParseState<E> {
+    /// Starts at the first element of the payload described by `header`.
+    pub fn new(header: Header) -> Self {
+        ParseState {
+            header,
+            element: 0,
+            index: 0,
+            offset: 0,
+            location: LocationTracker::payload(),
+            payload: Payload::new(),
+        }
+    }
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+    /// Number of payload bytes read successfully, the position to continue from.
+    ///
+    /// The payload starts right after the line break of `end_header`.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// The element read next, `None` once the payload is complete.
+    pub fn element(&self) -> Option<&ElementDef> {
+        self.header.elements.get(self.element)
+    }
+    /// Zero-based index of the entry of `element()` read next.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+    /// Elements read so far, by element name.
+    pub fn payload(&self) -> &Payload<E> {
+        &self.payload
+    }
+}
+
+impl<E: PropertyAccess> Parser<E> {
+    /// Reads the rest of the payload described by `state` from `reader`.
+    ///
+    /// `reader` has to start `state.offset()` bytes into the payload.
+    /// Progress is recorded in `state` after every element, so after an error,
+    /// e.g. because a connection dropped, reading can continue from a reopened source
+    /// without reading the elements before again. Returns the complete payload,
+    /// equal to the one of `read_payload()`, and leaves `state` without elements.
+    ///
+    /// Ascii elements have to stay on a single line. A line without line break is only accepted
+    /// as the last line of the payload, elsewhere it counts as cut off.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::{ParseState, Parser};
+    /// # use ply_rs::ply::DefaultElement;
+    /// let bytes = std::fs::read("example_plys/house_2_ok_little_endian.ply").unwrap();
+    /// let p = Parser::<DefaultElement>::new();
+    /// let mut reader = &bytes[..];
+    /// let header = p.read_header(&mut reader).unwrap();
+    /// let payload_start = bytes.len() - reader.len();
+    /// let mut state = ParseState::new(header);
+    /// // The source breaks off within the vertices.
+    /// assert!(p.read_payload_resumable(&mut &reader[..30], &mut state).is_err());
+    /// let mut rest = &bytes[payload_start + state.offset() as usize..];
+    /// let payload = p.read_payload_resumable(&mut rest, &mut state).unwrap();
+    /// assert_eq!(payload["vertex"].len(), 5);
+    /// ```
+    pub fn read_payload_resumable<T: BufRead>(
+        &self,
+        reader: &mut T,
+        state: &mut ParseState<E>,
+    ) -> Result<Payload<E>> {
+        let ParseState {
+            header,
+            element,
+            index,
+            offset,
+            location,
+            payload,
+        } = state;
+        let count = Cell::new(0);
+        let mut reader = CountingReader {
+            inner: reader,
+            count: &count,
+        };
+        let start = *offset;
+        let mut line = Vec::new();
+        while let Some(def) = header.elements.get(*element) {
+            let skipped = self.options.skips_element(&def.name);
+            if !skipped && !payload.contains_key(&def.name) {
+                payload.insert(def.name.clone(), Vec::new());
+            }
+            let last = *element + 1 == header.elements.len();
+            while *index < def.count {
+                let is_last_line = last && *index + 1 == def.count;
+                let value = match header.encoding {
+                    Encoding::Ascii => self.read_resumable_line(
+                        &mut reader,
+                        location,
+                        def,
+                        *index,
+                        is_last_line,
+                        skipped,
+                        &mut line,
+                    )?,
+                    Encoding::BinaryBigEndian => self.read_resumable_binary::<_, BigEndian>(
+                        &mut reader,
+                        def,
+                        *index,
+                        skipped,
+                    )?,
+                    Encoding::BinaryLittleEndian => self.read_resumable_binary::<_, LittleEndian>(
+                        &mut reader,
+                        def,
+                        *index,
+                        skipped,
+                    )?,
+                };
+                if let Some(value) = value {
+                    payload.get_mut(&def.name).unwrap().push(value);
+                }
+                *index += 1;
+                *offset = start + count.get();
+            }
+            *element += 1;
+            *index = 0;
+        }
+        Ok(std::mem::take(payload))
+    }
+
+    /// Reads the ascii element with the zero-based `index`, `None` if it is skipped.
+    #[allow(clippy::too_many_arguments)]
+    fn read_resumable_line<T: BufRead>(
+        &self,
+        reader: &mut T,
+        location: &mut LocationTracker,
+        element_def: &ElementDef,
+        index: u64,
+        is_last_line: bool,
+        skipped: bool,
+        line: &mut Vec<u8>,
+    ) -> Result<Option<E>> {
+        line.clear();
+        reader
+            .read_until(b'\n', line)
+            .map_err(|e| element_error(element_def, index, e))?;
+        if line.is_empty() && !element_def.properties.is_empty() {
+            return Err(unexpected_eof(element_def, index, None));
+        }
+        if !line.ends_with(b"\n") && !is_last_line {
+            // Only the last line may lack a line break, anything else was cut off.
+            return Err(unexpected_eof(element_def, index, None));
+        }
+        let mut element = None;
+        if !skipped {
+            let mut value = E::new_from_def(element_def);
+            self.read_ascii_line(line, location, element_def, index, None, &mut value)?;
+            element = Some(value);
+        }
+        location.next_line();
+        Ok(element)
+    }
+
+    /// Reads the binary element with the zero-based `index`, `None` if it is skipped.
+    fn read_resumable_binary<T: BufRead, B: ByteOrder>(
+        &self,
+        reader: &mut T,
+        element_def: &ElementDef,
+        index: u64,
+        skipped: bool,
+    ) -> Result<Option<E>> {
+        if skipped {
+            skip_binary_element::<T, B>(reader, element_def, index)?;
+            return Ok(None);
+        }
+        self.read_binary_element::<T, B>(reader, element_def, None)
+            .map(Some)
+            .map_err(|e| element_error(element_def, index, e))
+    }
+}
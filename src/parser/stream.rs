@@ -0,0 +1,192 @@
+//! Streams the elements of one element type from asynchronous sources.
+
+use super::{check_element_def, continuation_len, decode_entry, element_error, Entry, Parser};
+use crate::ply::{ElementDef, Encoding, Header, PropertyAccess};
+use crate::util::LocationTracker;
+use futures_core::Stream;
+use futures_io::AsyncBufRead;
+use std::io;
+use std::io::{ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Stream of the elements of one element type, created by `Parser::stream_payload_for_element()`.
+///
+/// Yields the elements one at a time as they are read, none of them is kept.
+/// Consumes exactly the bytes of the `count` elements, afterwards the reader is left
+/// at the start of the next element type. Ends after the first error.
+pub struct ElementStream<'a, R, E: PropertyAccess> {
+    parser: &'a Parser<E>,
+    reader: &'a mut R,
+    element_def: &'a ElementDef,
+    encoding: Encoding,
+    index: u64,
+    location: LocationTracker,
+    entries: AsyncEntries,
+}
+
+/// Drives `decode_entry()` and friends with the buffer of an asynchronous reader,
+/// taking only the bytes of one entry at a time. Used by `ElementStream` and the `*_async` methods.
+#[derive(Default)]
+pub(crate) struct AsyncEntries {
+    /// Start of an entry that didn't fit into the buffer of the reader.
+    partial: Vec<u8>,
+    /// Length the entry in `partial` has at least.
+    needed: usize,
+}
+
+/// Entry points for streaming from readers implementing `futures::io::AsyncBufRead`.
+///
+/// Requires the `futures-io` feature.
+impl<E: PropertyAccess> Parser<E> {
+    /// Streams the elements of `element_def` from `reader`, encoded as declared by `header`.
+    ///
+    /// Make sure to stream the elements in the order as they are defined in the header.
+    /// `element_def` has to be one of `header.elements`, with the same count and properties,
+    /// otherwise an error of kind `InvalidInput` is returned before anything is read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use futures::executor::block_on;
+    /// # use futures::io::Cursor;
+    /// # use futures::StreamExt;
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// let bytes = std::fs::read("example_plys/house_2_ok_little_endian.ply").unwrap();
+    /// let mut reader = Cursor::new(bytes);
+    /// let p = Parser::<DefaultElement>::new();
+    /// let header = block_on(p.read_header_async(&mut reader)).unwrap();
+    /// let vertex = &header.elements[0];
+    /// let mut vertices = p.stream_payload_for_element(&mut reader, vertex, &header).unwrap();
+    /// let mut count = 0;
+    /// while let Some(vertex) = block_on(vertices.next()) {
+    ///     vertex.unwrap();
+    ///     count += 1;
+    /// }
+    /// assert_eq!(count, 5);
+    /// ```
+    pub fn stream_payload_for_element<'a, R: AsyncBufRead + Unpin>(
+        &'a self,
+        reader: &'a mut R,
+        element_def: &'a ElementDef,
+        header: &Header,
+    ) -> Result<ElementStream<'a, R, E>> {
+        check_element_def(element_def, header)?;
+        Ok(ElementStream {
+            parser: self,
+            reader,
+            element_def,
+            encoding: header.encoding,
+            index: 0,
+            location: LocationTracker::payload(),
+            entries: AsyncEntries::default(),
+        })
+    }
+}
+
+impl AsyncEntries {
+    /// Waits for the next bytes of `reader`, retrying interrupted reads.
+    fn poll_fill_buf<'b, R: AsyncBufRead + Unpin>(
+        reader: &'b mut R,
+        cx: &mut Context,
+    ) -> Poll<Result<&'b [u8]>> {
+        loop {
+            match Pin::new(&mut *reader).poll_fill_buf(cx) {
+                Poll::Ready(Ok(_)) => break,
+                Poll::Ready(Err(e)) if e.kind() == ErrorKind::Interrupted => (),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        // The buffer is filled now, polling again only hands it out.
+        Pin::new(reader).poll_fill_buf(cx)
+    }
+
+    /// Decodes the next entry with `decode`, called with the bytes at hand and whether `reader` ended.
+    ///
+    /// `fail` names the entry in errors of `reader`, `consumed` sees every byte taken from it.
+    pub(crate) fn poll_entry<R: AsyncBufRead + Unpin, T>(
+        &mut self,
+        reader: &mut R,
+        cx: &mut Context,
+        encoding: Encoding,
+        mut decode: impl FnMut(&[u8], bool) -> Result<Entry<T>>,
+        fail: impl Fn(io::Error) -> io::Error,
+        mut consumed: impl FnMut(&[u8]),
+    ) -> Poll<Result<T>> {
+        loop {
+            let buf = match Self::poll_fill_buf(reader, cx) {
+                Poll::Ready(Ok(buf)) => buf,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(fail(e))),
+                Poll::Pending => return Poll::Pending,
+            };
+            let eof = buf.is_empty();
+            if self.partial.is_empty() {
+                match decode(buf, eof)? {
+                    Entry::Complete(value, len) => {
+                        consumed(&buf[..len]);
+                        Pin::new(&mut *reader).consume(len);
+                        return Poll::Ready(Ok(value));
+                    }
+                    Entry::Incomplete(needed) => self.needed = needed,
+                }
+            }
+            // Take only bytes known to belong to this entry, the rest stays in `reader`.
+            let missing = self.needed.saturating_sub(self.partial.len());
+            let (len, complete) = continuation_len(encoding, buf, missing);
+            consumed(&buf[..len]);
+            self.partial.extend_from_slice(&buf[..len]);
+            Pin::new(&mut *reader).consume(len);
+            if complete || eof {
+                match decode(&self.partial, eof)? {
+                    Entry::Complete(value, _) => {
+                        self.partial.clear();
+                        return Poll::Ready(Ok(value));
+                    }
+                    Entry::Incomplete(needed) => self.needed = needed,
+                }
+            }
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin, E: PropertyAccess> ElementStream<'_, R, E> {
+    fn poll_entry(&mut self, cx: &mut Context) -> Poll<Result<E>> {
+        let (parser, def, index) = (self.parser, self.element_def, self.index);
+        let location = &mut self.location;
+        self.entries.poll_entry(
+            self.reader,
+            cx,
+            self.encoding,
+            |bytes, eof| decode_entry(parser, self.encoding, def, index, location, bytes, eof),
+            |e| element_error(def, index, e),
+            |_| (),
+        )
+    }
+}
+
+impl<R: AsyncBufRead + Unpin, E: PropertyAccess> Stream for ElementStream<'_, R, E> {
+    type Item = Result<E>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<E>>> {
+        let this = self.get_mut();
+        if this.index >= this.element_def.count {
+            return Poll::Ready(None);
+        }
+        let result = match this.poll_entry(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        // Nothing sensible follows an error.
+        this.index = match result {
+            Ok(_) => this.index + 1,
+            Err(_) => this.element_def.count,
+        };
+        Poll::Ready(Some(result))
+    }
+    /// The count of the header is only an upper bound, the payload may end early.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.element_def.count - self.index).ok();
+        (0, remaining)
+    }
+}
@@ -0,0 +1,236 @@
+//! Copies a PLY file from a reader to a writer, one element at a time.
+//!
+//! The payload is never held in memory, which makes converting huge files cheap.
+//!
+//! # Examples
+//!
+//! Convert a file to ascii and drop every other vertex:
+//!
+//! ```rust
+//! # use ply_rs::pipeline::{self, CopyOptions};
+//! # use ply_rs::ply::{DefaultElement, Encoding};
+//! # use std::io::Cursor;
+//! let mut source = std::fs::File::open("example_plys/points_ok_little_endian.ply").unwrap();
+//! let mut target = Cursor::new(Vec::new());
+//! let mut index = 0;
+//! let options = CopyOptions::<DefaultElement>::new()
+//!     .map_header(|header| {
+//!         header.encoding = Encoding::Ascii;
+//!         Ok(())
+//!     })
+//!     .transform(|def, element| {
+//!         index += 1;
+//!         if def.name == "vertex" && index % 2 == 0 {
+//!             None
+//!         } else {
+//!             Some(element)
+//!         }
+//!     });
+//! let header = pipeline::copy(&mut source, &mut target, options).unwrap();
+//! assert_eq!(header.elements[0].count, 2);
+//! ```
+
+mod transform_spec;
+pub use self::transform_spec::*;
+
+use crate::parser::{Parser, ParserOptions};
+use crate::ply::{ElementDef, Encoding, Header, PropertyAccess};
+use crate::util::LocationTracker;
+use crate::writer::Writer;
+use std::io;
+use std::io::{BufReader, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+
+type HeaderHook<'a> = dyn FnMut(&mut Header) -> Result<()> + 'a;
+type ElementHook<'a, E> = dyn FnMut(&ElementDef, E) -> Option<E> + 'a;
+type ApplyPlan<E> = fn(&ElementPlan, &mut E);
+
+/// Configures `copy()`.
+pub struct CopyOptions<'a, E> {
+    parser: ParserOptions,
+    map_header: Option<Box<HeaderHook<'a>>>,
+    transform: Option<Box<ElementHook<'a, E>>>,
+    spec: Option<(TransformSpec, ApplyPlan<E>)>,
+}
+
+impl<E> Default for CopyOptions<'_, E> {
+    fn default() -> Self {
+        CopyOptions::new()
+    }
+}
+
+impl<'a, E> CopyOptions<'a, E> {
+    /// Copies the file unchanged.
+    pub fn new() -> Self {
+        CopyOptions {
+            parser: ParserOptions::new(),
+            map_header: None,
+            transform: None,
+            spec: None,
+        }
+    }
+    /// Options of the parser reading the source.
+    pub fn parser_options(mut self, options: ParserOptions) -> Self {
+        self.parser = options;
+        self
+    }
+    /// Adjusts the header before it is written, for example to change the encoding.
+    ///
+    /// If a `spec()` is set, `f` receives the header with the spec already applied.
+    ///
+    /// The elements of the target header must correspond to the elements of the source header,
+    /// in the same order. Each element is read as defined by the source header
+    /// and written as defined by the target header.
+    pub fn map_header<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut Header) -> Result<()> + 'a,
+    {
+        self.map_header = Some(Box::new(f));
+        self
+    }
+    /// Passes each element through `f`, returning `None` drops the element.
+    ///
+    /// `f` receives the definition of the element in the source header
+    /// and runs before the `spec()`, if any.
+    /// The counts in the target header are patched once all elements are written.
+    pub fn transform<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&ElementDef, E) -> Option<E> + 'a,
+    {
+        self.transform = Some(Box::new(f));
+        self
+    }
+}
+
+impl<E: RemoveProperty> CopyOptions<'_, E> {
+    /// Drops, renames and casts properties as described by `spec`.
+    ///
+    /// The spec is checked against the source header before anything is written.
+    pub fn spec(mut self, spec: TransformSpec) -> Self {
+        self.spec = Some((spec, |plan, element| plan.apply(element)));
+        self
+    }
+}
+
+/// Width of a count that can be patched in place, the number of digits of `u64::MAX`.
+const COUNT_WIDTH: usize = 20;
+
+/// Reads a PLY file from `source` and writes it to `target`, one element at a time.
+///
+/// `target` must be seekable, since the element counts are only known after all elements
+/// passed the transform. Counts are written padded with spaces, so they can be patched in place.
+///
+/// Returns the header as written, including the final counts.
+pub fn copy<R, W, E>(source: &mut R, target: &mut W, mut options: CopyOptions<E>) -> Result<Header>
+where
+    R: Read,
+    W: Write + Seek,
+    E: PropertyAccess,
+{
+    let parser = Parser::<E>::with_options(options.parser);
+    let writer = Writer::<E>::new();
+    let mut source = BufReader::new(source);
+    let mut location = LocationTracker::new();
+    let source_header = parser.__read_header(&mut source, &mut location)?;
+    let mut header = source_header.clone();
+    let plans = match options.spec {
+        Some((ref spec, _)) => spec.apply_to_header(&mut header)?,
+        None => Vec::new(),
+    };
+    if let Some(ref mut map_header) = options.map_header {
+        map_header(&mut header)?;
+    }
+    if header.elements.len() != source_header.elements.len() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "The target header has {} elements, but the source header has {}.",
+                header.elements.len(),
+                source_header.elements.len()
+            ),
+        ));
+    }
+    let width = match options.transform {
+        Some(_) => COUNT_WIDTH,
+        None => 0,
+    };
+
+    writer.write_line_magic_number(target)?;
+    writer.write_line_format(target, &header.encoding, &header.version)?;
+    for c in &header.comments {
+        writer.write_line_comment(target, c)?;
+    }
+    for oi in &header.obj_infos {
+        writer.write_line_obj_info(target, oi)?;
+    }
+    let mut count_positions = Vec::with_capacity(header.elements.len());
+    for e in &header.elements {
+        for c in &e.comments {
+            writer.write_line_comment(target, c)?;
+        }
+        count_positions.push(target.stream_position()?);
+        writer.write_line_element_definition_padded(target, e, width)?;
+        for p in &e.properties {
+            for c in &p.comments {
+                writer.write_line_comment(target, c)?;
+            }
+            writer.write_line_property_definition(target, p)?;
+        }
+    }
+    writer.write_line_end_header(target)?;
+
+    let mut line = Vec::new();
+    for (i, (source_def, target_def)) in source_header
+        .elements
+        .iter()
+        .zip(&mut header.elements)
+        .enumerate()
+    {
+        let plan = match options.spec {
+            Some((_, apply)) if !plans[i].is_empty() => Some((&plans[i], apply)),
+            _ => None,
+        };
+        let mut written = 0;
+        for index in 0..source_def.count {
+            let element = parser.read_element(
+                &mut source,
+                &mut location,
+                source_def,
+                source_header.encoding,
+                index,
+                &mut line,
+            )?;
+            let mut element = match options.transform {
+                Some(ref mut transform) => match transform(source_def, element) {
+                    Some(e) => e,
+                    None => continue,
+                },
+                None => element,
+            };
+            if let Some((plan, apply)) = plan {
+                apply(plan, &mut element);
+            }
+            match header.encoding {
+                Encoding::Ascii => writer.write_ascii_element(target, &element, target_def)?,
+                Encoding::BinaryBigEndian => {
+                    writer.write_big_endian_element(target, &element, target_def)?
+                }
+                Encoding::BinaryLittleEndian => {
+                    writer.write_little_endian_element(target, &element, target_def)?
+                }
+            };
+            written += 1;
+        }
+        target_def.count = written;
+    }
+
+    if options.transform.is_some() {
+        let end = target.stream_position()?;
+        for (e, &position) in header.elements.iter().zip(&count_positions) {
+            target.seek(SeekFrom::Start(position))?;
+            writer.write_line_element_definition_padded(target, e, width)?;
+        }
+        target.seek(SeekFrom::Start(end))?;
+    }
+    target.flush()?;
+    Ok(header)
+}
@@ -0,0 +1,351 @@
+use crate::ply::{
+    DefaultElement, Header, Property, PropertyAccess, PropertyType, ScalarType, SmallElement,
+};
+#[cfg(feature = "half")]
+use half::f16;
+use std::io;
+use std::io::{ErrorKind, Result};
+
+/// Elements that can give up a property, required to apply a `TransformSpec`.
+pub trait RemoveProperty: PropertyAccess {
+    /// Removes the property called `property_name` and returns it, if present.
+    fn remove_property(&mut self, property_name: &str) -> Option<Property>;
+}
+
+impl RemoveProperty for DefaultElement {
+    fn remove_property(&mut self, property_name: &str) -> Option<Property> {
+        self.remove(property_name)
+    }
+}
+
+impl RemoveProperty for SmallElement {
+    fn remove_property(&mut self, property_name: &str) -> Option<Property> {
+        self.remove(property_name)
+    }
+}
+
+/// Declarative schema changes applied by `copy()`, see `CopyOptions::spec()`.
+///
+/// Properties are addressed by element name and their name in the source header.
+/// Drops are applied first, then casts, then renames.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::pipeline::TransformSpec;
+/// # use ply_rs::ply::ScalarType;
+/// let spec = TransformSpec::new()
+///     .drop_property("vertex", "alpha")
+///     .rename_property("face", "vertex_index", "vertex_indices")
+///     .cast_property("vertex", "x", ScalarType::Double);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransformSpec {
+    /// Properties to remove, as `(element, property)`.
+    pub drop_properties: Vec<(String, String)>,
+    /// Properties to rename, as `(element, property, new name)`.
+    pub rename_properties: Vec<(String, String, String)>,
+    /// Properties to convert, as `(element, property, new type)`.
+    ///
+    /// Scalars become the new type, lists keep their index type and convert their values.
+    /// Values are converted like `as` casts in rust.
+    pub cast_properties: Vec<(String, String, ScalarType)>,
+}
+
+impl TransformSpec {
+    /// A spec that changes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Removes `property` from `element`.
+    pub fn drop_property(mut self, element: &str, property: &str) -> Self {
+        self.drop_properties
+            .push((element.to_string(), property.to_string()));
+        self
+    }
+    /// Renames `property` of `element` to `name`.
+    pub fn rename_property(mut self, element: &str, property: &str, name: &str) -> Self {
+        self.rename_properties
+            .push((element.to_string(), property.to_string(), name.to_string()));
+        self
+    }
+    /// Converts the values of `property` of `element` to `data_type`.
+    pub fn cast_property(mut self, element: &str, property: &str, data_type: ScalarType) -> Self {
+        self.cast_properties
+            .push((element.to_string(), property.to_string(), data_type));
+        self
+    }
+
+    /// Rewrites `header` according to the spec and returns the steps to apply per element.
+    ///
+    /// Fails without touching `header` if the spec refers to unknown elements or properties,
+    /// touches a property twice, or renames a property to a name that is already taken.
+    pub(crate) fn apply_to_header(&self, header: &mut Header) -> Result<Vec<ElementPlan>> {
+        let mut elements = header.elements.clone();
+        let mut plans = vec![ElementPlan::default(); elements.len()];
+        let mut touched: Vec<(&str, &str)> = Vec::new();
+        let steps = self
+            .drop_properties
+            .iter()
+            .map(|(e, p)| (e, p, Step::Drop))
+            .chain(
+                self.cast_properties
+                    .iter()
+                    .map(|(e, p, t)| (e, p, Step::Cast(*t))),
+            )
+            .chain(
+                self.rename_properties
+                    .iter()
+                    .map(|(e, p, n)| (e, p, Step::Rename(n))),
+            );
+        for (element, property, step) in steps {
+            if touched.contains(&(element.as_str(), property.as_str())) {
+                return Err(spec_error(format!(
+                    "Property '{}' of element '{}' appears more than once in the spec.",
+                    property, element
+                )));
+            }
+            touched.push((element, property));
+            let index = match elements.iter().position(|e| &e.name == element) {
+                Some(i) => i,
+                None => return Err(spec_error(format!("Unknown element '{}'.", element))),
+            };
+            let def = &mut elements[index];
+            let position = match def.property_index(property) {
+                Some(i) => i,
+                None => {
+                    return Err(spec_error(format!(
+                        "Element '{}' has no property '{}'.",
+                        element, property
+                    )))
+                }
+            };
+            let plan = &mut plans[index];
+            match step {
+                Step::Drop => {
+                    def.remove_property(property);
+                    plan.drop.push(property.clone());
+                }
+                Step::Cast(to) => {
+                    let data_type = &mut def.properties[position].data_type;
+                    *data_type = match *data_type {
+                        PropertyType::Scalar(_) => PropertyType::Scalar(to),
+                        PropertyType::List(index, _) => PropertyType::List(index, to),
+                        PropertyType::Custom { .. } => {
+                            return Err(spec_error(format!(
+                            "Property '{}' of element '{}' has a custom type and can't be cast.",
+                            property, element
+                        )))
+                        }
+                    };
+                    plan.cast.push((property.clone(), to));
+                }
+                Step::Rename(name) => {
+                    if def.property_index(name).is_some() {
+                        return Err(spec_error(format!(
+                            "Can't rename property '{}' of element '{}' to '{}', the name is already taken.",
+                            property, element, name
+                        )));
+                    }
+                    def.rename_property(property, name);
+                    plan.rename.push((property.clone(), name.clone()));
+                }
+            }
+        }
+        header.elements = elements;
+        Ok(plans)
+    }
+}
+
+enum Step<'a> {
+    Drop,
+    Cast(ScalarType),
+    Rename(&'a String),
+}
+
+fn spec_error(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, message)
+}
+
+/// Steps of a `TransformSpec` that apply to one element.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ElementPlan {
+    drop: Vec<String>,
+    cast: Vec<(String, ScalarType)>,
+    rename: Vec<(String, String)>,
+}
+
+impl ElementPlan {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.drop.is_empty() && self.cast.is_empty() && self.rename.is_empty()
+    }
+    pub(crate) fn apply<E: RemoveProperty>(&self, element: &mut E) {
+        for name in &self.drop {
+            element.remove_property(name);
+        }
+        for (name, to) in &self.cast {
+            if let Some(property) = element.remove_property(name) {
+                element.set_property(name, cast_property(property, *to));
+            }
+        }
+        for (name, new_name) in &self.rename {
+            if let Some(property) = element.remove_property(name) {
+                element.set_property(new_name, property);
+            }
+        }
+    }
+}
+
+macro_rules! cast_scalar(
+    ($x:expr, $to:expr) => (match $to {
+        ScalarType::Char => Property::Char($x as i8),
+        ScalarType::UChar => Property::UChar($x as u8),
+        ScalarType::Short => Property::Short($x as i16),
+        ScalarType::UShort => Property::UShort($x as u16),
+        ScalarType::Int => Property::Int($x as i32),
+        ScalarType::UInt => Property::UInt($x as u32),
+        ScalarType::Float => Property::Float($x as f32),
+        ScalarType::Double => Property::Double($x as f64),
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => Property::LongLong($x as i64),
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => Property::ULongLong($x as u64),
+        #[cfg(feature = "half")]
+        ScalarType::Half => Property::Half(f16::from_f64($x as f64)),
+    })
+);
+macro_rules! cast_list(
+    ($x:expr, $to:expr) => (match $to {
+        ScalarType::Char => Property::ListChar($x.iter().map(|&v| v as i8).collect()),
+        ScalarType::UChar => Property::ListUChar($x.iter().map(|&v| v as u8).collect()),
+        ScalarType::Short => Property::ListShort($x.iter().map(|&v| v as i16).collect()),
+        ScalarType::UShort => Property::ListUShort($x.iter().map(|&v| v as u16).collect()),
+        ScalarType::Int => Property::ListInt($x.iter().map(|&v| v as i32).collect()),
+        ScalarType::UInt => Property::ListUInt($x.iter().map(|&v| v as u32).collect()),
+        ScalarType::Float => Property::ListFloat($x.iter().map(|&v| v as f32).collect()),
+        ScalarType::Double => Property::ListDouble($x.iter().map(|&v| v as f64).collect()),
+        #[cfg(feature = "extended-types")]
+        ScalarType::LongLong => Property::ListLongLong($x.iter().map(|&v| v as i64).collect()),
+        #[cfg(feature = "extended-types")]
+        ScalarType::ULongLong => Property::ListULongLong($x.iter().map(|&v| v as u64).collect()),
+        #[cfg(feature = "half")]
+        ScalarType::Half => Property::ListHalf($x.iter().map(|&v| f16::from_f64(v as f64)).collect()),
+    })
+);
+
+/// Converts the values of `property` to `to`, custom properties are returned unchanged.
+fn cast_property(property: Property, to: ScalarType) -> Property {
+    match property {
+        Property::Char(x) => cast_scalar!(x, to),
+        Property::UChar(x) => cast_scalar!(x, to),
+        Property::Short(x) => cast_scalar!(x, to),
+        Property::UShort(x) => cast_scalar!(x, to),
+        Property::Int(x) => cast_scalar!(x, to),
+        Property::UInt(x) => cast_scalar!(x, to),
+        Property::Float(x) => cast_scalar!(x, to),
+        Property::Double(x) => cast_scalar!(x, to),
+        #[cfg(feature = "extended-types")]
+        Property::LongLong(x) => cast_scalar!(x, to),
+        #[cfg(feature = "extended-types")]
+        Property::ULongLong(x) => cast_scalar!(x, to),
+        #[cfg(feature = "half")]
+        Property::Half(x) => cast_scalar!(x.to_f64(), to),
+        Property::ListChar(x) => cast_list!(x, to),
+        Property::ListUChar(x) => cast_list!(x, to),
+        Property::ListShort(x) => cast_list!(x, to),
+        Property::ListUShort(x) => cast_list!(x, to),
+        Property::ListInt(x) => cast_list!(x, to),
+        Property::ListUInt(x) => cast_list!(x, to),
+        Property::ListFloat(x) => cast_list!(x, to),
+        Property::ListDouble(x) => cast_list!(x, to),
+        #[cfg(feature = "extended-types")]
+        Property::ListLongLong(x) => cast_list!(x, to),
+        #[cfg(feature = "extended-types")]
+        Property::ListULongLong(x) => cast_list!(x, to),
+        #[cfg(feature = "half")]
+        Property::ListHalf(x) => {
+            let x: Vec<f64> = x.iter().map(|v| v.to_f64()).collect();
+            cast_list!(x, to)
+        }
+        #[cfg(feature = "bytes")]
+        Property::ListBytes(x) => cast_list!(x, to),
+        Property::Custom(x) => Property::Custom(x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ply::{ElementDef, PropertyDef};
+
+    fn header() -> Header {
+        let mut header = Header::new();
+        let mut vertex = ElementDef::new("vertex");
+        for name in ["x", "y", "alpha"] {
+            vertex.properties.push(PropertyDef::new(
+                name,
+                PropertyType::Scalar(ScalarType::Float),
+            ));
+        }
+        header.elements.push(vertex);
+        header
+    }
+
+    #[test]
+    fn spec_header_ok() {
+        let mut header = header();
+        let spec = TransformSpec::new()
+            .drop_property("vertex", "alpha")
+            .rename_property("vertex", "y", "alpha")
+            .cast_property("vertex", "x", ScalarType::Double);
+        let plans = spec.apply_to_header(&mut header).unwrap();
+        let properties = &header.elements[0].properties;
+        assert_eq!(
+            properties,
+            &vec![
+                PropertyDef::new("x", PropertyType::Scalar(ScalarType::Double)),
+                PropertyDef::new("alpha", PropertyType::Scalar(ScalarType::Float)),
+            ]
+        );
+
+        let mut element = DefaultElement::new();
+        element.insert("x".to_string(), Property::Float(1.5));
+        element.insert("y".to_string(), Property::Float(2.0));
+        element.insert("alpha".to_string(), Property::Float(3.0));
+        plans[0].apply(&mut element);
+        assert_eq!(element.len(), 2);
+        assert_eq!(element["x"], Property::Double(1.5));
+        assert_eq!(element["alpha"], Property::Float(2.0));
+    }
+    #[test]
+    fn spec_header_err() {
+        let specs = [
+            TransformSpec::new().rename_property("vertex", "y", "x"),
+            TransformSpec::new().drop_property("face", "x"),
+            TransformSpec::new().cast_property("vertex", "z", ScalarType::Int),
+            TransformSpec::new()
+                .drop_property("vertex", "x")
+                .rename_property("vertex", "x", "z"),
+        ];
+        for spec in &specs {
+            let mut header = header();
+            let err = spec.apply_to_header(&mut header).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+            assert_eq!(header, self::header());
+        }
+    }
+    #[test]
+    fn cast_property_ok() {
+        assert_eq!(
+            cast_property(Property::Double(-2.7), ScalarType::Int),
+            Property::Int(-2)
+        );
+        assert_eq!(
+            cast_property(
+                Property::ListUChar([1, 2].into_iter().collect()),
+                ScalarType::Float
+            ),
+            Property::ListFloat([1.0, 2.0].into_iter().collect())
+        );
+    }
+}
@@ -1,7 +1,8 @@
 //! Allows a `Ply` object to be checked for consistency.
 
 use super::Ply;
-use super::PropertyAccess;
+use super::{Property, PropertyAccess, PropertyType, ScalarType};
+use crate::util::FloatValue;
 use std::error;
 use std::fmt;
 use std::fmt::{Display, Formatter};
@@ -36,6 +37,318 @@ impl error::Error for ConsistencyError {
     }
 }
 
+/// Additional checks and fixes of `Ply::make_consistent_with()`.
+///
+/// The default options only perform the checks of `Ply::make_consistent()`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::ply::{ConsistencyOptions, DefaultElement, Ply};
+/// let mut ply = Ply::<DefaultElement>::new();
+/// let options = ConsistencyOptions::new().replace_nan(0.0).deny_non_finite(true);
+/// let report = ply.make_consistent_with(&options).unwrap();
+/// assert_eq!(report.replaced_nan, 0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConsistencyOptions {
+    deny_non_finite: bool,
+    nan_replacement: Option<f64>,
+    check_property_types: bool,
+    split_multiline_comments: bool,
+    sanitize_names: bool,
+}
+
+impl ConsistencyOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        ConsistencyOptions::default()
+    }
+    /// Whether NaN or infinite float values in the payload are an error, `false` by default.
+    ///
+    /// Scalar and list properties of the float types are checked.
+    /// The error names the first such value by element, index and property.
+    pub fn deny_non_finite(mut self, deny: bool) -> Self {
+        self.deny_non_finite = deny;
+        self
+    }
+    /// Replaces NaN float values in the payload by `value`, before they are checked.
+    ///
+    /// The number of replaced values is reported in `ConsistencyReport::replaced_nan`.
+    /// Infinite values are kept.
+    pub fn replace_nan(mut self, value: f64) -> Self {
+        self.nan_replacement = Some(value);
+        self
+    }
+    /// Whether every element has to store each property with the type declared in the header,
+    /// `false` by default.
+    ///
+    /// A column mixing for example `Property::Float` and `Property::Double` would be written
+    /// with the declared type for some elements only. The payload is checked property by property,
+    /// the error names the first element whose value is missing or of another type.
+    /// Properties of custom types aren't checked.
+    pub fn check_property_types(mut self, check: bool) -> Self {
+        self.check_property_types = check;
+        self
+    }
+    /// Whether comments and obj_info lines with line breaks are split into several lines,
+    /// `false` by default, they are rejected then.
+    ///
+    /// Lines are split at `\n`, `\r\n` and `\r`, empty lines are dropped.
+    /// Comments of elements and properties are split as well.
+    /// The number of split lines is reported in `ConsistencyReport::split_comments`.
+    pub fn split_multiline_comments(mut self, split: bool) -> Self {
+        self.split_multiline_comments = split;
+        self
+    }
+    /// Whether element and property names other than identifiers are rewritten, `false` by default.
+    ///
+    /// The parser accepts names like `scale.x`, but some readers only accept identifiers.
+    /// Characters other than ascii letters, digits, `_` and `-` are replaced by `_`,
+    /// names not starting with a letter or `_` get a leading `_`.
+    /// A name taken by another element, or another property of the same element,
+    /// gets a suffix `_2`, `_3` and so on. Valid names are never changed.
+    ///
+    /// Payload keys and properties are renamed along, the latter with `PropertyAccess::rename_property()`.
+    /// Fails if the element type can't rename properties, the names may be partly sanitized then.
+    /// The changes are reported in `ConsistencyReport::renamed_elements` and `renamed_properties`.
+    pub fn sanitize_names(mut self, sanitize: bool) -> Self {
+        self.sanitize_names = sanitize;
+        self
+    }
+}
+
+/// Describes the fixes applied by `Ply::make_consistent_with()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// Number of NaN values replaced, see `ConsistencyOptions::replace_nan()`.
+    pub replaced_nan: usize,
+    /// Number of comments and obj_info lines split, see `ConsistencyOptions::split_multiline_comments()`.
+    pub split_comments: usize,
+    /// Elements renamed as `(original, sanitized)`, see `ConsistencyOptions::sanitize_names()`.
+    pub renamed_elements: Vec<(String, String)>,
+    /// Properties renamed as `(element, original, sanitized)`, `element` is the sanitized name.
+    pub renamed_properties: Vec<(String, String, String)>,
+}
+
+/// Position of a property value in the payload, used in error messages.
+struct ValueLocation<'a> {
+    element: &'a str,
+    index: usize,
+    property: &'a str,
+}
+
+impl ValueLocation<'_> {
+    fn non_finite<T: Display>(&self, value: T) -> ConsistencyError {
+        ConsistencyError::new(&format!(
+            "Property `{}` of element `{}` #{} is not finite: {}.",
+            self.property, self.element, self.index, value
+        ))
+    }
+}
+
+/// Applies `options` to a value, returns the replacement if the value has to be changed.
+fn check_float<T: FloatValue>(
+    value: T,
+    options: &ConsistencyOptions,
+    location: &ValueLocation,
+    replaced: &mut usize,
+) -> Result<Option<T>, ConsistencyError> {
+    let replacement = match options.nan_replacement {
+        Some(r) if value.is_nan() => Some(T::from_f64(r)),
+        _ => None,
+    };
+    let checked = replacement.unwrap_or(value);
+    if options.deny_non_finite && !checked.is_finite() {
+        return Err(location.non_finite(checked));
+    }
+    *replaced += replacement.is_some() as usize;
+    Ok(replacement)
+}
+
+/// Applies `options` to the values of a list, returns the fixed list if a value had to be changed.
+fn check_float_list<T: FloatValue>(
+    list: &[T],
+    options: &ConsistencyOptions,
+    location: &ValueLocation,
+    replaced: &mut usize,
+) -> Result<Option<Vec<T>>, ConsistencyError> {
+    let mut fixed: Option<Vec<T>> = None;
+    for (i, &v) in list.iter().enumerate() {
+        if let Some(r) = check_float(v, options, location, replaced)? {
+            fixed.get_or_insert_with(|| list.to_vec())[i] = r;
+        }
+    }
+    Ok(fixed)
+}
+
+/// Checks and fixes the float properties of `element` according to `options`.
+fn check_floats<E: PropertyAccess>(
+    element: &mut E,
+    def: &super::ElementDef,
+    index: usize,
+    options: &ConsistencyOptions,
+    replaced: &mut usize,
+) -> Result<(), ConsistencyError> {
+    for p in &def.properties {
+        let k = &p.name;
+        let location = ValueLocation {
+            element: &def.name,
+            index,
+            property: k,
+        };
+        let property = match p.data_type {
+            PropertyType::Scalar(ScalarType::Float) => match element.get_float(k) {
+                Some(v) => check_float(v, options, &location, replaced)?.map(Property::Float),
+                None => None,
+            },
+            PropertyType::Scalar(ScalarType::Double) => match element.get_double(k) {
+                Some(v) => check_float(v, options, &location, replaced)?.map(Property::Double),
+                None => None,
+            },
+            #[cfg(feature = "half")]
+            PropertyType::Scalar(ScalarType::Half) => match element.get_half(k) {
+                Some(v) => check_float(v, options, &location, replaced)?.map(Property::Half),
+                None => None,
+            },
+            PropertyType::List(_, ScalarType::Float) => match element.get_list_float(k) {
+                Some(l) => check_float_list(l, options, &location, replaced)?
+                    .map(|l| Property::ListFloat(l.into_iter().collect())),
+                None => None,
+            },
+            PropertyType::List(_, ScalarType::Double) => match element.get_list_double(k) {
+                Some(l) => check_float_list(l, options, &location, replaced)?
+                    .map(|l| Property::ListDouble(l.into_iter().collect())),
+                None => None,
+            },
+            #[cfg(feature = "half")]
+            PropertyType::List(_, ScalarType::Half) => match element.get_list_half(k) {
+                Some(l) => check_float_list(l, options, &location, replaced)?
+                    .map(|l| Property::ListHalf(l.into_iter().collect())),
+                None => None,
+            },
+            _ => None,
+        };
+        if let Some(property) = property {
+            element.set_property(k, property);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `element` stores the property `key` as a value of `data_type`, custom types always pass.
+fn has_declared_type<E: PropertyAccess>(element: &E, key: &str, data_type: &PropertyType) -> bool {
+    match *data_type {
+        PropertyType::Scalar(ref scalar_type) => match *scalar_type {
+            ScalarType::Char => element.get_char(key).is_some(),
+            ScalarType::UChar => element.get_uchar(key).is_some(),
+            ScalarType::Short => element.get_short(key).is_some(),
+            ScalarType::UShort => element.get_ushort(key).is_some(),
+            ScalarType::Int => element.get_int(key).is_some(),
+            ScalarType::UInt => element.get_uint(key).is_some(),
+            ScalarType::Float => element.get_float(key).is_some(),
+            ScalarType::Double => element.get_double(key).is_some(),
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => element.get_longlong(key).is_some(),
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => element.get_ulonglong(key).is_some(),
+            #[cfg(feature = "half")]
+            ScalarType::Half => element.get_half(key).is_some(),
+        },
+        PropertyType::List(_, ref scalar_type) => match *scalar_type {
+            ScalarType::Char => element.get_list_char(key).is_some(),
+            ScalarType::UChar => element.get_list_uchar(key).is_some(),
+            ScalarType::Short => element.get_list_short(key).is_some(),
+            ScalarType::UShort => element.get_list_ushort(key).is_some(),
+            ScalarType::Int => element.get_list_int(key).is_some(),
+            ScalarType::UInt => element.get_list_uint(key).is_some(),
+            ScalarType::Float => element.get_list_float(key).is_some(),
+            ScalarType::Double => element.get_list_double(key).is_some(),
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => element.get_list_longlong(key).is_some(),
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => element.get_list_ulonglong(key).is_some(),
+            #[cfg(feature = "half")]
+            ScalarType::Half => element.get_list_half(key).is_some(),
+        },
+        PropertyType::Custom { .. } => true,
+    }
+}
+
+/// Whether `name` is an identifier, see `NameCharacters::Identifier`.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Replaces the characters of `name` not allowed in identifiers.
+fn sanitized_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+    if !sanitized.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+/// Returns a sanitized, unique name for every invalid name of `names`, `None` for valid ones.
+fn unique_names(names: &[&str]) -> Vec<Option<String>> {
+    let mut taken: Vec<String> = names
+        .iter()
+        .filter(|n| is_valid_name(n))
+        .map(|n| n.to_string())
+        .collect();
+    names
+        .iter()
+        .map(|&n| {
+            if is_valid_name(n) {
+                return None;
+            }
+            let base = sanitized_name(n);
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while taken.contains(&name) {
+                name = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            taken.push(name.clone());
+            Some(name)
+        })
+        .collect()
+}
+
+/// Splits the entries of `lines` holding line breaks into one entry per non-empty line.
+///
+/// Returns the number of entries split.
+fn split_lines(lines: &mut Vec<String>) -> usize {
+    if !lines.iter().any(|l| has_line_break(l)) {
+        return 0;
+    }
+    let mut split = 0;
+    let mut result = Vec::with_capacity(lines.len());
+    for l in lines.drain(..) {
+        if !has_line_break(&l) {
+            result.push(l);
+            continue;
+        }
+        split += 1;
+        result.extend(
+            // Splitting on both characters drops the `\r` of `\r\n` as an empty part.
+            l.split(['\n', '\r'])
+                .filter(|part| !part.is_empty())
+                .map(str::to_string),
+        );
+    }
+    *lines = result;
+    split
+}
+
 fn has_white_space(s: &str) -> bool {
     s.contains(' ') || s.contains('\t')
 }
@@ -62,6 +375,174 @@ impl<E: PropertyAccess> Ply<E> {
     /// Every relevant string is checked to not contain line breaks.
     /// Identifiers are also checked to not contain white spaces.
     pub fn make_consistent(&mut self) -> Result<(), ConsistencyError> {
+        self.make_consistent_with(&ConsistencyOptions::new())
+            .map(|_| ())
+    }
+
+    /// Same as `make_consistent()`, additionally checks and fixes the payload as configured by `options`.
+    ///
+    /// Returns which fixes were applied.
+    pub fn make_consistent_with(
+        &mut self,
+        options: &ConsistencyOptions,
+    ) -> Result<ConsistencyReport, ConsistencyError> {
+        let mut report = ConsistencyReport::default();
+        if options.split_multiline_comments {
+            let header = &mut self.header;
+            report.split_comments += split_lines(&mut header.comments);
+            report.split_comments += split_lines(&mut header.obj_infos);
+            for e in &mut header.elements {
+                report.split_comments += split_lines(&mut e.comments);
+                for p in &mut e.properties {
+                    report.split_comments += split_lines(&mut p.comments);
+                }
+            }
+        }
+        if options.sanitize_names {
+            self.sanitize_names(&mut report)?;
+        }
+        self.make_header_consistent()?;
+        if options.check_property_types {
+            self.check_property_types()?;
+        }
+        if options.deny_non_finite || options.nan_replacement.is_some() {
+            for def in &self.header.elements {
+                let elements = match self.payload.get_mut(&def.name) {
+                    Some(elements) => elements,
+                    None => continue,
+                };
+                for (index, element) in elements.iter_mut().enumerate() {
+                    check_floats(element, def, index, options, &mut report.replaced_nan)?;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Appends `items` to the payload of the element `name` and updates its count right away.
+    ///
+    /// The header stays consistent, so the document can be written with `write_ply_unchecked()`.
+    /// Fails if `name` isn't declared in the header, nothing is appended then.
+    ///
+    /// Returns the number of entries of the element.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::ply::{DefaultElement, ElementDef, Ply};
+    /// let mut ply = Ply::<DefaultElement>::new();
+    /// ply.header.elements.push(ElementDef::new("point"));
+    /// assert_eq!(ply.append_elements("point", vec![DefaultElement::new(); 2]).unwrap(), 2);
+    /// assert_eq!(ply.push_element("point", DefaultElement::new()).unwrap(), 3);
+    /// assert_eq!(ply.header.elements[0].count, 3);
+    /// assert!(ply.push_element("face", DefaultElement::new()).is_err());
+    /// ```
+    pub fn append_elements<I: IntoIterator<Item = E>>(
+        &mut self,
+        name: &str,
+        items: I,
+    ) -> Result<usize, ConsistencyError> {
+        let element_def = match self.header.elements.iter_mut().find(|e| e.name == name) {
+            Some(element_def) => element_def,
+            None => {
+                return Err(ConsistencyError::new(&format!(
+                    "No decleration for element `{}` found.",
+                    name
+                )))
+            }
+        };
+        if !self.payload.contains_key(name) {
+            self.payload.insert(name.to_string(), Vec::new());
+        }
+        let elements = self.payload.get_mut(name).unwrap();
+        elements.extend(items);
+        element_def.count = elements.len() as u64;
+        Ok(elements.len())
+    }
+
+    /// Appends a single element, see `append_elements()`.
+    pub fn push_element(&mut self, name: &str, element: E) -> Result<usize, ConsistencyError> {
+        self.append_elements(name, Some(element))
+    }
+
+    /// Rewrites names other than identifiers, see `ConsistencyOptions::sanitize_names()`.
+    fn sanitize_names(&mut self, report: &mut ConsistencyReport) -> Result<(), ConsistencyError> {
+        let names: Vec<&str> = self
+            .header
+            .elements
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        let element_names = unique_names(&names);
+        for (e, name) in self.header.elements.iter_mut().zip(element_names) {
+            let name = match name {
+                Some(name) => name,
+                None => continue,
+            };
+            let original = std::mem::replace(&mut e.name, name);
+            report.renamed_elements.push((original, e.name.clone()));
+        }
+        if !report.renamed_elements.is_empty() {
+            // Rebuilt to keep the order of the payload.
+            let renamed = &report.renamed_elements;
+            self.payload = std::mem::take(&mut self.payload)
+                .into_iter()
+                .map(|(key, elements)| {
+                    match renamed.iter().find(|(original, _)| *original == key) {
+                        Some((_, name)) => (name.clone(), elements),
+                        None => (key, elements),
+                    }
+                })
+                .collect();
+        }
+        for e in &mut self.header.elements {
+            let names: Vec<&str> = e.properties.iter().map(|p| p.name.as_str()).collect();
+            let property_names = unique_names(&names);
+            for (p, name) in e.properties.iter_mut().zip(property_names) {
+                let name = match name {
+                    Some(name) => name,
+                    None => continue,
+                };
+                for element in self.payload.get_mut(&e.name).into_iter().flatten() {
+                    if !element.rename_property(&p.name, &name) {
+                        return Err(ConsistencyError::new(&format!(
+                            "Property `{}` of element `{}` can't be renamed to `{}`, the element type doesn't support renaming.",
+                            p.name, e.name, name
+                        )));
+                    }
+                }
+                let original = std::mem::replace(&mut p.name, name);
+                report
+                    .renamed_properties
+                    .push((e.name.clone(), original, p.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that all values of each property have the type declared in the header.
+    fn check_property_types(&self) -> Result<(), ConsistencyError> {
+        for def in &self.header.elements {
+            let elements = match self.payload.get(&def.name) {
+                Some(elements) => elements,
+                None => continue,
+            };
+            for p in &def.properties {
+                if let Some(index) = elements
+                    .iter()
+                    .position(|e| !has_declared_type(e, &p.name, &p.data_type))
+                {
+                    return Err(ConsistencyError::new(&format!(
+                        "Property `{}` of element `{}` #{} is missing or doesn't have the declared type {:?}.",
+                        p.name, def.name, index, p.data_type
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn make_header_consistent(&mut self) -> Result<(), ConsistencyError> {
         for e in &self.header.elements {
             if !self.payload.contains_key(&e.name) {
                 self.payload.insert(e.name.clone(), Vec::new());
@@ -78,7 +559,7 @@ impl<E: PropertyAccess> Ply<E> {
                     pk
                 )));
             }
-            ed.unwrap().count = pe.len();
+            ed.unwrap().count = pe.len() as u64;
         }
         for ref oi in &self.header.obj_infos {
             if has_line_break(oi) {
@@ -97,6 +578,14 @@ impl<E: PropertyAccess> Ply<E> {
             }
         }
         for e in &self.header.elements {
+            for c in &e.comments {
+                if has_line_break(c) {
+                    return Err(ConsistencyError::new(&format!(
+                        "Comment `{}` of element `{}` should not contain any line breaks.",
+                        c, e.name
+                    )));
+                }
+            }
             if has_line_break(&e.name) {
                 return Err(ConsistencyError::new(&format!(
                     "Name of element `{}` should not contain any line breaks.",
@@ -110,6 +599,14 @@ impl<E: PropertyAccess> Ply<E> {
                 )));
             }
             for def in &e.properties {
+                for c in &def.comments {
+                    if has_line_break(c) {
+                        return Err(ConsistencyError::new(&format!(
+                            "Comment `{}` of property `{}` of element `{}` should not contain any line breaks.",
+                            c, def.name, e.name
+                        )));
+                    }
+                }
                 if has_line_break(&def.name) {
                     return Err(ConsistencyError::new(&format!(
                         "Name of property `{}` of element `{}` should not contain any line breaks.",
@@ -167,6 +664,17 @@ mod tests {
         assert!(r.is_err());
     }
     #[test]
+    fn consistent_new_line_fail_property_comment() {
+        let mut p = Ply::<DefaultElement>::new();
+        let mut e = ElementDef::new("ok");
+        let mut def = PropertyDef::new("x", PropertyType::Scalar(ScalarType::Char));
+        def.comments.push("unit:\nmm".to_string());
+        e.properties.push(def);
+        p.header.elements.push(e);
+        let r = p.make_consistent();
+        assert!(r.is_err());
+    }
+    #[test]
     fn consistent_white_space_fail_element() {
         let mut p = Ply::<DefaultElement>::new();
         p.header.elements.push(ElementDef::new("white space"));
@@ -185,4 +693,107 @@ mod tests {
         let r = p.make_consistent();
         assert!(r.is_err());
     }
+    /// Three vertices, the second has a NaN `x`, the third a NaN and an infinity in its list.
+    fn non_finite_ply() -> Ply<DefaultElement> {
+        let mut p = Ply::<DefaultElement>::new();
+        let mut e = ElementDef::new("vertex");
+        e.properties.push(PropertyDef::new(
+            "x",
+            PropertyType::Scalar(ScalarType::Float),
+        ));
+        e.properties.push(PropertyDef::new(
+            "weights",
+            PropertyType::List(ScalarType::UChar, ScalarType::Double),
+        ));
+        p.header.elements.push(e);
+        let vertices = [
+            (1.0, vec![0.5]),
+            (f32::NAN, vec![0.5]),
+            (2.0, vec![f64::NAN, f64::INFINITY]),
+        ]
+        .into_iter()
+        .map(|(x, weights)| {
+            let mut v = DefaultElement::new();
+            v.insert("x".to_string(), Property::Float(x));
+            v.insert(
+                "weights".to_string(),
+                Property::ListDouble(weights.into_iter().collect()),
+            );
+            v
+        })
+        .collect();
+        p.payload.insert("vertex".to_string(), vertices);
+        p
+    }
+    #[test]
+    fn consistent_non_finite_ok() {
+        let mut p = non_finite_ply();
+        assert!(p.make_consistent().is_ok());
+        assert!(p.payload["vertex"][1].get_float("x").unwrap().is_nan());
+    }
+    #[test]
+    fn consistent_non_finite_fail_scalar() {
+        let mut p = non_finite_ply();
+        let options = ConsistencyOptions::new().deny_non_finite(true);
+        let r = p.make_consistent_with(&options).unwrap_err();
+        assert_eq!(
+            r.to_string(),
+            "ConsistencyError: Property `x` of element `vertex` #1 is not finite: NaN."
+        );
+    }
+    #[test]
+    fn consistent_non_finite_fail_list() {
+        let mut p = non_finite_ply();
+        let options = ConsistencyOptions::new()
+            .deny_non_finite(true)
+            .replace_nan(0.0);
+        let r = p.make_consistent_with(&options).unwrap_err();
+        assert_eq!(
+            r.to_string(),
+            "ConsistencyError: Property `weights` of element `vertex` #2 is not finite: inf."
+        );
+    }
+    #[test]
+    fn consistent_replace_nan() {
+        let mut p = non_finite_ply();
+        let options = ConsistencyOptions::new().replace_nan(-1.0);
+        let report = p.make_consistent_with(&options).unwrap();
+        assert_eq!(report.replaced_nan, 2);
+        let vertices = &p.payload["vertex"];
+        assert_eq!(vertices[1]["x"], Property::Float(-1.0));
+        assert_eq!(
+            vertices[2]["weights"],
+            Property::ListDouble([-1.0, f64::INFINITY].into_iter().collect())
+        );
+    }
+    #[test]
+    fn consistent_property_types() {
+        let mut p = non_finite_ply();
+        let options = ConsistencyOptions::new().check_property_types(true);
+        assert!(p.make_consistent_with(&options).is_ok());
+        let vertices = p.payload.get_mut("vertex").unwrap();
+        vertices[2].insert("x".to_string(), Property::Double(2.0));
+        assert!(p.make_consistent().is_ok());
+        let r = p.make_consistent_with(&options).unwrap_err();
+        assert_eq!(
+            r.to_string(),
+            "ConsistencyError: Property `x` of element `vertex` #2 is missing or doesn't have the declared type Scalar(Float)."
+        );
+    }
+    #[test]
+    fn consistent_property_types_list() {
+        let mut p = non_finite_ply();
+        let vertices = p.payload.get_mut("vertex").unwrap();
+        vertices[1].insert(
+            "weights".to_string(),
+            Property::ListFloat([0.5].into_iter().collect()),
+        );
+        vertices[2].remove("weights");
+        let options = ConsistencyOptions::new().check_property_types(true);
+        let r = p.make_consistent_with(&options).unwrap_err();
+        assert_eq!(
+            r.to_string(),
+            "ConsistencyError: Property `weights` of element `vertex` #1 is missing or doesn't have the declared type List(UChar, Double)."
+        );
+    }
 }
@@ -0,0 +1,327 @@
+//! Converts stored elements into another element type.
+
+use super::{Ply, Property, PropertyAccess};
+use std::error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::panic;
+
+/// Describes which property an element type rejected during `Ply::get_as()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    /// Index of the element in the payload.
+    pub index: usize,
+    /// Name of the rejected property.
+    pub key: String,
+    /// Reason given by `try_set_property()` or message of the panic raised by `set_property()`.
+    pub description: String,
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "Element #{}: property '{}' was rejected: {}",
+            self.index, self.key, self.description
+        )
+    }
+}
+
+impl error::Error for ConvertError {}
+
+impl<E> Ply<E>
+where
+    E: PropertyAccess,
+    for<'a> &'a E: IntoIterator<Item = (&'a String, &'a Property)>,
+{
+    /// Converts the stored elements called `name` to `T`, returns `None` if there are no such elements.
+    ///
+    /// Each property of an element is passed to `T::try_set_property()` in the stored order,
+    /// a rejected property is reported as `ConvertError`.
+    /// Element types that panic on keys they don't expect are supported as well, such a panic is
+    /// caught and reported the same way. The panic hook still runs, so the panic message is printed as usual.
+    /// If the crate is compiled with `panic = "abort"`, a rejected key aborts the process.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::{DefaultElement, Property, PropertyAccess};
+    /// struct Vertex {
+    ///     x: f32,
+    /// }
+    /// impl PropertyAccess for Vertex {
+    ///     fn new() -> Self {
+    ///         Vertex { x: 0.0 }
+    ///     }
+    ///     fn set_property(&mut self, key: &str, property: Property) {
+    ///         if let ("x", Property::Float(v)) = (key, property) {
+    ///             self.x = v;
+    ///         }
+    ///     }
+    /// }
+    /// let mut f = std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap();
+    /// let ply = Parser::<DefaultElement>::new().read_ply(&mut f).unwrap();
+    /// let vertices = ply.get_as::<Vertex>("vertex").unwrap().unwrap();
+    /// assert_eq!(vertices[4].x, 1.0);
+    /// ```
+    pub fn get_as<T: PropertyAccess>(&self, name: &str) -> Option<Result<Vec<T>, ConvertError>> {
+        let elements = self.payload.get(name)?;
+        let mut converted = Vec::with_capacity(elements.len());
+        for (index, element) in elements.iter().enumerate() {
+            let mut t = T::new();
+            for (key, property) in element {
+                let set = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                    t.try_set_property(key, property.clone())
+                }));
+                let description = match set {
+                    Ok(Ok(())) => continue,
+                    Ok(Err(e)) => e.reason().to_string(),
+                    Err(payload) => match payload.downcast_ref::<&str>() {
+                        Some(s) => s.to_string(),
+                        None => match payload.downcast_ref::<String>() {
+                            Some(s) => s.clone(),
+                            None => "set_property() panicked".to_string(),
+                        },
+                    },
+                };
+                return Some(Err(ConvertError {
+                    index,
+                    key: key.clone(),
+                    description,
+                }));
+            }
+            converted.push(t);
+        }
+        Some(Ok(converted))
+    }
+}
+
+/// Describes which value of a list didn't fit the type requested from
+/// `PropertyAccess::try_get_list_u32()` or `try_get_list_i64()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListConversionError {
+    /// Name of the list property.
+    pub key: String,
+    /// Index of the value in the list.
+    pub index: usize,
+    /// The value that didn't fit, formatted.
+    pub value: String,
+}
+
+impl Display for ListConversionError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "Value #{} of list '{}' is out of range: {}",
+            self.index, self.key, self.value
+        )
+    }
+}
+
+impl error::Error for ListConversionError {}
+
+/// Type a list of any stored type can be converted to.
+pub(crate) trait ListTarget: Sized {
+    fn from_signed(v: i64) -> Option<Self>;
+    fn from_unsigned(v: u64) -> Option<Self>;
+    fn from_float(v: f64) -> Option<Self>;
+}
+
+/// Whether `v` is a whole number within `min..=max`.
+fn whole_in_range(v: f64, min: f64, max: f64) -> bool {
+    v.fract() == 0.0 && v >= min && v <= max
+}
+
+impl ListTarget for u32 {
+    fn from_signed(v: i64) -> Option<Self> {
+        u32::try_from(v).ok()
+    }
+    fn from_unsigned(v: u64) -> Option<Self> {
+        u32::try_from(v).ok()
+    }
+    fn from_float(v: f64) -> Option<Self> {
+        whole_in_range(v, 0.0, u32::MAX as f64).then_some(v as u32)
+    }
+}
+
+impl ListTarget for i64 {
+    fn from_signed(v: i64) -> Option<Self> {
+        Some(v)
+    }
+    fn from_unsigned(v: u64) -> Option<Self> {
+        i64::try_from(v).ok()
+    }
+    fn from_float(v: f64) -> Option<Self> {
+        // 2^63 isn't an i64 anymore, while -2^63 is.
+        (whole_in_range(v, i64::MIN as f64, i64::MAX as f64) && v != i64::MAX as f64)
+            .then_some(v as i64)
+    }
+}
+
+impl ListTarget for f32 {
+    fn from_signed(v: i64) -> Option<Self> {
+        Some(v as f32)
+    }
+    fn from_unsigned(v: u64) -> Option<Self> {
+        Some(v as f32)
+    }
+    fn from_float(v: f64) -> Option<Self> {
+        Some(v as f32)
+    }
+}
+
+impl ListTarget for f64 {
+    fn from_signed(v: i64) -> Option<Self> {
+        Some(v as f64)
+    }
+    fn from_unsigned(v: u64) -> Option<Self> {
+        Some(v as f64)
+    }
+    fn from_float(v: f64) -> Option<Self> {
+        Some(v)
+    }
+}
+
+/// Converts the values of `list` with `f`, reports the first one it rejects.
+fn convert_values<S: Copy + Display, T>(
+    key: &str,
+    list: &[S],
+    f: impl Fn(S) -> Option<T>,
+) -> Result<Vec<T>, ListConversionError> {
+    list.iter()
+        .enumerate()
+        .map(|(index, &v)| {
+            f(v).ok_or_else(|| ListConversionError {
+                key: key.to_string(),
+                index,
+                value: v.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Converts the list `key` of `element` to `T`, whichever list getter returns it.
+pub(crate) fn convert_list<E: PropertyAccess + ?Sized, T: ListTarget>(
+    element: &E,
+    key: &str,
+) -> Option<Result<Vec<T>, ListConversionError>> {
+    macro_rules! try_list {
+        ($getter:ident, $from:ident, $wide:ty) => {
+            if let Some(list) = element.$getter(key) {
+                return Some(convert_values(key, list, |v| T::$from(<$wide>::from(v))));
+            }
+        };
+    }
+    try_list!(get_list_char, from_signed, i64);
+    try_list!(get_list_uchar, from_unsigned, u64);
+    try_list!(get_list_short, from_signed, i64);
+    try_list!(get_list_ushort, from_unsigned, u64);
+    try_list!(get_list_int, from_signed, i64);
+    try_list!(get_list_uint, from_unsigned, u64);
+    try_list!(get_list_float, from_float, f64);
+    try_list!(get_list_double, from_float, f64);
+    #[cfg(feature = "extended-types")]
+    try_list!(get_list_longlong, from_signed, i64);
+    #[cfg(feature = "extended-types")]
+    try_list!(get_list_ulonglong, from_unsigned, u64);
+    #[cfg(feature = "half")]
+    try_list!(get_list_half, from_float, f64);
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ply::DefaultElement;
+
+    fn element(lists: Vec<Property>) -> DefaultElement {
+        lists
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| (format!("l{}", i), p))
+            .collect()
+    }
+    #[test]
+    fn list_getters_convert_every_variant() {
+        #[allow(unused_mut)]
+        let mut lists = vec![
+            Property::ListChar([1, 2].into_iter().collect()),
+            Property::ListUChar([1, 2].into_iter().collect()),
+            Property::ListShort([1, 2].into_iter().collect()),
+            Property::ListUShort([1, 2].into_iter().collect()),
+            Property::ListInt([1, 2].into_iter().collect()),
+            Property::ListUInt([1, 2].into_iter().collect()),
+            Property::ListFloat([1.0, 2.0].into_iter().collect()),
+            Property::ListDouble([1.0, 2.0].into_iter().collect()),
+        ];
+        #[cfg(feature = "extended-types")]
+        lists.extend([
+            Property::ListLongLong([1, 2].into_iter().collect()),
+            Property::ListULongLong([1, 2].into_iter().collect()),
+        ]);
+        #[cfg(feature = "half")]
+        lists.push(Property::ListHalf(
+            [half::f16::from_f32(1.0), half::f16::from_f32(2.0)]
+                .into_iter()
+                .collect(),
+        ));
+        #[cfg(feature = "bytes")]
+        lists.push(Property::ListBytes(Box::new(bytes::Bytes::from_static(&[
+            1, 2,
+        ]))));
+        let e = element(lists);
+        for key in e.keys() {
+            assert_eq!(e.get_list_u32(key), Some(vec![1, 2]), "{}", key);
+            assert_eq!(e.get_list_i64(key), Some(vec![1, 2]), "{}", key);
+            assert_eq!(e.get_list_f32(key), Some(vec![1.0, 2.0]), "{}", key);
+            assert_eq!(e.get_list_f64(key), Some(vec![1.0, 2.0]), "{}", key);
+        }
+        let e = element(vec![Property::Int(1)]);
+        assert_eq!(e.get_list_u32("l0"), None);
+        assert_eq!(e.try_get_list_i64("l0"), None);
+        assert_eq!(e.get_list_f64("missing"), None);
+    }
+    #[test]
+    fn list_getters_out_of_range() {
+        #[allow(unused_mut)]
+        let mut lists = vec![
+            Property::ListChar([0, -1].into_iter().collect()),
+            Property::ListShort([0, -1].into_iter().collect()),
+            Property::ListInt([0, -1].into_iter().collect()),
+            Property::ListFloat([0.0, 0.5].into_iter().collect()),
+            Property::ListFloat([0.0, -1.0].into_iter().collect()),
+            Property::ListDouble([0.0, 4294967296.0].into_iter().collect()),
+            Property::ListDouble([0.0, f64::NAN].into_iter().collect()),
+        ];
+        #[cfg(feature = "extended-types")]
+        lists.extend([
+            Property::ListLongLong([0, -1].into_iter().collect()),
+            Property::ListULongLong([0, u64::MAX].into_iter().collect()),
+        ]);
+        let e = element(lists);
+        for key in e.keys() {
+            let err = e.try_get_list_u32(key).unwrap().unwrap_err();
+            assert_eq!((err.key.as_str(), err.index), (key.as_str(), 1));
+            assert_eq!(e.get_list_u32(key), None);
+        }
+        let e = element(vec![
+            Property::ListUInt([7, u32::MAX].into_iter().collect()),
+            Property::ListDouble([-1.0, 9.3e18].into_iter().collect()),
+        ]);
+        assert_eq!(e.get_list_u32("l0"), Some(vec![7, u32::MAX]));
+        assert_eq!(e.get_list_i64("l0"), Some(vec![7, u32::MAX as i64]));
+        assert_eq!(
+            e.try_get_list_i64("l1").unwrap().unwrap_err().to_string(),
+            "Value #1 of list 'l1' is out of range: 9300000000000000000"
+        );
+        #[cfg(feature = "extended-types")]
+        {
+            let e = element(vec![Property::ListULongLong(
+                [1, u64::MAX].into_iter().collect(),
+            )]);
+            assert_eq!(e.try_get_list_i64("l0").unwrap().unwrap_err().index, 1);
+            assert_eq!(e.get_list_f64("l0"), Some(vec![1.0, u64::MAX as f64]));
+        }
+    }
+}
@@ -1,6 +1,10 @@
+use super::ElementDef;
 use super::KeyMap;
 use super::Property;
 use super::PropertyAccess;
+#[cfg(feature = "half")]
+use half::f16;
+use std::collections::{BTreeMap, HashMap};
 
 /// Ready to use data-structure for all kind of element definitions.
 ///
@@ -9,113 +13,207 @@ use super::PropertyAccess;
 /// they define types very dinamically.
 /// To achieve this flexibility in rust, this alias to a HashMap is provided.
 ///
+/// The map preserves insertion order.
+/// The parser sets the properties in the order they are declared in the header,
+/// hence iterating over a parsed element yields its properties in header order.
+/// The writer never looks at the map order, it always follows the header.
+///
+/// For elements with few properties, `SmallElement` is a more compact drop-in replacement.
+/// `HashMap<String, Property>` and `BTreeMap<String, Property>` implement `PropertyAccess` as well:
+/// a `HashMap` is a bit leaner but iterates in arbitrary order, a `BTreeMap` iterates sorted by name.
+/// Both lose the header order, which only matters when iterating, the writer doesn't depend on it.
 /// If you need a more compact representation or faster access,
 /// you might want to define your own structures and implement the `PropertyAccess` trait.
 pub type DefaultElement = KeyMap<Property>;
 macro_rules! get(
     ($e:expr) => (match $e {None => return None, Some(x) => x})
 );
-impl PropertyAccess for DefaultElement {
-    fn new() -> Self {
-        DefaultElement::new()
-    }
-    fn set_property(&mut self, key: &str, property: Property) {
-        self.insert(key.to_string(), property);
-    }
-    fn get_char(&self, key: &str) -> Option<i8> {
-        match *get!(self.get(key)) {
-            Property::Char(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_uchar(&self, key: &str) -> Option<u8> {
-        match *get!(self.get(key)) {
-            Property::UChar(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_short(&self, key: &str) -> Option<i16> {
-        match *get!(self.get(key)) {
-            Property::Short(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_ushort(&self, key: &str) -> Option<u16> {
-        match *get!(self.get(key)) {
-            Property::UShort(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_int(&self, key: &str) -> Option<i32> {
-        match *get!(self.get(key)) {
-            Property::Int(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_uint(&self, key: &str) -> Option<u32> {
-        match *get!(self.get(key)) {
-            Property::UInt(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_float(&self, key: &str) -> Option<f32> {
-        match *get!(self.get(key)) {
-            Property::Float(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_double(&self, key: &str) -> Option<f64> {
-        match *get!(self.get(key)) {
-            Property::Double(x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_char(&self, key: &str) -> Option<&[i8]> {
-        match *get!(self.get(key)) {
-            Property::ListChar(ref x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_uchar(&self, key: &str) -> Option<&[u8]> {
-        match *get!(self.get(key)) {
-            Property::ListUChar(ref x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_short(&self, key: &str) -> Option<&[i16]> {
-        match *get!(self.get(key)) {
-            Property::ListShort(ref x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_ushort(&self, key: &str) -> Option<&[u16]> {
-        match *get!(self.get(key)) {
-            Property::ListUShort(ref x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_int(&self, key: &str) -> Option<&[i32]> {
-        match *get!(self.get(key)) {
-            Property::ListInt(ref x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_uint(&self, key: &str) -> Option<&[u32]> {
-        match *get!(self.get(key)) {
-            Property::ListUInt(ref x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_float(&self, key: &str) -> Option<&[f32]> {
-        match *get!(self.get(key)) {
-            Property::ListFloat(ref x) => Some(x),
-            _ => None,
-        }
-    }
-    fn get_list_double(&self, key: &str) -> Option<&[f64]> {
-        match *get!(self.get(key)) {
-            Property::ListDouble(ref x) => Some(x),
-            _ => None,
-        }
-    }
+/// Implements `PropertyAccess` for a map from property names to `Property` values.
+///
+/// The map needs `new()`, `insert(String, Property)`, `get(&str) -> Option<&Property>`,
+/// `get_mut(&str) -> Option<&mut Property>` and `remove(&str) -> Option<Property>`.
+/// `new_from_def()` reserves a slot per property with `with_capacity(usize)`,
+/// unless a constructor taking the number of properties is given.
+macro_rules! impl_property_access {
+    ($t:ty) => {
+        impl_property_access!($t, <$t>::with_capacity);
+    };
+    ($t:ty, $with_capacity:expr) => {
+        impl PropertyAccess for $t {
+            fn new() -> Self {
+                <$t>::new()
+            }
+            fn new_from_def(element_def: &ElementDef) -> Self {
+                $with_capacity(element_def.properties.len())
+            }
+            fn set_property(&mut self, key: &str, property: Property) {
+                // Overwrite in place, elements read again don't allocate their keys again.
+                match self.get_mut(key) {
+                    Some(p) => *p = property,
+                    None => {
+                        self.insert(key.to_string(), property);
+                    }
+                }
+            }
+            fn set_property_ref(&mut self, key: &str, property: &Property) {
+                match self.get_mut(key) {
+                    Some(p) => p.assign(property),
+                    None => {
+                        self.insert(key.to_string(), property.clone());
+                    }
+                }
+            }
+            /// Moves the renamed property to the end of the map.
+            fn rename_property(&mut self, old_name: &str, new_name: &str) -> bool {
+                if let Some(property) = self.remove(old_name) {
+                    self.insert(new_name.to_string(), property);
+                }
+                true
+            }
+            fn get_char(&self, key: &str) -> Option<i8> {
+                match *get!(self.get(key)) {
+                    Property::Char(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_uchar(&self, key: &str) -> Option<u8> {
+                match *get!(self.get(key)) {
+                    Property::UChar(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_short(&self, key: &str) -> Option<i16> {
+                match *get!(self.get(key)) {
+                    Property::Short(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_ushort(&self, key: &str) -> Option<u16> {
+                match *get!(self.get(key)) {
+                    Property::UShort(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_int(&self, key: &str) -> Option<i32> {
+                match *get!(self.get(key)) {
+                    Property::Int(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_uint(&self, key: &str) -> Option<u32> {
+                match *get!(self.get(key)) {
+                    Property::UInt(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_float(&self, key: &str) -> Option<f32> {
+                match *get!(self.get(key)) {
+                    Property::Float(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_double(&self, key: &str) -> Option<f64> {
+                match *get!(self.get(key)) {
+                    Property::Double(x) => Some(x),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "extended-types")]
+            fn get_longlong(&self, key: &str) -> Option<i64> {
+                match *get!(self.get(key)) {
+                    Property::LongLong(x) => Some(x),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "extended-types")]
+            fn get_ulonglong(&self, key: &str) -> Option<u64> {
+                match *get!(self.get(key)) {
+                    Property::ULongLong(x) => Some(x),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "half")]
+            fn get_half(&self, key: &str) -> Option<f16> {
+                match *get!(self.get(key)) {
+                    Property::Half(x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_char(&self, key: &str) -> Option<&[i8]> {
+                match *get!(self.get(key)) {
+                    Property::ListChar(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_uchar(&self, key: &str) -> Option<&[u8]> {
+                match *get!(self.get(key)) {
+                    Property::ListUChar(ref x) => Some(x),
+                    #[cfg(feature = "bytes")]
+                    Property::ListBytes(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_short(&self, key: &str) -> Option<&[i16]> {
+                match *get!(self.get(key)) {
+                    Property::ListShort(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_ushort(&self, key: &str) -> Option<&[u16]> {
+                match *get!(self.get(key)) {
+                    Property::ListUShort(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_int(&self, key: &str) -> Option<&[i32]> {
+                match *get!(self.get(key)) {
+                    Property::ListInt(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_uint(&self, key: &str) -> Option<&[u32]> {
+                match *get!(self.get(key)) {
+                    Property::ListUInt(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_float(&self, key: &str) -> Option<&[f32]> {
+                match *get!(self.get(key)) {
+                    Property::ListFloat(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            fn get_list_double(&self, key: &str) -> Option<&[f64]> {
+                match *get!(self.get(key)) {
+                    Property::ListDouble(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "extended-types")]
+            fn get_list_longlong(&self, key: &str) -> Option<&[i64]> {
+                match *get!(self.get(key)) {
+                    Property::ListLongLong(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "extended-types")]
+            fn get_list_ulonglong(&self, key: &str) -> Option<&[u64]> {
+                match *get!(self.get(key)) {
+                    Property::ListULongLong(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "half")]
+            fn get_list_half(&self, key: &str) -> Option<&[f16]> {
+                match *get!(self.get(key)) {
+                    Property::ListHalf(ref x) => Some(x),
+                    _ => None,
+                }
+            }
+        }
+    };
 }
+impl_property_access!(DefaultElement);
+impl_property_access!(HashMap<String, Property>);
+// B-trees can't reserve space.
+impl_property_access!(BTreeMap<String, Property>, |_| BTreeMap::new());
@@ -0,0 +1,168 @@
+//! Builds documents holding a single element.
+
+use super::{
+    ConsistencyError, DefaultElement, ElementDef, Encoding, Ply, Property, PropertyAccess,
+    PropertyDef, PropertyType, ScalarType,
+};
+
+impl<E: PropertyAccess> Ply<E> {
+    /// Creates a document holding `elements` as the element `name`, ready to be written.
+    ///
+    /// The count of the element is set to the number of `elements`.
+    /// Names are checked by `make_consistent()`, which `Writer::write_ply()` calls.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::ply::{DefaultElement, Encoding, Ply, Property, PropertyDef, PropertyType, ScalarType};
+    /// # use ply_rs::writer::Writer;
+    /// let mut point = DefaultElement::new();
+    /// point.insert("x".to_string(), Property::Float(1.5));
+    /// let x = PropertyDef::new("x", PropertyType::Scalar(ScalarType::Float));
+    /// let mut ply = Ply::from_elements("point", vec![x], vec![point], Encoding::Ascii);
+    /// let mut buf = Vec::new();
+    /// Writer::new().write_ply(&mut buf, &mut ply).unwrap();
+    /// assert!(String::from_utf8(buf).unwrap().ends_with("end_header\n1.5\n"));
+    /// ```
+    pub fn from_elements(
+        name: &str,
+        def_properties: Vec<PropertyDef>,
+        elements: Vec<E>,
+        encoding: Encoding,
+    ) -> Self {
+        let mut element_def = ElementDef::new(name);
+        element_def.count = elements.len() as u64;
+        element_def.properties = def_properties;
+        let mut ply = Ply::new();
+        ply.header.encoding = encoding;
+        ply.header.elements.push(element_def);
+        ply.payload.insert(name.to_string(), elements);
+        ply
+    }
+}
+
+impl Ply<DefaultElement> {
+    /// Same as `from_elements()`, but derives the properties from the elements.
+    ///
+    /// The properties are taken in the order of the first element, their types from the
+    /// variants of its values. Lists are indexed by the smallest unsigned type fitting the
+    /// longest list. All elements need to have the same properties with the same variants,
+    /// custom values can't be described and are an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::ply::{DefaultElement, Encoding, Ply, Property, PropertyType, ScalarType};
+    /// let mut face = DefaultElement::new();
+    /// face.insert("vertex_index".to_string(), Property::ListInt([0, 1, 2].into_iter().collect()));
+    /// let ply = Ply::from_default_elements("face", vec![face], Encoding::Ascii).unwrap();
+    /// assert_eq!(
+    ///     ply.header.elements[0].properties[0].data_type,
+    ///     PropertyType::List(ScalarType::UChar, ScalarType::Int)
+    /// );
+    /// ```
+    pub fn from_default_elements(
+        name: &str,
+        elements: Vec<DefaultElement>,
+        encoding: Encoding,
+    ) -> Result<Self, ConsistencyError> {
+        let mut properties = Vec::new();
+        if let Some(first) = elements.first() {
+            for (key, property) in first.iter() {
+                let (scalar_type, list_len) = value_type(key, property)?;
+                let mut max_len = 0;
+                for (index, element) in elements.iter().enumerate() {
+                    match element.get(key).map(|p| value_type(key, p)).transpose()? {
+                        Some((t, len))
+                            if t == scalar_type && len.is_some() == list_len.is_some() =>
+                        {
+                            max_len = max_len.max(len.unwrap_or(0));
+                        }
+                        _ => {
+                            return Err(ConsistencyError::new(&format!(
+                                "Element `{}` #{} doesn't have a property `{}` like the first one.",
+                                name, index, key
+                            )))
+                        }
+                    }
+                }
+                let data_type = match list_len {
+                    Some(_) => PropertyType::List(index_type(max_len), scalar_type),
+                    None => PropertyType::Scalar(scalar_type),
+                };
+                properties.push(PropertyDef::new(key, data_type));
+            }
+            if let Some((index, _)) = elements
+                .iter()
+                .enumerate()
+                .find(|(_, e)| e.len() != first.len())
+            {
+                return Err(ConsistencyError::new(&format!(
+                    "Element `{}` #{} has {} properties, the first one has {}.",
+                    name,
+                    index,
+                    elements[index].len(),
+                    first.len()
+                )));
+            }
+        }
+        Ok(Ply::from_elements(name, properties, elements, encoding))
+    }
+}
+
+/// Smallest unsigned type that can index a list of `len` values.
+fn index_type(len: usize) -> ScalarType {
+    if len <= u8::MAX as usize {
+        ScalarType::UChar
+    } else if len <= u16::MAX as usize {
+        ScalarType::UShort
+    } else {
+        ScalarType::UInt
+    }
+}
+
+/// Returns the scalar type of `property` and its length if it is a list.
+fn value_type(
+    key: &str,
+    property: &Property,
+) -> Result<(ScalarType, Option<usize>), ConsistencyError> {
+    let t = match *property {
+        Property::Char(_) => (ScalarType::Char, None),
+        Property::UChar(_) => (ScalarType::UChar, None),
+        Property::Short(_) => (ScalarType::Short, None),
+        Property::UShort(_) => (ScalarType::UShort, None),
+        Property::Int(_) => (ScalarType::Int, None),
+        Property::UInt(_) => (ScalarType::UInt, None),
+        Property::Float(_) => (ScalarType::Float, None),
+        Property::Double(_) => (ScalarType::Double, None),
+        #[cfg(feature = "extended-types")]
+        Property::LongLong(_) => (ScalarType::LongLong, None),
+        #[cfg(feature = "extended-types")]
+        Property::ULongLong(_) => (ScalarType::ULongLong, None),
+        #[cfg(feature = "half")]
+        Property::Half(_) => (ScalarType::Half, None),
+        Property::ListChar(ref l) => (ScalarType::Char, Some(l.len())),
+        Property::ListUChar(ref l) => (ScalarType::UChar, Some(l.len())),
+        Property::ListShort(ref l) => (ScalarType::Short, Some(l.len())),
+        Property::ListUShort(ref l) => (ScalarType::UShort, Some(l.len())),
+        Property::ListInt(ref l) => (ScalarType::Int, Some(l.len())),
+        Property::ListUInt(ref l) => (ScalarType::UInt, Some(l.len())),
+        Property::ListFloat(ref l) => (ScalarType::Float, Some(l.len())),
+        Property::ListDouble(ref l) => (ScalarType::Double, Some(l.len())),
+        #[cfg(feature = "extended-types")]
+        Property::ListLongLong(ref l) => (ScalarType::LongLong, Some(l.len())),
+        #[cfg(feature = "extended-types")]
+        Property::ListULongLong(ref l) => (ScalarType::ULongLong, Some(l.len())),
+        #[cfg(feature = "half")]
+        Property::ListHalf(ref l) => (ScalarType::Half, Some(l.len())),
+        #[cfg(feature = "bytes")]
+        Property::ListBytes(ref l) => (ScalarType::UChar, Some(l.len())),
+        Property::Custom(_) => {
+            return Err(ConsistencyError::new(&format!(
+                "Property `{}` has a custom value, its type can't be derived.",
+                key
+            )))
+        }
+    };
+    Ok(t)
+}
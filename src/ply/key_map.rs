@@ -1,4 +1,4 @@
 use linked_hash_map::LinkedHashMap;
 
-/// Alias to reduce coupling with `LinkedHashMap`
+/// Alias to reduce coupling with `LinkedHashMap`, keeps insertion order.
 pub type KeyMap<V> = LinkedHashMap<String, V>;
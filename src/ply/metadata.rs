@@ -0,0 +1,93 @@
+use super::{ElementDef, Header, PropertyAccess, ScalarType};
+
+/// Start of the comment holding the vertex bounding box: `bbox minx miny minz maxx maxy maxz`.
+const BBOX_COMMENT: &str = "bbox ";
+/// Start of the comments holding element counts: `count <element> <n>`.
+const COUNT_COMMENT: &str = "count ";
+
+/// Summary of the document written into header comments, see `Header::parsed_metadata()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// Bounding box of the vertex positions as `(min, max)`, from a `bbox` comment.
+    pub bbox: Option<([f64; 3], [f64; 3])>,
+    /// Element names and their number of entries, from `count` comments.
+    pub counts: Vec<(String, u64)>,
+}
+
+impl Header {
+    /// Recognizes the metadata comments written with `WriteOptions::embed_metadata()`.
+    ///
+    /// Comments that don't follow the conventions are ignored.
+    pub fn parsed_metadata(&self) -> Metadata {
+        let mut metadata = Metadata::default();
+        for c in &self.comments {
+            if let Some(bbox) = parse_bbox_comment(c) {
+                metadata.bbox = Some(bbox);
+            } else if let Some(count) = parse_count_comment(c) {
+                metadata.counts.push(count);
+            }
+        }
+        metadata
+    }
+}
+
+/// Whether `comment` is a metadata comment, which is replaced when writing new metadata.
+pub(crate) fn is_metadata_comment(comment: &str) -> bool {
+    parse_bbox_comment(comment).is_some() || parse_count_comment(comment).is_some()
+}
+
+fn parse_bbox_comment(comment: &str) -> Option<([f64; 3], [f64; 3])> {
+    let mut values = comment.strip_prefix(BBOX_COMMENT)?.split_whitespace();
+    let mut bbox = [0.0; 6];
+    for v in &mut bbox {
+        *v = values.next()?.parse().ok()?;
+    }
+    if values.next().is_some() {
+        return None;
+    }
+    Some(([bbox[0], bbox[1], bbox[2]], [bbox[3], bbox[4], bbox[5]]))
+}
+
+fn parse_count_comment(comment: &str) -> Option<(String, u64)> {
+    let mut tokens = comment.strip_prefix(COUNT_COMMENT)?.split_whitespace();
+    let (name, count) = (tokens.next()?, tokens.next()?.parse().ok()?);
+    match tokens.next() {
+        Some(_) => None,
+        None => Some((name.to_string(), count)),
+    }
+}
+
+/// The `bbox` comment for the positions `x`, `y`, `z` of `vertices`,
+/// `None` if the element has no such properties or no entries with all of them.
+pub(crate) fn bbox_comment<E: PropertyAccess>(def: &ElementDef, vertices: &[E]) -> Option<String> {
+    let position = def.group(&["x", "y", "z"])?;
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for p in vertices
+        .iter()
+        .filter_map(|v| v.get_group_f64::<3>(&position))
+    {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    if min[0] > max[0] {
+        return None;
+    }
+    let values: Vec<String> = min
+        .iter()
+        .chain(&max)
+        .map(|&v| match position.scalar_type {
+            // Shortest representation of the stored value.
+            ScalarType::Float => (v as f32).to_string(),
+            _ => v.to_string(),
+        })
+        .collect();
+    Some(format!("{}{}", BBOX_COMMENT, values.join(" ")))
+}
+
+/// The `count` comment for `count` entries of element `name`.
+pub(crate) fn count_comment(name: &str, count: u64) -> String {
+    format!("{}{} {}", COUNT_COMMENT, name, count)
+}
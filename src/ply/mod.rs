@@ -3,14 +3,41 @@
 mod consistency;
 pub use self::consistency::*;
 
+mod convert;
+pub use self::convert::*;
+
+#[macro_use]
 mod default_element;
 pub use self::default_element::*;
 
+mod from_elements;
+
 mod key_map;
 pub use self::key_map::*;
 
+mod metadata;
+pub use self::metadata::*;
+
 mod ply_data_structure;
 pub use self::ply_data_structure::*;
 
+mod payload_store;
+pub use self::payload_store::*;
+
+mod shared_ply;
+pub use self::shared_ply::*;
+
 mod property;
 pub use self::property::*;
+
+mod raw_element;
+pub use self::raw_element::*;
+
+mod small_element;
+pub use self::small_element::*;
+
+mod thin_list;
+pub use self::thin_list::*;
+
+mod visitor;
+pub use self::visitor::*;
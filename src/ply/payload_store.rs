@@ -0,0 +1,50 @@
+/// Storage the parser reads the elements of one element type into.
+///
+/// Implement it to decode directly into your own arena or slab instead of a `Vec`,
+/// see `Parser::read_payload_into_store()`.
+///
+/// # Examples
+///
+/// Counts the elements without keeping them:
+///
+/// ```rust
+/// # use ply_rs::parser::Parser;
+/// # use ply_rs::ply::{DefaultElement, PayloadStore};
+/// #[derive(Default)]
+/// struct Counter(usize);
+///
+/// impl<E> PayloadStore<E> for Counter {
+///     fn push(&mut self, _element: E) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// let p = Parser::<DefaultElement>::new();
+/// let mut f = std::io::BufReader::new(
+///     std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap(),
+/// );
+/// let header = p.read_header(&mut f).unwrap();
+/// let counts = p.read_payload_into_store(&mut f, &header, |_| Counter::default()).unwrap();
+/// assert_eq!(counts["vertex"].0, 8);
+/// assert_eq!(counts["face"].0, 6);
+/// ```
+pub trait PayloadStore<E> {
+    /// Called once before the elements are pushed, `count` is the number of elements expected.
+    ///
+    /// The count is taken from the header and capped, so it can't be used to request huge allocations.
+    /// Does nothing by default.
+    fn reserve(&mut self, _count: usize) {}
+    /// Adds the next element, in the order they appear in the payload.
+    fn push(&mut self, element: E);
+    /// Called once after all elements were pushed successfully. Does nothing by default.
+    fn finish(&mut self) {}
+}
+
+impl<E> PayloadStore<E> for Vec<E> {
+    fn reserve(&mut self, count: usize) {
+        Vec::reserve(self, count);
+    }
+    fn push(&mut self, element: E) {
+        Vec::push(self, element);
+    }
+}
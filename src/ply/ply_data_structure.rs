@@ -1,8 +1,11 @@
 use super::KeyMap;
 use super::PropertyAccess;
 use super::PropertyType;
+use super::ScalarType;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
 
 /// Models all necessary information to interact with a PLY file.
 ///
@@ -38,6 +41,21 @@ impl<E: PropertyAccess> Ply<E> {
             payload: Payload::new(),
         }
     }
+
+    /// Returns the values of `group` for all of its elements, one after the other, as a flat buffer.
+    ///
+    /// Useful to upload an attribute like positions to the GPU.
+    /// Returns `None` if there are no such elements or an element lacks a value, see `PropertyAccess::get_group_f32()`.
+    pub fn group_buffer_f32(&self, group: &PropertyGroup) -> Option<Vec<f32>> {
+        let elements = self.payload.get(&group.element)?;
+        let mut buffer = Vec::with_capacity(elements.len() * group.names.len());
+        for element in elements {
+            for name in &group.names {
+                buffer.push(element.get_scalar_f64(name, group.scalar_type)? as f32);
+            }
+        }
+        Some(buffer)
+    }
 }
 
 // Header Types
@@ -79,12 +97,51 @@ impl Header {
             comments: Vec::new(),
         }
     }
+    /// Returns true if payloads of both headers are read the same way, apart from the element counts.
+    ///
+    /// The encoding, the element names and their properties have to match in order.
+    /// Counts, comments, obj_info lines and the version may differ.
+    pub fn compatible_with(&self, other: &Header) -> bool {
+        self.incompatibility(other).is_none()
+    }
+    /// Describes why the header isn't compatible with `other`, see `compatible_with()`.
+    pub(crate) fn incompatibility(&self, other: &Header) -> Option<String> {
+        if self.encoding != other.encoding {
+            return Some(format!(
+                "the encoding is {}, expected {}",
+                self.encoding, other.encoding
+            ));
+        }
+        let names = |h: &Header| {
+            h.elements
+                .iter()
+                .map(|e| e.name.clone())
+                .collect::<Vec<_>>()
+        };
+        if names(self) != names(other) {
+            return Some(format!(
+                "the elements are {:?}, expected {:?}",
+                names(self),
+                names(other)
+            ));
+        }
+        self.elements
+            .iter()
+            .zip(&other.elements)
+            .find(|(a, b)| a.properties != b.properties)
+            .map(|(a, _)| format!("the properties of element '{}' differ", a.name))
+    }
 }
 
 /// Alias to give object informations an explicit type.
+///
+/// Holds the text after the `obj_info` keyword and a single space or tab, as written.
 pub type ObjInfo = String;
 
 /// Alias to give comments an explicit type.
+///
+/// Holds the text after the `comment` keyword and a single space or tab, as written.
+/// Further leading, inner and trailing spaces are kept.
 pub type Comment = String;
 
 /// Models a version number.
@@ -130,7 +187,7 @@ impl Display for Encoding {
 /// A single point is an element.
 /// We might model it as consisting of three coordinates: x, y, and z.
 /// Usually, one finds a list of elements in a ply file.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone)]
 pub struct ElementDef {
     /// Name of the element.
     ///
@@ -142,14 +199,74 @@ pub struct ElementDef {
     ///
     /// The `count` is used when reading since we need to know how many elements we should interprete as having this type.
     /// The `count` is also needed for writing, since it will be written to the header.
-    pub count: usize,
+    ///
+    /// The count is a `u64` so a header can describe more elements than fit into memory on the current platform.
+    /// Only reading the elements into a `Vec` requires them to fit into a `usize`.
+    pub count: u64,
     /// An element is modeled by multiple properties, those are named values or lists.
     ///
     /// # Examples
     ///
     /// - Point: We can define a point by its three coordinates. Hence we have three properties: x, y, and z. Reasonable types would be float or double.
     /// - Polygon: A polygon can be defined as a list of points. Since the points are stored in a list, we can define a list of indices. Good types would be some of the unsigned integer lists.
+    ///
+    /// Prefer `push_property()`, `remove_property()` and `rename_property()` over changing the list directly,
+    /// they keep the index used by `property_index()` up to date.
     pub properties: Vec<PropertyDef>,
+    /// Comments directly preceding the element line in the header.
+    ///
+    /// Only comments following an earlier element or property are attached,
+    /// comments before the first element belong to `Header::comments`.
+    pub comments: Vec<Comment>,
+    index: PropertyIndex,
+}
+
+/// Lazily built position of the first property with each name.
+///
+/// Entries are checked before they are used, so an index that is out of date
+/// after `properties` was changed directly is never wrong, only slower.
+// Boxed to keep `ElementDef`, and with it `Line`, small.
+#[allow(clippy::box_collection)]
+#[derive(Clone, Default)]
+struct PropertyIndex(OnceLock<Box<HashMap<String, usize>>>);
+
+impl PropertyIndex {
+    fn get(&self, properties: &[PropertyDef], name: &str) -> Option<usize> {
+        let map = self.0.get_or_init(|| {
+            let mut map = HashMap::with_capacity(properties.len());
+            for (i, p) in properties.iter().enumerate() {
+                map.entry(p.name.clone()).or_insert(i);
+            }
+            Box::new(map)
+        });
+        match map.get(name) {
+            Some(&i) if properties.get(i).is_some_and(|p| p.name == name) => Some(i),
+            _ => properties.iter().position(|p| p.name == name),
+        }
+    }
+    fn clear(&mut self) {
+        self.0.take();
+    }
+}
+
+/// The index is a cache, it doesn't take part in comparisons.
+impl PartialEq for PropertyIndex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for PropertyIndex {}
+
+impl fmt::Debug for ElementDef {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ElementDef")
+            .field("name", &self.name)
+            .field("count", &self.count)
+            .field("properties", &self.properties)
+            .field("comments", &self.comments)
+            .finish()
+    }
 }
 
 impl ElementDef {
@@ -165,8 +282,112 @@ impl ElementDef {
             name: name.to_string(),
             count: 0,
             properties: Vec::new(),
+            comments: Vec::new(),
+            index: PropertyIndex::default(),
+        }
+    }
+
+    /// Position of the first property called `name` in `properties`.
+    ///
+    /// Looked up in an index built on first use, so wide elements don't need a scan per lookup.
+    pub fn property_index(&self, name: &str) -> Option<usize> {
+        self.index.get(&self.properties, name)
+    }
+
+    /// Returns the first property called `name`.
+    pub fn property(&self, name: &str) -> Option<&PropertyDef> {
+        self.property_index(name).map(|i| &self.properties[i])
+    }
+
+    /// Appends `property`, keeping the index up to date.
+    pub fn push_property(&mut self, property: PropertyDef) {
+        if let Some(map) = self.index.0.get_mut() {
+            map.entry(property.name.clone())
+                .or_insert(self.properties.len());
+        }
+        self.properties.push(property);
+    }
+
+    /// Removes and returns the first property called `name`.
+    pub fn remove_property(&mut self, name: &str) -> Option<PropertyDef> {
+        let i = self.property_index(name)?;
+        self.index.clear();
+        Some(self.properties.remove(i))
+    }
+
+    /// Renames the first property called `from` to `to`, returns `false` if there is no such property.
+    ///
+    /// Doesn't check whether `to` is already taken.
+    pub fn rename_property(&mut self, from: &str, to: &str) -> bool {
+        match self.property_index(from) {
+            Some(i) => {
+                self.properties[i].name = to.to_string();
+                self.index.clear();
+                true
+            }
+            None => false,
         }
     }
+
+    /// Groups the scalar properties `names` into one attribute, like a position made of `x`, `y`, `z`.
+    ///
+    /// Returns `None` if `names` is empty, a property is missing or isn't a scalar,
+    /// or the properties don't share the same type.
+    /// Whether they directly follow each other is reported by `PropertyGroup::contiguous`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::{DefaultElement, PropertyAccess};
+    /// let mut f = std::fs::File::open("example_plys/greg_turk_example2_ok_ascii.ply").unwrap();
+    /// let ply = Parser::<DefaultElement>::new().read_ply(&mut f).unwrap();
+    /// let color = ply.header.elements[0].group(&["red", "green", "blue"]).unwrap();
+    /// assert!(color.contiguous);
+    /// let vertex = &ply.payload["vertex"][4];
+    /// assert_eq!(vertex.get_group_f32(&color), Some([0.0, 0.0, 255.0]));
+    /// ```
+    pub fn group(&self, names: &[&str]) -> Option<PropertyGroup> {
+        let mut indices = Vec::with_capacity(names.len());
+        let mut scalar_type = None;
+        for name in names {
+            let index = self.property_index(name)?;
+            let t = match self.properties[index].data_type {
+                PropertyType::Scalar(t) => t,
+                _ => return None,
+            };
+            if *scalar_type.get_or_insert(t) != t {
+                return None;
+            }
+            indices.push(index);
+        }
+        Some(PropertyGroup {
+            element: self.name.clone(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+            scalar_type: scalar_type?,
+            contiguous: indices.windows(2).all(|w| w[1] == w[0] + 1),
+            indices,
+        })
+    }
+}
+
+/// Scalar properties of an element that form one attribute, created by `ElementDef::group()`.
+///
+/// Read the values of an element with `PropertyAccess::get_group_f32()`,
+/// or of all elements with `Ply::group_buffer_f32()`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PropertyGroup {
+    /// Name of the element the properties belong to.
+    pub element: String,
+    /// Names of the properties, in the requested order.
+    pub names: Vec<String>,
+    /// Type shared by all properties.
+    pub scalar_type: ScalarType,
+    /// Position of each property in `ElementDef::properties`.
+    pub indices: Vec<usize>,
+    /// Whether the properties directly follow each other in the requested order,
+    /// hence are stored next to each other in the payload.
+    pub contiguous: bool,
 }
 
 /// Defines a property of an element.
@@ -180,6 +401,8 @@ pub struct PropertyDef {
     /// You can have simple scalars (ints, floats, etc.) or lists of scalars.
     /// In the case of lists you need to decide in which type you want to store the list length and what type to use for the list elemetns.
     pub data_type: PropertyType,
+    /// Comments directly preceding the property line in the header, for example the unit of a value.
+    pub comments: Vec<Comment>,
 }
 
 impl PropertyDef {
@@ -188,6 +411,7 @@ impl PropertyDef {
         PropertyDef {
             name: name.to_string(),
             data_type,
+            comments: Vec::new(),
         }
     }
 }
@@ -1,3 +1,18 @@
+use super::convert::convert_list;
+use super::ElementDef;
+use super::ListConversionError;
+use super::PropertyGroup;
+use super::RawElement;
+use super::ThinList;
+#[cfg(feature = "bytes")]
+use bytes::Bytes;
+#[cfg(feature = "half")]
+use half::f16;
+use std::error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+
 /// Scalar type used to encode properties in the payload.
 ///
 /// For the translation to rust types, see individual documentation.
@@ -19,13 +34,44 @@ pub enum ScalarType {
     Float,
     /// 64 bit floating point number, rust: `f64`.
     Double,
+    /// Signed 64 bit integer, rust: `i64`.
+    ///
+    /// Not part of the PLY standard, requires the `extended-types` feature.
+    #[cfg(feature = "extended-types")]
+    LongLong,
+    /// Unsigned 64 bit integer, rust: `u64`.
+    ///
+    /// Not part of the PLY standard, requires the `extended-types` feature.
+    #[cfg(feature = "extended-types")]
+    ULongLong,
+    /// 16 bit floating point number, rust: `half::f16`.
+    ///
+    /// Not part of the PLY standard, requires the `half` feature.
+    #[cfg(feature = "half")]
+    Half,
+}
+
+impl ScalarType {
+    /// Number of bytes a value of this type occupies in a binary payload.
+    pub fn size(&self) -> usize {
+        match *self {
+            ScalarType::Char | ScalarType::UChar => 1,
+            ScalarType::Short | ScalarType::UShort => 2,
+            ScalarType::Int | ScalarType::UInt | ScalarType::Float => 4,
+            ScalarType::Double => 8,
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong | ScalarType::ULongLong => 8,
+            #[cfg(feature = "half")]
+            ScalarType::Half => 2,
+        }
+    }
 }
 
 /// Data type used to encode properties in the payload.
 ///
 /// There are two possible types: scalars and lists.
 /// Lists are a sequence of scalars with a leading integer value defining how many elements the list contains.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PropertyType {
     /// Simple, "one-number" type.
     Scalar(ScalarType),
@@ -39,8 +85,20 @@ pub enum PropertyType {
     ///
     /// Second value is the type of the list elemetns.
     List(ScalarType, ScalarType),
+    /// A scalar type unknown to the PLY format, see `ParserOptions::on_unknown_type()`.
+    Custom {
+        /// Type keyword as found in the header.
+        name: String,
+        /// Number of bytes of a single value in a binary payload.
+        size: usize,
+    },
 }
 
+/// Storage of the values of a list property, see `ThinList`.
+///
+/// The same type with and without the `smallvec` feature, which only changes how values are stored.
+pub type PropertyList<T> = ThinList<T>;
+
 /// Wrapper used to implement a dynamic type system as required by the PLY file format.
 #[derive(Debug, PartialEq, Clone)]
 pub enum Property {
@@ -52,16 +110,96 @@ pub enum Property {
     UInt(u32),
     Float(f32),
     Double(f64),
-    ListChar(Vec<i8>),
-    ListUChar(Vec<u8>),
-    ListShort(Vec<i16>),
-    ListUShort(Vec<u16>),
-    ListInt(Vec<i32>),
-    ListUInt(Vec<u32>),
-    ListFloat(Vec<f32>),
-    ListDouble(Vec<f64>),
+    #[cfg(feature = "extended-types")]
+    LongLong(i64),
+    #[cfg(feature = "extended-types")]
+    ULongLong(u64),
+    #[cfg(feature = "half")]
+    Half(f16),
+    ListChar(PropertyList<i8>),
+    ListUChar(PropertyList<u8>),
+    ListShort(PropertyList<i16>),
+    ListUShort(PropertyList<u16>),
+    ListInt(PropertyList<i32>),
+    ListUInt(PropertyList<u32>),
+    ListFloat(PropertyList<f32>),
+    ListDouble(PropertyList<f64>),
+    #[cfg(feature = "extended-types")]
+    ListLongLong(PropertyList<i64>),
+    #[cfg(feature = "extended-types")]
+    ListULongLong(PropertyList<u64>),
+    #[cfg(feature = "half")]
+    ListHalf(PropertyList<f16>),
+    /// A uchar list referencing the buffer it was read from, see `Parser::read_ply_from_bytes()`.
+    ///
+    /// Requires the `bytes` feature. Written like `ListUChar`.
+    /// Boxed to keep `Property` small.
+    #[cfg(feature = "bytes")]
+    ListBytes(Box<Bytes>),
+    /// Raw bytes of a value with a `PropertyType::Custom` type.
+    Custom(PropertyList<u8>),
+}
+
+/// Replaces the contents of `target` by `source`, keeping the buffer of `target`.
+fn copy_list<T: Copy>(target: &mut PropertyList<T>, source: &[T]) {
+    target.clear();
+    target.extend_from_slice(source);
 }
 
+impl Property {
+    /// Sets `self` to a copy of `source`, reusing the buffer of `self` if both are lists of the same type.
+    pub(crate) fn assign(&mut self, source: &Property) {
+        match (&mut *self, source) {
+            (Property::ListChar(t), Property::ListChar(s)) => copy_list(t, s),
+            (Property::ListUChar(t), Property::ListUChar(s)) => copy_list(t, s),
+            (Property::ListShort(t), Property::ListShort(s)) => copy_list(t, s),
+            (Property::ListUShort(t), Property::ListUShort(s)) => copy_list(t, s),
+            (Property::ListInt(t), Property::ListInt(s)) => copy_list(t, s),
+            (Property::ListUInt(t), Property::ListUInt(s)) => copy_list(t, s),
+            (Property::ListFloat(t), Property::ListFloat(s)) => copy_list(t, s),
+            (Property::ListDouble(t), Property::ListDouble(s)) => copy_list(t, s),
+            #[cfg(feature = "extended-types")]
+            (Property::ListLongLong(t), Property::ListLongLong(s)) => copy_list(t, s),
+            #[cfg(feature = "extended-types")]
+            (Property::ListULongLong(t), Property::ListULongLong(s)) => copy_list(t, s),
+            #[cfg(feature = "half")]
+            (Property::ListHalf(t), Property::ListHalf(s)) => copy_list(t, s),
+            (Property::Custom(t), Property::Custom(s)) => {
+                t.clear();
+                t.extend_from_slice(s);
+            }
+            (_, source) => *self = source.clone(),
+        }
+    }
+}
+
+/// Describes why an element rejected a property in `PropertyAccess::try_set_property()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertySetError {
+    reason: String,
+}
+
+impl PropertySetError {
+    /// Creates an error with a description of why the property was rejected.
+    pub fn new(reason: &str) -> Self {
+        PropertySetError {
+            reason: reason.to_string(),
+        }
+    }
+    /// Why the property was rejected.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for PropertySetError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_str(&self.reason)
+    }
+}
+
+impl error::Error for PropertySetError {}
+
 /// Provides setters and getters for the Parser and the Writer.
 ///
 /// This trait allows you to create your own data structure for the case that the
@@ -73,12 +211,107 @@ pub enum Property {
 /// If you know, that you only expect unsigned shorts, don't bother about implementing signed shorts or floats, it won't be called.
 ///
 /// The getters are named in congruence with `PropertyType` and `ScalarType`.
+///
+/// The parser sets properties through `try_set_property()`. Types that only know some
+/// properties should override it and return an error for the others instead of panicking,
+/// the parser then fails with the element index and the key, or skips the property,
+/// see `ParserOptions::skip_rejected_properties()`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::ply::{Property, PropertyAccess, PropertySetError};
+/// struct Vertex {
+///     x: f32,
+/// }
+/// impl PropertyAccess for Vertex {
+///     fn new() -> Self {
+///         Vertex { x: 0.0 }
+///     }
+///     fn try_set_property(&mut self, key: &str, property: Property) -> Result<(), PropertySetError> {
+///         match (key, property) {
+///             ("x", Property::Float(v)) => self.x = v,
+///             (k, p) => return Err(PropertySetError::new(&format!("Unexpected {}: {:?}", k, p))),
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
 pub trait PropertyAccess {
     fn new() -> Self;
+    /// Creates an element to be filled with the properties of `element_def`.
+    ///
+    /// The parser calls this instead of `new()`, override it to size buffers for the known properties.
+    /// Calls `new()` by default.
+    fn new_from_def(_element_def: &ElementDef) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new()
+    }
     fn set_property(&mut self, _property_name: &str, _property: Property) {
         // By default, do nothing
         // Sombody might only want to write, no point in bothering him/her with setter implementations.
     }
+    /// Sets a property that is only borrowed, used by `Parser::read_payload_for_element_pooled()`.
+    ///
+    /// The parser reuses the buffer of `property` for the next element.
+    /// By default, `property` is cloned and passed to `set_property()`,
+    /// override this to copy only what you need without allocating.
+    fn set_property_ref(&mut self, property_name: &str, property: &Property) {
+        self.set_property(property_name, property.clone());
+    }
+    /// Sets a property or rejects it, this is what the parser calls.
+    ///
+    /// Calls `set_property()` and succeeds by default.
+    fn try_set_property(
+        &mut self,
+        property_name: &str,
+        property: Property,
+    ) -> Result<(), PropertySetError> {
+        self.set_property(property_name, property);
+        Ok(())
+    }
+    /// Borrowing version of `try_set_property()`, used by `Parser::read_payload_for_element_pooled()`.
+    ///
+    /// Calls `set_property_ref()` and succeeds by default,
+    /// override it as well if `try_set_property()` rejects properties.
+    fn try_set_property_ref(
+        &mut self,
+        property_name: &str,
+        property: &Property,
+    ) -> Result<(), PropertySetError> {
+        self.set_property_ref(property_name, property);
+        Ok(())
+    }
+    /// Renames the property `old_name` to `new_name`, used by `ConsistencyOptions::sanitize_names()`.
+    ///
+    /// Returns `false` if the element type can't rename properties, which is the default.
+    fn rename_property(&mut self, _old_name: &str, _new_name: &str) -> bool {
+        false
+    }
+    /// Called by the parser once all properties of an element have been set.
+    ///
+    /// Override this to validate the element or to derive state from several properties.
+    /// An error aborts the parse, it is reported with the index of the element.
+    /// Does nothing by default.
+    fn finalize(&mut self, _element_def: &ElementDef) -> io::Result<()> {
+        Ok(())
+    }
+    /// Returns the element if it holds undecoded values, see `RawElement`.
+    ///
+    /// The writer then writes the stored bytes instead of calling the getters.
+    /// Returns `None` by default.
+    fn raw(&self) -> Option<&RawElement> {
+        None
+    }
+    /// Returns the element if it is to be filled with undecoded values, see `RawElement`.
+    ///
+    /// The parser then stores the encoded values instead of calling the setters.
+    /// Returns `None` by default.
+    fn raw_mut(&mut self) -> Option<&mut RawElement> {
+        None
+    }
     fn get_char(&self, _property_name: &str) -> Option<i8> {
         None
     }
@@ -103,6 +336,18 @@ pub trait PropertyAccess {
     fn get_double(&self, _property_name: &str) -> Option<f64> {
         None
     }
+    #[cfg(feature = "extended-types")]
+    fn get_longlong(&self, _property_name: &str) -> Option<i64> {
+        None
+    }
+    #[cfg(feature = "extended-types")]
+    fn get_ulonglong(&self, _property_name: &str) -> Option<u64> {
+        None
+    }
+    #[cfg(feature = "half")]
+    fn get_half(&self, _property_name: &str) -> Option<f16> {
+        None
+    }
     fn get_list_char(&self, _property_name: &str) -> Option<&[i8]> {
         None
     }
@@ -127,4 +372,100 @@ pub trait PropertyAccess {
     fn get_list_double(&self, _property_name: &str) -> Option<&[f64]> {
         None
     }
+    #[cfg(feature = "extended-types")]
+    fn get_list_longlong(&self, _property_name: &str) -> Option<&[i64]> {
+        None
+    }
+    #[cfg(feature = "extended-types")]
+    fn get_list_ulonglong(&self, _property_name: &str) -> Option<&[u64]> {
+        None
+    }
+    #[cfg(feature = "half")]
+    fn get_list_half(&self, _property_name: &str) -> Option<&[f16]> {
+        None
+    }
+    /// Returns the scalar `property_name` of type `scalar_type` as `f64`, using the matching getter.
+    fn get_scalar_f64(&self, property_name: &str, scalar_type: ScalarType) -> Option<f64> {
+        let k = property_name;
+        match scalar_type {
+            ScalarType::Char => self.get_char(k).map(f64::from),
+            ScalarType::UChar => self.get_uchar(k).map(f64::from),
+            ScalarType::Short => self.get_short(k).map(f64::from),
+            ScalarType::UShort => self.get_ushort(k).map(f64::from),
+            ScalarType::Int => self.get_int(k).map(f64::from),
+            ScalarType::UInt => self.get_uint(k).map(f64::from),
+            ScalarType::Float => self.get_float(k).map(f64::from),
+            ScalarType::Double => self.get_double(k),
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => self.get_longlong(k).map(|v| v as f64),
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => self.get_ulonglong(k).map(|v| v as f64),
+            #[cfg(feature = "half")]
+            ScalarType::Half => self.get_half(k).map(f64::from),
+        }
+    }
+    /// Returns the values of `group` as `f32`, whichever scalar type they are stored as.
+    ///
+    /// Returns `None` if `N` isn't the number of properties in `group` or a value is missing.
+    /// Built on the scalar getters above, so it works for every element type implementing them.
+    fn get_group_f32<const N: usize>(&self, group: &PropertyGroup) -> Option<[f32; N]>
+    where
+        Self: Sized,
+    {
+        Some(self.get_group_f64::<N>(group)?.map(|v| v as f32))
+    }
+    /// Returns the values of `group` as `f64`, see `get_group_f32()`.
+    fn get_group_f64<const N: usize>(&self, group: &PropertyGroup) -> Option<[f64; N]>
+    where
+        Self: Sized,
+    {
+        if group.names.len() != N {
+            return None;
+        }
+        let mut values = [0.0; N];
+        for (value, name) in values.iter_mut().zip(&group.names) {
+            *value = self.get_scalar_f64(name, group.scalar_type)?;
+        }
+        Some(values)
+    }
+    /// Returns the list `property_name` as `u32` values, whichever list type it is stored as.
+    ///
+    /// Returns `None` if there is no such list or a value doesn't fit, see `try_get_list_u32()`.
+    /// Built on the list getters above, so it works for every element type implementing them.
+    fn get_list_u32(&self, property_name: &str) -> Option<Vec<u32>> {
+        self.try_get_list_u32(property_name)?.ok()
+    }
+    /// Same as `get_list_u32()`, but reports the first value that doesn't fit.
+    ///
+    /// Integers have to be in range, floats have to be whole numbers in range.
+    /// Returns `None` if there is no such list.
+    fn try_get_list_u32(
+        &self,
+        property_name: &str,
+    ) -> Option<Result<Vec<u32>, ListConversionError>> {
+        convert_list(self, property_name)
+    }
+    /// Returns the list `property_name` as `i64` values, see `get_list_u32()`.
+    fn get_list_i64(&self, property_name: &str) -> Option<Vec<i64>> {
+        self.try_get_list_i64(property_name)?.ok()
+    }
+    /// Same as `get_list_i64()`, but reports the first value that doesn't fit, see `try_get_list_u32()`.
+    fn try_get_list_i64(
+        &self,
+        property_name: &str,
+    ) -> Option<Result<Vec<i64>, ListConversionError>> {
+        convert_list(self, property_name)
+    }
+    /// Returns the list `property_name` as `f32` values, whichever list type it is stored as.
+    ///
+    /// The conversion is lossy: Doubles are rounded and integers above 2^24 lose precision.
+    fn get_list_f32(&self, property_name: &str) -> Option<Vec<f32>> {
+        convert_list(self, property_name)?.ok()
+    }
+    /// Returns the list `property_name` as `f64` values, whichever list type it is stored as.
+    ///
+    /// 64 bit integers above 2^53 lose precision, all other values are exact.
+    fn get_list_f64(&self, property_name: &str) -> Option<Vec<f64>> {
+        convert_list(self, property_name)?.ok()
+    }
 }
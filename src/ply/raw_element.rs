@@ -0,0 +1,131 @@
+use super::{ElementDef, Encoding, Property, PropertyAccess};
+use crate::parser::{BigEndian, LittleEndian, Parser};
+use std::io;
+use std::io::ErrorKind;
+
+/// Element type that keeps the payload of an element undecoded.
+///
+/// `Parser::<RawElement>` doesn't decode any values: Each element holds the bytes of a
+/// binary element or the line of an ascii element, along with the encoding they are in.
+/// The values aren't checked until the element is decoded with `decode()`.
+///
+/// `Writer::<RawElement>` writes the stored bytes as they are if the target encoding
+/// matches, swaps the byte order if only the endianness differs and decodes the element otherwise.
+/// Copying a file without changing its encoding is thus nearly free.
+///
+/// The element definition isn't stored per element, pass the one of the header
+/// the element was read with.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::Parser;
+/// # use ply_rs::ply::{DefaultElement, Encoding, Property, RawElement};
+/// let mut f = std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap();
+/// let ply = Parser::<RawElement>::new().read_ply(&mut f).unwrap();
+/// let vertex = &ply.payload["vertex"][1];
+/// assert_eq!(vertex.encoding(), Encoding::Ascii);
+/// assert_eq!(vertex.bytes(), b"0 0 1");
+/// let decoded: DefaultElement = vertex.decode(&ply.header.elements[0]).unwrap();
+/// assert_eq!(decoded["z"], Property::Float(1.0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawElement {
+    bytes: Vec<u8>,
+    encoding: Encoding,
+}
+
+impl RawElement {
+    /// Creates an empty ascii element.
+    pub fn new() -> Self {
+        RawElement {
+            bytes: Vec::new(),
+            encoding: Encoding::Ascii,
+        }
+    }
+    /// Creates an element from the encoded values of an element.
+    ///
+    /// For `Encoding::Ascii`, `bytes` is a data line without its line break.
+    pub fn from_bytes(bytes: Vec<u8>, encoding: Encoding) -> Self {
+        RawElement { bytes, encoding }
+    }
+    /// The encoded values, an ascii data line doesn't include its line break.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    /// Encoding of `bytes()`.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+    /// Decodes the element into `E`, `element_def` has to describe the element.
+    pub fn decode<E: PropertyAccess>(&self, element_def: &ElementDef) -> io::Result<E> {
+        self.decode_with(&Parser::new(), element_def)
+    }
+    /// Same as `decode()`, but uses the options of `parser`.
+    pub fn decode_with<E: PropertyAccess>(
+        &self,
+        parser: &Parser<E>,
+        element_def: &ElementDef,
+    ) -> io::Result<E> {
+        let (element, len) = match self.encoding {
+            Encoding::Ascii => {
+                let line = std::str::from_utf8(&self.bytes)
+                    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+                return parser.read_ascii_element(line, element_def);
+            }
+            Encoding::BinaryBigEndian => {
+                parser.read_binary_element_from_slice::<BigEndian>(&self.bytes, element_def)?
+            }
+            Encoding::BinaryLittleEndian => {
+                parser.read_binary_element_from_slice::<LittleEndian>(&self.bytes, element_def)?
+            }
+        };
+        if len != self.bytes.len() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Element '{}' ends after {} of {} bytes.",
+                    element_def.name,
+                    len,
+                    self.bytes.len()
+                ),
+            ));
+        }
+        Ok(element)
+    }
+    /// Clears the element and returns its buffer to be filled with values in `encoding`.
+    pub(crate) fn reset(&mut self, encoding: Encoding) -> &mut Vec<u8> {
+        self.encoding = encoding;
+        self.bytes.clear();
+        &mut self.bytes
+    }
+}
+
+impl Default for RawElement {
+    fn default() -> Self {
+        RawElement::new()
+    }
+}
+
+impl PropertyAccess for RawElement {
+    fn new() -> Self {
+        RawElement::new()
+    }
+    /// Panics, the parser fills a `RawElement` through `raw_mut()` instead.
+    fn set_property(&mut self, property_name: &str, _property: Property) {
+        panic!(
+            "RawElement holds undecoded values, property '{}' can't be set",
+            property_name
+        );
+    }
+    /// Succeeds without changes, the stored values aren't named.
+    fn rename_property(&mut self, _old_name: &str, _new_name: &str) -> bool {
+        true
+    }
+    fn raw(&self) -> Option<&RawElement> {
+        Some(self)
+    }
+    fn raw_mut(&mut self) -> Option<&mut RawElement> {
+        Some(self)
+    }
+}
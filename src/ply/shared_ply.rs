@@ -0,0 +1,60 @@
+use super::{Header, KeyMap, Ply, PropertyAccess};
+use std::sync::Arc;
+
+/// A `Ply` whose elements can be handed out cheaply, created by `Ply::into_shared()`.
+///
+/// Each element type is stored in its own `Arc<[E]>`, so tasks can hold on to
+/// exactly the elements they need without cloning them.
+/// Write it with `Writer::write_shared_ply()`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::Parser;
+/// # use ply_rs::ply::DefaultElement;
+/// # use std::sync::Arc;
+/// let mut f = std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap();
+/// let ply = Parser::<DefaultElement>::new().read_ply(&mut f).unwrap();
+/// let shared = ply.into_shared();
+/// let vertices = shared.elements("vertex").unwrap();
+/// let for_other_task = Arc::clone(&vertices);
+/// assert_eq!(for_other_task.len(), 8);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedPly<E: PropertyAccess> {
+    /// All header information found in a PLY file.
+    pub header: Header,
+    /// The elements of each element type, keyed by element name like `Payload`.
+    pub payload: KeyMap<Arc<[E]>>,
+}
+
+impl<E: PropertyAccess> SharedPly<E> {
+    /// Returns a handle to the elements called `name`, or `None` if there are no such elements.
+    ///
+    /// The elements aren't copied, the handle points to the same memory as the payload.
+    pub fn elements(&self, name: &str) -> Option<Arc<[E]>> {
+        self.payload.get(name).cloned()
+    }
+}
+
+impl<E: PropertyAccess> Ply<E> {
+    /// Moves the payload into shared storage, see `SharedPly`.
+    ///
+    /// Elements are moved to a new allocation once, they aren't cloned.
+    pub fn into_shared(self) -> SharedPly<E> {
+        SharedPly {
+            header: self.header,
+            payload: self
+                .payload
+                .into_iter()
+                .map(|(name, elements)| (name, Arc::from(elements)))
+                .collect(),
+        }
+    }
+}
+
+impl<E: PropertyAccess> From<Ply<E>> for SharedPly<E> {
+    fn from(ply: Ply<E>) -> Self {
+        ply.into_shared()
+    }
+}
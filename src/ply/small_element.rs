@@ -0,0 +1,170 @@
+use super::ElementDef;
+use super::Property;
+use super::PropertyAccess;
+#[cfg(feature = "half")]
+use half::f16;
+use std::iter::FromIterator;
+use std::ops::Index;
+use std::slice;
+
+/// Alternative to `DefaultElement` that stores its properties in a `Vec`.
+///
+/// Elements typically hold only a handful of properties.
+/// At these sizes, a linear search through a `Vec` is faster than hashing the key,
+/// and the element is considerably smaller than a hash map.
+/// Lookups take linear time, so prefer `DefaultElement` for elements with many properties.
+///
+/// Like `DefaultElement`, the properties keep their insertion order and can be indexed by name.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::parser::Parser;
+/// # use ply_rs::ply::{Property, SmallElement};
+/// let mut f = std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap();
+/// let p = Parser::<SmallElement>::new();
+/// let ply = p.read_ply(&mut f).unwrap();
+/// let vertex = &ply.payload["vertex"][1];
+/// assert_eq!(vertex["z"], Property::Float(1.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SmallElement {
+    properties: Vec<(String, Property)>,
+}
+
+impl SmallElement {
+    /// Creates an element without properties.
+    pub fn new() -> Self {
+        SmallElement {
+            properties: Vec::new(),
+        }
+    }
+    /// Creates an element with room for `capacity` properties.
+    pub fn with_capacity(capacity: usize) -> Self {
+        SmallElement {
+            properties: Vec::with_capacity(capacity),
+        }
+    }
+    /// Sets the property `key`, returns the previous value if there was one.
+    ///
+    /// A new property is appended, an existing one keeps its position.
+    pub fn insert(&mut self, key: String, property: Property) -> Option<Property> {
+        match self.get_mut(&key) {
+            Some(p) => Some(std::mem::replace(p, property)),
+            None => {
+                self.properties.push((key, property));
+                None
+            }
+        }
+    }
+    /// Returns the property `key`, if present.
+    pub fn get(&self, key: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, p)| p)
+    }
+    /// Returns the property `key` mutably, if present.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Property> {
+        self.properties
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, p)| p)
+    }
+    /// Returns true if the element has a property called `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+    /// Removes the property `key` and returns it, the order of the other properties is kept.
+    pub fn remove(&mut self, key: &str) -> Option<Property> {
+        let i = self.properties.iter().position(|(k, _)| k == key)?;
+        Some(self.properties.remove(i).1)
+    }
+    /// Number of properties.
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+    /// Returns true if the element has no properties.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+    /// Iterates over the properties in insertion order.
+    pub fn iter(&self) -> SmallElementIter<'_> {
+        SmallElementIter {
+            inner: self.properties.iter(),
+        }
+    }
+}
+
+/// Iterator over the properties of a `SmallElement`, see `SmallElement::iter()`.
+#[derive(Debug, Clone)]
+pub struct SmallElementIter<'a> {
+    inner: slice::Iter<'a, (String, Property)>,
+}
+
+impl<'a> Iterator for SmallElementIter<'a> {
+    type Item = (&'a String, &'a Property);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, p)| (k, p))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> IntoIterator for &'a SmallElement {
+    type Item = (&'a String, &'a Property);
+    type IntoIter = SmallElementIter<'a>;
+    fn into_iter(self) -> SmallElementIter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<(String, Property)> for SmallElement {
+    fn from_iter<I: IntoIterator<Item = (String, Property)>>(iter: I) -> Self {
+        let mut element = SmallElement::new();
+        for (k, p) in iter {
+            element.insert(k, p);
+        }
+        element
+    }
+}
+
+impl Index<&str> for SmallElement {
+    type Output = Property;
+    /// Panics if the element has no property called `key`.
+    fn index(&self, key: &str) -> &Property {
+        match self.get(key) {
+            Some(p) => p,
+            None => panic!("SmallElement has no property '{}'", key),
+        }
+    }
+}
+
+impl_property_access!(SmallElement);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn small_element_ok() {
+        let mut e = SmallElement::new();
+        assert_eq!(e.insert("x".to_string(), Property::Float(1.0)), None);
+        e.insert("y".to_string(), Property::Float(2.0));
+        e.insert("z".to_string(), Property::Float(3.0));
+        assert_eq!(
+            e.insert("x".to_string(), Property::Float(4.0)),
+            Some(Property::Float(1.0))
+        );
+        let keys: Vec<&String> = e.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec!["x", "y", "z"]);
+        assert_eq!(e["x"], Property::Float(4.0));
+        assert_eq!(e.get_float("y"), Some(2.0));
+        assert_eq!(e.get_int("y"), None);
+        assert_eq!(e.remove("y"), Some(Property::Float(2.0)));
+        assert_eq!(e.len(), 2);
+        assert!(!e.contains_key("y"));
+        let keys: Vec<&String> = e.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec!["x", "z"]);
+    }
+}
@@ -0,0 +1,314 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Growable list stored behind a single pointer, the storage of list properties.
+///
+/// A `Vec` takes three words inline, which made every `Property` four words large,
+/// even for a single `uchar`. `ThinList` boxes the `Vec` and allocates nothing while empty,
+/// so a `Property` is only two words large.
+///
+/// With the `smallvec` feature, up to four values are stored inline instead, hence triangles
+/// and quads don't need a heap allocation, at the cost of a larger `Property`.
+/// The API is the same either way, use `From`/`Into` to convert from and to `Vec` or `SmallVec`.
+///
+/// Dereferences to a slice and offers the `Vec` methods used to fill lists.
+#[derive(Clone, Default)]
+pub struct ThinList<T>(Storage<T>);
+
+// The extra indirection is the point, it keeps the list a single word.
+#[cfg(not(feature = "smallvec"))]
+#[allow(clippy::box_collection)]
+type Storage<T> = Option<Box<Vec<T>>>;
+#[cfg(feature = "smallvec")]
+type Storage<T> = smallvec::SmallVec<[T; 4]>;
+
+#[cfg(not(feature = "smallvec"))]
+impl<T> ThinList<T> {
+    /// Creates an empty list, doesn't allocate.
+    pub const fn new() -> Self {
+        ThinList(None)
+    }
+    /// Creates an empty list with room for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity).into()
+    }
+    fn storage_mut(&mut self) -> &mut Vec<T> {
+        self.0.get_or_insert_with(Default::default)
+    }
+    /// Number of values the list can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.as_ref().map_or(0, |v| v.capacity())
+    }
+    /// The values as a slice, same as dereferencing the list.
+    pub fn as_slice(&self) -> &[T] {
+        self.0.as_deref().map_or(&[], |v| v)
+    }
+    /// The values as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.0.as_deref_mut().map_or(&mut [], |v| v)
+    }
+    /// Converts the list into a `Vec`, reusing its buffer where possible.
+    pub fn into_vec(self) -> Vec<T> {
+        self.0.map_or_else(Vec::new, |v| *v)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T> ThinList<T> {
+    /// Creates an empty list, doesn't allocate.
+    pub const fn new() -> Self {
+        ThinList(smallvec::SmallVec::new_const())
+    }
+    /// Creates an empty list with room for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ThinList(smallvec::SmallVec::with_capacity(capacity))
+    }
+    fn storage_mut(&mut self) -> &mut smallvec::SmallVec<[T; 4]> {
+        &mut self.0
+    }
+    /// Number of values the list can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+    /// The values as a slice, same as dereferencing the list.
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+    /// The values as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.0
+    }
+    /// Converts the list into a `Vec`, reusing its buffer where possible.
+    pub fn into_vec(self) -> Vec<T> {
+        self.0.into_vec()
+    }
+}
+
+impl<T> ThinList<T> {
+    /// Appends `value`, allocates on the first value.
+    pub fn push(&mut self, value: T) {
+        self.storage_mut().push(value);
+    }
+    /// Removes the last value and returns it, `None` if the list is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.storage_mut().pop()
+    }
+    /// Reserves room for at least `additional` more values.
+    pub fn reserve(&mut self, additional: usize) {
+        if additional > 0 {
+            self.storage_mut().reserve(additional);
+        }
+    }
+    /// Removes all values, keeping the allocated buffer.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+    /// Shortens the list to `len` values, keeping the allocated buffer.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.storage_mut().truncate(len);
+        }
+    }
+}
+
+impl<T: Clone> ThinList<T> {
+    /// Appends clones of `values`.
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        if !values.is_empty() {
+            self.storage_mut().extend(values.iter().cloned());
+        }
+    }
+    /// Changes the length to `len`, new slots are filled with clones of `value`.
+    pub fn resize(&mut self, len: usize, value: T) {
+        if len != self.len() {
+            self.storage_mut().resize(len, value);
+        }
+    }
+}
+
+impl<T> Deref for ThinList<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for ThinList<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> AsRef<[T]> for ThinList<T> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ThinList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<T> From<Vec<T>> for ThinList<T> {
+    #[cfg(not(feature = "smallvec"))]
+    fn from(v: Vec<T>) -> Self {
+        if v.capacity() == 0 {
+            ThinList(None)
+        } else {
+            ThinList(Some(Box::new(v)))
+        }
+    }
+    #[cfg(feature = "smallvec")]
+    fn from(v: Vec<T>) -> Self {
+        ThinList(smallvec::SmallVec::from_vec(v))
+    }
+}
+
+impl<T> From<ThinList<T>> for Vec<T> {
+    fn from(l: ThinList<T>) -> Self {
+        l.into_vec()
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T> From<smallvec::SmallVec<[T; 4]>> for ThinList<T> {
+    fn from(v: smallvec::SmallVec<[T; 4]>) -> Self {
+        ThinList(v)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T> From<ThinList<T>> for smallvec::SmallVec<[T; 4]> {
+    fn from(l: ThinList<T>) -> Self {
+        l.0
+    }
+}
+
+impl<T: Clone> From<&[T]> for ThinList<T> {
+    fn from(s: &[T]) -> Self {
+        s.to_vec().into()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for ThinList<T> {
+    fn from(a: [T; N]) -> Self {
+        Vec::from(a).into()
+    }
+}
+
+impl<T> FromIterator<T> for ThinList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<T>>().into()
+    }
+}
+
+impl<T> Extend<T> for ThinList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().peekable();
+        if iter.peek().is_some() {
+            self.storage_mut().extend(iter);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for ThinList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T> IntoIterator for ThinList<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ThinList<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ThinList<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_mut_slice().iter_mut()
+    }
+}
+
+/// Compares the values, an emptied list equals a list that never held any.
+impl<T: PartialEq> PartialEq for ThinList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq> Eq for ThinList<T> {}
+
+impl<T: PartialEq> PartialEq<[T]> for ThinList<T> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq> PartialEq<Vec<T>> for ThinList<T> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        self.as_slice() == &other[..]
+    }
+}
+
+impl<T: PartialEq> PartialEq<ThinList<T>> for Vec<T> {
+    fn eq(&self, other: &ThinList<T>) -> bool {
+        self[..] == *other.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    #[cfg(not(feature = "smallvec"))]
+    fn thin_list_is_one_word() {
+        assert_eq!(
+            std::mem::size_of::<ThinList<f64>>(),
+            std::mem::size_of::<usize>()
+        );
+    }
+    #[test]
+    #[cfg(all(not(feature = "smallvec"), target_pointer_width = "64"))]
+    fn property_is_two_words() {
+        // Values like `f64` take 8 bytes, hence `Property` is larger on 32 bit targets.
+        assert_eq!(std::mem::size_of::<crate::ply::Property>(), 16);
+    }
+    #[test]
+    fn thin_list_ok() {
+        let mut l = ThinList::new();
+        assert!(cfg!(feature = "smallvec") || l.capacity() == 0);
+        assert_eq!(l, ThinList::from(Vec::new()));
+        l.extend_from_slice(&[1, 2]);
+        l.push(3);
+        l.extend([4, 5].iter());
+        assert_eq!(l, vec![1, 2, 3, 4, 5]);
+        assert_eq!(format!("{:?}", l), "[1, 2, 3, 4, 5]");
+        l[0] = 0;
+        assert_eq!(l.iter().sum::<i32>(), 14);
+        let capacity = l.capacity();
+        l.clear();
+        // Emptied lists keep their buffer, but equal lists that never held anything.
+        assert_eq!(l.capacity(), capacity);
+        assert_eq!(l, ThinList::new());
+        assert!(l.is_empty());
+        let l: ThinList<i32> = (0..3).collect();
+        assert_eq!(l.into_vec(), vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,49 @@
+use super::{ElementDef, Header};
+use std::io::Result;
+
+/// Receives the contents of a PLY file while it is read, see `Parser::read_ply_with_visitor()`.
+///
+/// No payload is kept, use it to compute aggregates over files of any size.
+/// Returning an error from any method stops reading, the error is passed on with its kind.
+///
+/// # Examples
+///
+/// Finds the largest `x` of all vertices:
+///
+/// ```rust
+/// # use ply_rs::parser::Parser;
+/// # use ply_rs::ply::{DefaultElement, ElementDef, PlyVisitor, Property};
+/// struct MaxX(f32);
+///
+/// impl PlyVisitor<DefaultElement> for MaxX {
+///     fn element(&mut self, def: &ElementDef, element: &DefaultElement) -> std::io::Result<()> {
+///         if let (true, Some(Property::Float(x))) = (def.name == "vertex", element.get("x")) {
+///             self.0 = self.0.max(*x);
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut f = std::fs::File::open("example_plys/greg_turk_example1_ok_ascii.ply").unwrap();
+/// let mut max_x = MaxX(f32::MIN);
+/// Parser::<DefaultElement>::new().read_ply_with_visitor(&mut f, &mut max_x).unwrap();
+/// assert_eq!(max_x.0, 1.0);
+/// ```
+pub trait PlyVisitor<E> {
+    /// Called once with the header, before any element. Does nothing by default.
+    fn header(&mut self, _header: &Header) -> Result<()> {
+        Ok(())
+    }
+    /// Called before the elements of `element_def` are read. Does nothing by default.
+    fn begin_element(&mut self, _element_def: &ElementDef) -> Result<()> {
+        Ok(())
+    }
+    /// Called with each element, in the order they appear in the payload.
+    ///
+    /// `element` is overwritten by the next element of the same type, copy what you need.
+    fn element(&mut self, element_def: &ElementDef, element: &E) -> Result<()>;
+    /// Called after all elements of `element_def` were read. Does nothing by default.
+    fn end_element(&mut self, _element_def: &ElementDef) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+//! Reads numbered sequences of PLY files, for example the frames of a capture.
+
+use crate::parser::{BoundParser, Parser};
+use crate::ply::{Ply, PropertyAccess};
+use std::fs;
+use std::io;
+use std::io::{ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::thread::JoinHandle;
+
+/// A numbered sequence of PLY files sharing one schema.
+///
+/// Iterating yields the files in numeric order as `(path, ply)`, each read in one go.
+/// Every header has to be compatible with the header of the first file,
+/// see `Header::compatible_with()`. An incompatible file, an unreadable file or a gap in
+/// the numbering is yielded as an error, iteration continues with the next file.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use ply_rs::ply::DefaultElement;
+/// # use ply_rs::sequence::PlySequence;
+/// let frames = PlySequence::<DefaultElement>::open("capture/frame_*.ply")
+///     .unwrap()
+///     .prefetch(true);
+/// for frame in frames {
+///     let (path, ply) = frame.unwrap();
+///     println!("{}: {} vertices", path.display(), ply.payload["vertex"].len());
+/// }
+/// ```
+pub struct PlySequence<E: PropertyAccess> {
+    /// Files with their numbers, in ascending order.
+    files: Vec<(u64, PathBuf)>,
+    parser: Parser<E>,
+    prefetch: bool,
+}
+
+impl<E: PropertyAccess> PlySequence<E> {
+    /// Lists the files of a sequence.
+    ///
+    /// `pattern_or_dir` is either a directory or a path whose file name contains a single `*`.
+    /// For a pattern, the `*` has to match the number of a file, for example `frame_*.ply`
+    /// matches `frame_000001.ply`. For a directory, all `.ply` files are taken
+    /// and numbered by the last run of digits in their name.
+    /// Fails if the directory can't be read or no file is found.
+    pub fn open<P: AsRef<Path>>(pattern_or_dir: P) -> Result<Self> {
+        let path = pattern_or_dir.as_ref();
+        let (dir, matcher) = if path.is_dir() {
+            (path, Matcher::Directory)
+        } else {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let (prefix, suffix) = match name.split_once('*') {
+                Some((prefix, suffix)) if !suffix.contains('*') => (prefix, suffix),
+                _ => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "'{}' is neither a directory nor a file name with a single '*'.",
+                            path.display()
+                        ),
+                    ))
+                }
+            };
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            (dir, Matcher::Pattern { prefix, suffix })
+        };
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let number = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| matcher.number(n));
+            if let Some(number) = number {
+                files.push((number, path));
+            }
+        }
+        if files.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                format!("No numbered PLY files found for '{}'.", path.display()),
+            ));
+        }
+        files.sort();
+        Ok(PlySequence {
+            files,
+            parser: Parser::new(),
+            prefetch: false,
+        })
+    }
+    /// Reads the files with `parser`, a default `Parser` is used otherwise.
+    pub fn parser(mut self, parser: Parser<E>) -> Self {
+        self.parser = parser;
+        self
+    }
+    /// Whether the next file is read on a background thread while the current one is used,
+    /// `false` by default.
+    pub fn prefetch(mut self, prefetch: bool) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+    /// The files of the sequence in numeric order.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(_, path)| path.as_path())
+    }
+}
+
+/// Decides which file names belong to a sequence and extracts their number.
+enum Matcher<'a> {
+    Directory,
+    Pattern { prefix: &'a str, suffix: &'a str },
+}
+
+impl Matcher<'_> {
+    fn number(&self, name: &str) -> Option<u64> {
+        let digits = match *self {
+            Matcher::Directory => {
+                let stem = name.strip_suffix(".ply")?;
+                let end = stem.rfind(|c: char| c.is_ascii_digit())? + 1;
+                let start = stem[..end]
+                    .rfind(|c: char| !c.is_ascii_digit())
+                    .map_or(0, |i| i + 1);
+                &stem[start..end]
+            }
+            Matcher::Pattern { prefix, suffix } => name
+                .strip_prefix(prefix)?
+                .strip_suffix(suffix)
+                .filter(|d| !d.is_empty() && d.bytes().all(|b| b.is_ascii_digit()))?,
+        };
+        digits.parse().ok()
+    }
+}
+
+impl<E: PropertyAccess + Send + 'static> IntoIterator for PlySequence<E> {
+    type Item = Result<(PathBuf, Ply<E>)>;
+    type IntoIter = PlySequenceIter<E>;
+    fn into_iter(self) -> PlySequenceIter<E> {
+        PlySequenceIter {
+            files: self.files,
+            next: 0,
+            expected_number: None,
+            parser: self.parser,
+            bound: None,
+            prefetch: self.prefetch,
+            pending: None,
+        }
+    }
+}
+
+/// Iterator over the files of a `PlySequence`.
+pub struct PlySequenceIter<E: PropertyAccess> {
+    files: Vec<(u64, PathBuf)>,
+    /// Index of the next file in `files`.
+    next: usize,
+    /// Number of the file following the last one yielded.
+    expected_number: Option<u64>,
+    parser: Parser<E>,
+    /// Bound to the header of the first file once it was read.
+    bound: Option<BoundParser<E>>,
+    prefetch: bool,
+    /// The file `next` being read in the background.
+    pending: Option<JoinHandle<Result<Ply<E>>>>,
+}
+
+impl<E: PropertyAccess + Send + 'static> Iterator for PlySequenceIter<E> {
+    type Item = Result<(PathBuf, Ply<E>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (number, path) = self.files.get(self.next)?.clone();
+        if let Some(expected) = self.expected_number.filter(|&n| n < number) {
+            self.expected_number = Some(number);
+            return Some(Err(io::Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "Files {} to {} of the sequence are missing before '{}'.",
+                    expected,
+                    number - 1,
+                    path.display()
+                ),
+            )));
+        }
+        self.expected_number = Some(number + 1);
+        self.next += 1;
+        let ply = match (self.pending.take(), &self.bound) {
+            (Some(pending), _) => pending
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e)),
+            (None, Some(bound)) => read_checked(bound, &path),
+            (None, None) => read_first(&self.parser, &path).inspect(|ply| {
+                self.bound = Some(self.parser.bind(ply.header.clone()));
+            }),
+        };
+        if let (true, Some(bound), Some((_, next))) =
+            (self.prefetch, &self.bound, self.files.get(self.next))
+        {
+            let (bound, next) = (bound.clone(), next.clone());
+            self.pending = Some(thread::spawn(move || read_checked(&bound, &next)));
+        }
+        Some(ply.map(|ply| (path, ply)))
+    }
+}
+
+fn read_first<E: PropertyAccess + Send>(parser: &Parser<E>, path: &Path) -> Result<Ply<E>> {
+    let mut f = fs::File::open(path).map_err(|e| path_error(path, e))?;
+    parser.read_ply(&mut f).map_err(|e| path_error(path, e))
+}
+
+fn read_checked<E: PropertyAccess + Send>(bound: &BoundParser<E>, path: &Path) -> Result<Ply<E>> {
+    let mut f = fs::File::open(path).map_err(|e| path_error(path, e))?;
+    bound
+        .read_ply_checked(&mut f)
+        .map_err(|e| path_error(path, e))
+}
+
+/// Adds the path of the file to an error, keeping its kind.
+fn path_error(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,258 @@
+//! Helpers to compare `Ply` objects in tests.
+//!
+//! A plain `assert_eq!` on two large `Ply` objects dumps both structures entirely.
+//! The functions and macros in this module compare the header field by field and the payload
+//! element by element and only report the first difference they find.
+//!
+//! Only available with the `test-util` feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # #[macro_use] extern crate ply_rs;
+//! # use ply_rs::ply::{Ply, DefaultElement};
+//! # fn main() {
+//! let a = Ply::<DefaultElement>::new();
+//! let b = Ply::<DefaultElement>::new();
+//! assert_ply_eq!(a, b);
+//! // Floats may differ by at most 1e-6:
+//! assert_ply_eq!(a, b, 1e-6);
+//! # }
+//! ```
+
+use crate::ply::{DefaultElement, Header, Payload, Ply, Property};
+use std::fmt::Debug;
+
+/// Asserts that two `Ply<DefaultElement>` are equal, reports only the first difference.
+///
+/// An optional third argument gives the tolerance used to compare float values.
+#[macro_export]
+macro_rules! assert_ply_eq {
+    ($a:expr, $b:expr) => {
+        $crate::test_util::assert_ply_eq_with_epsilon(&$a, &$b, 0.0)
+    };
+    ($a:expr, $b:expr, $epsilon:expr) => {
+        $crate::test_util::assert_ply_eq_with_epsilon(&$a, &$b, $epsilon)
+    };
+}
+
+/// Asserts that two `Payload<DefaultElement>` are equal, reports only the first difference.
+///
+/// An optional third argument gives the tolerance used to compare float values.
+#[macro_export]
+macro_rules! assert_payload_eq {
+    ($a:expr, $b:expr) => {
+        $crate::test_util::assert_payload_eq_with_epsilon(&$a, &$b, 0.0)
+    };
+    ($a:expr, $b:expr, $epsilon:expr) => {
+        $crate::test_util::assert_payload_eq_with_epsilon(&$a, &$b, $epsilon)
+    };
+}
+
+pub use crate::assert_payload_eq;
+pub use crate::assert_ply_eq;
+
+/// Panics with a description of the first difference between `a` and `b`.
+#[track_caller]
+pub fn assert_ply_eq_with_epsilon(a: &Ply<DefaultElement>, b: &Ply<DefaultElement>, epsilon: f64) {
+    if let Some(d) = ply_diff(a, b, epsilon) {
+        panic!("assertion failed: plys are not equal\n\t{}", d);
+    }
+}
+
+/// Panics with a description of the first difference between `a` and `b`.
+#[track_caller]
+pub fn assert_payload_eq_with_epsilon(
+    a: &Payload<DefaultElement>,
+    b: &Payload<DefaultElement>,
+    epsilon: f64,
+) {
+    if let Some(d) = payload_diff(a, b, epsilon) {
+        panic!("assertion failed: payloads are not equal\n\t{}", d);
+    }
+}
+
+/// Describes the first difference between `a` and `b`, `None` if they are equal.
+///
+/// Float values are considered equal if they differ by at most `epsilon`.
+pub fn ply_diff(a: &Ply<DefaultElement>, b: &Ply<DefaultElement>, epsilon: f64) -> Option<String> {
+    header_diff(&a.header, &b.header).or_else(|| payload_diff(&a.payload, &b.payload, epsilon))
+}
+
+/// Describes the first difference between two headers, `None` if they are equal.
+pub fn header_diff(a: &Header, b: &Header) -> Option<String> {
+    if a.encoding != b.encoding {
+        return Some(field_diff("header.encoding", &a.encoding, &b.encoding));
+    }
+    if a.version != b.version {
+        return Some(field_diff("header.version", &a.version, &b.version));
+    }
+    if a.comments != b.comments {
+        return Some(field_diff("header.comments", &a.comments, &b.comments));
+    }
+    if a.obj_infos != b.obj_infos {
+        return Some(field_diff("header.obj_infos", &a.obj_infos, &b.obj_infos));
+    }
+    if a.elements.len() != b.elements.len() {
+        return Some(field_diff(
+            "number of element definitions",
+            &a.elements.len(),
+            &b.elements.len(),
+        ));
+    }
+    for (ea, eb) in a.elements.iter().zip(b.elements.iter()) {
+        if ea.name != eb.name {
+            return Some(field_diff("element name", &ea.name, &eb.name));
+        }
+        if ea.count != eb.count {
+            return Some(field_diff(
+                &format!("count of element `{}`", ea.name),
+                &ea.count,
+                &eb.count,
+            ));
+        }
+        if ea.properties != eb.properties {
+            return Some(field_diff(
+                &format!("properties of element `{}`", ea.name),
+                &ea.properties,
+                &eb.properties,
+            ));
+        }
+    }
+    None
+}
+
+/// Describes the first difference between two payloads, `None` if they are equal.
+///
+/// Float values are considered equal if they differ by at most `epsilon`.
+pub fn payload_diff(
+    a: &Payload<DefaultElement>,
+    b: &Payload<DefaultElement>,
+    epsilon: f64,
+) -> Option<String> {
+    let keys_a: Vec<&String> = a.keys().collect();
+    let keys_b: Vec<&String> = b.keys().collect();
+    if keys_a != keys_b {
+        return Some(field_diff("payload elements", &keys_a, &keys_b));
+    }
+    for (name, list_a) in a {
+        let list_b = &b[name];
+        if list_a.len() != list_b.len() {
+            return Some(field_diff(
+                &format!("number of `{}` elements", name),
+                &list_a.len(),
+                &list_b.len(),
+            ));
+        }
+        for (i, (ea, eb)) in list_a.iter().zip(list_b.iter()).enumerate() {
+            if let Some(d) = element_diff(ea, eb, epsilon) {
+                return Some(format!("element `{}` #{}: {}", name, i, d));
+            }
+        }
+    }
+    None
+}
+
+fn element_diff(a: &DefaultElement, b: &DefaultElement, epsilon: f64) -> Option<String> {
+    let keys_a: Vec<&String> = a.keys().collect();
+    let keys_b: Vec<&String> = b.keys().collect();
+    if keys_a != keys_b {
+        return Some(field_diff("properties", &keys_a, &keys_b));
+    }
+    for (k, pa) in a {
+        let pb = &b[k];
+        if !property_eq(pa, pb, epsilon) {
+            return Some(field_diff(&format!("property `{}`", k), pa, pb));
+        }
+    }
+    None
+}
+
+fn field_diff<T: Debug + ?Sized>(what: &str, a: &T, b: &T) -> String {
+    format!("{} differs:\n\t  left: {:?}\n\t right: {:?}", what, a, b)
+}
+
+fn float_eq(a: f64, b: f64, epsilon: f64) -> bool {
+    a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= epsilon
+}
+
+fn list_eq<T: Copy + Into<f64>>(a: &[T], b: &[T], epsilon: f64) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(x, y)| float_eq((*x).into(), (*y).into(), epsilon))
+}
+
+fn property_eq(a: &Property, b: &Property, epsilon: f64) -> bool {
+    match (a, b) {
+        (Property::Float(x), Property::Float(y)) => float_eq(*x as f64, *y as f64, epsilon),
+        (Property::Double(x), Property::Double(y)) => float_eq(*x, *y, epsilon),
+        (Property::ListFloat(x), Property::ListFloat(y)) => list_eq(x, y, epsilon),
+        (Property::ListDouble(x), Property::ListDouble(y)) => list_eq(x, y, epsilon),
+        #[cfg(feature = "half")]
+        (Property::Half(x), Property::Half(y)) => float_eq(x.to_f64(), y.to_f64(), epsilon),
+        #[cfg(feature = "half")]
+        (Property::ListHalf(x), Property::ListHalf(y)) => list_eq(x, y, epsilon),
+        #[cfg(feature = "bytes")]
+        (Property::ListBytes(x), Property::ListBytes(y)) => x == y,
+        #[cfg(feature = "bytes")]
+        (Property::ListBytes(x), Property::ListUChar(y))
+        | (Property::ListUChar(y), Property::ListBytes(x)) => x[..] == y[..],
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ply::{ElementDef, Encoding};
+
+    fn create_ply(x: f32) -> Ply<DefaultElement> {
+        let mut ply = Ply::<DefaultElement>::new();
+        ply.header.elements.push(ElementDef::new("point"));
+        let mut e = DefaultElement::new();
+        e.insert("x".to_string(), Property::Float(1.0));
+        let mut f = DefaultElement::new();
+        f.insert("x".to_string(), Property::Float(x));
+        ply.payload.insert("point".to_string(), vec![e, f]);
+        ply.make_consistent().unwrap();
+        ply
+    }
+
+    #[test]
+    fn diff_equal() {
+        assert_eq!(ply_diff(&create_ply(2.0), &create_ply(2.0), 0.0), None);
+    }
+    #[test]
+    fn diff_reports_element_index_and_key() {
+        let d = ply_diff(&create_ply(2.0), &create_ply(3.0), 0.0).unwrap();
+        assert!(
+            d.starts_with("element `point` #1: property `x` differs"),
+            "{}",
+            d
+        );
+        assert!(
+            d.contains("Float(2.0)") && d.contains("Float(3.0)"),
+            "{}",
+            d
+        );
+    }
+    #[test]
+    fn diff_epsilon() {
+        assert_eq!(ply_diff(&create_ply(2.0), &create_ply(2.05), 0.1), None);
+        assert!(ply_diff(&create_ply(2.0), &create_ply(2.05), 0.01).is_some());
+    }
+    #[test]
+    fn diff_header() {
+        let a = create_ply(2.0);
+        let mut b = create_ply(2.0);
+        b.header.encoding = Encoding::BinaryLittleEndian;
+        let d = ply_diff(&a, &b, 0.0).unwrap();
+        assert!(d.starts_with("header.encoding differs"), "{}", d);
+    }
+    #[test]
+    #[should_panic(expected = "element `point` #1")]
+    fn assert_macro_panics() {
+        assert_ply_eq!(create_ply(2.0), create_ply(3.0));
+    }
+}
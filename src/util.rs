@@ -1,12 +1,181 @@
+#[cfg(feature = "half")]
+use half::f16;
+use std::fmt;
+use std::fmt::Display;
+
+/// Tracks the line being read, to locate errors.
 #[derive(Debug, Clone, Copy)]
 pub struct LocationTracker {
+    /// Line within the file, or within the payload if its header was read separately. Starts at 1.
     pub line_index: usize,
+    /// Whether the header is done and payload lines are read.
+    pub in_payload: bool,
+    /// Number of header lines, if the header was read with this tracker.
+    pub header_lines: Option<usize>,
 }
 impl LocationTracker {
+    /// Tracks a file from its first line.
     pub fn new() -> Self {
-        LocationTracker { line_index: 0 }
+        LocationTracker {
+            line_index: 0,
+            in_payload: false,
+            header_lines: None,
+        }
+    }
+    /// Tracks a payload whose header was read separately, the file line is unknown.
+    pub fn payload() -> Self {
+        LocationTracker {
+            line_index: 1,
+            in_payload: true,
+            header_lines: None,
+        }
     }
     pub fn next_line(&mut self) {
         self.line_index += 1;
     }
+    /// Moves from the last header line to the first payload line.
+    pub fn start_payload(&mut self) {
+        self.header_lines = Some(self.line_index);
+        self.in_payload = true;
+        self.next_line();
+    }
+}
+
+/// `Line 4` in the header, `File line 13, payload line 5` or `Payload line 5` in the payload.
+impl Display for LocationTracker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.in_payload, self.header_lines) {
+            (false, _) => write!(f, "Line {}", self.line_index),
+            (true, Some(header_lines)) => write!(
+                f,
+                "File line {}, payload line {}",
+                self.line_index,
+                self.line_index - header_lines
+            ),
+            (true, None) => write!(f, "Payload line {}", self.line_index),
+        }
+    }
+}
+
+/// Start of the header comment holding the payload checksum, followed by the checksum in hex.
+pub const CHECKSUM_COMMENT: &str = "ply-rs-checksum crc32 ";
+
+/// Extracts the checksum from a header comment, `None` if it isn't a checksum comment.
+pub fn parse_checksum_comment(comment: &str) -> Option<u32> {
+    let hex = comment.strip_prefix(CHECKSUM_COMMENT)?;
+    u32::from_str_radix(hex.trim_end(), 16).ok()
+}
+
+/// CRC-32 (IEEE) of a byte stream, used as payload checksum.
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32(u32);
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Crc32(!0)
+    }
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = CRC32_TABLE[((self.0 ^ b as u32) & 0xff) as usize] ^ (self.0 >> 8);
+        }
+    }
+    pub fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Float types, to treat NaN and infinite values alike.
+pub trait FloatValue: Copy + Display {
+    fn is_nan(self) -> bool;
+    fn is_finite(self) -> bool;
+    fn is_sign_negative(self) -> bool;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl FloatValue for f32 {
+    fn is_nan(self) -> bool {
+        self.is_nan()
+    }
+    fn is_finite(self) -> bool {
+        self.is_finite()
+    }
+    fn is_sign_negative(self) -> bool {
+        self.is_sign_negative()
+    }
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl FloatValue for f64 {
+    fn is_nan(self) -> bool {
+        self.is_nan()
+    }
+    fn is_finite(self) -> bool {
+        self.is_finite()
+    }
+    fn is_sign_negative(self) -> bool {
+        self.is_sign_negative()
+    }
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+#[cfg(feature = "half")]
+impl FloatValue for f16 {
+    fn is_nan(self) -> bool {
+        self.is_nan()
+    }
+    fn is_finite(self) -> bool {
+        self.is_finite()
+    }
+    fn is_sign_negative(self) -> bool {
+        self.is_sign_negative()
+    }
+    fn from_f64(value: f64) -> Self {
+        f16::from_f64(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn crc32_ok() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+        assert_eq!(Crc32::new().finish(), 0);
+    }
+    #[test]
+    fn checksum_comment_ok() {
+        let comment = format!("{}{:08x}", CHECKSUM_COMMENT, 0xcbf4_3926u32);
+        assert_eq!(parse_checksum_comment(&comment), Some(0xcbf4_3926));
+        assert_eq!(parse_checksum_comment("ply-rs-checksum xxhash64 12"), None);
+        assert_eq!(parse_checksum_comment("made by hand"), None);
+    }
 }
@@ -0,0 +1,104 @@
+//! Reads the points of a file in a single call.
+//!
+//! Use the `Parser` for anything beyond positions and colors of the `vertex` element.
+
+use crate::parser::Parser;
+use crate::ply::{DefaultElement, Ply, PropertyAccess, ScalarType};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+
+/// Positions and colors of the vertices, returned by `read_vertices_with_colors()`.
+pub type ColoredVertices = (Vec<[f32; 3]>, Vec<[u8; 3]>);
+
+const POSITION: [&str; 3] = ["x", "y", "z"];
+const COLOR: [&str; 3] = ["red", "green", "blue"];
+
+/// Reads the positions `x`, `y`, `z` of the `vertex` element of the file at `path`.
+///
+/// Works with ascii and binary files and with any scalar coordinate type, like float or double.
+/// Fails with an error of kind `InvalidInput` if there is no `vertex` element,
+/// or it doesn't have scalar properties `x`, `y` and `z` of the same type.
+///
+/// # Examples
+///
+/// ```rust
+/// let points = ply_rs::read_vertices("example_plys/house_2_ok_little_endian.ply").unwrap();
+/// assert_eq!(points[1], [-1.0, 1.0, 0.0]);
+/// ```
+pub fn read_vertices<P: AsRef<Path>>(path: P) -> io::Result<Vec<[f32; 3]>> {
+    let ply = read(path.as_ref())?;
+    attribute(&ply, POSITION, |v, _| v as f32)
+}
+
+/// Reads the positions and the colors `red`, `green`, `blue` of the `vertex` element of the file at `path`.
+///
+/// Colors stored as floats or doubles are expected to range from 0 to 1 and are scaled to 0 to 255,
+/// integers are clamped to that range.
+/// Fails like `read_vertices()`, and if the properties `red`, `green` and `blue` are missing.
+///
+/// # Examples
+///
+/// ```rust
+/// let (points, colors) =
+///     ply_rs::read_vertices_with_colors("example_plys/greg_turk_example2_ok_ascii.ply").unwrap();
+/// assert_eq!(points.len(), colors.len());
+/// assert_eq!(colors[0], [255, 0, 0]);
+/// ```
+pub fn read_vertices_with_colors<P: AsRef<Path>>(path: P) -> io::Result<ColoredVertices> {
+    let ply = read(path.as_ref())?;
+    let positions = attribute(&ply, POSITION, |v, _| v as f32)?;
+    let colors = attribute(&ply, COLOR, |v, scalar_type| {
+        let v = match scalar_type {
+            ScalarType::Float | ScalarType::Double => (v * 255.0).round(),
+            _ => v,
+        };
+        v.clamp(0.0, 255.0) as u8
+    })?;
+    Ok((positions, colors))
+}
+
+fn read(path: &Path) -> io::Result<Ply<DefaultElement>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Parser::<DefaultElement>::new().read_ply(&mut reader)
+}
+
+/// Values of the scalar properties `names` of every vertex, converted with `convert` given their type.
+fn attribute<T>(
+    ply: &Ply<DefaultElement>,
+    names: [&str; 3],
+    convert: impl Fn(f64, ScalarType) -> T,
+) -> io::Result<Vec<[T; 3]>> {
+    let def = ply
+        .header
+        .elements
+        .iter()
+        .find(|e| e.name == "vertex")
+        .ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, "The file has no `vertex` element.")
+        })?;
+    let group = def.group(&names).ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Element `vertex` has no scalar properties `{}`, `{}` and `{}` of the same type.",
+                names[0], names[1], names[2]
+            ),
+        )
+    })?;
+    let vertices = ply.payload.get("vertex").map_or(&[][..], |v| &v[..]);
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(i, vertex)| {
+            let values = vertex.get_group_f64::<3>(&group).ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Vertex {} lacks one of `{}`.", i, names.join("`, `")),
+                )
+            })?;
+            Ok(values.map(|v| convert(v, group.scalar_type)))
+        })
+        .collect()
+}
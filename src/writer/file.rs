@@ -0,0 +1,75 @@
+//! Writes files at a path, optionally through a temporary file.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Result, Write};
+use std::path::{Path, PathBuf};
+
+/// Creates the file at `path` and fills it with `write`, see `WriteOptions::atomic()`.
+///
+/// If `atomic` is set, `write` fills a temporary sibling of `path`
+/// which replaces `path` once it is completely written and synced.
+/// Returns what `write` returned.
+pub(crate) fn write_file(
+    path: &Path,
+    atomic: bool,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<usize>,
+) -> Result<usize> {
+    if !atomic {
+        let mut out = BufWriter::new(File::create(path)?);
+        let written = write(&mut out)?;
+        out.flush()?;
+        return Ok(written);
+    }
+    let temp = temp_path(path);
+    let result = write_synced(&temp, write).and_then(|written| {
+        fs::rename(&temp, path)?;
+        sync_parent(path)?;
+        Ok(written)
+    });
+    if result.is_err() {
+        // The original error is more useful than one about the cleanup.
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// `.<file name>.<process id>.tmp` in the directory of `path`.
+///
+/// Being in the same directory keeps it on the same filesystem, so it can be renamed over `path`.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+fn write_synced(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<usize>,
+) -> Result<usize> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    let mut out = BufWriter::new(file);
+    let written = write(&mut out)?;
+    let file = out.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    Ok(written)
+}
+
+/// Syncs the directory of `path`, making the rename durable.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => File::open(dir)?.sync_all(),
+        _ => File::open(".")?.sync_all(),
+    }
+}
+
+/// Directories can't be opened as files on other platforms.
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> Result<()> {
+    Ok(())
+}
@@ -3,6 +3,12 @@
 use crate::ply::PropertyAccess;
 use std::marker::PhantomData;
 
+mod file;
+mod options;
+pub use self::options::*;
+/// Byte orders for `Writer::write_binary_element()`.
+pub use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
 /// Writes a `Ply` to a `Write` trait.
 ///
 /// The simplest function to start with is `write_ply()`.
@@ -36,11 +42,32 @@ use std::marker::PhantomData;
 /// // Write your data:
 /// let written = w.write_ply(&mut buf, &mut ply).unwrap();
 /// ```
+///
+/// Like `Parser`, a `Writer` is `Send + Sync + Clone` for every element type.
 #[derive(Default)]
 pub struct Writer<E: PropertyAccess> {
     /// Should be fairly efficient, se `as_bytes()` in https://doc.rust-lang.org/src/collections/string.rs.html#1001
     new_line: String,
-    phantom: PhantomData<E>,
+    options: WriteOptions,
+    phantom: PhantomData<fn(&E)>,
+}
+
+// Implemented by hand, deriving would require `E: Clone`.
+impl<E: PropertyAccess> Clone for Writer<E> {
+    fn clone(&self) -> Self {
+        Writer {
+            new_line: self.new_line.clone(),
+            options: self.options.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Fails to compile if `Writer` stops being `Send + Sync + Clone` for some element type.
+#[allow(dead_code)]
+fn assert_writer_send_sync_clone<E: PropertyAccess>() {
+    fn check<T: Send + Sync + Clone>() {}
+    check::<Writer<E>>();
 }
 
 /*
@@ -51,8 +78,86 @@ use ply::PropertyAccess;
 
 use std::io;
 use std::io::{ErrorKind, Result, Write};
+use std::path::Path;
 
-use crate::ply::Ply;
+use crate::ply::{bbox_comment, count_comment, is_metadata_comment, KeyMap, Ply, SharedPly};
+use crate::util::{parse_checksum_comment, Crc32, FloatValue, CHECKSUM_COMMENT};
+use std::borrow::Cow;
+
+/// Writes all of `bytes`, returns their number.
+fn write_all<T: Write>(out: &mut T, bytes: &[u8]) -> Result<usize> {
+    out.write_all(bytes)?;
+    Ok(bytes.len())
+}
+
+macro_rules! get_prop(
+    ($element:ident.$getter:ident($key:expr)) => (match $element.$getter($key) {None => return Err(missing_property_error($key)), Some(x) => x})
+);
+
+/// Computes the checksum of the bytes written to it and discards them.
+struct ChecksumWriter(Crc32);
+
+impl Write for ChecksumWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Adds the element name and index to an error raised while writing that element.
+fn element_error(e: io::Error, element_def: &ElementDef, index: u64) -> io::Error {
+    io::Error::new(
+        e.kind(),
+        format!("Element '{}' #{}: {}", element_def.name, index, e),
+    )
+}
+
+fn missing_property_error(key: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidInput,
+        format!(
+            "Property '{}' is missing or doesn't have the declared type.",
+            key
+        ),
+    )
+}
+
+/// Pairs the element definitions of `header` with their entries in `payload`, in header order.
+///
+/// Elements missing from `payload` have no entries. Payload entries without a definition are an error,
+/// there is no place in the file they could be written to.
+fn payload_in_header_order<'a, E, V: AsRef<[E]>>(
+    payload: &'a KeyMap<V>,
+    header: &'a Header,
+) -> Result<Vec<(&'a ElementDef, &'a [E])>> {
+    if let Some(name) = payload
+        .keys()
+        .find(|k| !header.elements.iter().any(|e| e.name == **k))
+    {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Payload contains element '{}' which is not declared in the header.",
+                name
+            ),
+        ));
+    }
+    Ok(header
+        .elements
+        .iter()
+        .map(|e| (e, payload.get(&e.name).map_or(&[][..], |v| v.as_ref())))
+        .collect())
+}
+
+fn custom_type_error(type_name: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidInput,
+        format!("Custom property type '{}' can not be written.", type_name),
+    )
+}
 
 // ////////////////////////////
 // General
@@ -60,8 +165,13 @@ use crate::ply::Ply;
 impl<E: PropertyAccess> Writer<E> {
     /// Create a new `Writer<E>` where `E` is the element type. To get started quickly use `DefaultElement`.
     pub fn new() -> Self {
+        Self::with_options(WriteOptions::new())
+    }
+    /// Create a new `Writer<E>` configured by `options`.
+    pub fn with_options(options: WriteOptions) -> Self {
         Writer {
             new_line: "\n".to_string(),
+            options,
             phantom: PhantomData,
         }
     }
@@ -83,6 +193,34 @@ impl<E: PropertyAccess> Writer<E> {
         };
         self.write_ply_unchecked(out, ply)
     }
+    /// Writes an entire PLY file modeled by `ply` to the file at `path`, checking it like `write_ply()`.
+    ///
+    /// The file is created or replaced, by default only once it is completely written, see `WriteOptions::atomic()`.
+    ///
+    /// Returns number of bytes written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::builder::point_cloud;
+    /// # use ply_rs::writer::Writer;
+    /// let mut ply = point_cloud(&[[0.0, 1.0, 2.0]], None, None).unwrap();
+    /// let path = std::env::temp_dir().join("ply_rs_write_ply_to_path_doc.ply");
+    /// Writer::new().write_ply_to_path(&path, &mut ply).unwrap();
+    /// assert_eq!(ply_rs::read_vertices(&path).unwrap(), vec![[0.0, 1.0, 2.0]]);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn write_ply_to_path<P: AsRef<Path>>(&self, path: P, ply: &mut Ply<E>) -> Result<usize> {
+        ply.make_consistent().map_err(|e| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("The given ply isn't consistent: {:?}", e),
+            )
+        })?;
+        file::write_file(path.as_ref(), self.options.atomic, |out| {
+            self.write_ply_unchecked(out, ply)
+        })
+    }
     /// Writes an entire PLY file modeled by `ply` to `out`, performes no consistency check.
     ///
     /// Like `write_ply` but doesn't check the input for inconsistency.
@@ -90,14 +228,194 @@ impl<E: PropertyAccess> Writer<E> {
     /// if not, behaviour is undefined and might result
     /// in a corrupted output.
     pub fn write_ply_unchecked<T: Write>(&self, out: &mut T, ply: &Ply<E>) -> Result<usize> {
+        self.__write_ply(out, &ply.header, &ply.payload)
+    }
+    /// Writes a `SharedPly` to `out`, performs no consistency check like `write_ply_unchecked()`.
+    ///
+    /// The shared elements are written in place, nothing is copied.
+    ///
+    /// Returns number of bytes written.
+    pub fn write_shared_ply<T: Write>(&self, out: &mut T, ply: &SharedPly<E>) -> Result<usize> {
+        self.__write_ply(out, &ply.header, &ply.payload)
+    }
+    fn __write_ply<T: Write, V: AsRef<[E]>>(
+        &self,
+        out: &mut T,
+        header: &Header,
+        payload: &KeyMap<V>,
+    ) -> Result<usize> {
+        let header = &*self.header_with_metadata(header, payload);
+        if self.options.checksum {
+            return self.write_ply_with_checksum(out, header, payload);
+        }
         let mut written = 0;
-        written += self.write_header(out, &ply.header)?;
-        written += self.write_payload(out, &ply.payload, &ply.header)?;
-        out.flush().unwrap();
+        written += self.write_header(out, header)?;
+        written += self.__write_payload(out, payload, header)?;
+        out.flush()?;
+        Ok(written)
+    }
+    /// Writes the documents of `plys` back to back to `out`, checking each like `write_ply()`.
+    ///
+    /// Every document ends with its last payload byte and the next one starts right after it,
+    /// so `Parser::read_ply_multi()` splits the stream into the same documents,
+    /// whatever their encodings.
+    ///
+    /// Returns number of bytes written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::builder::point_cloud;
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::{DefaultElement, Encoding};
+    /// # use ply_rs::writer::Writer;
+    /// let mut ascii = point_cloud(&[[0.0, 1.0, 2.0]], None, None).unwrap();
+    /// ascii.header.encoding = Encoding::Ascii;
+    /// let binary = point_cloud(&[[3.0, 4.0, 5.0]], None, None).unwrap();
+    /// let mut plys = [ascii, binary];
+    /// let mut buf = Vec::new();
+    /// Writer::new().write_ply_multi(&mut buf, &mut plys).unwrap();
+    /// let read = Parser::<DefaultElement>::new().read_ply_multi(&mut buf.as_slice()).unwrap();
+    /// assert_eq!(read, plys);
+    /// ```
+    pub fn write_ply_multi<T: Write>(&self, out: &mut T, plys: &mut [Ply<E>]) -> Result<usize> {
+        let mut written = 0;
+        for (index, ply) in plys.iter_mut().enumerate() {
+            written += self
+                .write_ply(out, ply)
+                .map_err(|e| io::Error::new(e.kind(), format!("Document #{}: {}", index, e)))?;
+        }
         Ok(written)
     }
+    fn write_ply_with_checksum<T: Write, V: AsRef<[E]>>(
+        &self,
+        out: &mut T,
+        header: &Header,
+        elements: &KeyMap<V>,
+    ) -> Result<usize> {
+        // The checksum precedes the payload in the header, hence the payload is encoded twice:
+        // once to compute the checksum and once more to write it, without buffering it.
+        let mut crc = ChecksumWriter(Crc32::new());
+        self.__write_payload(&mut crc, elements, header)?;
+        let mut header = header.clone();
+        header
+            .comments
+            .retain(|c| parse_checksum_comment(c).is_none());
+        header
+            .comments
+            .push(format!("{}{:08x}", CHECKSUM_COMMENT, crc.0.finish()));
+        let mut written = self.write_header(out, &header)?;
+        written += self.__write_payload(out, elements, &header)?;
+        out.flush()?;
+        Ok(written)
+    }
+    /// `header` with the comments asked for by `WriteOptions::embed_metadata()`.
+    fn header_with_metadata<'h, V: AsRef<[E]>>(
+        &self,
+        header: &'h Header,
+        payload: &KeyMap<V>,
+    ) -> Cow<'h, Header> {
+        let metadata = self.options.metadata;
+        if !metadata.bbox && !metadata.counts {
+            return Cow::Borrowed(header);
+        }
+        let mut header = header.clone();
+        header.comments.retain(|c| !is_metadata_comment(c));
+        if metadata.bbox {
+            if let Some(def) = header.elements.iter().find(|e| e.name == "vertex") {
+                let vertices = payload.get("vertex").map_or(&[][..], |v| v.as_ref());
+                header.comments.extend(bbox_comment(def, vertices));
+            }
+        }
+        if metadata.counts {
+            let counts: Vec<String> = header
+                .elements
+                .iter()
+                .map(|e| count_comment(&e.name, e.count))
+                .collect();
+            header.comments.extend(counts);
+        }
+        Cow::Owned(header)
+    }
     fn write_new_line<T: Write>(&self, out: &mut T) -> Result<usize> {
-        out.write(self.new_line.as_bytes())
+        write_all(out, self.new_line.as_bytes())
+    }
+}
+
+/// Discards everything written to it, only counting the bytes.
+struct CountingSink(u64);
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ////////////////////////////
+/// # Measure
+// ////////////////////////////
+impl<E: PropertyAccess> Writer<E> {
+    /// Computes the number of bytes `write_ply_unchecked()` would write for `ply`, without writing it.
+    ///
+    /// Binary payloads are measured from the property types and list lengths,
+    /// ascii payloads are formatted into a sink that only counts the bytes.
+    pub fn measure(&self, ply: &Ply<E>) -> Result<u64> {
+        let mut sink = CountingSink(0);
+        let header = self.header_with_metadata(&ply.header, &ply.payload);
+        if self.options.checksum {
+            let mut header = header.into_owned();
+            header
+                .comments
+                .retain(|c| parse_checksum_comment(c).is_none());
+            header
+                .comments
+                .push(format!("{}{:08x}", CHECKSUM_COMMENT, 0));
+            self.write_header(&mut sink, &header)?;
+        } else {
+            self.write_header(&mut sink, &header)?;
+        }
+        if ply.header.encoding == Encoding::Ascii {
+            self.write_payload(&mut sink, &ply.payload, &ply.header)?;
+            return Ok(sink.0);
+        }
+        let mut size = sink.0;
+        for (element_def, elements) in payload_in_header_order(&ply.payload, &ply.header)? {
+            for element in elements {
+                for def in &element_def.properties {
+                    size += self.binary_property_size(element, def)?;
+                }
+            }
+        }
+        Ok(size)
+    }
+    fn binary_property_size(&self, element: &E, def: &PropertyDef) -> Result<u64> {
+        let k = &def.name;
+        let (index_type, scalar_type) = match def.data_type {
+            PropertyType::Scalar(ref scalar_type) => return Ok(scalar_type.size() as u64),
+            PropertyType::List(index_type, scalar_type) => (index_type, scalar_type),
+            PropertyType::Custom { ref name, .. } => return Err(custom_type_error(name)),
+        };
+        let len = match scalar_type {
+            ScalarType::Char => get_prop!(element.get_list_char(k)).len(),
+            ScalarType::UChar => get_prop!(element.get_list_uchar(k)).len(),
+            ScalarType::Short => get_prop!(element.get_list_short(k)).len(),
+            ScalarType::UShort => get_prop!(element.get_list_ushort(k)).len(),
+            ScalarType::Int => get_prop!(element.get_list_int(k)).len(),
+            ScalarType::UInt => get_prop!(element.get_list_uint(k)).len(),
+            ScalarType::Float => get_prop!(element.get_list_float(k)).len(),
+            ScalarType::Double => get_prop!(element.get_list_double(k)).len(),
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => get_prop!(element.get_list_longlong(k)).len(),
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => get_prop!(element.get_list_ulonglong(k)).len(),
+            #[cfg(feature = "half")]
+            ScalarType::Half => get_prop!(element.get_list_half(k)).len(),
+        };
+        Ok(index_type.size() as u64 + (len * scalar_type.size()) as u64)
     }
 }
 
@@ -108,8 +426,10 @@ use std::io::{ Write, ErrorKind, Result };
 use super::general;
 use ply::PropertyAccess;
 // */
+use crate::parser::{binary_encoding, binary_list_count};
 use crate::ply::{
-    Comment, ElementDef, Encoding, Header, ObjInfo, PropertyDef, PropertyType, ScalarType, Version,
+    Comment, ElementDef, Encoding, Header, ObjInfo, PropertyDef, PropertyType, RawElement,
+    ScalarType, SmallElement, Version,
 };
 
 // ////////////////////////
@@ -121,7 +441,7 @@ impl<E: PropertyAccess> Writer<E> {
     /// Each PLY file must start with "ply\n".
     pub fn write_line_magic_number<T: Write>(&self, out: &mut T) -> Result<usize> {
         let mut written = 0;
-        written += out.write("ply".as_bytes())?;
+        written += write_all(out, "ply".as_bytes())?;
         written += self.write_new_line(out)?;
         Ok(written)
     }
@@ -135,18 +455,22 @@ impl<E: PropertyAccess> Writer<E> {
         version: &Version,
     ) -> Result<usize> {
         let mut written = 0;
-        written += out.write("format ".as_bytes())?;
+        written += write_all(out, "format ".as_bytes())?;
         written += self.write_encoding(out, encoding)?;
-        written += out.write(format!(" {}.{}", version.major, version.minor).as_bytes())?;
+        written += write_all(
+            out,
+            format!(" {}.{}", version.major, version.minor).as_bytes(),
+        )?;
         written += self.write_new_line(out)?;
         Ok(written)
     }
     /// Writes a comment line.
     ///
+    /// The comment is written unchanged after a single space, so parsed comments round trip.
     /// A comment must not contain a line break and only consist of ascii characters.
     pub fn write_line_comment<T: Write>(&self, out: &mut T, comment: &Comment) -> Result<usize> {
         let mut written = 0;
-        written += out.write(format!("comment {}", comment).as_bytes())?;
+        written += write_all(out, format!("comment {}", comment).as_bytes())?;
         written += self.write_new_line(out)?;
         Ok(written)
     }
@@ -155,7 +479,7 @@ impl<E: PropertyAccess> Writer<E> {
     /// An object informatio line must not contain a line break an only consist of ascii characters.
     pub fn write_line_obj_info<T: Write>(&self, out: &mut T, obj_info: &ObjInfo) -> Result<usize> {
         let mut written = 0;
-        written += out.write(format!("obj_info {}", obj_info).as_bytes())?;
+        written += write_all(out, format!("obj_info {}", obj_info).as_bytes())?;
         written += self.write_new_line(out)?;
         Ok(written)
     }
@@ -169,9 +493,29 @@ impl<E: PropertyAccess> Writer<E> {
         &self,
         out: &mut T,
         element: &ElementDef,
+    ) -> Result<usize> {
+        self.write_line_element_definition_padded(out, element, 0)
+    }
+    /// Like `write_line_element_definition()`, but pads the count with trailing spaces to `width` characters.
+    ///
+    /// The line can be overwritten in place with any other count, as long as it fits `width`.
+    pub(crate) fn write_line_element_definition_padded<T: Write>(
+        &self,
+        out: &mut T,
+        element: &ElementDef,
+        width: usize,
     ) -> Result<usize> {
         let mut written = 0;
-        written += out.write(format!("element {} {}", element.name, element.count).as_bytes())?;
+        written += write_all(
+            out,
+            format!(
+                "element {} {:<width$}",
+                element.name,
+                element.count,
+                width = width
+            )
+            .as_bytes(),
+        )?;
         written += self.write_new_line(out)?;
         Ok(written)
     }
@@ -184,10 +528,10 @@ impl<E: PropertyAccess> Writer<E> {
         property: &PropertyDef,
     ) -> Result<usize> {
         let mut written = 0;
-        written += out.write("property ".as_bytes())?;
+        written += write_all(out, "property ".as_bytes())?;
         written += self.write_property_type(out, &property.data_type)?;
-        written += out.write(" ".as_bytes())?;
-        written += out.write(property.name.as_bytes())?;
+        written += write_all(out, " ".as_bytes())?;
+        written += write_all(out, property.name.as_bytes())?;
         written += self.write_new_line(out)?;
         Ok(written)
     }
@@ -202,8 +546,14 @@ impl<E: PropertyAccess> Writer<E> {
         element: &ElementDef,
     ) -> Result<usize> {
         let mut written = 0;
+        for c in &element.comments {
+            written += self.write_line_comment(out, c)?;
+        }
         written += self.write_line_element_definition(out, element)?;
         for def in &element.properties {
+            for c in &def.comments {
+                written += self.write_line_comment(out, c)?;
+            }
             written += self.write_line_property_definition(out, def)?;
         }
         Ok(written)
@@ -211,7 +561,7 @@ impl<E: PropertyAccess> Writer<E> {
     /// Writes `end_header\n`. This terminates the header. Each following byte belongs to the payload.
     pub fn write_line_end_header<T: Write>(&self, out: &mut T) -> Result<usize> {
         let mut written = 0;
-        written += out.write("end_header".as_bytes())?;
+        written += write_all(out, "end_header".as_bytes())?;
         written += self.write_new_line(out)?;
         Ok(written)
     }
@@ -242,7 +592,7 @@ impl<E: PropertyAccess> Writer<E> {
             Encoding::BinaryBigEndian => "binary_big_endian",
             Encoding::BinaryLittleEndian => "binary_little_endian",
         };
-        out.write(s.as_bytes())
+        write_all(out, s.as_bytes())
     }
     fn write_property_type<T: Write>(
         &self,
@@ -252,7 +602,7 @@ impl<E: PropertyAccess> Writer<E> {
         match *data_type {
             PropertyType::Scalar(ref scalar_type) => self.write_scalar_type(out, scalar_type),
             PropertyType::List(ref index_type, ref content_type) => {
-                let mut written = out.write("list ".as_bytes())?;
+                let mut written = write_all(out, "list ".as_bytes())?;
                 match *index_type {
                     ScalarType::Float => {
                         return Err(io::Error::new(
@@ -266,25 +616,39 @@ impl<E: PropertyAccess> Writer<E> {
                             "List index can not be of type double.",
                         ))
                     }
+                    #[cfg(feature = "half")]
+                    ScalarType::Half => {
+                        return Err(io::Error::new(
+                            ErrorKind::InvalidInput,
+                            "List index can not be of type half.",
+                        ))
+                    }
                     _ => (),
                 };
                 written += self.write_scalar_type(out, index_type)?;
-                written += out.write(" ".as_bytes())?;
+                written += write_all(out, " ".as_bytes())?;
                 written += self.write_scalar_type(out, content_type)?;
                 Ok(written)
             }
+            PropertyType::Custom { ref name, .. } => Err(custom_type_error(name)),
         }
     }
     fn write_scalar_type<T: Write>(&self, out: &mut T, scalar_type: &ScalarType) -> Result<usize> {
         match *scalar_type {
-            ScalarType::Char => out.write("char".as_bytes()),
-            ScalarType::UChar => out.write("uchar".as_bytes()),
-            ScalarType::Short => out.write("short".as_bytes()),
-            ScalarType::UShort => out.write("ushort".as_bytes()),
-            ScalarType::Int => out.write("int".as_bytes()),
-            ScalarType::UInt => out.write("uint".as_bytes()),
-            ScalarType::Float => out.write("float".as_bytes()),
-            ScalarType::Double => out.write("double".as_bytes()),
+            ScalarType::Char => write_all(out, "char".as_bytes()),
+            ScalarType::UChar => write_all(out, "uchar".as_bytes()),
+            ScalarType::Short => write_all(out, "short".as_bytes()),
+            ScalarType::UShort => write_all(out, "ushort".as_bytes()),
+            ScalarType::Int => write_all(out, "int".as_bytes()),
+            ScalarType::UInt => write_all(out, "uint".as_bytes()),
+            ScalarType::Float => write_all(out, "float".as_bytes()),
+            ScalarType::Double => write_all(out, "double".as_bytes()),
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => write_all(out, "int64".as_bytes()),
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => write_all(out, "uint64".as_bytes()),
+            #[cfg(feature = "half")]
+            ScalarType::Half => write_all(out, "float16".as_bytes()),
         }
     }
 }
@@ -301,18 +665,33 @@ use crate::ply::Payload;
 impl<E: PropertyAccess> Writer<E> {
     /// Writes the payload of a `ply` (`ply.playload`).
     ///
+    /// Elements are written in the order of `header.elements`, whatever the order of `payload`.
+    /// Payload entries that aren't declared in the header are an error of kind `InvalidInput`.
     /// Make sure the Header is consistent with the payload.
     pub fn write_payload<T: Write>(
         &self,
         out: &mut T,
         payload: &Payload<E>,
         header: &Header,
+    ) -> Result<usize> {
+        self.__write_payload(out, payload, header)
+    }
+    fn __write_payload<T: Write, V: AsRef<[E]>>(
+        &self,
+        out: &mut T,
+        payload: &KeyMap<V>,
+        header: &Header,
     ) -> Result<usize> {
         let mut written = 0;
-        let element_defs = &header.elements;
-        for (k, element_list) in payload {
-            let element_def = &element_defs.iter().find(|x| x.name == *k).unwrap();
-            written += self.write_payload_of_element(out, element_list, element_def, header)?;
+        let mut flush = self.options.flush_tracker();
+        for (element_def, element_list) in payload_in_header_order(payload, header)? {
+            written += self.__write_payload_for_element(
+                out,
+                element_list,
+                element_def,
+                header.encoding,
+                &mut flush,
+            )?;
         }
         Ok(written)
     }
@@ -322,30 +701,134 @@ impl<E: PropertyAccess> Writer<E> {
     pub fn write_payload_of_element<T: Write>(
         &self,
         out: &mut T,
-        element_list: &Vec<E>,
+        element_list: &[E],
         element_def: &ElementDef,
         header: &Header,
+    ) -> Result<usize> {
+        self.write_payload_for_element(out, element_list, element_def, header.encoding)
+    }
+    /// Writes all `elements` of `element_def` in `encoding`.
+    ///
+    /// Building block for streaming pipelines: Call `write_header()` first,
+    /// then write the elements in the order they are defined in the header.
+    /// Make sure the number of elements matches `element_def.count`.
+    pub fn write_payload_for_element<T: Write>(
+        &self,
+        out: &mut T,
+        elements: &[E],
+        element_def: &ElementDef,
+        encoding: Encoding,
+    ) -> Result<usize> {
+        let mut flush = self.options.flush_tracker();
+        self.__write_payload_for_element(out, elements, element_def, encoding, &mut flush)
+    }
+    fn __write_payload_for_element<T: Write>(
+        &self,
+        out: &mut T,
+        elements: &[E],
+        element_def: &ElementDef,
+        encoding: Encoding,
+        flush: &mut FlushTracker,
     ) -> Result<usize> {
         let mut written = 0;
-        match header.encoding {
-            Encoding::Ascii => {
-                for element in element_list {
-                    written += self.write_ascii_element(out, element, element_def)?;
-                }
-            }
-            Encoding::BinaryBigEndian => {
-                for element in element_list {
-                    written += self.write_big_endian_element(out, element, element_def)?;
-                }
-            }
-            Encoding::BinaryLittleEndian => {
-                for element in element_list {
-                    written += self.write_little_endian_element(out, element, element_def)?;
-                }
+        let mut scratch = Vec::new();
+        for (index, element) in elements.iter().enumerate() {
+            written += self
+                .write_element(out, element, element_def, encoding, &mut scratch, flush)
+                .map_err(|e| element_error(e, element_def, index as u64))?;
+        }
+        Ok(written)
+    }
+    /// Writes the elements produced by `elements` one by one, without collecting them first.
+    ///
+    /// Useful if the elements are decoded from another source on the fly.
+    /// Exactly `element_def.count` elements must be produced, as declared in the header.
+    /// An error of `elements` is returned with the number of elements already written.
+    pub fn write_payload_of_element_iter<T, I>(
+        &self,
+        out: &mut T,
+        elements: I,
+        element_def: &ElementDef,
+        encoding: Encoding,
+    ) -> Result<usize>
+    where
+        T: Write,
+        I: IntoIterator<Item = Result<E>>,
+    {
+        let mut written = 0;
+        let mut count = 0u64;
+        let mut flush = self.options.flush_tracker();
+        let mut scratch = Vec::new();
+        for element in elements {
+            let element = element.map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "Element '{}': source failed after {} of {} elements: {}",
+                        element_def.name, count, element_def.count, e
+                    ),
+                )
+            })?;
+            if count == element_def.count {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Element '{}': source produced more than the declared {} elements.",
+                        element_def.name, element_def.count
+                    ),
+                ));
             }
+            written += self
+                .write_element(
+                    out,
+                    &element,
+                    element_def,
+                    encoding,
+                    &mut scratch,
+                    &mut flush,
+                )
+                .map_err(|e| element_error(e, element_def, count))?;
+            count += 1;
+        }
+        if count != element_def.count {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Element '{}': source produced {} of the declared {} elements.",
+                    element_def.name, count, element_def.count
+                ),
+            ));
         }
         Ok(written)
     }
+    /// Writes a payload element in `encoding` and applies the flush policy.
+    fn write_element<T: Write>(
+        &self,
+        out: &mut T,
+        element: &E,
+        element_def: &ElementDef,
+        encoding: Encoding,
+        scratch: &mut Vec<u8>,
+        flush: &mut FlushTracker,
+    ) -> Result<usize> {
+        let written = match encoding {
+            Encoding::Ascii => self.write_ascii_element(out, element, element_def)?,
+            Encoding::BinaryBigEndian => self.write_binary_element_buffered::<T, BigEndian>(
+                out,
+                element,
+                element_def,
+                scratch,
+            )?,
+            Encoding::BinaryLittleEndian => self.write_binary_element_buffered::<T, LittleEndian>(
+                out,
+                element,
+                element_def,
+                scratch,
+            )?,
+        };
+        flush.element_written(out, written)?;
+        Ok(written)
+    }
 }
 /*
 use std::io::{ Write, Result, ErrorKind };
@@ -354,33 +837,69 @@ use super::Writer;
 // */
 use std::fmt::Display;
 
-macro_rules! get_prop(
-    ($e:expr) => (match $e {None => return Err(io::Error::new(ErrorKind::InvalidInput, "No property available for given key.")), Some(x) => x})
-);
-
 /// # Ascii
 impl<E: PropertyAccess> Writer<E> {
     /// Write a single ascii formatted element.
+    ///
+    /// Make sure all elements are written in the order they are defined in the header.
     pub fn write_ascii_element<T: Write>(
         &self,
         out: &mut T,
         element: &E,
         element_def: &ElementDef,
     ) -> Result<usize> {
+        let written = self.write_ascii_values(out, element, element_def)?;
+        Ok(written + self.write_new_line(out)?)
+    }
+    /// Formats `element` as an ascii data line, without the line break.
+    ///
+    /// Every property of `element_def` has to be present in `element` with the declared type,
+    /// otherwise an error naming the property is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::ply::{DefaultElement, ElementDef, Property, PropertyDef, PropertyType, ScalarType};
+    /// # use ply_rs::writer::Writer;
+    /// let mut def = ElementDef::new("vertex");
+    /// def.properties.push(PropertyDef::new("x", PropertyType::Scalar(ScalarType::Float)));
+    /// def.properties.push(PropertyDef::new("y", PropertyType::Scalar(ScalarType::Float)));
+    /// let mut vertex = DefaultElement::new();
+    /// vertex.insert("x".to_string(), Property::Float(1.5));
+    /// vertex.insert("y".to_string(), Property::Float(-2.0));
+    /// let w = Writer::new();
+    /// assert_eq!(w.write_ascii_element_to_string(&vertex, &def).unwrap(), "1.5 -2");
+    /// ```
+    pub fn write_ascii_element_to_string(
+        &self,
+        element: &E,
+        element_def: &ElementDef,
+    ) -> Result<String> {
+        let mut line = Vec::new();
+        self.write_ascii_values(&mut line, element, element_def)?;
+        // Only numbers and spaces are written.
+        Ok(String::from_utf8(line).expect("ascii values are valid utf-8"))
+    }
+    /// Writes the values of an ascii data line.
+    fn write_ascii_values<T: Write>(
+        &self,
+        out: &mut T,
+        element: &E,
+        element_def: &ElementDef,
+    ) -> Result<usize> {
+        if let Some(raw) = element.raw() {
+            return self.write_raw_ascii(out, raw, element_def);
+        }
         let mut written = 0;
-        let mut p_iter = element_def.properties.iter();
-        let def = p_iter.next().unwrap();
-        written += self.write_ascii_property(out, element, def)?;
-        loop {
-            written += out.write(" ".as_bytes())?;
-            let n = p_iter.next();
-            if n.is_none() {
-                break;
+        for (i, def) in element_def.properties.iter().enumerate() {
+            if i > 0 {
+                written += write_all(out, " ".as_bytes())?;
             }
-            let def = n.unwrap();
             written += self.write_ascii_property(out, element, def)?;
         }
-        written += self.write_new_line(out)?;
+        if self.options.trailing_space && !element_def.properties.is_empty() {
+            written += write_all(out, " ".as_bytes())?;
+        }
         Ok(written)
     }
     fn write_ascii_property<T: Write>(
@@ -401,10 +920,22 @@ impl<E: PropertyAccess> Writer<E> {
                 }
                 ScalarType::Int => self.write_ascii_scalar(out, get_prop!(element.get_int(k))),
                 ScalarType::UInt => self.write_ascii_scalar(out, get_prop!(element.get_uint(k))),
-                ScalarType::Float => self.write_ascii_scalar(out, get_prop!(element.get_float(k))),
+                ScalarType::Float => {
+                    self.write_ascii_float(out, get_prop!(element.get_float(k)), k)
+                }
                 ScalarType::Double => {
-                    self.write_ascii_scalar(out, get_prop!(element.get_double(k)))
+                    self.write_ascii_float(out, get_prop!(element.get_double(k)), k)
+                }
+                #[cfg(feature = "extended-types")]
+                ScalarType::LongLong => {
+                    self.write_ascii_scalar(out, get_prop!(element.get_longlong(k)))
                 }
+                #[cfg(feature = "extended-types")]
+                ScalarType::ULongLong => {
+                    self.write_ascii_scalar(out, get_prop!(element.get_ulonglong(k)))
+                }
+                #[cfg(feature = "half")]
+                ScalarType::Half => self.write_ascii_float(out, get_prop!(element.get_half(k)), k),
             },
             PropertyType::List(_, ref scalar_type) => match *scalar_type {
                 ScalarType::Char => self.write_ascii_list(get_prop!(element.get_list_char(k)), out),
@@ -420,16 +951,72 @@ impl<E: PropertyAccess> Writer<E> {
                 ScalarType::Int => self.write_ascii_list(get_prop!(element.get_list_int(k)), out),
                 ScalarType::UInt => self.write_ascii_list(get_prop!(element.get_list_uint(k)), out),
                 ScalarType::Float => {
-                    self.write_ascii_list(get_prop!(element.get_list_float(k)), out)
+                    self.write_ascii_float_list(out, get_prop!(element.get_list_float(k)), k)
                 }
                 ScalarType::Double => {
-                    self.write_ascii_list(get_prop!(element.get_list_double(k)), out)
+                    self.write_ascii_float_list(out, get_prop!(element.get_list_double(k)), k)
+                }
+                #[cfg(feature = "extended-types")]
+                ScalarType::LongLong => {
+                    self.write_ascii_list(get_prop!(element.get_list_longlong(k)), out)
+                }
+                #[cfg(feature = "extended-types")]
+                ScalarType::ULongLong => {
+                    self.write_ascii_list(get_prop!(element.get_list_ulonglong(k)), out)
+                }
+                #[cfg(feature = "half")]
+                ScalarType::Half => {
+                    self.write_ascii_float_list(out, get_prop!(element.get_list_half(k)), k)
                 }
             },
+            PropertyType::Custom { ref name, .. } => Err(custom_type_error(name)),
         }
     }
     fn write_ascii_scalar<T: Write, V: ToString>(&self, out: &mut T, value: V) -> Result<usize> {
-        out.write(value.to_string().as_bytes())
+        write_all(out, value.to_string().as_bytes())
+    }
+    /// Writes a float, applying `WriteOptions::non_finite()` to NaN and infinity.
+    fn write_ascii_float<T: Write, V: FloatValue>(
+        &self,
+        out: &mut T,
+        value: V,
+        key: &str,
+    ) -> Result<usize> {
+        if value.is_finite() {
+            return write_all(out, value.to_string().as_bytes());
+        }
+        match self.options.non_finite {
+            NonFinite::Error => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Property '{}' is not finite: {}.", key, value),
+            )),
+            NonFinite::WriteTokens => {
+                let token = if value.is_nan() {
+                    "nan"
+                } else if value.is_sign_negative() {
+                    "-inf"
+                } else {
+                    "inf"
+                };
+                write_all(out, token.as_bytes())
+            }
+            NonFinite::Substitute(v) => write_all(out, V::from_f64(v).to_string().as_bytes()),
+        }
+    }
+    fn write_ascii_float_list<T: Write, V: FloatValue>(
+        &self,
+        out: &mut T,
+        list: &[V],
+        key: &str,
+    ) -> Result<usize> {
+        let mut written = 0;
+        written += write_all(out, list.len().to_string().as_bytes())?;
+        let b = " ".as_bytes();
+        for v in list {
+            written += write_all(out, b)?;
+            written += self.write_ascii_float(out, *v, key)?;
+        }
+        Ok(written)
     }
     fn write_ascii_list<T: Write, D: Clone + Display>(
         &self,
@@ -437,11 +1024,11 @@ impl<E: PropertyAccess> Writer<E> {
         out: &mut T,
     ) -> Result<usize> {
         let mut written = 0;
-        written += out.write(list.len().to_string().as_bytes())?;
+        written += write_all(out, list.len().to_string().as_bytes())?;
         let b = " ".as_bytes();
         for v in list {
-            written += out.write(b)?;
-            written += out.write(v.to_string().as_bytes())?;
+            written += write_all(out, b)?;
+            written += write_all(out, v.to_string().as_bytes())?;
         }
         Ok(written)
     }
@@ -453,7 +1040,7 @@ use super::Writer;
 use std::io;
 use std::io::{ Write, Result, ErrorKind };
 // */
-use byteorder::{BigEndian, ByteOrder, LittleEndian, WriteBytesExt};
+use byteorder::WriteBytesExt;
 
 /*
 macro_rules! get_prop(
@@ -463,31 +1050,100 @@ macro_rules! get_prop(
 
 /// # Binary
 impl<E: PropertyAccess> Writer<E> {
-    // private payload
     /// Write a single binary formatted element in big endian.
+    ///
+    /// Make sure all elements are written in the order they are defined in the header.
     pub fn write_big_endian_element<T: Write>(
         &self,
         out: &mut T,
         element: &E,
         element_def: &ElementDef,
     ) -> Result<usize> {
-        self.__write_binary_element::<T, BigEndian>(out, element, element_def)
+        self.write_binary_element::<T, BigEndian>(out, element, element_def)
     }
     /// Write a single binary formatted element in little endian.
+    ///
+    /// Make sure all elements are written in the order they are defined in the header.
     pub fn write_little_endian_element<T: Write>(
         &self,
         out: &mut T,
         element: &E,
         element_def: &ElementDef,
     ) -> Result<usize> {
-        self.__write_binary_element::<T, LittleEndian>(out, element, element_def)
+        self.write_binary_element::<T, LittleEndian>(out, element, element_def)
+    }
+    /// Appends `element` to `out` in the byte order `B`, returns the number of bytes appended.
+    ///
+    /// Every property of `element_def` has to be present in `element` with the declared type,
+    /// otherwise an error naming the property is returned and `out` is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::ply::{DefaultElement, ElementDef, Property, PropertyDef, PropertyType, ScalarType};
+    /// # use ply_rs::writer::{BigEndian, Writer};
+    /// let mut def = ElementDef::new("vertex");
+    /// def.properties.push(PropertyDef::new("x", PropertyType::Scalar(ScalarType::Short)));
+    /// let mut vertex = DefaultElement::new();
+    /// vertex.insert("x".to_string(), Property::Short(258));
+    /// let mut frame = vec![0xff];
+    /// let w = Writer::new();
+    /// let len = w.write_binary_element_to_vec::<BigEndian>(&vertex, &def, &mut frame).unwrap();
+    /// assert_eq!(len, 2);
+    /// assert_eq!(frame, [0xff, 1, 2]);
+    /// ```
+    pub fn write_binary_element_to_vec<B: ByteOrder>(
+        &self,
+        element: &E,
+        element_def: &ElementDef,
+        out: &mut Vec<u8>,
+    ) -> Result<usize> {
+        let start = out.len();
+        self.write_binary_element::<Vec<u8>, B>(out, element, element_def)
+            .inspect_err(|_| out.truncate(start))
+    }
+    /// Serializes `element` into `scratch`, then passes it to `out` in a single write.
+    ///
+    /// `scratch` is reused for all elements of a payload, it is cleared but keeps its capacity.
+    fn write_binary_element_buffered<T: Write, B: ByteOrder>(
+        &self,
+        out: &mut T,
+        element: &E,
+        element_def: &ElementDef,
+        scratch: &mut Vec<u8>,
+    ) -> Result<usize> {
+        scratch.clear();
+        self.write_binary_element::<Vec<u8>, B>(scratch, element, element_def)?;
+        out.write_all(scratch)?;
+        Ok(scratch.len())
     }
-    fn __write_binary_element<T: Write, B: ByteOrder>(
+    /// Write a single binary formatted element in the byte order `B`, `BigEndian` or `LittleEndian`.
+    ///
+    /// Make sure all elements are written in the order they are defined in the header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::ply::{DefaultElement, ElementDef, Property, PropertyDef, PropertyType, ScalarType};
+    /// # use ply_rs::writer::{LittleEndian, Writer};
+    /// let mut def = ElementDef::new("vertex");
+    /// def.properties.push(PropertyDef::new("x", PropertyType::Scalar(ScalarType::Float)));
+    /// let mut vertex = DefaultElement::new();
+    /// vertex.insert("x".to_string(), Property::Float(1.0));
+    /// let mut buf = Vec::new();
+    /// let w = Writer::new();
+    /// w.write_binary_element::<_, LittleEndian>(&mut buf, &vertex, &def).unwrap();
+    /// assert_eq!(buf, 1.0f32.to_le_bytes());
+    /// ```
+    pub fn write_binary_element<T: Write, B: ByteOrder>(
         &self,
         out: &mut T,
         element: &E,
         element_def: &ElementDef,
     ) -> Result<usize> {
+        if let Some(raw) = element.raw() {
+            return self.write_raw_binary::<T, B>(out, raw, element_def);
+        }
         let mut written = 0;
         for def in &element_def.properties {
             let k = &def.name;
@@ -527,102 +1183,325 @@ impl<E: PropertyAccess> Writer<E> {
                             out.write_f64::<B>(get_prop!(element.get_double(k)))?;
                             8
                         }
+                        #[cfg(feature = "extended-types")]
+                        ScalarType::LongLong => {
+                            out.write_i64::<B>(get_prop!(element.get_longlong(k)))?;
+                            8
+                        }
+                        #[cfg(feature = "extended-types")]
+                        ScalarType::ULongLong => {
+                            out.write_u64::<B>(get_prop!(element.get_ulonglong(k)))?;
+                            8
+                        }
+                        #[cfg(feature = "half")]
+                        ScalarType::Half => {
+                            out.write_u16::<B>(get_prop!(element.get_half(k)).to_bits())?;
+                            2
+                        }
                     };
                 }
                 PropertyType::List(ref index_type, ref scalar_type) => {
-                    let vec_len = element_def.count;
-                    written += match *index_type {
-                        ScalarType::Char => {out.write_i8(vec_len as i8)?; 1},
-                        ScalarType::UChar => {out.write_u8(vec_len as u8)?; 1},
-                        ScalarType::Short => {out.write_i16::<B>(vec_len as i16)?; 2},
-                        ScalarType::UShort => {out.write_u16::<B>(vec_len as u16)?; 2},
-                        ScalarType::Int => {out.write_i32::<B>(vec_len as i32)?; 4},
-                        ScalarType::UInt => {out.write_u32::<B>(vec_len as u32)?; 4},
-                        ScalarType::Float => return Err(io::Error::new(ErrorKind::InvalidInput, "Index of list must be an integer type, float declared in PropertyType.")),
-                        ScalarType::Double => return Err(io::Error::new(ErrorKind::InvalidInput, "Index of list must be an integer type, double declared in PropertyType.")),
-                    };
-
                     written += match *scalar_type {
-                        ScalarType::Char => self.write_binary_list::<T, i8>(
+                        ScalarType::Char => self.write_binary_list::<T, B, i8>(
                             get_prop!(element.get_list_char(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_i8(*x)?;
                                 Ok(1)
                             },
                         )?,
-                        ScalarType::UChar => self.write_binary_list::<T, u8>(
+                        ScalarType::UChar => self.write_binary_list::<T, B, u8>(
                             get_prop!(element.get_list_uchar(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_u8(*x)?;
                                 Ok(1)
                             },
                         )?,
-                        ScalarType::Short => self.write_binary_list::<T, i16>(
+                        ScalarType::Short => self.write_binary_list::<T, B, i16>(
                             get_prop!(element.get_list_short(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_i16::<B>(*x)?;
                                 Ok(2)
                             },
                         )?,
-                        ScalarType::UShort => self.write_binary_list::<T, u16>(
+                        ScalarType::UShort => self.write_binary_list::<T, B, u16>(
                             get_prop!(element.get_list_ushort(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_u16::<B>(*x)?;
                                 Ok(2)
                             },
                         )?,
-                        ScalarType::Int => self.write_binary_list::<T, i32>(
+                        ScalarType::Int => self.write_binary_list::<T, B, i32>(
                             get_prop!(element.get_list_int(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_i32::<B>(*x)?;
                                 Ok(4)
                             },
                         )?,
-                        ScalarType::UInt => self.write_binary_list::<T, u32>(
+                        ScalarType::UInt => self.write_binary_list::<T, B, u32>(
                             get_prop!(element.get_list_uint(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_u32::<B>(*x)?;
                                 Ok(4)
                             },
                         )?,
-                        ScalarType::Float => self.write_binary_list::<T, f32>(
+                        ScalarType::Float => self.write_binary_list::<T, B, f32>(
                             get_prop!(element.get_list_float(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_f32::<B>(*x)?;
                                 Ok(4)
                             },
                         )?,
-                        ScalarType::Double => self.write_binary_list::<T, f64>(
+                        ScalarType::Double => self.write_binary_list::<T, B, f64>(
                             get_prop!(element.get_list_double(k)),
+                            index_type,
                             out,
                             &|o, x| {
                                 o.write_f64::<B>(*x)?;
                                 Ok(8)
                             },
                         )?,
+                        #[cfg(feature = "extended-types")]
+                        ScalarType::LongLong => self.write_binary_list::<T, B, i64>(
+                            get_prop!(element.get_list_longlong(k)),
+                            index_type,
+                            out,
+                            &|o, x| {
+                                o.write_i64::<B>(*x)?;
+                                Ok(8)
+                            },
+                        )?,
+                        #[cfg(feature = "extended-types")]
+                        ScalarType::ULongLong => self.write_binary_list::<T, B, u64>(
+                            get_prop!(element.get_list_ulonglong(k)),
+                            index_type,
+                            out,
+                            &|o, x| {
+                                o.write_u64::<B>(*x)?;
+                                Ok(8)
+                            },
+                        )?,
+                        #[cfg(feature = "half")]
+                        ScalarType::Half => self.write_binary_list::<T, B, half::f16>(
+                            get_prop!(element.get_list_half(k)),
+                            index_type,
+                            out,
+                            &|o, x| {
+                                o.write_u16::<B>(x.to_bits())?;
+                                Ok(2)
+                            },
+                        )?,
                     }
                 }
+                PropertyType::Custom { ref name, .. } => return Err(custom_type_error(name)),
             }
         }
         Ok(written)
     }
-    fn write_binary_list<T: Write, D>(
+    /// Writes the length of `list` as `index_type`, followed by the list elements.
+    fn write_binary_list<T: Write, B: ByteOrder, D>(
         &self,
         list: &[D],
+        index_type: &ScalarType,
         out: &mut T,
         out_val: &dyn Fn(&mut T, &D) -> Result<usize>,
     ) -> Result<usize> {
-        let mut written = 0;
+        let mut written = self.write_binary_list_len::<T, B>(out, index_type, list.len())?;
         for v in list {
             written += out_val(out, v)?;
         }
         Ok(written)
     }
+    fn write_binary_list_len<T: Write, B: ByteOrder>(
+        &self,
+        out: &mut T,
+        index_type: &ScalarType,
+        vec_len: usize,
+    ) -> Result<usize> {
+        let written = match *index_type {
+            ScalarType::Char => {
+                out.write_i8(vec_len as i8)?;
+                1
+            }
+            ScalarType::UChar => {
+                out.write_u8(vec_len as u8)?;
+                1
+            }
+            ScalarType::Short => {
+                out.write_i16::<B>(vec_len as i16)?;
+                2
+            }
+            ScalarType::UShort => {
+                out.write_u16::<B>(vec_len as u16)?;
+                2
+            }
+            ScalarType::Int => {
+                out.write_i32::<B>(vec_len as i32)?;
+                4
+            }
+            ScalarType::UInt => {
+                out.write_u32::<B>(vec_len as u32)?;
+                4
+            }
+            #[cfg(feature = "extended-types")]
+            ScalarType::LongLong => {
+                out.write_i64::<B>(vec_len as i64)?;
+                8
+            }
+            #[cfg(feature = "extended-types")]
+            ScalarType::ULongLong => {
+                out.write_u64::<B>(vec_len as u64)?;
+                8
+            }
+            ScalarType::Float => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "Index of list must be an integer type, float declared in PropertyType.",
+                ))
+            }
+            ScalarType::Double => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "Index of list must be an integer type, double declared in PropertyType.",
+                ))
+            }
+            #[cfg(feature = "half")]
+            ScalarType::Half => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "Index of list must be an integer type, half declared in PropertyType.",
+                ))
+            }
+        };
+        Ok(written)
+    }
+}
+
+// ////////////////////////
+/// # Raw
+// ////////////////////////
+impl<E: PropertyAccess> Writer<E> {
+    /// Writes the values of an undecoded element as an ascii data line.
+    fn write_raw_ascii<T: Write>(
+        &self,
+        out: &mut T,
+        raw: &RawElement,
+        element_def: &ElementDef,
+    ) -> Result<usize> {
+        match raw.encoding() {
+            Encoding::Ascii => write_all(out, raw.bytes()),
+            _ => {
+                let element: SmallElement = raw.decode(element_def)?;
+                self.decoded_writer()
+                    .write_ascii_values(out, &element, element_def)
+            }
+        }
+    }
+    /// Writes the values of an undecoded element in the byte order `B`.
+    fn write_raw_binary<T: Write, B: ByteOrder>(
+        &self,
+        out: &mut T,
+        raw: &RawElement,
+        element_def: &ElementDef,
+    ) -> Result<usize> {
+        let bytes = match raw.encoding() {
+            Encoding::Ascii => {
+                let element: SmallElement = raw.decode(element_def)?;
+                return self.decoded_writer().write_binary_element::<T, B>(
+                    out,
+                    &element,
+                    element_def,
+                );
+            }
+            encoding if encoding == binary_encoding::<B>() => raw.bytes(),
+            // Swapping back and forth is the same.
+            _ => &swap_byte_order::<B>(raw.bytes(), element_def)?,
+        };
+        write_all(out, bytes)
+    }
+    /// A writer with the same options for elements that had to be decoded.
+    fn decoded_writer(&self) -> Writer<SmallElement> {
+        Writer {
+            new_line: self.new_line.clone(),
+            options: self.options.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Reverses the bytes of every value of an element in the byte order `B`.
+///
+/// Values of custom types are copied unchanged.
+fn swap_byte_order<B: ByteOrder>(bytes: &[u8], element_def: &ElementDef) -> Result<Vec<u8>> {
+    let mut swapped = Vec::with_capacity(bytes.len());
+    let mut pos = 0;
+    for def in &element_def.properties {
+        let (size, count) = match def.data_type {
+            PropertyType::Scalar(scalar_type) => (scalar_type.size(), 1),
+            PropertyType::Custom { size, .. } => {
+                swapped.extend_from_slice(raw_values(bytes, pos, size, element_def, def)?);
+                pos += size;
+                continue;
+            }
+            PropertyType::List(index_type, scalar_type) => {
+                let index = raw_values(bytes, pos, index_type.size(), element_def, def)?;
+                let count = binary_list_count::<B>(&index_type, index)?;
+                swapped.extend(index.iter().rev());
+                pos += index.len();
+                (scalar_type.size(), count)
+            }
+        };
+        let len = size.saturating_mul(count);
+        for value in raw_values(bytes, pos, len, element_def, def)?.chunks(size) {
+            swapped.extend(value.iter().rev());
+        }
+        pos += len;
+    }
+    if pos != bytes.len() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Element '{}' ends after {} of {} bytes.",
+                element_def.name,
+                pos,
+                bytes.len()
+            ),
+        ));
+    }
+    Ok(swapped)
+}
+
+/// Returns the `len` bytes at `pos`, which hold values of the property `def`.
+fn raw_values<'a>(
+    bytes: &'a [u8],
+    pos: usize,
+    len: usize,
+    element_def: &ElementDef,
+    def: &PropertyDef,
+) -> Result<&'a [u8]> {
+    bytes
+        .get(pos..)
+        .and_then(|rest| rest.get(..len))
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Element '{}' ends within property '{}'.",
+                    element_def.name, def.name
+                ),
+            )
+        })
 }
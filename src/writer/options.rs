@@ -0,0 +1,185 @@
+use std::io;
+use std::io::Write;
+
+/// When the writer flushes its target while writing a payload.
+///
+/// `Writer::write_ply()` always flushes once at the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlushPolicy {
+    /// Flush after every `n` written elements.
+    EveryNElements(usize),
+    /// Flush as soon as at least `n` bytes were written since the last flush.
+    EveryNBytes(usize),
+    /// Never flush while writing the payload, the caller is responsible.
+    #[default]
+    Manual,
+}
+
+/// How NaN and infinite floats are written in ascii payloads.
+///
+/// Binary payloads store every float as is.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NonFinite {
+    /// Return an error naming the element and property.
+    Error,
+    /// Write `nan`, `inf` or `-inf`, which the parser accepts as well.
+    #[default]
+    WriteTokens,
+    /// Write the given value instead.
+    Substitute(f64),
+}
+
+/// Which metadata comments `Writer::write_ply()` adds to the header, see `WriteOptions::embed_metadata()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MetadataOptions {
+    /// Adds `comment bbox minx miny minz maxx maxy maxz` with the bounding box of the `x`, `y`, `z`
+    /// properties of the `vertex` element, if there are any.
+    pub bbox: bool,
+    /// Adds `comment count <element> <n>` for every element.
+    pub counts: bool,
+}
+
+/// Configures the behaviour of a `Writer`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use ply_rs::writer::{FlushPolicy, WriteOptions, Writer};
+/// # use ply_rs::ply::DefaultElement;
+/// let options = WriteOptions::new().flush_policy(FlushPolicy::EveryNBytes(64 * 1024));
+/// let w = Writer::<DefaultElement>::with_options(options);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    flush_policy: FlushPolicy,
+    pub(crate) checksum: bool,
+    pub(crate) trailing_space: bool,
+    pub(crate) non_finite: NonFinite,
+    pub(crate) atomic: bool,
+    pub(crate) metadata: MetadataOptions,
+}
+
+impl WriteOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        WriteOptions {
+            flush_policy: FlushPolicy::Manual,
+            checksum: false,
+            trailing_space: false,
+            non_finite: NonFinite::WriteTokens,
+            atomic: true,
+            metadata: MetadataOptions {
+                bbox: false,
+                counts: false,
+            },
+        }
+    }
+    /// Sets when the target is flushed while writing a payload, `FlushPolicy::Manual` by default.
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+    /// Whether `Writer::write_ply()` adds a checksum of the payload to the header, `false` by default.
+    ///
+    /// The checksum is written as `comment ply-rs-checksum crc32 <hex>`, replacing an existing one,
+    /// see `ParserOptions::verify_checksum()`.
+    /// The header precedes the payload, hence the payload is encoded twice, first to compute
+    /// the checksum. It isn't held in memory.
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+    /// Whether ascii data lines end with a space after the last value, `false` by default.
+    ///
+    /// Older versions of this crate always wrote one, some strict parsers reject it.
+    /// The parser accepts both forms.
+    pub fn trailing_space(mut self, trailing_space: bool) -> Self {
+        self.trailing_space = trailing_space;
+        self
+    }
+    /// How NaN and infinite floats are written in ascii payloads, `NonFinite::WriteTokens` by default.
+    pub fn non_finite(mut self, policy: NonFinite) -> Self {
+        self.non_finite = policy;
+        self
+    }
+    /// Which metadata comments are added to the header, none by default.
+    ///
+    /// Lets viewers show the extent and size of a document without reading its payload,
+    /// read them back with `Header::parsed_metadata()`.
+    /// Metadata comments already in the header are replaced, unless nothing is embedded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ply_rs::builder::point_cloud;
+    /// # use ply_rs::parser::Parser;
+    /// # use ply_rs::ply::DefaultElement;
+    /// # use ply_rs::writer::{MetadataOptions, WriteOptions, Writer};
+    /// let mut ply = point_cloud(&[[0.0, 1.0, 2.0], [-1.0, 3.0, 0.5]], None, None).unwrap();
+    /// let options = WriteOptions::new().embed_metadata(MetadataOptions { bbox: true, counts: true });
+    /// let mut buf = Vec::new();
+    /// Writer::with_options(options).write_ply(&mut buf, &mut ply).unwrap();
+    ///
+    /// let header = Parser::<DefaultElement>::new().read_header(&mut buf.as_slice()).unwrap();
+    /// assert_eq!(header.comments, vec!["bbox -1 1 0.5 0 3 2", "count vertex 2"]);
+    /// let metadata = header.parsed_metadata();
+    /// assert_eq!(metadata.bbox, Some(([-1.0, 1.0, 0.5], [0.0, 3.0, 2.0])));
+    /// assert_eq!(metadata.counts, vec![("vertex".to_string(), 2)]);
+    /// ```
+    pub fn embed_metadata(mut self, metadata: MetadataOptions) -> Self {
+        self.metadata = metadata;
+        self
+    }
+    /// Whether `Writer::write_ply_to_path()` replaces the file only once it is completely written, `true` by default.
+    ///
+    /// The document is written to a temporary file `.<name>.<process id>.tmp` next to the target,
+    /// synced to disk and renamed over the target. If writing fails, the temporary file is removed
+    /// and the target keeps its previous content, or stays absent. Hence a process dying mid-write
+    /// never leaves a truncated file behind, at worst a stray temporary file.
+    ///
+    /// Renaming over a file is atomic on POSIX filesystems and NTFS. On others, like some network filesystems,
+    /// readers may briefly find no file at the target, but still never a partially written one.
+    /// Set to `false` to write the target directly.
+    pub fn atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+    /// Starts tracking a payload for the flush policy.
+    pub(crate) fn flush_tracker(&self) -> FlushTracker {
+        FlushTracker {
+            policy: self.flush_policy,
+            elements: 0,
+            bytes: 0,
+        }
+    }
+}
+
+/// Counts what was written since the last flush.
+pub(crate) struct FlushTracker {
+    policy: FlushPolicy,
+    elements: usize,
+    bytes: usize,
+}
+
+impl FlushTracker {
+    /// Records an element of `bytes` bytes and flushes `out` if the policy asks for it.
+    pub(crate) fn element_written<T: Write>(
+        &mut self,
+        out: &mut T,
+        bytes: usize,
+    ) -> io::Result<()> {
+        self.elements += 1;
+        self.bytes += bytes;
+        let due = match self.policy {
+            FlushPolicy::EveryNElements(n) => self.elements >= n,
+            FlushPolicy::EveryNBytes(n) => self.bytes >= n,
+            FlushPolicy::Manual => false,
+        };
+        if due {
+            out.flush()?;
+            self.elements = 0;
+            self.bytes = 0;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Counts allocations, hence lives in its own test binary.
+//!
+//! The count is kept per thread, so the tests don't see each other's allocations.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::{
+    DefaultElement, ElementDef, Encoding, Header, Property, PropertyAccess, PropertyDef,
+    PropertyType, ScalarType,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Ignore allocations while the thread is torn down.
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Keeps the first three indices of a face, doesn't need to own the list.
+struct Triangle {
+    indices: [i32; 3],
+}
+
+impl PropertyAccess for Triangle {
+    fn new() -> Self {
+        Triangle { indices: [0; 3] }
+    }
+    fn set_property(&mut self, key: &str, property: Property) {
+        self.set_property_ref(key, &property);
+    }
+    fn set_property_ref(&mut self, key: &str, property: &Property) {
+        if let ("vertex_index", Property::ListInt(v)) = (key, property) {
+            self.indices.copy_from_slice(&v[..3]);
+        }
+    }
+}
+
+const FACES: usize = 1000;
+
+fn face_def() -> (Header, ElementDef) {
+    let mut header = Header::new();
+    header.encoding = Encoding::BinaryLittleEndian;
+    let mut face = ElementDef::new("face");
+    face.count = FACES as u64;
+    face.properties.push(PropertyDef::new(
+        "vertex_index",
+        PropertyType::List(ScalarType::UChar, ScalarType::Int),
+    ));
+    header.elements.push(face.clone());
+    (header, face)
+}
+
+fn face_payload() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for i in 0..FACES as i32 {
+        bytes.push(3);
+        for v in [i, i + 1, i + 2] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let before = allocations();
+    f();
+    allocations() - before
+}
+
+#[test]
+fn pooled_lists_reduce_allocations() {
+    let p = Parser::<Triangle>::new();
+    let (header, face) = face_def();
+    let bytes = face_payload();
+
+    let unpooled = count_allocations(|| {
+        let faces = p
+            .read_payload_for_element(&mut bytes.as_slice(), &face, &header)
+            .unwrap();
+        assert_eq!(faces[FACES - 1].indices, [999, 1000, 1001]);
+    });
+    let pooled = count_allocations(|| {
+        let faces = p
+            .read_payload_for_element_pooled(&mut bytes.as_slice(), &face, &header)
+            .unwrap();
+        assert_eq!(faces[FACES - 1].indices, [999, 1000, 1001]);
+    });
+    // Short lists are stored inline with the `smallvec` feature.
+    if !cfg!(feature = "smallvec") {
+        assert!(unpooled >= FACES, "unpooled: {}", unpooled);
+    }
+    assert!(pooled < 10, "pooled: {}", pooled);
+}
+
+#[test]
+fn reused_elements_avoid_allocations() {
+    let p = Parser::<DefaultElement>::new();
+    let (header, face) = face_def();
+    let bytes = face_payload();
+    let mut faces = Vec::new();
+
+    let mut read_frame = || {
+        count_allocations(|| {
+            p.read_payload_for_element_into(&mut bytes.as_slice(), &face, &header, &mut faces)
+                .unwrap();
+        })
+    };
+    let first = read_frame();
+    let frames: Vec<usize> = (0..4).map(|_| read_frame()).collect();
+    assert!(first >= FACES, "first frame: {}", first);
+    for allocations in frames {
+        assert!(allocations < 10, "later frame: {}", allocations);
+    }
+    assert_eq!(faces.len(), FACES);
+    assert_eq!(
+        faces[FACES - 1]["vertex_index"],
+        Property::ListInt(vec![999, 1000, 1001].into_iter().collect())
+    );
+}
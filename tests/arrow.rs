@@ -0,0 +1,67 @@
+//! Round trips through Apache Arrow, requires the `arrow` feature.
+#![cfg(feature = "arrow")]
+extern crate ply_rs;
+use arrow_array::{ArrayRef, Int32Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use ply_rs::arrow::{element_to_record_batch, record_batch_to_elements};
+use ply_rs::parser::Parser;
+use ply_rs::ply::{DefaultElement, Ply};
+use std::io::ErrorKind;
+use std::sync::Arc;
+
+fn read_file(path: &str) -> Ply<DefaultElement> {
+    let mut f = std::fs::File::open(path).unwrap();
+    Parser::<DefaultElement>::new().read_ply(&mut f).unwrap()
+}
+
+fn assert_round_trip(path: &str) {
+    let ply = read_file(path);
+    for def in &ply.header.elements {
+        let batch = element_to_record_batch(&ply, &def.name).unwrap();
+        assert_eq!(batch.num_rows() as u64, def.count);
+        assert_eq!(batch.num_columns(), def.properties.len());
+        let (inferred, elements) = record_batch_to_elements(&batch, &def.name).unwrap();
+        assert_eq!(&inferred, def, "{}", path);
+        assert_eq!(elements, ply.payload[&def.name], "{}", path);
+    }
+}
+
+#[test]
+fn arrow_round_trip_lists() {
+    assert_round_trip("example_plys/house_2_ok_little_endian.ply");
+    assert_round_trip("example_plys/triangles_ok_ascii.ply");
+}
+#[test]
+fn arrow_round_trip_scalars() {
+    assert_round_trip("example_plys/all_atomic_types_ok_ascii.ply");
+}
+#[test]
+fn arrow_schema_ok() {
+    let ply = read_file("example_plys/house_2_ok_little_endian.ply");
+    let batch = element_to_record_batch(&ply, "face").unwrap();
+    let field = batch.schema().field(0).clone();
+    assert_eq!(field.name(), "vertex_indices");
+    assert!(!field.is_nullable());
+    match field.data_type() {
+        DataType::List(item) => {
+            assert!(!item.is_nullable());
+            assert_eq!(item.data_type(), &DataType::Int32);
+        }
+        d => panic!("Expected a list, found {}", d),
+    }
+    assert_eq!(field.metadata()["ply:index_type"], "uchar");
+}
+#[test]
+fn arrow_unknown_element_err() {
+    let ply = read_file("example_plys/house_2_ok_little_endian.ply");
+    let err = element_to_record_batch(&ply, "edge").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+#[test]
+fn arrow_nulls_err() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let column: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None]));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![column]).unwrap();
+    let err = record_batch_to_elements(&batch, "vertex").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,129 @@
+//! Frames documents with `tokio_util::codec`, requires the `codec` feature.
+#![cfg(feature = "codec")]
+#[macro_use]
+extern crate ply_rs;
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt};
+use ply_rs::codec::{PlyDecoder, PlyEncoder};
+use ply_rs::parser::{Parser, ParserOptions};
+use ply_rs::ply::{DefaultElement, Ply};
+use std::io::ErrorKind;
+use tokio_util::codec::{Decoder, FramedRead, FramedWrite};
+
+const FIXTURES: [&str; 4] = [
+    "example_plys/house_ok_ascii.ply",
+    "example_plys/house_2_ok_little_endian.ply",
+    "example_plys/points_ok_little_endian.ply",
+    "example_plys/empty_ok_ascii.ply",
+];
+
+fn read_file(path: &str) -> Ply<DefaultElement> {
+    let mut f = std::fs::File::open(path).unwrap();
+    Parser::<DefaultElement>::new().read_ply(&mut f).unwrap()
+}
+
+#[test]
+fn decode_byte_by_byte() {
+    let mut stream = Vec::new();
+    for path in &FIXTURES {
+        stream.extend(std::fs::read(path).unwrap());
+    }
+    let mut decoder = PlyDecoder::<DefaultElement>::new();
+    let mut buf = BytesMut::new();
+    let mut decoded = Vec::new();
+    for b in stream {
+        buf.extend_from_slice(&[b]);
+        while let Some(ply) = decoder.decode(&mut buf).unwrap() {
+            decoded.push(ply);
+        }
+    }
+    assert!(decoder.decode_eof(&mut buf).unwrap().is_none());
+    assert_eq!(decoded.len(), FIXTURES.len());
+    for (ply, path) in decoded.iter().zip(&FIXTURES) {
+        assert_ply_eq!(ply, read_file(path));
+    }
+}
+
+#[test]
+fn decode_missing_last_line_break() {
+    let path = "example_plys/house_ok_ascii.ply";
+    let mut bytes = std::fs::read(path).unwrap();
+    assert_eq!(bytes.pop(), Some(b'\n'));
+    let mut decoder = PlyDecoder::<DefaultElement>::new();
+    let mut buf = BytesMut::from(&bytes[..]);
+    assert!(decoder.decode(&mut buf).unwrap().is_none());
+    let ply = decoder.decode_eof(&mut buf).unwrap().unwrap();
+    assert_ply_eq!(ply, read_file(path));
+}
+
+#[test]
+fn decode_err() {
+    let mut decoder = PlyDecoder::<DefaultElement>::new();
+    let mut buf = BytesMut::from(&b"PLY\nformat ascii 1.0\n"[..]);
+    let err = decoder.decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    let bytes = std::fs::read("example_plys/house_2_ok_little_endian.ply").unwrap();
+    let mut decoder = PlyDecoder::<DefaultElement>::new().max_len(bytes.len() - 1);
+    let mut buf = BytesMut::from(&bytes[..bytes.len() / 2]);
+    assert!(decoder.decode(&mut buf).unwrap().is_none());
+    buf.extend_from_slice(&bytes[bytes.len() / 2..]);
+    let err = decoder.decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut decoder = PlyDecoder::<DefaultElement>::new();
+    let mut buf = BytesMut::from(&bytes[..bytes.len() - 1]);
+    let err = decoder.decode_eof(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn decode_limits_err() {
+    // A list claiming four billion entries fails without waiting for its entries.
+    let mut bytes = b"ply\nformat binary_little_endian 1.0\nelement face 1\n\
+        property list uint int vertex_indices\nend_header\n"
+        .to_vec();
+    bytes.extend_from_slice(&4_000_000_000u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 64]);
+    let mut decoder =
+        PlyDecoder::<DefaultElement>::with_options(ParserOptions::new().max_list_len(16));
+    let mut buf = BytesMut::from(&bytes[..]);
+    let err = decoder.decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("limit of 16"), "{}", err);
+
+    // A header line without an end is rejected once it exceeds the limit.
+    let mut decoder =
+        PlyDecoder::<DefaultElement>::with_options(ParserOptions::new().max_header_line_len(1024));
+    let mut buf = BytesMut::from(&b"ply\ncomment "[..]);
+    assert!(decoder.decode(&mut buf).unwrap().is_none());
+    buf.extend_from_slice(&[b'x'; 1024]);
+    let err = decoder.decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn framed_duplex_small_chunks() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        // Every read and write moves at most 7 bytes.
+        let (client, server) = tokio::io::duplex(7);
+        let send = async move {
+            let mut sink = FramedWrite::new(client, PlyEncoder::<DefaultElement>::new());
+            for path in &FIXTURES {
+                sink.send(read_file(path)).await.unwrap();
+            }
+        };
+        let receive = async move {
+            let stream = FramedRead::new(server, PlyDecoder::<DefaultElement>::new());
+            stream.map(|ply| ply.unwrap()).collect::<Vec<_>>().await
+        };
+        let ((), received) = futures::join!(send, receive);
+        assert_eq!(received.len(), FIXTURES.len());
+        for (ply, path) in received.iter().zip(&FIXTURES) {
+            assert_ply_eq!(ply, read_file(path));
+        }
+    });
+}
@@ -0,0 +1,85 @@
+//! Malformed input must be rejected with an error, never with a panic.
+//!
+//! See `fuzz/README.md` for the coverage guided fuzz targets.
+//! That counts from these files reserve at most `MAX_PREALLOCATION` entries
+//! is checked by the unit tests of the parser, where the cap is defined.
+extern crate ply_rs;
+use ply_rs::parser::Parser;
+use ply_rs::ply::DefaultElement;
+use std::panic;
+
+#[test]
+fn fuzz_regressions_err() {
+    let p = Parser::<DefaultElement>::new();
+    for entry in std::fs::read_dir("fuzz/regressions").unwrap() {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        assert!(
+            p.read_ply(&mut data.as_slice()).is_err(),
+            "{}",
+            path.display()
+        );
+    }
+}
+
+/// Cheap stand-in for the fuzz targets: mutates the example files and parses the result.
+#[test]
+fn mutated_examples_dont_panic() {
+    let mut seeds = Vec::new();
+    for entry in std::fs::read_dir("example_plys").unwrap() {
+        seeds.push(std::fs::read(entry.unwrap().path()).unwrap());
+    }
+    let tokens: &[&[u8]] = &[
+        b"-1",
+        b"0",
+        b"255",
+        b"4294967295",
+        b"18446744073709551615",
+        b"99999999999999999999",
+        b"1e999",
+        b" ",
+        b"\n",
+        b"\xff\xff\xff\xff",
+        b"list",
+        b"element",
+        b"property",
+        b"end_header\n",
+    ];
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize
+    };
+    let p = Parser::<DefaultElement>::new();
+    for _ in 0..20_000 {
+        let mut data = seeds[next() % seeds.len()].clone();
+        for _ in 0..1 + next() % 4 {
+            let pos = next() % (data.len() + 1);
+            let token = tokens[next() % tokens.len()];
+            match next() % 4 {
+                0 if pos < data.len() => data[pos] = next() as u8,
+                1 if pos < data.len() => {
+                    data.remove(pos);
+                }
+                2 => {
+                    let end = (pos + token.len()).min(data.len());
+                    data.splice(pos..end, token.iter().copied());
+                }
+                _ => data.truncate(pos),
+            }
+        }
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _ = p.read_ply(&mut data.as_slice());
+            for line in String::from_utf8_lossy(&data).lines() {
+                let _ = p.read_header_line(line);
+            }
+        }));
+        assert!(
+            result.is_ok(),
+            "panicked on:\n{}",
+            String::from_utf8_lossy(&data)
+        );
+    }
+}
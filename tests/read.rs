@@ -31,7 +31,7 @@ fn read_empy_equal() {
     let bin = read_file("example_plys/empty_2_ok_little_endian.ply");
     let ascii = read_file("example_plys/empty_2_ok_ascii.ply");
     assert_eq!(bin.header.elements, ascii.header.elements);
-    assert_eq!(bin.payload, ascii.payload);
+    assert_payload_eq!(bin.payload, ascii.payload);
 }
 #[test]
 fn read_house() {
@@ -54,7 +54,7 @@ fn read_house_equal() {
     let bin = read_file("example_plys/house_2_ok_little_endian.ply");
     let ascii = read_file("example_plys/house_2_ok_ascii.ply");
     assert_eq!(bin.header.elements, ascii.header.elements);
-    assert_eq!(bin.payload, ascii.payload);
+    assert_payload_eq!(bin.payload, ascii.payload);
 }
 #[test]
 fn read_greg_turk_1() {
@@ -81,9 +81,17 @@ fn read_all_atomic_types_ok() {
     let ply = read_file("example_plys/all_atomic_types_ok_ascii.ply");
     println!("Created ply: {:?}", ply);
 }
+#[test]
+fn read_unusual_property_order_ok() {
+    let ply = read_file("example_plys/unusual_property_order_ok_ascii.ply");
+    for v in &ply.payload["vertex"] {
+        let keys: Vec<&str> = v.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["z", "red", "x", "idx", "y"]);
+    }
+}
 
 mod struct_test_1 {
-    use super::parser::Parser;
+    use super::parser::{MultiParser, UnregisteredElements};
     use super::ply;
     use super::read_file;
 
@@ -128,7 +136,7 @@ mod struct_test_1 {
 
         fn set_property(&mut self, key: &str, property: ply::Property) {
             match (key, property) {
-                ("vertex_index", ply::Property::ListInt(vec)) => self.vertex_index = vec,
+                ("vertex_index", ply::Property::ListInt(vec)) => self.vertex_index = vec.into(),
                 (k, _) => panic!("Face: Unexpected key/value combination: key: {}", k),
             }
         }
@@ -139,45 +147,23 @@ mod struct_test_1 {
     fn read_into_struct() {
         // set up a reader, in this a file.
         let path = "example_plys/greg_turk_example1_ok_ascii.ply";
+        let mut f = std::fs::File::open(path).unwrap();
 
-        let f = std::fs::File::open(path).unwrap();
-        // The header of a ply file consists of ascii lines, BufRead provides useful methods for that.
-        let mut f = std::io::BufReader::new(f);
-
-        // Create a parser for each struct. Parsers are cheap objects.
-        let vertex_parser = Parser::<Vertex>::new();
-        let face_parser = Parser::<Face>::new();
-
-        // lets first consume the header
-        // We also could use `face_parser`, The configuration is a parser's only state.
-        // The reading position only depends on `f`.
-        let header = vertex_parser.read_header(&mut f).unwrap();
-
-        // Depending on the header, read the data into our structs..
-        let mut vertex_list = Vec::new();
-        let mut face_list = Vec::new();
-        for element in &header.elements {
-            // we could also just parse them in sequence, but the file format might change
-            match element.name.as_ref() {
-                "vertex" => {
-                    vertex_list = vertex_parser
-                        .read_payload_for_element(&mut f, element, &header)
-                        .unwrap();
-                }
-                "face" => {
-                    face_list = face_parser
-                        .read_payload_for_element(&mut f, element, &header)
-                        .unwrap();
-                }
-                _ => panic!("Enexpeced element!"),
-            }
-        }
+        // Register a struct for each element, the parser reads every element into its struct.
+        let parser = MultiParser::new()
+            .element::<Vertex>("vertex")
+            .element::<Face>("face");
+        let mut typed = parser.read(&mut f).unwrap();
+        let vertex_list = typed.take::<Vertex>("vertex");
+        let face_list = typed.take::<Face>("face");
 
-        println!("header: {:#?}", header);
+        println!("header: {:#?}", typed.header);
         println!("vertex list: {:#?}", vertex_list);
         println!("face list: {:#?}", face_list);
 
         let ply = read_file(path);
+        assert_eq!(vertex_list.len(), ply.payload["vertex"].len());
+        assert_eq!(face_list.len(), ply.payload["face"].len());
 
         for (i, vert) in vertex_list.iter().enumerate() {
             let x = match ply.payload["vertex"][i]["x"] {
@@ -205,4 +191,2437 @@ mod struct_test_1 {
             assert_eq!(face.vertex_index, *v);
         }
     }
+
+    #[test]
+    fn read_unregistered_elements() {
+        let path = "example_plys/greg_turk_example1_ok_ascii.ply";
+        let mut f = std::fs::File::open(path).unwrap();
+        let mut typed = MultiParser::new()
+            .element::<Face>("face")
+            .element::<Vertex>("edge")
+            .read(&mut f)
+            .unwrap();
+        assert_eq!(typed.take::<Face>("face").len(), 6);
+        // Skipped, respectively missing from the file.
+        assert!(typed.take::<Vertex>("vertex").is_empty());
+        assert!(typed.take::<Vertex>("edge").is_empty());
+
+        let mut f = std::fs::File::open(path).unwrap();
+        let err = MultiParser::new()
+            .element::<Face>("face")
+            .on_unregistered(UnregisteredElements::Error)
+            .read(&mut f)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("'vertex'"), "{}", err);
+    }
+
+    #[test]
+    fn get_as_struct() {
+        let ply = read_file("example_plys/greg_turk_example1_ok_ascii.ply");
+        let vertices = ply.get_as::<Vertex>("vertex").unwrap().unwrap();
+        assert_eq!(vertices.len(), 8);
+        for (vert, e) in vertices.iter().zip(&ply.payload["vertex"]) {
+            assert_eq!(ply::Property::Float(vert.x), e["x"]);
+            assert_eq!(ply::Property::Float(vert.y), e["y"]);
+            assert_eq!(ply::Property::Float(vert.z), e["z"]);
+        }
+        assert!(ply.get_as::<Vertex>("edge").is_none());
+
+        let err = ply.get_as::<Face>("vertex").unwrap().unwrap_err();
+        assert_eq!(err.index, 0);
+        assert_eq!(err.key, "x");
+        assert!(err.description.contains("Unexpected key/value"), "{}", err);
+    }
+}
+#[test]
+fn read_fixed_size_binary_ok() {
+    let ply = read_file("example_plys/points_ok_little_endian.ply");
+    assert_eq!(ply.payload["vertex"].len(), 3);
+    assert_eq!(ply.payload["edge"].len(), 2);
+}
+#[test]
+fn scan_intact_ok() {
+    let info = info::scan("example_plys/points_ok_little_endian.ply").unwrap();
+    assert_eq!(info.header.elements.len(), 2);
+    assert_eq!(info.header_len, 218);
+    assert_eq!(info.file_size, 218 + 3 * 13 + 2 * 8);
+    assert_eq!(info.expected_size, Some(info.file_size));
+    assert_eq!(info.size_matches, Some(true));
+}
+#[test]
+fn scan_truncated_err() {
+    let info = info::scan("example_plys/points_truncated_err_little_endian.ply").unwrap();
+    assert_eq!(info.file_size + 5, info.expected_size.unwrap());
+    assert_eq!(info.size_matches, Some(false));
+}
+#[test]
+fn scan_variable_size() {
+    let info = info::scan("example_plys/house_ok_ascii.ply").unwrap();
+    assert_eq!(info.size_matches, None);
+    let info = info::scan("example_plys/house_2_ok_little_endian.ply").unwrap();
+    assert_eq!(info.expected_size, None);
+}
+#[test]
+fn read_mislabeled_binary_err() {
+    let mut f = std::fs::File::open("example_plys/mislabeled_binary_err_ascii.ply").unwrap();
+    let err = parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut f)
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Payload does not look like ascii, the format line may be wrong"),
+        "{}",
+        err
+    );
+}
+#[test]
+fn read_mislabeled_ascii_err() {
+    let mut f = std::fs::File::open("example_plys/mislabeled_ascii_err_little_endian.ply").unwrap();
+    let err = parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut f)
+        .unwrap_err();
+    assert!(err.to_string().contains("did you mean ascii?"), "{}", err);
+}
+/// Checks that `err` carries an `ElementError` for entry `index` of `element`.
+fn assert_element_error(err: &std::io::Error, element: &str, index: u64) {
+    let e = err
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<parser::ElementError>())
+        .unwrap_or_else(|| panic!("not an element error: {}", err));
+    assert_eq!((e.element(), e.index()), (element, index), "{}", err);
+}
+fn vertex_header(format: &str) -> Vec<u8> {
+    format!(
+        "ply\nformat {} 1.0\nelement vertex 10\nproperty float x\nproperty uchar y\nend_header\n",
+        format
+    )
+    .into_bytes()
+}
+#[test]
+fn read_element_index_ascii_err() {
+    let mut txt = vertex_header("ascii");
+    for i in 0..10 {
+        let line = if i == 7 { "1.5 300\n" } else { "1.5 3\n" };
+        txt.extend_from_slice(line.as_bytes());
+    }
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let err = p.read_ply(&mut txt.as_slice()).unwrap_err();
+    assert_element_error(&err, "vertex", 7);
+}
+fn read_face_line(line: &str) -> std::io::Error {
+    let mut txt = b"ply\nformat ascii 1.0\nelement face 3\nproperty list uchar int vertex_index\nend_header\n3 0 1 2\n3 1 2 3\n".to_vec();
+    txt.extend_from_slice(line.as_bytes());
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    p.read_ply(&mut txt.as_slice()).unwrap_err()
+}
+#[test]
+fn read_negative_list_count_err() {
+    let err = read_face_line("-1 0\n");
+    assert_element_error(&err, "face", 2);
+    let err = err.to_string();
+    assert!(
+        err.contains("List count for property 'vertex_index' is negative (-1)."),
+        "{}",
+        err
+    );
+}
+#[test]
+fn read_invalid_list_count_err() {
+    for count in ["1.5", "99999999999999999999999"] {
+        let err = read_face_line(&format!("{} 0\n", count));
+        assert_element_error(&err, "face", 2);
+        let err = err.to_string();
+        let expected = format!(
+            "List count for property 'vertex_index' is not a valid count: '{}'.",
+            count
+        );
+        assert!(err.contains(&expected), "{}", err);
+    }
+}
+fn tristrip_ply(strip: &str) -> Vec<u8> {
+    let header = "ply\nformat ascii 1.0\nelement tristrips 1\nproperty list int int vertex_indices\nelement vertex 1\nproperty float x\nend_header\n";
+    format!("{}{}\n0.5\n", header, strip).into_bytes()
+}
+#[test]
+fn read_long_line_ok() {
+    let values: Vec<String> = (0..700_000)
+        .map(|i| if i % 7 == 6 { -1 } else { i * 31 % 100_003 }.to_string())
+        .collect();
+    let strip = format!("{} {}", values.len(), values.join(" "));
+    assert!(strip.len() > 3 << 20);
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let ply = p.read_ply(&mut tristrip_ply(&strip).as_slice()).unwrap();
+    let expected = p
+        .read_ascii_element(&strip, &ply.header.elements[0])
+        .unwrap();
+    assert_eq!(ply.payload["tristrips"], vec![expected]);
+    assert_eq!(ply.payload["vertex"][0]["x"], ply::Property::Float(0.5));
+}
+#[test]
+fn read_long_line_err() {
+    let mut strip = "2000000".to_string();
+    for i in 0..2_000_000 {
+        strip.push_str(if i == 1_500_000 { " x" } else { " 1" });
+    }
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let err = p
+        .read_ply(&mut tristrip_ply(&strip).as_slice())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_element_error(&err, "tristrips", 0);
+    let err = err.to_string();
+    assert!(err.contains("Invalid value 'x'"), "{}", err);
+}
+#[test]
+fn read_normalized_names_ok() {
+    let options =
+        parser::ParserOptions::new().normalize_property_names(parser::NameNormalization::Lowercase);
+    let p = parser::Parser::<ply::DefaultElement>::with_options(options);
+    let mut f = std::fs::File::open("example_plys/mixed_case_ok_ascii.ply").unwrap();
+    let ply = p.read_ply(&mut f).unwrap();
+    let names: Vec<&str> = ply.header.elements[0]
+        .properties
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["x", "y", "z", "red", "green", "blue"]);
+    assert_eq!(ply.payload["vertex"][1]["x"], ply::Property::Float(1.0));
+    assert_eq!(ply.payload["vertex"][2]["blue"], ply::Property::UChar(255));
+    assert!(ply.payload["face"][0].contains_key("vertex_indices"));
+
+    // The normalized names are written back out.
+    let mut written = Vec::<u8>::new();
+    writer::Writer::new()
+        .write_ply(&mut written, &mut ply.clone())
+        .unwrap();
+    let text = String::from_utf8(written).unwrap();
+    assert!(text.contains("property uchar red\n"), "{}", text);
+    let read_back = parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut text.as_bytes())
+        .unwrap();
+    assert_ply_eq!(read_back, ply);
+}
+#[test]
+fn read_preserved_names_ok() {
+    let ply = read_file("example_plys/mixed_case_ok_ascii.ply");
+    assert_eq!(ply.header.elements[0].properties[3].name, "Red");
+    assert!(ply.payload["vertex"][0].contains_key("Red"));
+}
+#[test]
+fn read_normalized_names_collision_err() {
+    let txt = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float X\nproperty float x\nend_header\n1 2\n";
+    let options =
+        parser::ParserOptions::new().normalize_property_names(parser::NameNormalization::Lowercase);
+    let p = parser::Parser::<ply::DefaultElement>::with_options(options);
+    let err = p.read_ply(&mut txt.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("'X' and 'x'"), "{}", err);
+    // Without normalization, the names are distinct.
+    parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut txt.as_bytes())
+        .unwrap();
+}
+/// Reads `path` sequentially and, with the `parallel` feature, on multiple threads.
+fn read_errors(path: &str) -> Vec<std::io::Error> {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    #[allow(unused_mut)] // Only extended with the `parallel` feature.
+    let mut errors = vec![p
+        .read_ply(&mut std::fs::File::open(path).unwrap())
+        .unwrap_err()];
+    #[cfg(feature = "parallel")]
+    {
+        let options = parser::ParserOptions::new().parallel_threshold(1);
+        let p = parser::Parser::<ply::DefaultElement>::with_options(options);
+        let mut f = std::fs::File::open(path).unwrap();
+        errors.push(p.read_ply_parallel(&mut f).unwrap_err());
+    }
+    errors
+}
+/// Keeps its values behind an `Rc`, so it can't be sent to another thread.
+#[derive(Debug)]
+struct LocalVertex {
+    x: std::rc::Rc<f32>,
+}
+impl ply::PropertyAccess for LocalVertex {
+    fn new() -> Self {
+        LocalVertex {
+            x: std::rc::Rc::new(0.0),
+        }
+    }
+    fn set_property(&mut self, key: &str, property: ply::Property) {
+        if let ("x", ply::Property::Float(v)) = (key, property) {
+            self.x = std::rc::Rc::new(v);
+        }
+    }
+}
+#[test]
+fn read_not_send_ok() {
+    // Compiles with every set of features, the `parallel` feature only adds methods.
+    let p = parser::Parser::<LocalVertex>::new();
+    let mut f = std::fs::File::open("example_plys/house_ok_ascii.ply").unwrap();
+    let ply = p.read_ply(&mut f).unwrap();
+    assert_eq!(*ply.payload["vertex"][1].x, -1.0);
+}
+#[test]
+fn read_truncated_ascii_err() {
+    for err in read_errors("example_plys/triangles_truncated_err_ascii.ply") {
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "Unexpected end of file: element 'face' declared 4 entries but only 2 data lines were found."
+        );
+    }
+}
+#[test]
+fn read_miscounted_ascii_err() {
+    for err in read_errors("example_plys/triangles_miscounted_err_ascii.ply") {
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "Unexpected end of file: element 'vertex' declared 9 entries but only 4 data lines were found. \
+            The 4 lines after them don't match the properties of 'vertex', they likely belong to the next element."
+        );
+    }
+}
+#[test]
+fn read_partial_decimal_floats_ok() {
+    let mut txt = b"ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\nproperty float z\nend_header\n".to_vec();
+    txt.extend_from_slice(b".5 -.25 1.\n+.5 2.e1 -3.\n");
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let ply = p.read_ply(&mut txt.as_slice()).unwrap();
+    let values: Vec<Vec<ply::Property>> = ply.payload["vertex"]
+        .iter()
+        .map(|e| e.values().cloned().collect())
+        .collect();
+    let f = ply::Property::Float;
+    assert_eq!(
+        values,
+        vec![
+            vec![f(0.5), f(-0.25), f(1.0)],
+            vec![f(0.5), f(20.0), f(-3.0)]
+        ]
+    );
+}
+#[test]
+fn read_element_index_binary_err() {
+    let mut bytes = vertex_header("binary_big_endian");
+    for _ in 0..7 {
+        bytes.extend_from_slice(&1.5f32.to_be_bytes());
+        bytes.push(3);
+    }
+    bytes.extend_from_slice(&[0, 0]);
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let err = p.read_ply(&mut bytes.as_slice()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert_element_error(&err, "vertex", 7);
+
+    let mut f = std::fs::File::open("example_plys/points_truncated_err_little_endian.ply").unwrap();
+    let err = p.read_ply(&mut f).unwrap_err();
+    assert_element_error(&err, "edge", 1);
+}
+#[test]
+fn read_indented_header_ok() {
+    let indented = read_file("example_plys/house_indented_header_ok_ascii.ply");
+    let plain = read_file("example_plys/house_ok_ascii.ply");
+    assert_ply_eq!(indented, plain);
+}
+#[test]
+fn read_fixed_list_ok() {
+    let options = parser::ParserOptions::new().expect_fixed_list("face", "vertex_indices", 3);
+    let p = parser::Parser::<ply::DefaultElement>::with_options(options);
+    for path in [
+        "example_plys/triangles_ok_ascii.ply",
+        "example_plys/triangles_ok_little_endian.ply",
+    ] {
+        let mut f = std::fs::File::open(path).unwrap();
+        let ply = p.read_ply(&mut f).unwrap();
+        assert_ply_eq!(ply, read_file(path));
+        assert_eq!(ply.payload["face"].len(), 4);
+    }
+}
+#[test]
+fn read_fixed_list_err() {
+    let options = parser::ParserOptions::new().expect_fixed_list("face", "vertex_indices", 3);
+    let p = parser::Parser::<ply::DefaultElement>::with_options(options);
+    for path in [
+        "example_plys/triangles_quad_err_ascii.ply",
+        "example_plys/triangles_quad_err_little_endian.ply",
+    ] {
+        read_file(path);
+        let mut f = std::fs::File::open(path).unwrap();
+        let err = p.read_ply(&mut f).unwrap_err();
+        assert_element_error(&err, "face", 2);
+        let err = err.to_string();
+        assert!(
+            err.contains("List 'vertex_indices' has 4 entries, expected 3."),
+            "{}",
+            err
+        );
+    }
+}
+#[test]
+fn read_small_element_ok() {
+    let p = parser::Parser::<ply::SmallElement>::new();
+    for entry in std::fs::read_dir("example_plys").unwrap() {
+        let path = entry.unwrap().path();
+        let path = path.to_str().unwrap();
+        if !path.contains("_ok_") {
+            continue;
+        }
+        let expected = read_file(path);
+        let mut f = std::fs::File::open(path).unwrap();
+        let ply = p.read_ply(&mut f).unwrap();
+        assert_eq!(ply.header, expected.header);
+        for (name, elements) in &expected.payload {
+            assert_eq!(ply.payload[name].len(), elements.len(), "{}", path);
+            for (small, default) in ply.payload[name].iter().zip(elements) {
+                let small: Vec<_> = small.iter().collect();
+                let default: Vec<_> = default.iter().collect();
+                assert_eq!(small, default, "{}", path);
+            }
+        }
+    }
+}
+
+/// Returns at most one byte per call, and `Interrupted` on every other call.
+struct InterruptingReader<'a> {
+    data: &'a [u8],
+    interrupt: bool,
+}
+impl std::io::Read for InterruptingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(std::io::ErrorKind::Interrupted.into());
+        }
+        let n = buf.len().min(self.data.len()).min(1);
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+/// Returns `WouldBlock` once `available` bytes have been read.
+struct BlockingReader<'a> {
+    data: &'a [u8],
+    available: usize,
+}
+impl std::io::Read for BlockingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.available == 0 {
+            return Err(std::io::ErrorKind::WouldBlock.into());
+        }
+        let n = buf.len().min(self.data.len()).min(self.available);
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        self.available -= n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn read_interrupted_ok() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for path in [
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let data = std::fs::read(path).unwrap();
+        let mut reader = InterruptingReader {
+            data: &data,
+            interrupt: false,
+        };
+        let ply = p.read_ply(&mut reader).unwrap();
+        assert_ply_eq!(ply, read_file(path));
+    }
+}
+#[test]
+fn read_would_block_err() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for path in [
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let data = std::fs::read(path).unwrap();
+        // Stop in the header, within the payload and within the list of the last face.
+        for available in [10, data.len() - 30, data.len() - 2] {
+            let mut reader = BlockingReader {
+                data: &data,
+                available,
+            };
+            let err = p.read_ply(&mut reader).unwrap_err();
+            assert_eq!(
+                err.kind(),
+                std::io::ErrorKind::WouldBlock,
+                "{} {}: {}",
+                path,
+                available,
+                err
+            );
+        }
+    }
+}
+#[test]
+fn read_shared_parser() {
+    let p = std::sync::Arc::new(parser::Parser::<ply::DefaultElement>::new());
+    let path = "example_plys/house_2_ok_ascii.ply";
+    let expected = read_file(path);
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            let shared = p.clone();
+            let cloned = (*p).clone();
+            std::thread::spawn(move || {
+                let mut f = std::fs::File::open(path).unwrap();
+                if i % 2 == 0 {
+                    shared.read_ply(&mut f).unwrap()
+                } else {
+                    cloned.read_ply(&mut f).unwrap()
+                }
+            })
+        })
+        .collect();
+    for t in threads {
+        assert_ply_eq!(t.join().unwrap(), expected);
+    }
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_futures_io_ok() {
+    use futures::executor::block_on;
+    use futures::io::{BufReader, Cursor};
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for entry in std::fs::read_dir("example_plys").unwrap() {
+        let path = entry.unwrap().path();
+        let path = path.to_str().unwrap();
+        let bytes = std::fs::read(path).unwrap();
+        let expected = p.read_ply(&mut &bytes[..]);
+        let ply = block_on(p.read_ply_async(&mut Cursor::new(&bytes)));
+        match (ply, &expected) {
+            (Ok(ply), Ok(expected)) => assert_ply_eq!(ply, *expected),
+            (Err(e), Err(expected)) => assert_eq!(e.to_string(), expected.to_string(), "{}", path),
+            (ply, expected) => panic!("{}: {:?} != {:?}", path, ply.is_ok(), expected.is_ok()),
+        }
+        // Entries split across refills of the read buffer.
+        let mut reader = BufReader::with_capacity(3, Cursor::new(&bytes));
+        let ply = block_on(p.read_ply_async(&mut reader));
+        match (ply, &expected) {
+            (Ok(ply), Ok(expected)) => assert_ply_eq!(ply, *expected),
+            (Err(e), Err(expected)) => assert_eq!(e.to_string(), expected.to_string(), "{}", path),
+            (ply, expected) => panic!("{}: {:?} != {:?}", path, ply.is_ok(), expected.is_ok()),
+        }
+    }
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_futures_io_header_then_payload() {
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let path = "example_plys/house_2_ok_little_endian.ply";
+    let expected = read_file(path);
+    let mut cursor = Cursor::new(std::fs::read(path).unwrap());
+    let header = block_on(p.read_header_async(&mut cursor)).unwrap();
+    assert_eq!(header, expected.header);
+    let payload = block_on(p.read_payload_async(&mut cursor, &header)).unwrap();
+    assert_eq!(payload, expected.payload);
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_futures_io_async_read_ok() {
+    use futures::executor::block_on;
+    use futures::io::{AsyncRead, BufReader, Cursor};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    /// Implements `AsyncRead` only, like most sockets.
+    struct ReadOnly(Cursor<Vec<u8>>);
+    impl AsyncRead for ReadOnly {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+    let path = "example_plys/house_ok_ascii.ply";
+    let expected = read_file(path);
+    let bytes = std::fs::read(path).unwrap();
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let ply = block_on(p.read_ply_async(&mut Cursor::new(&bytes))).unwrap();
+    assert_ply_eq!(ply, expected);
+    let mut reader = BufReader::new(ReadOnly(Cursor::new(bytes)));
+    let ply = block_on(p.read_ply_async(&mut reader)).unwrap();
+    assert_ply_eq!(ply, expected);
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_futures_io_open_source_ok() {
+    use futures::channel::mpsc;
+    use futures::io::AsyncBufReadExt;
+    use futures::{FutureExt, TryStreamExt};
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for path in [
+        "example_plys/house_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        // Like a socket the other side keeps open, nothing is sent after the trailing bytes.
+        let (sender, receiver) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+        for chunk in std::fs::read(path).unwrap().chunks(16) {
+            sender.unbounded_send(Ok(chunk.to_vec())).unwrap();
+        }
+        sender.unbounded_send(Ok(b"trailing".to_vec())).unwrap();
+        let mut reader = receiver.into_async_read();
+        let ply = p
+            .read_ply_async(&mut reader)
+            .now_or_never()
+            .expect("waits for more bytes after the payload")
+            .unwrap();
+        assert_ply_eq!(ply, read_file(path));
+        let rest = reader.fill_buf().now_or_never().unwrap().unwrap();
+        assert_eq!(rest, b"trailing", "{}", path);
+        drop(sender);
+    }
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_futures_io_stream_elements() {
+    use futures::executor::block_on;
+    use futures::io::{AsyncReadExt, BufReader, Cursor};
+    use futures::StreamExt;
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for path in [
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/points_double_ok_big_endian.ply",
+        "example_plys/house_2_ok_ascii.ply",
+    ] {
+        let expected = read_file(path);
+        // A tiny buffer splits most elements.
+        let mut reader = BufReader::with_capacity(3, Cursor::new(std::fs::read(path).unwrap()));
+        let header = block_on(p.read_header_async(&mut reader)).unwrap();
+        for def in &header.elements {
+            let mut elements = p
+                .stream_payload_for_element(&mut reader, def, &header)
+                .unwrap();
+            let mut index = 0;
+            while let Some(element) = block_on(elements.next()) {
+                assert_eq!(
+                    element.unwrap(),
+                    expected.payload[&def.name][index],
+                    "{}",
+                    path
+                );
+                index += 1;
+            }
+            assert_eq!(index, expected.payload[&def.name].len());
+        }
+        let mut rest = Vec::new();
+        block_on(reader.read_to_end(&mut rest)).unwrap();
+        assert!(rest.is_empty(), "{}", path);
+    }
+
+    let path = "example_plys/points_truncated_err_little_endian.ply";
+    let mut reader = Cursor::new(std::fs::read(path).unwrap());
+    let header = block_on(p.read_header_async(&mut reader)).unwrap();
+    let vertices = p.stream_payload_for_element(&mut reader, &header.elements[0], &header);
+    let vertices: Vec<_> = block_on(vertices.unwrap().collect());
+    assert!(vertices.iter().all(|r| r.is_ok()));
+    // The file ends within the edges.
+    let edges = p.stream_payload_for_element(&mut reader, &header.elements[1], &header);
+    let results: Vec<_> = block_on(edges.unwrap().collect());
+    let err = results.last().unwrap().as_ref().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert!(results[..results.len() - 1].iter().all(|r| r.is_ok()));
+}
+/// Fails the first poll with `error`, then returns `data`.
+#[cfg(feature = "futures-io")]
+struct FailingOnceReader {
+    error: Option<std::io::Error>,
+    data: futures::io::Cursor<Vec<u8>>,
+}
+#[cfg(feature = "futures-io")]
+impl futures::io::AsyncRead for FailingOnceReader {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+        buf: &mut [u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        std::pin::Pin::new(&mut self.get_mut().data).poll_read(cx, buf)
+    }
+}
+#[cfg(feature = "futures-io")]
+impl futures::io::AsyncBufRead for FailingOnceReader {
+    fn poll_fill_buf(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+    ) -> std::task::Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+        match this.error.take() {
+            Some(e) => std::task::Poll::Ready(Err(e)),
+            None => std::pin::Pin::new(&mut this.data).poll_fill_buf(cx),
+        }
+    }
+    fn consume(self: std::pin::Pin<&mut Self>, amt: usize) {
+        std::pin::Pin::new(&mut self.get_mut().data).consume(amt)
+    }
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_futures_io_stream_error_err() {
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures::StreamExt;
+    let bytes = std::fs::read("example_plys/house_2_ok_little_endian.ply").unwrap();
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let mut reader = Cursor::new(bytes);
+    let header = block_on(p.read_header_async(&mut reader)).unwrap();
+    let mut reader = FailingOnceReader {
+        error: Some(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "reset",
+        )),
+        data: reader,
+    };
+    let mut vertices = p
+        .stream_payload_for_element(&mut reader, &header.elements[0], &header)
+        .unwrap();
+    // The error reaches the caller instead of the data of a second poll.
+    let err = block_on(vertices.next()).unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::ConnectionReset);
+    assert_element_error(&err, "vertex", 0);
+
+    // Interrupted polls are retried.
+    let mut reader = FailingOnceReader {
+        error: Some(std::io::ErrorKind::Interrupted.into()),
+        data: reader.data,
+    };
+    let mut vertices = p
+        .stream_payload_for_element(&mut reader, &header.elements[0], &header)
+        .unwrap();
+    assert!(block_on(vertices.next()).unwrap().is_ok());
+}
+/// Records the offset and length of every read.
+#[cfg(feature = "futures-io")]
+struct SpyReader {
+    inner: futures::io::Cursor<Vec<u8>>,
+    reads: Vec<(u64, usize)>,
+}
+#[cfg(feature = "futures-io")]
+impl futures::io::AsyncRead for SpyReader {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+        buf: &mut [u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let position = self.inner.position();
+        let poll = std::pin::Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let std::task::Poll::Ready(Ok(n)) = poll {
+            self.reads.push((position, n));
+        }
+        poll
+    }
+}
+#[cfg(feature = "futures-io")]
+impl futures::io::AsyncSeek for SpyReader {
+    fn poll_seek(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+        pos: std::io::SeekFrom,
+    ) -> std::task::Poll<std::io::Result<u64>> {
+        std::pin::Pin::new(&mut self.inner).poll_seek(cx, pos)
+    }
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_indexed_range_ok() {
+    use futures::executor::block_on;
+    let positions: Vec<[f32; 3]> = (0..1000).map(|i| [i as f32, 0.5, -1.0]).collect();
+    for encoding in [
+        ply::Encoding::BinaryLittleEndian,
+        ply::Encoding::BinaryBigEndian,
+    ] {
+        let mut ply = builder::point_cloud(&positions, None, None).unwrap();
+        ply.header.encoding = encoding;
+        let mut bytes = Vec::new();
+        writer::Writer::new()
+            .write_ply(&mut bytes, &mut ply)
+            .unwrap();
+        let header_len = bytes.len() as u64 - 12 * 1000;
+
+        let p = parser::Parser::<ply::DefaultElement>::new();
+        let spy = SpyReader {
+            inner: futures::io::Cursor::new(bytes),
+            reads: Vec::new(),
+        };
+        let mut indexed = block_on(p.open_indexed(spy)).unwrap();
+        assert_eq!(indexed.header(), &ply.header);
+        let vertices = block_on(indexed.read_range("vertex", 100..110)).unwrap();
+        assert_eq!(vertices, ply.payload["vertex"][100..110]);
+        assert!(block_on(indexed.read_range("vertex", 0..0))
+            .unwrap()
+            .is_empty());
+        let err = block_on(indexed.read_range("vertex", 999..1001)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let err = block_on(indexed.read_range("face", 0..1)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let reads = indexed.into_inner().reads;
+        assert_eq!(reads, vec![(0, 1024), (header_len + 1200, 120)]);
+    }
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_indexed_unsupported_err() {
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for (path, reason) in [
+        (
+            "example_plys/house_2_ok_little_endian.ply",
+            "property 'vertex_indices' of element 'face' is a list",
+        ),
+        ("example_plys/house_2_ok_ascii.ply", "ascii payloads"),
+    ] {
+        let bytes = std::fs::read(path).unwrap();
+        let err = block_on(p.open_indexed(Cursor::new(bytes))).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        assert!(err.to_string().contains(reason), "{}", err);
+    }
+    let bytes = std::fs::read("example_plys/points_ok_little_endian.ply").unwrap();
+    let expected = read_file("example_plys/points_ok_little_endian.ply");
+    let mut indexed = block_on(p.open_indexed(Cursor::new(bytes))).unwrap();
+    let edges = block_on(indexed.read_range("edge", 0..2)).unwrap();
+    assert_eq!(edges, expected.payload["edge"]);
+}
+/// Connects to a server that sends `data`, then keeps the connection open until `done` is dropped.
+fn stalling_connection(data: Vec<u8>) -> (std::net::TcpStream, std::sync::mpsc::Sender<()>) {
+    use std::io::Write;
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (done, wait) = std::sync::mpsc::channel::<()>();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.write_all(&data).unwrap();
+        let _ = wait.recv();
+    });
+    (std::net::TcpStream::connect(address).unwrap(), done)
+}
+#[test]
+fn read_with_timeout_ok() {
+    let path = "example_plys/house_2_ok_little_endian.ply";
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let (mut stream, done) = stalling_connection(std::fs::read(path).unwrap());
+    // The server closes the connection after the file.
+    drop(done);
+    let ply = p
+        .read_ply_with_timeout(&mut stream, std::time::Duration::from_secs(10))
+        .unwrap();
+    assert_ply_eq!(ply, read_file(path));
+    assert_eq!(stream.read_timeout().unwrap(), None);
+}
+#[test]
+fn read_with_timeout_err() {
+    let text = std::fs::read_to_string("example_plys/house_ok_ascii.ply").unwrap();
+    let end = text.find("end_header\n").unwrap() + "end_header\n".len();
+    let mut data = text[..end].to_string();
+    data.extend(text[end..].split_inclusive('\n').take(2));
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let (mut stream, _done) = stalling_connection(data.into_bytes());
+    let previous = Some(std::time::Duration::from_secs(60));
+    stream.set_read_timeout(previous).unwrap();
+    let err = p
+        .read_ply_with_timeout(&mut stream, std::time::Duration::from_millis(100))
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    assert_element_error(&err, "vertex", 2);
+    // The timeout of the stream is restored.
+    assert_eq!(stream.read_timeout().unwrap(), previous);
+}
+/// Rejects vertices with `x == y` once all properties are set.
+#[derive(Debug)]
+struct OffDiagonalVertex {
+    x: f32,
+    y: f32,
+}
+impl ply::PropertyAccess for OffDiagonalVertex {
+    fn new() -> Self {
+        OffDiagonalVertex { x: 0.0, y: 0.0 }
+    }
+    fn set_property(&mut self, key: &str, property: ply::Property) {
+        match (key, property) {
+            ("x", ply::Property::Float(v)) => self.x = v,
+            ("y", ply::Property::Float(v)) => self.y = v,
+            _ => (),
+        }
+    }
+    fn finalize(&mut self, element_def: &ply::ElementDef) -> std::io::Result<()> {
+        if self.x == self.y {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{} on the diagonal", element_def.name),
+            ));
+        }
+        Ok(())
+    }
+}
+#[test]
+fn read_finalize_err() {
+    for path in &[
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let p = parser::Parser::<OffDiagonalVertex>::new();
+        let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+        let header = p.read_header(&mut f).unwrap();
+        let err = p
+            .read_payload_for_element(&mut f, &header.elements[0], &header)
+            .unwrap_err();
+        assert_element_error(&err, "vertex", 2);
+        let message = err.to_string();
+        assert!(message.contains("vertex on the diagonal"), "{}", message);
+    }
+}
+/// Knows `x` and `y` only, rejects other properties.
+#[derive(Debug, Default, PartialEq)]
+struct XyVertex {
+    x: f32,
+    y: f32,
+}
+impl ply::PropertyAccess for XyVertex {
+    fn new() -> Self {
+        XyVertex::default()
+    }
+    fn try_set_property(
+        &mut self,
+        key: &str,
+        property: ply::Property,
+    ) -> Result<(), ply::PropertySetError> {
+        match (key, property) {
+            ("x", ply::Property::Float(v)) => self.x = v,
+            ("y", ply::Property::Float(v)) => self.y = v,
+            (k, _) => return Err(ply::PropertySetError::new(&format!("unknown key {}", k))),
+        }
+        Ok(())
+    }
+}
+#[test]
+fn read_rejected_property_err() {
+    for path in &[
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let p = parser::Parser::<XyVertex>::new();
+        let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+        let header = p.read_header(&mut f).unwrap();
+        let err = p
+            .read_payload_for_element(&mut f, &header.elements[0], &header)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_element_error(&err, "vertex", 0);
+        let message = err.to_string();
+        assert!(
+            message.contains("Property 'z' was rejected: unknown key z"),
+            "{}",
+            message
+        );
+        let err = p
+            .bind(header)
+            .read_ply_checked(&mut std::fs::File::open(path).unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("'z' was rejected"), "{}", err);
+    }
+    let ply = read_file("example_plys/house_2_ok_ascii.ply");
+    let err = ply.get_as::<XyVertex>("vertex").unwrap().unwrap_err();
+    assert_eq!((err.index, err.key.as_str()), (0, "z"));
+    assert_eq!(err.description, "unknown key z");
+}
+#[test]
+fn read_rejected_property_skipped() {
+    let options = parser::ParserOptions::new().skip_rejected_properties(true);
+    let p = parser::Parser::<XyVertex>::with_options(options);
+    for path in &[
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+        let header = p.read_header(&mut f).unwrap();
+        let vertices = p
+            .read_payload_for_element(&mut f, &header.elements[0], &header)
+            .unwrap();
+        assert_eq!(vertices.len(), 5);
+        assert_eq!(vertices[1], XyVertex { x: -1.0, y: 1.0 });
+    }
+}
+#[test]
+fn read_payload_for_foreign_element_def_err() {
+    let path = "example_plys/house_2_ok_ascii.ply";
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+    let header = p.read_header(&mut f).unwrap();
+
+    let mut bogus = ply::ElementDef::new("edge");
+    bogus.count = header.elements[0].count;
+    let mut changed = header.elements[0].clone();
+    changed.properties.pop();
+    let mut commented = header.elements[0].clone();
+    commented.comments.push("only a comment".to_string());
+    for (def, message) in [
+        (&bogus, "Element 'edge' isn't defined in the header."),
+        (
+            &changed,
+            "Element 'vertex' doesn't match its definition in the header.",
+        ),
+    ] {
+        let err = p
+            .read_payload_for_element(&mut f, def, &header)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), message);
+        let err = p
+            .read_payload_for_element_into(&mut f, def, &header, &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.to_string(), message);
+    }
+    // Nothing was read, comments don't matter.
+    let vertices = p
+        .read_payload_for_element(&mut f, &commented, &header)
+        .unwrap();
+    assert_eq!(vertices.len(), header.elements[0].count as usize);
+}
+/// Remembers the definition it was created from, and the properties set afterwards.
+#[derive(Debug)]
+struct DefRecorder {
+    def: Option<ply::ElementDef>,
+    keys: Vec<String>,
+}
+impl ply::PropertyAccess for DefRecorder {
+    fn new() -> Self {
+        DefRecorder {
+            def: None,
+            keys: Vec::new(),
+        }
+    }
+    fn new_from_def(element_def: &ply::ElementDef) -> Self {
+        DefRecorder {
+            def: Some(element_def.clone()),
+            keys: Vec::with_capacity(element_def.properties.len()),
+        }
+    }
+    fn set_property(&mut self, key: &str, _property: ply::Property) {
+        self.keys.push(key.to_string());
+    }
+}
+#[test]
+fn read_new_from_def_ok() {
+    for path in &[
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let p = parser::Parser::<DefRecorder>::new();
+        let mut f = std::fs::File::open(path).unwrap();
+        let ply = p.read_ply(&mut f).unwrap();
+        for def in &ply.header.elements {
+            let names: Vec<&str> = def.properties.iter().map(|p| p.name.as_str()).collect();
+            for e in &ply.payload[&def.name] {
+                assert_eq!(e.def.as_ref(), Some(def));
+                assert_eq!(e.keys, names);
+            }
+        }
+    }
+}
+#[test]
+fn read_element_from_slice_ok() {
+    let path = "example_plys/house_2_ok_little_endian.ply";
+    let ply = read_file(path);
+    let bytes = std::fs::read(path).unwrap();
+    let end = b"end_header\n";
+    let mut offset = bytes.windows(end.len()).position(|w| w == end).unwrap() + end.len();
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for def in &ply.header.elements {
+        for expected in &ply.payload[&def.name] {
+            let (element, len) = p
+                .read_little_endian_element_from_slice(&bytes[offset..], def)
+                .unwrap();
+            assert_eq!(&element, expected);
+            offset += len;
+        }
+    }
+    assert_eq!(offset, bytes.len());
+}
+#[test]
+fn read_element_from_slice_err() {
+    let path = "example_plys/house_2_ok_little_endian.ply";
+    let ply = read_file(path);
+    let bytes = std::fs::read(path).unwrap();
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    // The last face, cut short by one byte.
+    let face = &ply.header.elements[1];
+    let start = bytes.len() - 13;
+    let (element, len) = p
+        .read_binary_element_from_slice::<parser::LittleEndian>(&bytes[start..], face)
+        .unwrap();
+    assert_eq!(len, 13);
+    assert_eq!(&element, ply.payload["face"].last().unwrap());
+    for cut in [1, 12] {
+        let err = p
+            .read_little_endian_element_from_slice(&bytes[start..bytes.len() - cut], face)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(
+            err.to_string().starts_with(&format!(
+                "Slice of {} bytes ends within element 'face'",
+                13 - cut
+            )),
+            "{}",
+            err
+        );
+    }
+}
+#[test]
+fn bound_parser_ok() {
+    for path in [
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/points_ok_little_endian.ply",
+    ] {
+        let p = parser::Parser::<ply::DefaultElement>::new();
+        let expected = p.read_ply(&mut std::fs::File::open(path).unwrap()).unwrap();
+        let bound = p.bind(expected.header.clone());
+        for _ in 0..2 {
+            let ply = bound
+                .read_ply_checked(&mut std::fs::File::open(path).unwrap())
+                .unwrap();
+            assert_eq!(ply, expected);
+        }
+        let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+        p.read_header(&mut f).unwrap();
+        let payload = bound.read_payload_trusted(&mut f).unwrap();
+        assert_eq!(payload, expected.payload);
+    }
+}
+#[test]
+fn bound_parser_schema_mismatch_err() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let path = "example_plys/house_2_ok_little_endian.ply";
+    let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+    let header = p.read_header(&mut f).unwrap();
+
+    let mut renamed = header.clone();
+    renamed.elements[1].name = "polygon".to_string();
+    let mut retyped = header.clone();
+    retyped.elements[0].properties[0].data_type =
+        ply::PropertyType::Scalar(ply::ScalarType::Double);
+    let mut counted = header.clone();
+    counted.elements[0].count += 1;
+    counted.comments.push("other frame".to_string());
+    for (bound, message) in [
+        (
+            renamed,
+            "the elements are [\"vertex\", \"face\"], expected [\"vertex\", \"polygon\"]",
+        ),
+        (retyped, "the properties of element 'vertex' differ"),
+    ] {
+        let err = p
+            .bind(bound)
+            .read_ply_checked(&mut std::fs::File::open(path).unwrap())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            format!("Header doesn't match the bound schema: {}.", message)
+        );
+    }
+    let err = p
+        .bind(header)
+        .read_ply_checked(&mut std::fs::File::open("example_plys/house_2_ok_ascii.ply").unwrap())
+        .unwrap_err();
+    assert!(err.to_string().contains("the encoding is ascii"), "{}", err);
+    // Counts and comments may differ.
+    let ply = p
+        .bind(counted)
+        .read_ply_checked(&mut std::fs::File::open(path).unwrap())
+        .unwrap();
+    assert_eq!(ply.payload["vertex"].len(), 5);
+}
+fn write_frames(name: &str, frames: &[(u64, usize)]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ply_rs_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for &(number, len) in frames {
+        let positions = vec![[number as f32, 0.0, 1.0]; len];
+        let mut ply = builder::point_cloud(&positions, None, None).unwrap();
+        let mut f = std::fs::File::create(dir.join(format!("frame_{:04}.ply", number))).unwrap();
+        writer::Writer::new().write_ply(&mut f, &mut ply).unwrap();
+    }
+    dir
+}
+#[test]
+fn sequence_ok() {
+    let dir = write_frames("sequence_ok", &[(2, 3), (0, 1), (1, 2)]);
+    std::fs::write(dir.join("notes.txt"), "not a frame").unwrap();
+    for prefetch in [false, true] {
+        for source in [dir.clone(), dir.join("frame_*.ply")] {
+            let frames = sequence::PlySequence::<ply::DefaultElement>::open(&source)
+                .unwrap()
+                .prefetch(prefetch);
+            assert_eq!(frames.paths().count(), 3);
+            let mut read = 0;
+            for (number, frame) in frames.into_iter().enumerate() {
+                let (path, ply) = frame.unwrap();
+                assert_eq!(path, dir.join(format!("frame_{:04}.ply", number)));
+                assert_eq!(ply.payload["vertex"].len(), number + 1);
+                assert_eq!(
+                    ply.payload["vertex"][0]["x"],
+                    ply::Property::Float(number as f32)
+                );
+                read += 1;
+            }
+            assert_eq!(read, 3);
+        }
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+#[test]
+fn sequence_missing_err() {
+    let dir = write_frames("sequence_missing", &[(1, 1), (2, 1), (5, 1)]);
+    let frames: Vec<_> = sequence::PlySequence::<ply::DefaultElement>::open(&dir)
+        .unwrap()
+        .prefetch(true)
+        .into_iter()
+        .collect();
+    assert_eq!(frames.len(), 4);
+    assert!(frames[0].is_ok() && frames[1].is_ok() && frames[3].is_ok());
+    let err = frames[2].as_ref().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Files 3 to 4 of the sequence are missing before '{}'.",
+            dir.join("frame_0005.ply").display()
+        )
+    );
+    let err = sequence::PlySequence::<ply::DefaultElement>::open(dir.join("other_*.ply"))
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let err = sequence::PlySequence::<ply::DefaultElement>::open(dir.join("frame.ply"))
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+#[test]
+fn sequence_incompatible_err() {
+    let dir = write_frames("sequence_incompatible", &[(0, 2), (2, 2)]);
+    let colors = [[255, 0, 0]];
+    let mut ply = builder::point_cloud(&[[1.0, 2.0, 3.0]], Some(&colors), None).unwrap();
+    let path = dir.join("frame_0001.ply");
+    let mut f = std::fs::File::create(&path).unwrap();
+    writer::Writer::new().write_ply(&mut f, &mut ply).unwrap();
+    let first = read_file(dir.join("frame_0000.ply").to_str().unwrap());
+    assert!(!ply.header.compatible_with(&first.header));
+    assert!(first.header.compatible_with(&first.header));
+
+    let frames: Vec<_> = sequence::PlySequence::<ply::DefaultElement>::open(&dir)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(frames.len(), 3);
+    assert!(frames[0].is_ok() && frames[2].is_ok());
+    let err = frames[1].as_ref().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{}: Header doesn't match the bound schema: the properties of element 'vertex' differ.",
+            path.display()
+        )
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+fn header_syntax_error(header: &str) -> (std::io::Error, parser::HeaderSyntaxError) {
+    let txt = format!("ply\n{}end_header\n", header);
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let err = p.read_ply(&mut txt.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let syntax = err
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<parser::HeaderSyntaxError>())
+        .unwrap()
+        .clone();
+    (err, syntax)
+}
+#[test]
+fn read_header_syntax_error_column() {
+    let (err, syntax) =
+        header_syntax_error("format ascii 1.0\nelement vertex 1\nproperty flaot x\n");
+    assert_eq!((syntax.line, syntax.column), (4, 10));
+    assert_eq!(syntax.text, "property flaot x");
+    let message = err.to_string();
+    assert!(
+        message.starts_with(
+            "Line 4, column 10: Couldn't parse line.\n\tString: 'property flaot x'\n\t                  ^\n\tError: expected "
+        ),
+        "{}",
+        message
+    );
+
+    let (_, syntax) = header_syntax_error("format ascii 1,0\n");
+    assert_eq!((syntax.line, syntax.column), (2, 15));
+    let (_, syntax) =
+        header_syntax_error("format ascii 1.0\nelement vertex 1\n  property flaot x\r\n");
+    assert_eq!((syntax.line, syntax.column), (4, 12));
+    assert_eq!(syntax.text, "  property flaot x");
+}
+/// Counts the calls made by the parser, keeps the elements in a `Vec`.
+#[derive(Default)]
+struct CountingStore {
+    reserved: Option<usize>,
+    finished: bool,
+    elements: Vec<ply::DefaultElement>,
+}
+impl ply::PayloadStore<ply::DefaultElement> for CountingStore {
+    fn reserve(&mut self, count: usize) {
+        assert!(self.reserved.is_none() && self.elements.is_empty());
+        self.reserved = Some(count);
+    }
+    fn push(&mut self, element: ply::DefaultElement) {
+        assert!(!self.finished);
+        self.elements.push(element);
+    }
+    fn finish(&mut self) {
+        assert!(!self.finished);
+        self.finished = true;
+    }
+}
+/// Elements of all types live in one shared arena, the store only keeps their indices.
+struct ArenaStore<'a> {
+    arena: &'a std::cell::RefCell<Vec<ply::DefaultElement>>,
+    indices: Vec<usize>,
+}
+impl ply::PayloadStore<ply::DefaultElement> for ArenaStore<'_> {
+    fn push(&mut self, element: ply::DefaultElement) {
+        let mut arena = self.arena.borrow_mut();
+        self.indices.push(arena.len());
+        arena.push(element);
+    }
+}
+#[test]
+fn read_payload_into_store_same_as_vec() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for path in [
+        "example_plys/greg_turk_example1_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/all_atomic_types_ok_ascii.ply",
+    ] {
+        let expected = read_file(path);
+        let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+        let header = p.read_header(&mut f).unwrap();
+        let stores = p
+            .read_payload_into_store(&mut f, &header, |_| CountingStore::default())
+            .unwrap();
+        assert_eq!(stores.len(), expected.payload.len());
+        for (def, (name, store)) in header.elements.iter().zip(&stores) {
+            assert_eq!(name, &def.name);
+            assert_eq!(store.reserved, Some(def.count as usize));
+            assert!(store.finished);
+            assert_eq!(store.elements, expected.payload[name]);
+        }
+
+        let arena = std::cell::RefCell::new(Vec::new());
+        let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+        let header = p.read_header(&mut f).unwrap();
+        let stores = p
+            .read_payload_into_store(&mut f, &header, |_| ArenaStore {
+                arena: &arena,
+                indices: Vec::new(),
+            })
+            .unwrap();
+        let arena = arena.borrow();
+        for (name, store) in &stores {
+            let elements: Vec<_> = store.indices.iter().map(|&i| arena[i].clone()).collect();
+            assert_eq!(elements, expected.payload[name]);
+        }
+    }
+}
+#[test]
+fn read_payload_for_element_into_store_truncated_err() {
+    let path = "example_plys/triangles_truncated_err_ascii.ply";
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let mut f = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+    let header = p.read_header(&mut f).unwrap();
+    let mut store = CountingStore::default();
+    let mut result = Ok(());
+    for def in &header.elements {
+        result = p.read_payload_for_element_into_store(&mut f, def, &header, &mut store);
+        if result.is_err() {
+            break;
+        }
+        store = CountingStore::default();
+    }
+    let mut f = std::fs::File::open(path).unwrap();
+    let expected = p.read_ply(&mut f).unwrap_err();
+    assert_eq!(result.unwrap_err().to_string(), expected.to_string());
+    assert!(!store.finished);
+}
+#[test]
+fn property_groups_ok() {
+    use ply::PropertyAccess;
+    let ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    let vertex_def = &ply.header.elements[0];
+    let position = vertex_def.group(&["x", "y", "z"]).unwrap();
+    assert_eq!(position.element, "vertex");
+    assert_eq!(position.scalar_type, ply::ScalarType::Float);
+    assert_eq!(position.indices, vec![0, 1, 2]);
+    assert!(position.contiguous);
+    let color = vertex_def.group(&["red", "green", "blue"]).unwrap();
+    assert_eq!(color.scalar_type, ply::ScalarType::UChar);
+    assert_eq!(color.indices, vec![3, 4, 5]);
+
+    let vertex = &ply.payload["vertex"][5];
+    assert_eq!(vertex.get_group_f32(&position), Some([1.0, 0.0, 1.0]));
+    assert_eq!(vertex.get_group_f64(&color), Some([0.0, 0.0, 255.0]));
+    // The array has to fit the group.
+    assert_eq!(vertex.get_group_f32::<2>(&position), None);
+
+    let positions = ply.group_buffer_f32(&position).unwrap();
+    assert_eq!(positions.len(), 8 * 3);
+    assert_eq!(&positions[15..18], &[1.0, 0.0, 1.0]);
+
+    // Same attribute on another element.
+    let edge_color = ply.header.elements[2]
+        .group(&["red", "green", "blue"])
+        .unwrap();
+    assert_eq!(edge_color.element, "edge");
+    assert_eq!(ply.group_buffer_f32(&edge_color).unwrap().len(), 5 * 3);
+}
+#[test]
+fn property_groups_err() {
+    use ply::PropertyAccess;
+    let ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    let vertex_def = &ply.header.elements[0];
+    // Missing component.
+    assert_eq!(vertex_def.group(&["x", "y", "w"]), None);
+    // Mixed types.
+    assert_eq!(vertex_def.group(&["x", "red"]), None);
+    // Lists aren't components.
+    assert_eq!(ply.header.elements[1].group(&["vertex_index"]), None);
+    assert_eq!(vertex_def.group(&[]), None);
+
+    // A value missing in an element.
+    let position = vertex_def.group(&["x", "y", "z"]).unwrap();
+    let mut vertex = ply.payload["vertex"][0].clone();
+    vertex.remove("z");
+    assert_eq!(vertex.get_group_f32::<3>(&position), None);
+
+    // Components in another order than declared.
+    let ply = read_file("example_plys/unusual_property_order_ok_ascii.ply");
+    let position = ply.header.elements[0].group(&["x", "y", "z"]).unwrap();
+    assert_eq!(position.indices, vec![2, 4, 0]);
+    assert!(!position.contiguous);
+    assert_eq!(
+        ply.group_buffer_f32(&position).unwrap(),
+        vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+    );
+}
+
+#[test]
+fn property_index_ok() {
+    use ply::{PropertyDef, PropertyType, ScalarType};
+    let ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    let mut def = ply.header.elements[0].clone();
+    assert_eq!(def.property_index("x"), Some(0));
+    assert_eq!(def.property_index("blue"), Some(5));
+    assert_eq!(def.property_index("w"), None);
+    assert_eq!(def.property("red").unwrap().name, "red");
+
+    let float = PropertyType::Scalar(ScalarType::Float);
+    def.push_property(PropertyDef::new("w", float.clone()));
+    assert_eq!(def.property_index("w"), Some(6));
+    assert!(def.rename_property("w", "weight"));
+    assert!(!def.rename_property("w", "weight"));
+    assert_eq!(def.property_index("weight"), Some(6));
+    assert_eq!(def.remove_property("x").unwrap().name, "x");
+    assert_eq!(def.property_index("x"), None);
+    assert_eq!(def.property_index("weight"), Some(5));
+
+    // Changing the properties directly doesn't lead to wrong positions.
+    def.properties.remove(0);
+    assert_eq!(def.property_index("weight"), Some(4));
+    def.properties[0].name = "depth".to_string();
+    assert_eq!(def.property_index("depth"), Some(0));
+    // The index doesn't take part in comparisons.
+    let mut fresh = ply::ElementDef::new(&def.name);
+    fresh.count = def.count;
+    fresh.properties = def.properties.clone();
+    assert_eq!(fresh, def);
+}
+#[test]
+fn read_dotted_names_ok() {
+    let txt = "ply\nformat ascii 1.0\nelement vertex.data 1\nproperty float scale.x\nproperty float pos-x\nproperty list uchar int f_rest.0\nend_header\n1 2 1 3\n";
+    let ply = parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut txt.as_bytes())
+        .unwrap();
+    let def = &ply.header.elements[0];
+    assert_eq!(def.name, "vertex.data");
+    let names: Vec<&str> = def.properties.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["scale.x", "pos-x", "f_rest.0"]);
+    let vertex = &ply.payload["vertex.data"][0];
+    assert_eq!(vertex["scale.x"], ply::Property::Float(1.0));
+    assert_eq!(vertex["pos-x"], ply::Property::Float(2.0));
+
+    // Other printable characters only with `NameCharacters::Printable`.
+    let txt = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float rot(x)\nend_header\n1\n";
+    let err = parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut txt.as_bytes())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("Line 4, column 19"), "{}", err);
+    let options = parser::ParserOptions::new().name_characters(parser::NameCharacters::Printable);
+    let ply = parser::Parser::<ply::DefaultElement>::with_options(options)
+        .read_ply(&mut txt.as_bytes())
+        .unwrap();
+    assert_eq!(
+        ply.payload["vertex"][0]["rot(x)"],
+        ply::Property::Float(1.0)
+    );
+}
+#[test]
+fn read_vertices_ok() {
+    let points = read_vertices("example_plys/greg_turk_example1_ok_ascii.ply").unwrap();
+    assert_eq!(points.len(), 8);
+    assert_eq!(points[6], [1.0, 1.0, 1.0]);
+    let points = read_vertices("example_plys/triangles_ok_little_endian.ply").unwrap();
+    assert_eq!(points.len(), 4);
+    // Double precision, big endian.
+    let points = read_vertices("example_plys/points_double_ok_big_endian.ply").unwrap();
+    assert_eq!(
+        points,
+        vec![[0.5, -1.25, 1e10], [1.0, 2.0, 3.0], [-0.1, 0.0, 7.5]]
+    );
+
+    let (points, colors) =
+        read_vertices_with_colors("example_plys/points_double_ok_big_endian.ply").unwrap();
+    assert_eq!(points.len(), 3);
+    assert_eq!(colors, vec![[255, 0, 0], [0, 255, 0], [0, 0, 255]]);
+    let (_, colors) =
+        read_vertices_with_colors("example_plys/greg_turk_example2_ok_ascii.ply").unwrap();
+    assert_eq!(colors[4], [0, 0, 255]);
+}
+#[test]
+fn read_vertices_err() {
+    let err = read_vertices("example_plys/header_min_ok_ascii.ply").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("no `vertex` element"), "{}", err);
+    // Capitalized `X` and `Y`.
+    let err = read_vertices("example_plys/mixed_case_ok_ascii.ply").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("`x`, `y` and `z`"), "{}", err);
+    // No colors.
+    let err = read_vertices_with_colors("example_plys/house_2_ok_little_endian.ply").unwrap_err();
+    assert!(
+        err.to_string().contains("`red`, `green` and `blue`"),
+        "{}",
+        err
+    );
+    let err = read_vertices("example_plys/points_truncated_err_little_endian.ply").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+#[test]
+fn read_digit_names_ok() {
+    let ply = read_file("example_plys/digit_names_ok_ascii.ply");
+    let def = &ply.header.elements[0];
+    assert_eq!(def.name, "3dpoints");
+    assert_eq!(def.count, 10);
+    assert_eq!(def.properties[1].name, "0_weight");
+    assert_eq!(ply.payload["3dpoints"].len(), 10);
+    assert_eq!(
+        ply.payload["3dpoints"][9]["0_weight"],
+        ply::Property::Float(0.9)
+    );
+    let err = parser::Parser::<ply::DefaultElement>::new()
+        .read_header(&mut &b"ply\nformat ascii 1.0\nelement 3dpoints\nend_header\n"[..])
+        .unwrap_err();
+    assert!(err.to_string().contains("Line 3"), "{}", err);
+}
+#[test]
+fn read_ascii_payload_line_err() {
+    // 8 header lines, 3 vertices, the second face is corrupted.
+    let txt = "ply\nformat ascii 1.0\ncomment a\nelement vertex 3\nproperty float x\nelement face 2\nproperty list uchar int vertex_indices\nend_header\n1\n2\n3\n3 0 1 2\n3 0 x 2\n";
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let err = p.read_ply(&mut txt.as_bytes()).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Element 'face' #1: File line 13, payload line 5:"),
+        "{}",
+        err
+    );
+    assert_eq!(txt.lines().nth(12), Some("3 0 x 2"));
+
+    // Without the header, only the payload line is known.
+    let mut reader = txt.as_bytes();
+    let header = p.read_header(&mut reader).unwrap();
+    let err = p.read_payload(&mut reader, &header).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Element 'face' #1: Payload line 5:"),
+        "{}",
+        err
+    );
+}
+#[test]
+fn read_std_maps_ok() {
+    use std::collections::{BTreeMap, HashMap};
+    fn read_as<E: ply::PropertyAccess>(path: &str) -> ply::Ply<E> {
+        let mut f = std::fs::File::open(path).unwrap();
+        parser::Parser::<E>::new().read_ply(&mut f).unwrap()
+    }
+    fn write<E: ply::PropertyAccess>(ply: &ply::Ply<E>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        writer::Writer::new()
+            .write_ply_unchecked(&mut buf, ply)
+            .unwrap();
+        buf
+    }
+    for path in [
+        "example_plys/greg_turk_example2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/all_atomic_types_ok_ascii.ply",
+    ] {
+        let linked = read_as::<ply::DefaultElement>(path);
+        let hashed = read_as::<HashMap<String, ply::Property>>(path);
+        let sorted = read_as::<BTreeMap<String, ply::Property>>(path);
+        for (name, elements) in &linked.payload {
+            for (i, element) in elements.iter().enumerate() {
+                let expected: HashMap<_, _> = element.clone().into_iter().collect();
+                assert_eq!(hashed.payload[name][i], expected, "{}", path);
+                let keys: Vec<_> = sorted.payload[name][i].keys().collect();
+                let mut sorted_keys: Vec<_> = element.keys().collect();
+                sorted_keys.sort();
+                assert_eq!(keys, sorted_keys);
+                assert!(sorted.payload[name][i]
+                    .iter()
+                    .all(|(k, v)| element[k] == *v));
+            }
+        }
+        // The writer follows the header, whatever the map order.
+        let expected = write(&linked);
+        assert_eq!(write(&hashed), expected, "{}", path);
+        assert_eq!(write(&sorted), expected, "{}", path);
+    }
+}
+#[test]
+fn read_multiline_ascii_elements_ok() {
+    // Neither ok nor err, only readable with the option.
+    let path = "example_plys/triangles_wrapped_ascii.ply";
+    let p = parser::Parser::<ply::DefaultElement>::with_options(
+        parser::ParserOptions::new().multiline_ascii_elements(true),
+    );
+    let ply = p.read_ply(&mut std::fs::File::open(path).unwrap()).unwrap();
+    let mut f = std::fs::File::open("example_plys/triangles_ok_ascii.ply").unwrap();
+    let expected = parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut f)
+        .unwrap();
+    assert_eq!(ply.payload, expected.payload);
+    // Entries split across refills of the read buffer.
+    let bytes = std::fs::read(path).unwrap();
+    let mut reader = std::io::BufReader::with_capacity(3, &bytes[..]);
+    let ply = p.read_ply_buffered(&mut reader).unwrap();
+    assert_eq!(ply.payload, expected.payload);
+
+    // Strict by default.
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    assert!(p.read_ply(&mut std::fs::File::open(path).unwrap()).is_err());
+}
+#[test]
+fn read_multiline_ascii_elements_err() {
+    // 7 header lines, the second face wraps and ends early.
+    let txt = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nelement face 2\nproperty list uchar int vertex_indices\nend_header\n1\n2\n3\n3 0\n1 2\n4 0\n1\n";
+    let p = parser::Parser::<ply::DefaultElement>::with_options(
+        parser::ParserOptions::new().multiline_ascii_elements(true),
+    );
+    let err = p.read_ply(&mut txt.as_bytes()).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Element 'face' #1: File line 14, payload line 7:"),
+        "{}",
+        err
+    );
+    assert_eq!(txt.lines().nth(13), Some("1"));
+
+    // Invalid values are reported on the line they are on.
+    let txt = txt.replace("1 2\n", "1 x\n");
+    let err = p.read_ply(&mut txt.as_bytes()).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Element 'face' #0: File line 12, payload line 5:"),
+        "{}",
+        err
+    );
+}
+#[test]
+fn read_ply_buffered_leaves_trailing_bytes() {
+    use std::io::Read;
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for path in [
+        "example_plys/house_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/points_double_ok_big_endian.ply",
+    ] {
+        let expected = read_file(path);
+        let mut bytes = std::fs::read(path).unwrap();
+        let trailer: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        bytes.extend_from_slice(&trailer);
+        let mut reader = std::io::BufReader::with_capacity(64, &bytes[..]);
+        let ply = p.read_ply_buffered(&mut reader).unwrap();
+        assert_ply_eq!(ply, expected);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, trailer, "{}", path);
+    }
+}
+#[test]
+fn read_ply_from_path_ok() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let path = "example_plys/house_2_ok_little_endian.ply";
+    let ply = p.read_ply_from_path(path).unwrap();
+    assert_ply_eq!(ply, read_file(path));
+}
+#[test]
+fn read_ply_from_path_err() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let path = "example_plys/triangles_truncated_err_ascii.ply";
+    let err = p.read_ply_from_path(path).unwrap_err();
+    let expected = p
+        .read_ply(&mut std::fs::File::open(path).unwrap())
+        .unwrap_err();
+    assert_eq!(err.kind(), expected.kind());
+    assert_eq!(err.to_string(), format!("{}: {}", path, expected));
+
+    let err = p
+        .read_ply_from_path("example_plys/missing.ply")
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(
+        err.to_string().starts_with("example_plys/missing.ply: "),
+        "{}",
+        err
+    );
+}
+#[test]
+fn read_ply_from_slice_ok() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for entry in std::fs::read_dir("example_plys").unwrap() {
+        let path = entry.unwrap().path();
+        let path = path.to_str().unwrap();
+        let bytes = std::fs::read(path).unwrap();
+        let expected = p.read_ply(&mut &bytes[..]);
+        match (p.read_ply_from_slice(&bytes), expected) {
+            (Ok(ply), Ok(expected)) => assert_ply_eq!(ply, expected),
+            (Err(e), Err(expected)) => assert_eq!(e.kind(), expected.kind(), "{}", path),
+            (ply, expected) => panic!("{}: {:?} != {:?}", path, ply.is_ok(), expected.is_ok()),
+        }
+    }
+}
+#[test]
+fn read_ply_from_slice_err() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let bytes = std::fs::read("example_plys/points_truncated_err_little_endian.ply").unwrap();
+    let err = p.read_ply_from_slice(&bytes).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    let prefix = format!("Byte offset {}: ", bytes.len());
+    assert!(err.to_string().starts_with(&prefix), "{}", err);
+}
+#[test]
+fn read_ply_with_visitor_ok() {
+    #[derive(Default)]
+    struct Bounds {
+        min_x: f32,
+        max_x: f32,
+        elements: Vec<(String, usize)>,
+    }
+    impl ply::PlyVisitor<ply::DefaultElement> for Bounds {
+        fn begin_element(&mut self, def: &ply::ElementDef) -> std::io::Result<()> {
+            self.elements.push((def.name.clone(), 0));
+            Ok(())
+        }
+        fn element(
+            &mut self,
+            def: &ply::ElementDef,
+            element: &ply::DefaultElement,
+        ) -> std::io::Result<()> {
+            self.elements.last_mut().unwrap().1 += 1;
+            if def.name == "vertex" {
+                if let ply::Property::Float(x) = element["x"] {
+                    self.min_x = self.min_x.min(x);
+                    self.max_x = self.max_x.max(x);
+                }
+            }
+            Ok(())
+        }
+    }
+    let path = "example_plys/house_ok_ascii.ply";
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let mut bounds = Bounds::default();
+    let mut f = std::fs::File::open(path).unwrap();
+    let header = p.read_ply_with_visitor(&mut f, &mut bounds).unwrap();
+    assert_eq!((bounds.min_x, bounds.max_x), (-1.0, 1.0));
+    let expected = read_file(path);
+    assert_eq!(header, expected.header);
+    let counts: Vec<_> = expected
+        .payload
+        .iter()
+        .map(|(name, elements)| (name.clone(), elements.len()))
+        .collect();
+    assert_eq!(bounds.elements, counts);
+}
+#[test]
+fn read_ply_with_visitor_abort() {
+    struct StopAt(usize);
+    impl ply::PlyVisitor<ply::DefaultElement> for StopAt {
+        fn element(&mut self, _: &ply::ElementDef, _: &ply::DefaultElement) -> std::io::Result<()> {
+            self.0 -= 1;
+            if self.0 == 0 {
+                return Err(std::io::Error::other("enough"));
+            }
+            Ok(())
+        }
+    }
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let mut f = std::fs::File::open("example_plys/house_2_ok_little_endian.ply").unwrap();
+    let err = p.read_ply_with_visitor(&mut f, &mut StopAt(3)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    assert!(err.to_string().ends_with("enough"), "{}", err);
+}
+#[test]
+fn read_skip_elements_ok() {
+    let mut sources = Vec::new();
+    for path in [
+        "example_plys/house_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/greg_turk_example2_ok_ascii.ply",
+        "example_plys/points_ok_little_endian.ply",
+    ] {
+        sources.push(std::fs::read(path).unwrap());
+    }
+    // Faces with lists followed by edges, in binary.
+    let mut ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    ply.header.encoding = ply::Encoding::BinaryBigEndian;
+    let mut bytes = Vec::new();
+    writer::Writer::new()
+        .write_ply(&mut bytes, &mut ply)
+        .unwrap();
+    sources.push(bytes);
+
+    for bytes in &sources {
+        let expected = parser::Parser::<ply::DefaultElement>::new()
+            .read_ply(&mut &bytes[..])
+            .unwrap();
+        for skipped in expected.payload.keys() {
+            let p = parser::Parser::<ply::DefaultElement>::with_options(
+                parser::ParserOptions::new().skip_elements([skipped.as_str()]),
+            );
+            let ply = p.read_ply(&mut &bytes[..]).unwrap();
+            assert_eq!(ply.header, expected.header);
+            let mut payload = expected.payload.clone();
+            payload.remove(skipped);
+            assert_eq!(
+                ply.payload, payload,
+                "{:?} skipping {}",
+                expected.header.encoding, skipped
+            );
+        }
+    }
+}
+#[test]
+fn read_skip_elements_err() {
+    // The file ends within the skipped edges.
+    let p = parser::Parser::<ply::DefaultElement>::with_options(
+        parser::ParserOptions::new().skip_elements(["edge"]),
+    );
+    let mut f = std::fs::File::open("example_plys/points_truncated_err_little_endian.ply").unwrap();
+    let err = p.read_ply(&mut f).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert!(err.to_string().contains("'edge'"), "{}", err);
+
+    let mut f = std::fs::File::open("example_plys/triangles_truncated_err_ascii.ply").unwrap();
+    let p = parser::Parser::<ply::DefaultElement>::with_options(
+        parser::ParserOptions::new().skip_elements(["face"]),
+    );
+    let err = p.read_ply(&mut f).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+#[test]
+fn read_keep_properties_ok() {
+    let mut sources = Vec::new();
+    for path in [
+        "example_plys/greg_turk_example2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        sources.push(std::fs::read(path).unwrap());
+    }
+    let mut ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    ply.header.encoding = ply::Encoding::BinaryLittleEndian;
+    let mut bytes = Vec::new();
+    writer::Writer::new()
+        .write_ply(&mut bytes, &mut ply)
+        .unwrap();
+    sources.push(bytes);
+
+    // Lists are dropped from the faces, the edges keep everything.
+    let options = parser::ParserOptions::new()
+        .keep_properties("vertex", ["z", "x"])
+        .keep_properties("face", Vec::<String>::new());
+    let p = parser::Parser::<ply::DefaultElement>::with_options(options.clone());
+    let bound = parser::Parser::<ply::DefaultElement>::with_options(options);
+    for bytes in &sources {
+        let expected = parser::Parser::<ply::DefaultElement>::new()
+            .read_ply(&mut &bytes[..])
+            .unwrap();
+        let ply = p.read_ply(&mut &bytes[..]).unwrap();
+        let mut reader = &bytes[..];
+        let header = bound.read_header(&mut reader).unwrap();
+        let bound_payload = bound
+            .bind(header)
+            .read_payload_trusted(&mut reader)
+            .unwrap();
+        assert_eq!(ply.payload, bound_payload);
+        for (name, elements) in &expected.payload {
+            for (element, full) in ply.payload[name].iter().zip(elements) {
+                let keys: Vec<_> = element.keys().cloned().collect();
+                let expected_keys: Vec<_> = match name.as_str() {
+                    "vertex" => vec!["x".to_string(), "z".to_string()],
+                    "face" => vec![],
+                    _ => full.keys().cloned().collect(),
+                };
+                assert_eq!(keys, expected_keys, "{}", name);
+                assert!(element.iter().all(|(k, v)| full[k] == *v));
+            }
+        }
+    }
+}
+#[test]
+fn read_payload_for_element_range_ok() {
+    let mut sources = Vec::new();
+    for path in [
+        "example_plys/greg_turk_example2_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/points_double_ok_big_endian.ply",
+    ] {
+        sources.push(std::fs::read(path).unwrap());
+    }
+    // Faces with lists, in binary.
+    let mut ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    ply.header.encoding = ply::Encoding::BinaryLittleEndian;
+    let mut bytes = Vec::new();
+    writer::Writer::new()
+        .write_ply(&mut bytes, &mut ply)
+        .unwrap();
+    sources.push(bytes);
+
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for bytes in &sources {
+        let expected = p.read_ply(&mut &bytes[..]).unwrap();
+        for (i, def) in expected.header.elements.iter().enumerate() {
+            let count = def.count as usize;
+            for range in [
+                0..count,
+                0..0,
+                1..count.min(3),
+                count / 2..count,
+                count..count,
+            ] {
+                let mut reader = &bytes[..];
+                let header = p.read_header(&mut reader).unwrap();
+                for before in &header.elements[..i] {
+                    p.read_payload_for_element(&mut reader, before, &header)
+                        .unwrap();
+                }
+                let elements = p
+                    .read_payload_for_element_range(&mut reader, def, &header, range.clone())
+                    .unwrap();
+                assert_eq!(
+                    elements[..],
+                    expected.payload[&def.name][range.clone()],
+                    "{:?} {} {:?}",
+                    header.encoding,
+                    def.name,
+                    range
+                );
+            }
+        }
+    }
+}
+#[test]
+fn read_payload_for_element_range_err() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let bytes = std::fs::read("example_plys/house_ok_ascii.ply").unwrap();
+    let mut reader = &bytes[..];
+    let header = p.read_header(&mut reader).unwrap();
+    let vertex = &header.elements[0];
+    let err = p
+        .read_payload_for_element_range(&mut reader, vertex, &header, 3..6)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    // Nothing was read, the vertices are still there.
+    let vertices = p
+        .read_payload_for_element_range(&mut reader, vertex, &header, 3..5)
+        .unwrap();
+    assert_eq!(vertices.len(), 2);
+
+    // The range is past the truncation.
+    let bytes = std::fs::read("example_plys/triangles_truncated_err_ascii.ply").unwrap();
+    let mut reader = &bytes[..];
+    let header = p.read_header(&mut reader).unwrap();
+    let last = header.elements.last().unwrap();
+    for before in &header.elements[..header.elements.len() - 1] {
+        p.read_payload_for_element(&mut reader, before, &header)
+            .unwrap();
+    }
+    let err = p
+        .read_payload_for_element_range(&mut reader, last, &header, 0..last.count as usize)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+#[test]
+fn read_element_at_payload_index_ok() {
+    let mut sources = vec![std::fs::read("example_plys/house_2_ok_little_endian.ply").unwrap()];
+    // Faces with lists followed by edges, in binary.
+    let mut ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    ply.header.encoding = ply::Encoding::BinaryBigEndian;
+    let mut bytes = Vec::new();
+    writer::Writer::new()
+        .write_ply(&mut bytes, &mut ply)
+        .unwrap();
+    sources.push(bytes);
+
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let mut seed = 7u64;
+    for bytes in &sources {
+        let expected = p.read_ply(&mut &bytes[..]).unwrap();
+        for interval in [1, 2, 1024] {
+            let mut reader = std::io::Cursor::new(&bytes[..]);
+            let header = p.read_header(&mut reader).unwrap();
+            let index = p
+                .build_index_with_interval(&mut reader, &header, interval)
+                .unwrap();
+            let mut stored = Vec::new();
+            index.write_to(&mut stored).unwrap();
+            let index = parser::PayloadIndex::read_from(&mut &stored[..], &header).unwrap();
+            for def in &header.elements {
+                for _ in 0..20 {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    let i = (seed >> 33) % def.count;
+                    let element = p
+                        .read_element_at(&mut reader, &index, &def.name, i)
+                        .unwrap();
+                    assert_eq!(
+                        element, expected.payload[&def.name][i as usize],
+                        "{} #{}",
+                        def.name, i
+                    );
+                }
+            }
+            let err = p
+                .read_element_at(&mut reader, &index, "vertex", header.elements[0].count)
+                .unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+}
+#[test]
+fn read_element_at_payload_index_err() {
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let bytes = std::fs::read("example_plys/house_ok_ascii.ply").unwrap();
+    let mut reader = std::io::Cursor::new(&bytes[..]);
+    let header = p.read_header(&mut reader).unwrap();
+    let err = p.build_index(&mut reader, &header).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+
+    let bytes = std::fs::read("example_plys/points_truncated_err_little_endian.ply").unwrap();
+    let mut reader = std::io::Cursor::new(&bytes[..]);
+    let header = p.read_header(&mut reader).unwrap();
+    let err = p.build_index(&mut reader, &header).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // An index of another file is rejected.
+    let bytes = std::fs::read("example_plys/house_2_ok_little_endian.ply").unwrap();
+    let mut reader = std::io::Cursor::new(&bytes[..]);
+    let header = p.read_header(&mut reader).unwrap();
+    let mut stored = Vec::new();
+    p.build_index(&mut reader, &header)
+        .unwrap()
+        .write_to(&mut stored)
+        .unwrap();
+    let points = std::fs::read("example_plys/points_ok_little_endian.ply").unwrap();
+    let other = p.read_header(&mut &points[..]).unwrap();
+    let err = parser::PayloadIndex::read_from(&mut &stored[..], &other).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+#[test]
+fn read_payload_resumable_ok() {
+    let mut sources = Vec::new();
+    for path in [
+        "example_plys/house_2_ok_little_endian.ply",
+        "example_plys/house_ok_ascii.ply",
+        "example_plys/greg_turk_example2_ok_ascii.ply",
+    ] {
+        sources.push(std::fs::read(path).unwrap());
+    }
+    // Faces with lists followed by edges, in binary.
+    let mut ply = read_file("example_plys/greg_turk_example2_ok_ascii.ply");
+    ply.header.encoding = ply::Encoding::BinaryBigEndian;
+    let mut bytes = Vec::new();
+    writer::Writer::new()
+        .write_ply(&mut bytes, &mut ply)
+        .unwrap();
+    sources.push(bytes);
+
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for bytes in &sources {
+        let expected = p.read_ply(&mut &bytes[..]).unwrap();
+        let mut reader = &bytes[..];
+        let header = p.read_header(&mut reader).unwrap();
+        let payload = reader;
+
+        // Cut within the first vertex and resume once.
+        let mut state = parser::ParseState::new(header.clone());
+        let err = p
+            .read_payload_resumable(&mut &payload[..payload.len().min(13)], &mut state)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(state.element().unwrap().name, "vertex");
+        let resumed = p
+            .read_payload_resumable(&mut &payload[state.offset() as usize..], &mut state)
+            .unwrap();
+        assert_eq!(resumed, expected.payload, "{:?}", header.encoding);
+
+        // The source breaks off every few bytes.
+        let mut state = parser::ParseState::new(header.clone());
+        let mut end = 0;
+        let resumed = loop {
+            end = (end + 7).min(payload.len());
+            let start = state.offset() as usize;
+            match p.read_payload_resumable(&mut &payload[start..end], &mut state) {
+                Ok(resumed) => break resumed,
+                // Cut off list and ascii lines may fail to parse instead.
+                Err(_) => assert!(end < payload.len()),
+            }
+        };
+        assert_eq!(resumed, expected.payload, "{:?}", header.encoding);
+    }
+}
+#[test]
+fn read_progress_events_ok() {
+    use std::sync::{Arc, Mutex};
+    for path in [
+        "example_plys/house_ok_ascii.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let bytes = std::fs::read(path).unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collected = Arc::clone(&events);
+        let options = parser::ParserOptions::new().on_progress(2, move |event| {
+            collected.lock().unwrap().push((
+                event.element.to_string(),
+                event.read,
+                event.total,
+                event.bytes,
+            ));
+        });
+        let p = parser::Parser::<ply::DefaultElement>::with_options(options);
+        let mut reader = &bytes[..];
+        let header = p.read_header(&mut reader).unwrap();
+        let payload_len = reader.len() as u64;
+        p.read_payload(&mut reader, &header).unwrap();
+
+        let events = events.lock().unwrap();
+        let mut bytes_read = 0;
+        let mut i = 0;
+        for e in &header.elements {
+            // Starts at 0, steps by 2, ends at the count.
+            let mut expected: Vec<u64> = (0..e.count).step_by(2).collect();
+            expected.push(e.count);
+            for read in expected {
+                let (ref element, r, total, b) = events[i];
+                assert_eq!(
+                    (element.as_str(), r, total),
+                    (e.name.as_str(), read, e.count)
+                );
+                assert!(b >= bytes_read, "{}: {} < {}", path, b, bytes_read);
+                bytes_read = b;
+                i += 1;
+            }
+        }
+        assert_eq!(i, events.len());
+        assert_eq!(bytes_read, payload_len, "{}", path);
+    }
+}
+#[test]
+fn read_cancel_flag_err() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    const COUNT: u64 = 100_000;
+    let mut files = Vec::new();
+    for format in ["ascii", "binary_little_endian"] {
+        let mut bytes = format!(
+            "ply\nformat {} 1.0\nelement vertex {}\nproperty float x\nproperty float y\nend_header\n",
+            format, COUNT
+        )
+        .into_bytes();
+        for i in 0..COUNT {
+            if format == "ascii" {
+                bytes.extend_from_slice(format!("{} {}\n", i, i).as_bytes());
+            } else {
+                bytes.extend_from_slice(&(i as f32).to_le_bytes());
+                bytes.extend_from_slice(&(i as f32).to_le_bytes());
+            }
+        }
+        files.push(bytes);
+    }
+    for bytes in &files {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        // Cancels half way through, like a client going away.
+        let options = parser::ParserOptions::new()
+            .cancel_flag(cancel)
+            .on_progress(1000, move |event| {
+                if event.read == COUNT / 2 {
+                    flag.store(true, Ordering::Relaxed);
+                }
+            });
+        let p = parser::Parser::<ply::DefaultElement>::with_options(options);
+        let err = p.read_ply(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        let cancelled = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<parser::Cancelled>())
+            .unwrap();
+        assert_eq!(
+            (cancelled.element.as_str(), cancelled.index),
+            ("vertex", 50000)
+        );
+    }
+    // An unset flag doesn't change the result.
+    let cancel = Arc::new(AtomicBool::new(false));
+    let p = parser::Parser::<ply::DefaultElement>::with_options(
+        parser::ParserOptions::new().cancel_flag(cancel),
+    );
+    let ply = p.read_ply(&mut &files[1][..]).unwrap();
+    assert_eq!(ply.payload["vertex"].len() as u64, COUNT);
+}
+#[test]
+fn read_parser_builder_ok() {
+    let read = |p: &parser::Parser<ply::DefaultElement>, path: &str| {
+        let mut f = std::fs::File::open(path).unwrap();
+        p.read_ply(&mut f)
+    };
+    let default = parser::Parser::<ply::DefaultElement>::new();
+    let built = parser::Parser::<ply::DefaultElement>::builder().build();
+    let path = "example_plys/house_indented_header_ok_ascii.ply";
+    assert_eq!(read(&built, path).unwrap(), read(&default, path).unwrap());
+
+    // Strict headers reject the indented properties.
+    let strict = parser::Parser::<ply::DefaultElement>::builder()
+        .allow_indented_header(false)
+        .build();
+    assert!(read(&strict, path).is_err());
+    assert!(read(&strict, "example_plys/house_ok_ascii.ply").is_ok());
+
+    // Skipped elements are left out, the others stay the same.
+    let skipping = parser::Parser::<ply::DefaultElement>::builder()
+        .allow_indented_header(false)
+        .skip_elements(["face"])
+        .build();
+    let expected = read(&default, "example_plys/house_ok_ascii.ply").unwrap();
+    let ply = read(&skipping, "example_plys/house_ok_ascii.ply").unwrap();
+    assert!(!ply.payload.contains_key("face"));
+    assert_eq!(ply.payload["vertex"], expected.payload["vertex"]);
+    assert_eq!(ply.header, expected.header);
+}
+#[test]
+fn read_limits_err() {
+    let limited = || {
+        parser::Parser::<ply::DefaultElement>::builder()
+            .max_element_count(1000)
+            .max_list_len(16)
+            .max_total_properties(2000)
+            .build()
+    };
+    let read = |p: &parser::Parser<ply::DefaultElement>, bytes: &[u8]| {
+        p.read_ply(&mut &bytes[..]).unwrap_err()
+    };
+
+    // A binary list claiming four billion entries.
+    let mut bytes = b"ply\nformat binary_little_endian 1.0\nelement face 1\n\
+        property list uint int vertex_indices\nend_header\n"
+        .to_vec();
+    bytes.extend_from_slice(&4_000_000_000u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 64]);
+    let err = read(&limited(), &bytes);
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let message = err.to_string();
+    assert!(message.contains("'vertex_indices'"), "{}", message);
+    assert!(message.contains("4000000000 entries"), "{}", message);
+    assert!(message.contains("limit of 16"), "{}", message);
+
+    // The same in ascii, and for elements that aren't kept.
+    let ascii = "ply\nformat ascii 1.0\nelement face 1\n\
+        property list uchar int vertex_indices\nend_header\n\
+        17 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16\n";
+    let err = read(&limited(), ascii.as_bytes());
+    assert!(err.to_string().contains("limit of 16"), "{}", err);
+    let skipping = parser::Parser::<ply::DefaultElement>::builder()
+        .max_list_len(16)
+        .keep_properties("face", ["other"])
+        .build();
+    let err = read(&skipping, ascii.as_bytes());
+    assert!(err.to_string().contains("limit of 16"), "{}", err);
+    assert!(parser::Parser::<ply::DefaultElement>::new()
+        .read_ply(&mut ascii.as_bytes())
+        .is_ok());
+
+    // Hostile element counts fail with the header, before any payload is read.
+    let huge = "ply\nformat ascii 1.0\nelement vertex 18446744073709551615\n\
+        property float x\nend_header\n";
+    let err = limited().read_header(&mut huge.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("'vertex'"), "{}", err);
+    assert!(err.to_string().contains("limit of 1000"), "{}", err);
+
+    let wide = "ply\nformat ascii 1.0\nelement vertex 1000\nproperty float x\n\
+        property float y\nproperty float z\nend_header\n";
+    let err = limited().read_header(&mut wide.as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("3000"), "{}", err);
+    assert!(err.to_string().contains("limit of 2000"), "{}", err);
+
+    // Files within the limits read as before.
+    let ply = limited()
+        .read_ply(&mut std::fs::File::open("example_plys/house_ok_ascii.ply").unwrap())
+        .unwrap();
+    assert_eq!(ply.payload["face"].len(), 3);
+}
+#[test]
+fn read_header_line_limit_err() {
+    // Binary data without line breaks, passed in by mistake.
+    let mut bytes = b"ply\nformat ascii 1.0\ncomment ".to_vec();
+    bytes.extend_from_slice(&[b'x'; 1 << 16]);
+    let p = parser::Parser::<ply::DefaultElement>::builder()
+        .max_header_line_len(1024)
+        .build();
+    let err = p.read_header(&mut &bytes[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(
+        err.to_string()
+            .contains("Header line 3 is longer than the limit of 1024 bytes"),
+        "{}",
+        err
+    );
+    let mut decoder = parser::ElementDecoder::<ply::DefaultElement>::with_options(
+        parser::ParserOptions::new().max_header_line_len(1024),
+    );
+    decoder.feed(&bytes);
+    let err = decoder.decode().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    // Lines within the limit read as before, the end of the input ends the header early.
+    let err = p
+        .read_header(&mut &b"ply\nformat ascii 1.0\ncomment short\n"[..])
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{}", err);
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_header_async_line_limit_err() {
+    use futures::executor::block_on;
+    let mut bytes = b"ply\nformat ascii 1.0\ncomment ".to_vec();
+    bytes.extend_from_slice(&[b'x'; 1 << 16]);
+    let p = parser::Parser::<ply::DefaultElement>::builder()
+        .max_header_line_len(1024)
+        .build();
+    let mut reader = futures::io::Cursor::new(bytes);
+    let err = block_on(p.read_header_async(&mut reader)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    // Nothing after the limit was read.
+    assert!(reader.position() <= 1024 + 24, "{}", reader.position());
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn stream_limits_err() {
+    use futures::executor::block_on;
+    use futures::StreamExt;
+    // The list of four billion entries from `read_limits_err`, rejected before it is buffered.
+    let mut bytes = b"ply\nformat binary_little_endian 1.0\nelement face 1\n\
+        property list uint int vertex_indices\nend_header\n"
+        .to_vec();
+    bytes.extend_from_slice(&4_000_000_000u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 64]);
+    let p = parser::Parser::<ply::DefaultElement>::builder()
+        .max_list_len(16)
+        .build();
+    let mut reader = futures::io::Cursor::new(bytes);
+    let header = block_on(p.read_header_async(&mut reader)).unwrap();
+    let mut faces = p
+        .stream_payload_for_element(&mut reader, &header.elements[0], &header)
+        .unwrap();
+    let err = block_on(faces.next()).unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("limit of 16"), "{}", err);
+}
+#[test]
+fn read_huge_declared_count_err() {
+    // Claims 10^12 vertices but holds two.
+    let ascii = "ply\nformat ascii 1.0\nelement vertex 1000000000000\n\
+        property float x\nproperty float y\nend_header\n0 1\n2 3\n";
+    let mut binary = b"ply\nformat binary_little_endian 1.0\nelement vertex 1000000000000\n\
+        property float x\nproperty float y\nend_header\n"
+        .to_vec();
+    for v in [0f32, 1.0, 2.0, 3.0] {
+        binary.extend_from_slice(&v.to_le_bytes());
+    }
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    for bytes in [ascii.as_bytes(), &binary[..]] {
+        let err = p.read_ply(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{}", err);
+        let err = p.read_ply_from_slice(bytes).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{}", err);
+
+        let mut reader = bytes;
+        let header = p.read_header(&mut reader).unwrap();
+        let vertex = &header.elements[0];
+        let err = p
+            .read_payload_for_element_pooled(&mut &reader[..], vertex, &header)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{}", err);
+        let err = p
+            .read_payload_for_element_range(&mut &reader[..], vertex, &header, 1..1_000_000_000)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{}", err);
+        let mut state = parser::ParseState::new(header.clone());
+        assert!(p
+            .read_payload_resumable(&mut &reader[..], &mut state)
+            .is_err());
+        assert_eq!(state.payload()["vertex"].len(), 2);
+    }
+}
+#[cfg(feature = "futures-io")]
+#[test]
+fn read_indexed_huge_declared_count_err() {
+    use futures::executor::block_on;
+    let mut bytes = b"ply\nformat binary_little_endian 1.0\nelement vertex 1000000000000\n\
+        property float x\nproperty float y\nend_header\n"
+        .to_vec();
+    bytes.extend_from_slice(&[0; 16]);
+    let p = parser::Parser::<ply::DefaultElement>::new();
+    let mut indexed = block_on(p.open_indexed(futures::io::Cursor::new(bytes))).unwrap();
+    let err = block_on(indexed.read_range("vertex", 0..1_000_000_000_000)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{}", err);
+
+    let mut reader = futures::io::Cursor::new(indexed.into_inner().into_inner());
+    let header = block_on(p.read_header_async(&mut reader)).unwrap();
+    let vertices = p
+        .stream_payload_for_element(&mut reader, &header.elements[0], &header)
+        .unwrap();
+    // The count in the header doesn't promise any elements.
+    assert_eq!(futures::Stream::size_hint(&vertices).0, 0);
 }
@@ -22,13 +22,12 @@ fn write_buff(ply: &Ply) -> Vec<u8> {
 fn read_write_ply(ply: &Ply) -> Ply {
     println!("writing ply:\n{:?}", ply);
     let ve: Vec<u8> = write_buff(ply);
-    let txt = String::from_utf8(ve.clone()).unwrap();
+    let txt = String::from_utf8_lossy(&ve);
     println!("written ply:\n{}", txt);
     let mut buff = BufReader::new(&(*ve));
     let new_ply = read_buff(&mut buff);
     println!("read ply:\n{:?}", new_ply);
-    assert_eq!(ply.header, new_ply.header);
-    assert_eq!(ply.payload, new_ply.payload);
+    assert_ply_eq!(ply, new_ply);
     new_ply
 }
 
@@ -89,10 +88,13 @@ fn create_list_elements() -> Ply {
 
     let mut list = Vec::new();
     let mut pe = KeyMap::new();
-    pe.insert("x".to_string(), Property::ListInt(vec![-7, 17, 38]));
+    pe.insert("x".to_string(), Property::ListInt(vec![-7, 17, 38].into()));
     list.push(pe);
     let mut pe = KeyMap::new();
-    pe.insert("x".to_string(), Property::ListInt(vec![13, -19, 8, 33]));
+    pe.insert(
+        "x".to_string(),
+        Property::ListInt(vec![13, -19, 8, 33].into()),
+    );
     list.push(pe);
     ply.payload.insert("aList".to_string(), list);
 
@@ -129,3 +131,1466 @@ fn write_list_elements() {
     let new_ply = read_write_ply(&ply);
     assert_eq!(ply, new_ply);
 }
+#[test]
+fn write_list_elements_binary() {
+    for encoding in [Encoding::BinaryBigEndian, Encoding::BinaryLittleEndian] {
+        let mut ply = create_list_elements();
+        ply.header.encoding = encoding;
+        let new_ply = read_write_ply(&ply);
+        assert_eq!(ply, new_ply);
+    }
+}
+#[cfg(feature = "extended-types")]
+#[test]
+fn write_extended_types() {
+    for encoding in [
+        Encoding::Ascii,
+        Encoding::BinaryBigEndian,
+        Encoding::BinaryLittleEndian,
+    ] {
+        let mut ply = Ply::new();
+        ply.header.encoding = encoding;
+        let mut e = ElementDef::new("point");
+        e.properties.push(PropertyDef::new(
+            "timestamp",
+            PropertyType::Scalar(ScalarType::LongLong),
+        ));
+        e.properties.push(PropertyDef::new(
+            "id",
+            PropertyType::Scalar(ScalarType::ULongLong),
+        ));
+        e.properties.push(PropertyDef::new(
+            "deltas",
+            PropertyType::List(ScalarType::UChar, ScalarType::LongLong),
+        ));
+        e.properties.push(PropertyDef::new(
+            "ids",
+            PropertyType::List(ScalarType::ULongLong, ScalarType::ULongLong),
+        ));
+        ply.header.elements.push(e);
+        let mut pe = KeyMap::new();
+        pe.insert("timestamp".to_string(), Property::LongLong(-5_000_000_000));
+        pe.insert("id".to_string(), Property::ULongLong(u64::MAX));
+        pe.insert(
+            "deltas".to_string(),
+            Property::ListLongLong(vec![i64::MIN, 0, 1 << 40].into()),
+        );
+        pe.insert(
+            "ids".to_string(),
+            Property::ListULongLong(vec![1 << 33, u64::MAX - 1].into()),
+        );
+        ply.payload.insert("point".to_string(), vec![pe]);
+        assert!(ply.make_consistent().is_ok());
+        let new_ply = read_write_ply(&ply);
+        assert_eq!(ply, new_ply);
+    }
+}
+#[cfg(feature = "half")]
+#[test]
+fn write_half() {
+    use half::f16;
+    // smallest and largest subnormal, a normal value and a negative value
+    let values = vec![
+        f16::from_bits(0x0001),
+        f16::from_bits(0x03ff),
+        f16::from_f32(1.5),
+        f16::from_f32(-65504.0),
+    ];
+    for encoding in [
+        Encoding::Ascii,
+        Encoding::BinaryBigEndian,
+        Encoding::BinaryLittleEndian,
+    ] {
+        let mut ply = Ply::new();
+        ply.header.encoding = encoding;
+        let mut e = ElementDef::new("splat");
+        e.properties.push(PropertyDef::new(
+            "f_dc_0",
+            PropertyType::Scalar(ScalarType::Half),
+        ));
+        e.properties.push(PropertyDef::new(
+            "f_rest",
+            PropertyType::List(ScalarType::UChar, ScalarType::Half),
+        ));
+        ply.header.elements.push(e);
+        let mut list = Vec::new();
+        for v in &values {
+            let mut pe = KeyMap::new();
+            pe.insert("f_dc_0".to_string(), Property::Half(*v));
+            pe.insert(
+                "f_rest".to_string(),
+                Property::ListHalf(values.clone().into()),
+            );
+            list.push(pe);
+        }
+        ply.payload.insert("splat".to_string(), list);
+        assert!(ply.make_consistent().is_ok());
+        let new_ply = read_write_ply(&ply);
+        assert_eq!(ply, new_ply);
+    }
+}
+#[test]
+fn write_custom_type_err() {
+    let mut ply = Ply::new();
+    let mut e = ElementDef::new("point");
+    e.properties.push(PropertyDef::new(
+        "x",
+        PropertyType::Custom {
+            name: "int24".to_string(),
+            size: 3,
+        },
+    ));
+    ply.header.elements.push(e);
+    let mut buf = Vec::<u8>::new();
+    let w = writer::Writer::new();
+    assert!(w.write_ply(&mut buf, &mut ply).is_err());
+}
+#[test]
+fn write_keeps_header_property_order() {
+    let mut f = std::fs::File::open("example_plys/unusual_property_order_ok_ascii.ply").unwrap();
+    let ply = read_buff(&mut f);
+    let txt = String::from_utf8(write_buff(&ply)).unwrap();
+    let lines: Vec<&str> = txt.lines().collect();
+    assert_eq!(
+        lines[4..9].to_vec(),
+        vec![
+            "property float z",
+            "property uchar red",
+            "property float x",
+            "property list uchar int idx",
+            "property float y",
+        ]
+    );
+    let data: Vec<&str> = lines[10].split_whitespace().collect();
+    assert_eq!(data, vec!["3", "255", "1", "2", "7", "8", "2"]);
+}
+#[cfg(feature = "bytes")]
+fn create_splats(encoding: Encoding) -> Ply {
+    let mut ply = Ply::new();
+    ply.header.encoding = encoding;
+    let mut e = ElementDef::new("splat");
+    e.properties.push(PropertyDef::new(
+        "x",
+        PropertyType::Scalar(ScalarType::Float),
+    ));
+    e.properties.push(PropertyDef::new(
+        "payload",
+        PropertyType::List(ScalarType::UInt, ScalarType::UChar),
+    ));
+    ply.header.elements.push(e);
+    let mut list = Vec::new();
+    for i in 0..4u8 {
+        let mut pe = DefaultElement::new();
+        pe.insert("x".to_string(), Property::Float(i as f32));
+        let payload = (0..i * 10).map(|v| v.wrapping_mul(i)).collect();
+        pe.insert("payload".to_string(), Property::ListUChar(payload));
+        list.push(pe);
+    }
+    ply.payload.insert("splat".to_string(), list);
+    ply.make_consistent().unwrap();
+    ply
+}
+#[test]
+#[cfg(feature = "bytes")]
+fn read_bytes_zero_copy() {
+    let p = parser::Parser::<DefaultElement>::new();
+    for encoding in [
+        Encoding::BinaryLittleEndian,
+        Encoding::BinaryBigEndian,
+        Encoding::Ascii,
+    ] {
+        let ply = create_splats(encoding);
+        let source = bytes::Bytes::from(write_buff(&ply));
+        let copied = read_buff(&mut &source[..]);
+        let shared = p.read_ply_from_bytes(&source).unwrap();
+        assert_ply_eq!(copied, shared);
+
+        let range = source.as_ptr_range();
+        for e in &shared.payload["splat"] {
+            match e["payload"] {
+                Property::ListBytes(ref b) if encoding != Encoding::Ascii => {
+                    let r = b.as_ptr_range();
+                    assert!(range.start <= r.start && r.end <= range.end);
+                }
+                Property::ListUChar(_) if encoding == Encoding::Ascii => (),
+                ref p => panic!("Unexpected property: {:?}", p),
+            }
+        }
+    }
+}
+#[test]
+#[cfg(feature = "bytes")]
+fn write_bytes() {
+    let ply = create_splats(Encoding::BinaryLittleEndian);
+    let mut shared = ply.clone();
+    for e in shared.payload.get_mut("splat").unwrap() {
+        let payload = match e["payload"] {
+            Property::ListUChar(ref v) => bytes::Bytes::copy_from_slice(v),
+            _ => unreachable!(),
+        };
+        e.insert(
+            "payload".to_string(),
+            Property::ListBytes(Box::new(payload)),
+        );
+    }
+    assert_eq!(write_buff(&ply), write_buff(&shared));
+}
+#[test]
+fn write_element_iter() {
+    let p = parser::Parser::<DefaultElement>::new();
+    let w = writer::Writer::new();
+    let mut f =
+        BufReader::new(std::fs::File::open("example_plys/house_2_ok_little_endian.ply").unwrap());
+    let mut header = p.read_header(&mut f).unwrap();
+    header.encoding = Encoding::Ascii;
+    let mut buf = Vec::new();
+    w.write_header(&mut buf, &header).unwrap();
+    for def in &header.elements {
+        let elements = (0..def.count).map(|_| p.read_little_endian_element(&mut f, def));
+        w.write_payload_of_element_iter(&mut buf, elements, def, Encoding::Ascii)
+            .unwrap();
+    }
+    let ply = read_buff(&mut buf.as_slice());
+    let expected =
+        read_buff(&mut std::fs::File::open("example_plys/house_2_ok_ascii.ply").unwrap());
+    assert_ply_eq!(ply, expected);
+}
+#[test]
+fn write_element_iter_err() {
+    let w = writer::Writer::<DefaultElement>::new();
+    let mut def = ElementDef::new("point");
+    def.count = 2;
+    def.properties.push(PropertyDef::new(
+        "x",
+        PropertyType::Scalar(ScalarType::Float),
+    ));
+    let point = || {
+        let mut e = DefaultElement::new();
+        e.insert("x".to_string(), Property::Float(0.5));
+        Ok(e)
+    };
+    let mut buf = Vec::new();
+    let one = vec![point()];
+    let err = w
+        .write_payload_of_element_iter(&mut buf, one, &def, Encoding::Ascii)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Element 'point': source produced 1 of the declared 2 elements."
+    );
+    let three = (0..3).map(|_| point());
+    assert!(w
+        .write_payload_of_element_iter(&mut buf, three, &def, Encoding::Ascii)
+        .is_err());
+    let failing = vec![point(), Err(std::io::Error::other("offline"))];
+    let err = w
+        .write_payload_of_element_iter(&mut buf, failing, &def, Encoding::Ascii)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    assert_eq!(
+        err.to_string(),
+        "Element 'point': source failed after 1 of 2 elements: offline"
+    );
+}
+#[test]
+fn write_binary_payload_matches_elements() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let hash = |bytes: &[u8]| {
+        let mut h = DefaultHasher::new();
+        bytes.hash(&mut h);
+        h.finish()
+    };
+    let w = writer::Writer::<DefaultElement>::new();
+    for encoding in [Encoding::BinaryBigEndian, Encoding::BinaryLittleEndian] {
+        let mut ply = create_list_elements();
+        ply.header.encoding = encoding;
+        let def = &ply.header.elements[0];
+        let mut payload = Vec::new();
+        let written = w
+            .write_payload_of_element(&mut payload, &ply.payload["aList"], def, &ply.header)
+            .unwrap();
+        assert_eq!(written, payload.len());
+        let mut elementwise = Vec::new();
+        for e in &ply.payload["aList"] {
+            match encoding {
+                Encoding::BinaryBigEndian => w.write_big_endian_element(&mut elementwise, e, def),
+                _ => w.write_little_endian_element(&mut elementwise, e, def),
+            }
+            .unwrap();
+        }
+        assert_eq!(hash(&payload), hash(&elementwise));
+    }
+}
+
+/// Accepts at most 3 bytes per call and records the number of bytes at each flush.
+#[derive(Default)]
+struct ThrottledWriter {
+    bytes: Vec<u8>,
+    flushes: Vec<usize>,
+}
+
+impl std::io::Write for ThrottledWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(3);
+        self.bytes.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flushes.push(self.bytes.len());
+        Ok(())
+    }
+}
+
+#[test]
+fn write_flush_policy() {
+    let ply = create_list_elements();
+    let expected = write_buff(&ply);
+    let flushes = |policy| {
+        let w = writer::Writer::with_options(writer::WriteOptions::new().flush_policy(policy));
+        let mut out = ThrottledWriter::default();
+        w.write_ply_unchecked(&mut out, &ply).unwrap();
+        assert_eq!(out.bytes, expected);
+        out.flushes
+    };
+    let w = writer::Writer::new();
+    let header_len = w.write_header(&mut Vec::new(), &ply.header).unwrap();
+    let first_len = w
+        .write_ascii_element(
+            &mut Vec::new(),
+            &ply.payload["aList"][0],
+            &ply.header.elements[0],
+        )
+        .unwrap();
+    let end = expected.len();
+    assert_eq!(flushes(writer::FlushPolicy::Manual), vec![end]);
+    assert_eq!(
+        flushes(writer::FlushPolicy::EveryNElements(1)),
+        vec![header_len + first_len, end, end]
+    );
+    assert_eq!(
+        flushes(writer::FlushPolicy::EveryNElements(2)),
+        vec![end, end]
+    );
+    assert_eq!(
+        flushes(writer::FlushPolicy::EveryNBytes(first_len)),
+        vec![header_len + first_len, end, end]
+    );
+    assert_eq!(
+        flushes(writer::FlushPolicy::EveryNBytes(first_len + 1)),
+        vec![end, end]
+    );
+}
+#[test]
+fn write_checksum_ok() {
+    let w = writer::Writer::with_options(writer::WriteOptions::new().checksum(true));
+    let p = parser::Parser::<DefaultElement>::with_options(
+        parser::ParserOptions::new().verify_checksum(true),
+    );
+    for encoding in [Encoding::Ascii, Encoding::BinaryLittleEndian] {
+        let mut ply = create_list_elements();
+        ply.header.encoding = encoding;
+        let mut buf = Vec::new();
+        w.write_ply(&mut buf, &mut ply).unwrap();
+        let new_ply = p.read_ply(&mut buf.as_slice()).unwrap();
+        assert_eq!(new_ply.payload, ply.payload);
+        let checksums: Vec<_> = new_ply
+            .header
+            .comments
+            .iter()
+            .filter(|c| c.starts_with("ply-rs-checksum crc32 "))
+            .collect();
+        assert_eq!(checksums.len(), 1);
+
+        // Writing again replaces the checksum instead of adding a second one.
+        let mut again = Vec::new();
+        w.write_ply(&mut again, &mut new_ply.clone()).unwrap();
+        assert_eq!(again, buf);
+    }
+}
+#[test]
+fn write_checksum_mismatch_err() {
+    let w = writer::Writer::with_options(writer::WriteOptions::new().checksum(true));
+    let mut ply = create_list_elements();
+    ply.header.encoding = Encoding::BinaryLittleEndian;
+    let mut buf = Vec::new();
+    w.write_ply(&mut buf, &mut ply).unwrap();
+    let last = buf.len() - 1;
+    buf[last] ^= 0x01;
+
+    // Without verification, the corruption goes unnoticed.
+    assert!(parser::Parser::<DefaultElement>::new()
+        .read_ply(&mut buf.as_slice())
+        .is_ok());
+    let p = parser::Parser::<DefaultElement>::with_options(
+        parser::ParserOptions::new().verify_checksum(true),
+    );
+    let err = p.read_ply(&mut buf.as_slice()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let mismatch = err
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<parser::ChecksumMismatch>())
+        .unwrap();
+    assert_ne!(mismatch.expected, mismatch.found);
+    #[cfg(feature = "bytes")]
+    {
+        let err = p.read_ply_from_bytes(&buf.into()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+#[test]
+fn write_primitives() {
+    let mut ply = create_list_elements();
+    let w = writer::Writer::new();
+    for encoding in [
+        Encoding::Ascii,
+        Encoding::BinaryBigEndian,
+        Encoding::BinaryLittleEndian,
+    ] {
+        ply.header.encoding = encoding;
+        let def = &ply.header.elements[0];
+        let elements = &ply.payload["aList"];
+
+        let mut buf = Vec::new();
+        w.write_header(&mut buf, &ply.header).unwrap();
+        w.write_payload_for_element(&mut buf, elements, def, encoding)
+            .unwrap();
+        assert_ply_eq!(read_buff(&mut buf.as_slice()), ply);
+
+        let mut buf = Vec::new();
+        w.write_header(&mut buf, &ply.header).unwrap();
+        for e in elements {
+            match encoding {
+                Encoding::Ascii => w.write_ascii_element(&mut buf, e, def),
+                Encoding::BinaryBigEndian => {
+                    w.write_binary_element::<_, writer::BigEndian>(&mut buf, e, def)
+                }
+                Encoding::BinaryLittleEndian => {
+                    w.write_binary_element::<_, writer::LittleEndian>(&mut buf, e, def)
+                }
+            }
+            .unwrap();
+        }
+        assert_eq!(buf, write_buff(&ply));
+    }
+}
+#[test]
+fn write_measure() {
+    for path in [
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/greg_turk_example1_ok_ascii.ply",
+        "example_plys/points_ok_little_endian.ply",
+    ] {
+        let mut ply = read_buff(&mut std::fs::File::open(path).unwrap());
+        for encoding in [
+            Encoding::Ascii,
+            Encoding::BinaryBigEndian,
+            Encoding::BinaryLittleEndian,
+        ] {
+            ply.header.encoding = encoding;
+            for checksum in [false, true] {
+                let w =
+                    writer::Writer::with_options(writer::WriteOptions::new().checksum(checksum));
+                let mut buf = Vec::new();
+                let written = w.write_ply_unchecked(&mut buf, &ply).unwrap();
+                assert_eq!(written, buf.len());
+                assert_eq!(w.measure(&ply).unwrap(), buf.len() as u64, "{}", path);
+            }
+        }
+    }
+}
+#[test]
+fn pipeline_copy() {
+    for path in [
+        "example_plys/house_2_ok_ascii.ply",
+        "example_plys/points_ok_little_endian.ply",
+    ] {
+        let original = read_buff(&mut std::fs::File::open(path).unwrap());
+        for encoding in [
+            Encoding::Ascii,
+            Encoding::BinaryBigEndian,
+            Encoding::BinaryLittleEndian,
+        ] {
+            let options = pipeline::CopyOptions::<DefaultElement>::new().map_header(|header| {
+                header.encoding = encoding;
+                Ok(())
+            });
+            let mut source = std::fs::File::open(path).unwrap();
+            let mut target = std::io::Cursor::new(Vec::new());
+            let header = pipeline::copy(&mut source, &mut target, options).unwrap();
+            let copy = read_buff(&mut target.get_ref().as_slice());
+            assert_eq!(header, copy.header);
+            let mut expected = original.clone();
+            expected.header.encoding = encoding;
+            assert_ply_eq!(copy, expected);
+        }
+    }
+}
+#[test]
+fn pipeline_copy_transform() {
+    let path = "example_plys/points_ok_little_endian.ply";
+    let original = read_buff(&mut std::fs::File::open(path).unwrap());
+    let mut index = 0;
+    let options = pipeline::CopyOptions::<DefaultElement>::new()
+        .map_header(|header| {
+            header.encoding = Encoding::Ascii;
+            Ok(())
+        })
+        .transform(|def, element| {
+            if def.name != "vertex" {
+                return Some(element);
+            }
+            index += 1;
+            if index % 2 == 0 {
+                None
+            } else {
+                Some(element)
+            }
+        });
+    let mut source = std::fs::File::open(path).unwrap();
+    let mut target = std::io::Cursor::new(Vec::new());
+    let header = pipeline::copy(&mut source, &mut target, options).unwrap();
+    let copy = read_buff(&mut target.get_ref().as_slice());
+    assert_eq!(header, copy.header);
+    assert_eq!(copy.header.encoding, Encoding::Ascii);
+
+    let expected: Vec<_> = original.payload["vertex"].iter().step_by(2).collect();
+    let actual: Vec<_> = copy.payload["vertex"].iter().collect();
+    assert_eq!(copy.header.elements[0].count, expected.len() as u64);
+    assert_eq!(actual, expected);
+    assert_eq!(copy.payload["edge"], original.payload["edge"]);
+}
+#[test]
+fn pipeline_copy_spec() {
+    let path = "example_plys/greg_turk_example1_ok_ascii.ply";
+    let original = read_buff(&mut std::fs::File::open(path).unwrap());
+    let spec = pipeline::TransformSpec::new()
+        .drop_property("vertex", "z")
+        .cast_property("vertex", "x", ScalarType::Double)
+        .rename_property("face", "vertex_index", "vertex_indices")
+        .cast_property("face", "vertex_index", ScalarType::UInt);
+    let options = pipeline::CopyOptions::<DefaultElement>::new()
+        .spec(spec)
+        .map_header(|header| {
+            header.encoding = Encoding::BinaryLittleEndian;
+            Ok(())
+        });
+    let mut source = std::fs::File::open(path).unwrap();
+    let mut target = std::io::Cursor::new(Vec::new());
+    let err = pipeline::copy(&mut source, &mut target, options).unwrap_err();
+    assert!(err.to_string().contains("more than once"), "{}", err);
+    assert!(target.get_ref().is_empty());
+
+    let spec = pipeline::TransformSpec::new()
+        .drop_property("vertex", "z")
+        .cast_property("vertex", "x", ScalarType::Double)
+        .rename_property("face", "vertex_index", "vertex_indices");
+    let options = pipeline::CopyOptions::<DefaultElement>::new()
+        .spec(spec)
+        .map_header(|header| {
+            header.encoding = Encoding::BinaryLittleEndian;
+            Ok(())
+        });
+    let mut source = std::fs::File::open(path).unwrap();
+    let header = pipeline::copy(&mut source, &mut target, options).unwrap();
+    let copy = read_buff(&mut target.get_ref().as_slice());
+    assert_eq!(header, copy.header);
+    assert_eq!(
+        copy.header.elements[0].properties,
+        vec![
+            PropertyDef::new("x", PropertyType::Scalar(ScalarType::Double)),
+            PropertyDef::new("y", PropertyType::Scalar(ScalarType::Float)),
+        ]
+    );
+    assert_eq!(
+        copy.header.elements[1].properties,
+        vec![PropertyDef::new(
+            "vertex_indices",
+            PropertyType::List(ScalarType::UChar, ScalarType::Int)
+        )]
+    );
+    assert_eq!(
+        copy.payload["vertex"].len(),
+        original.payload["vertex"].len()
+    );
+    for (a, b) in copy.payload["vertex"]
+        .iter()
+        .zip(&original.payload["vertex"])
+    {
+        assert_eq!(a.len(), 2);
+        assert_eq!(a["x"], Property::Double(b.get_float("x").unwrap() as f64));
+        assert_eq!(a["y"], b["y"]);
+    }
+    for (a, b) in copy.payload["face"].iter().zip(&original.payload["face"]) {
+        assert_eq!(a["vertex_indices"], b["vertex_index"]);
+    }
+
+    let spec = pipeline::TransformSpec::new().rename_property("vertex", "x", "y");
+    let options = pipeline::CopyOptions::<DefaultElement>::new().spec(spec);
+    let mut source = std::fs::File::open(path).unwrap();
+    let mut target = std::io::Cursor::new(Vec::new());
+    let err = pipeline::copy(&mut source, &mut target, options).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(target.get_ref().is_empty());
+}
+#[test]
+fn write_definition_comments() {
+    let mut ply = create_single_elements();
+    let point = &mut ply.header.elements[0];
+    point.properties[0]
+        .comments
+        .push("x is in millimeters".to_string());
+    point.properties[0].comments.push("rounded".to_string());
+    let mut edge = ElementDef::new("edge");
+    edge.comments.push("no edges yet".to_string());
+    edge.properties.push(PropertyDef::new(
+        "vertex1",
+        PropertyType::Scalar(ScalarType::Int),
+    ));
+    ply.header.elements.push(edge);
+    ply.payload.insert("edge".to_string(), Vec::new());
+    for encoding in [Encoding::Ascii, Encoding::BinaryLittleEndian] {
+        ply.header.encoding = encoding;
+        let new_ply = read_write_ply(&ply);
+        assert_eq!(new_ply.header, ply.header);
+    }
+    ply.header.encoding = Encoding::Ascii;
+    let text = String::from_utf8(write_buff(&ply)).unwrap();
+    assert!(text.contains(
+        "element point 2\ncomment x is in millimeters\ncomment rounded\nproperty int x\n"
+    ));
+    assert!(text.contains("comment no edges yet\nelement edge 0\n"));
+}
+#[test]
+fn write_ascii_golden() {
+    let golden = std::fs::read("example_plys/triangles_ok_ascii.ply").unwrap();
+    let ply = read_buff(&mut golden.as_slice());
+    assert_eq!(write_buff(&ply), golden);
+
+    let with_space = String::from_utf8(golden.clone())
+        .unwrap()
+        .split_inclusive('\n')
+        .enumerate()
+        // The header has 9 lines.
+        .map(|(i, l)| {
+            if i < 9 {
+                l.to_string()
+            } else {
+                l.replace('\n', " \n")
+            }
+        })
+        .collect::<String>();
+    let w = writer::Writer::with_options(writer::WriteOptions::new().trailing_space(true));
+    let mut buf = Vec::new();
+    w.write_ply_unchecked(&mut buf, &ply).unwrap();
+    assert_eq!(String::from_utf8(buf.clone()).unwrap(), with_space);
+    // Both forms read back the same.
+    assert_ply_eq!(read_buff(&mut buf.as_slice()), ply);
+}
+/// An element with a scalar and a list property of every type, named after the type.
+fn create_all_types() -> (ElementDef, DefaultElement) {
+    #[allow(unused_mut)] // Only extended with the `extended-types` and `half` features.
+    let mut values = vec![
+        (
+            ScalarType::Char,
+            Property::Char(-7),
+            Property::ListChar([-1, 2].into_iter().collect()),
+        ),
+        (
+            ScalarType::UChar,
+            Property::UChar(250),
+            Property::ListUChar([1, 255].into_iter().collect()),
+        ),
+        (
+            ScalarType::Short,
+            Property::Short(-300),
+            Property::ListShort([-3, 4].into_iter().collect()),
+        ),
+        (
+            ScalarType::UShort,
+            Property::UShort(65000),
+            Property::ListUShort([5].into_iter().collect()),
+        ),
+        (
+            ScalarType::Int,
+            Property::Int(-70000),
+            Property::ListInt([6, -7, 8].into_iter().collect()),
+        ),
+        (
+            ScalarType::UInt,
+            Property::UInt(4_000_000_000),
+            Property::ListUInt(PropertyList::new()),
+        ),
+        (
+            ScalarType::Float,
+            Property::Float(0.1),
+            Property::ListFloat([1.5, -0.25].into_iter().collect()),
+        ),
+        (
+            ScalarType::Double,
+            Property::Double(-1e-300),
+            Property::ListDouble([0.1].into_iter().collect()),
+        ),
+    ];
+    #[cfg(feature = "extended-types")]
+    values.extend([
+        (
+            ScalarType::LongLong,
+            Property::LongLong(i64::MIN),
+            Property::ListLongLong([-9, 9].into_iter().collect()),
+        ),
+        (
+            ScalarType::ULongLong,
+            Property::ULongLong(u64::MAX),
+            Property::ListULongLong([10].into_iter().collect()),
+        ),
+    ]);
+    #[cfg(feature = "half")]
+    values.push((
+        ScalarType::Half,
+        Property::Half(half::f16::from_f32(0.5)),
+        Property::ListHalf([half::f16::from_f32(-2.0)].into_iter().collect()),
+    ));
+    let mut def = ElementDef::new("all");
+    let mut element = DefaultElement::new();
+    for (scalar_type, scalar, list) in values {
+        let name = format!("{:?}", scalar_type);
+        def.properties
+            .push(PropertyDef::new(&name, PropertyType::Scalar(scalar_type)));
+        def.properties.push(PropertyDef::new(
+            &format!("list_{}", name),
+            PropertyType::List(ScalarType::UShort, scalar_type),
+        ));
+        element.insert(name.clone(), scalar);
+        element.insert(format!("list_{}", name), list);
+    }
+    (def, element)
+}
+#[test]
+fn write_element_to_vec_roundtrip() {
+    let (def, element) = create_all_types();
+    let w = writer::Writer::<DefaultElement>::new();
+    let p = parser::Parser::<DefaultElement>::new();
+
+    let mut frame = vec![42];
+    let len = w
+        .write_binary_element_to_vec::<writer::LittleEndian>(&element, &def, &mut frame)
+        .unwrap();
+    assert_eq!(len, frame.len() - 1);
+    let (read, read_len) = p
+        .read_little_endian_element_from_slice(&frame[1..], &def)
+        .unwrap();
+    assert_eq!((read, read_len), (element.clone(), len));
+
+    let mut frame = Vec::new();
+    let len = w
+        .write_binary_element_to_vec::<writer::BigEndian>(&element, &def, &mut frame)
+        .unwrap();
+    let (read, read_len) = p.read_big_endian_element_from_slice(&frame, &def).unwrap();
+    assert_eq!((read, read_len), (element.clone(), len));
+
+    let line = w.write_ascii_element_to_string(&element, &def).unwrap();
+    assert!(!line.ends_with('\n'));
+    assert_eq!(p.read_ascii_element(&line, &def).unwrap(), element);
+}
+#[test]
+fn write_element_to_vec_err() {
+    let (def, element) = create_all_types();
+    let w = writer::Writer::<DefaultElement>::new();
+    let mut missing = element.clone();
+    missing.remove("list_Int");
+    let mut mismatched = element;
+    mismatched.insert("Short".to_string(), Property::Int(1));
+    for (e, name) in [(missing, "list_Int"), (mismatched, "Short")] {
+        let mut frame = vec![1, 2, 3];
+        let err = w
+            .write_binary_element_to_vec::<writer::LittleEndian>(&e, &def, &mut frame)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains(&format!("'{}'", name)), "{}", err);
+        assert_eq!(frame, [1, 2, 3]);
+        let err = w.write_ascii_element_to_string(&e, &def).unwrap_err();
+        assert!(err.to_string().contains(&format!("'{}'", name)), "{}", err);
+    }
+}
+/// Two vertices, the second one holds NaN and infinite values.
+fn create_non_finite(encoding: Encoding) -> Ply {
+    let mut ply = Ply::new();
+    ply.header.encoding = encoding;
+    let mut e = ElementDef::new("vertex");
+    e.properties.push(PropertyDef::new(
+        "x",
+        PropertyType::Scalar(ScalarType::Float),
+    ));
+    e.properties.push(PropertyDef::new(
+        "y",
+        PropertyType::Scalar(ScalarType::Double),
+    ));
+    e.properties.push(PropertyDef::new(
+        "w",
+        PropertyType::List(ScalarType::UChar, ScalarType::Float),
+    ));
+    ply.header.elements.push(e);
+    let vertex = |x: f32, y: f64, w: f32| {
+        let mut pe = KeyMap::new();
+        pe.insert("x".to_string(), Property::Float(x));
+        pe.insert("y".to_string(), Property::Double(y));
+        pe.insert(
+            "w".to_string(),
+            Property::ListFloat([w, 1.0].into_iter().collect()),
+        );
+        pe
+    };
+    let list = vec![
+        vertex(1.0, 0.5, 2.0),
+        vertex(f32::NAN, f64::INFINITY, f32::NEG_INFINITY),
+    ];
+    ply.payload.insert("vertex".to_string(), list);
+    assert!(ply.make_consistent().is_ok());
+    ply
+}
+fn write_non_finite(options: writer::WriteOptions, ply: &Ply) -> std::io::Result<Vec<u8>> {
+    let w = writer::Writer::with_options(options);
+    let mut buf = Vec::new();
+    w.write_ply_unchecked(&mut buf, ply)?;
+    Ok(buf)
+}
+#[test]
+fn write_non_finite_tokens() {
+    let ply = create_non_finite(Encoding::Ascii);
+    let buf = write_non_finite(writer::WriteOptions::new(), &ply).unwrap();
+    let text = String::from_utf8(buf.clone()).unwrap();
+    assert!(
+        text.ends_with("end_header\n1 0.5 2 2 1\nnan inf 2 -inf 1\n"),
+        "{}",
+        text
+    );
+
+    let read = read_buff(&mut buf.as_slice());
+    let vertex = &read.payload["vertex"][1];
+    assert!(vertex.get_float("x").unwrap().is_nan());
+    assert_eq!(vertex.get_double("y"), Some(f64::INFINITY));
+    assert_eq!(
+        vertex.get_list_float("w").unwrap(),
+        &[f32::NEG_INFINITY, 1.0][..]
+    );
+}
+#[test]
+fn write_non_finite_error() {
+    let ply = create_non_finite(Encoding::Ascii);
+    let options = writer::WriteOptions::new().non_finite(writer::NonFinite::Error);
+    let err = write_non_finite(options, &ply).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        "Element 'vertex' #1: Property 'x' is not finite: NaN."
+    );
+}
+#[test]
+fn write_non_finite_substitute() {
+    let ply = create_non_finite(Encoding::Ascii);
+    let options = writer::WriteOptions::new().non_finite(writer::NonFinite::Substitute(-1.0));
+    let buf = write_non_finite(options, &ply).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    assert!(
+        text.ends_with("end_header\n1 0.5 2 2 1\n-1 -1 2 -1 1\n"),
+        "{}",
+        text
+    );
+}
+#[test]
+fn write_non_finite_binary() {
+    let ply = create_non_finite(Encoding::BinaryLittleEndian);
+    let options = writer::WriteOptions::new().non_finite(writer::NonFinite::Error);
+    let buf = write_non_finite(options, &ply).unwrap();
+    let read = read_buff(&mut buf.as_slice());
+    let vertex = &read.payload["vertex"][1];
+    assert!(vertex.get_float("x").unwrap().is_nan());
+    assert_eq!(vertex.get_double("y"), Some(f64::INFINITY));
+}
+#[test]
+fn raw_element_copy() {
+    for path in [
+        "example_plys/triangles_ok_ascii.ply",
+        "example_plys/triangles_ok_little_endian.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let original = std::fs::read(path).unwrap();
+        let p = parser::Parser::<RawElement>::new();
+        let mut ply = p.read_ply(&mut original.as_slice()).unwrap();
+        let w = writer::Writer::new();
+        let mut buf = Vec::new();
+        w.write_ply(&mut buf, &mut ply).unwrap();
+        assert_eq!(buf, original, "{}", path);
+    }
+}
+#[test]
+fn raw_element_cross_encoding() {
+    for path in [
+        "example_plys/triangles_ok_ascii.ply",
+        "example_plys/triangles_ok_little_endian.ply",
+        "example_plys/house_2_ok_little_endian.ply",
+    ] {
+        let original = read_buff(&mut std::fs::File::open(path).unwrap());
+        let p = parser::Parser::<RawElement>::new();
+        let mut raw = p.read_ply(&mut std::fs::File::open(path).unwrap()).unwrap();
+        for encoding in [
+            Encoding::Ascii,
+            Encoding::BinaryBigEndian,
+            Encoding::BinaryLittleEndian,
+        ] {
+            raw.header.encoding = encoding;
+            let mut buf = Vec::new();
+            writer::Writer::new().write_ply(&mut buf, &mut raw).unwrap();
+            let copy = read_buff(&mut buf.as_slice());
+            let mut expected = original.clone();
+            expected.header.encoding = encoding;
+            assert_ply_eq!(copy, expected);
+        }
+    }
+}
+#[test]
+fn raw_element_decode() {
+    let path = "example_plys/house_2_ok_little_endian.ply";
+    let original = read_buff(&mut std::fs::File::open(path).unwrap());
+    let p = parser::Parser::<RawElement>::new();
+    let raw = p.read_ply(&mut std::fs::File::open(path).unwrap()).unwrap();
+    for def in &raw.header.elements {
+        for (r, e) in raw.payload[&def.name]
+            .iter()
+            .zip(&original.payload[&def.name])
+        {
+            assert_eq!(r.encoding(), Encoding::BinaryLittleEndian);
+            assert_eq!(&r.decode::<DefaultElement>(def).unwrap(), e);
+        }
+    }
+    let vertex = &raw.header.elements[0];
+    let short = RawElement::from_bytes(vec![0; 3], Encoding::BinaryLittleEndian);
+    assert!(short.decode::<DefaultElement>(vertex).is_err());
+    let long = RawElement::from_bytes(vec![0; 64], Encoding::BinaryLittleEndian);
+    assert!(long.decode::<DefaultElement>(vertex).is_err());
+}
+#[test]
+fn from_elements_roundtrip() {
+    let single = create_single_elements();
+    let def = &single.header.elements[0];
+    for encoding in [Encoding::Ascii, Encoding::BinaryBigEndian] {
+        let mut ply = Ply::from_elements(
+            "point",
+            def.properties.clone(),
+            single.payload["point"].clone(),
+            encoding,
+        );
+        assert_eq!(ply.header.elements[0].count, 2);
+        let before = ply.clone();
+        ply.make_consistent().unwrap();
+        assert_eq!(ply, before);
+        let mut buf = Vec::new();
+        writer::Writer::new().write_ply(&mut buf, &mut ply).unwrap();
+        let read = read_buff(&mut buf.as_slice());
+        assert_eq!(read.header.encoding, encoding);
+        assert_ply_eq!(read, ply);
+    }
+}
+#[test]
+fn from_default_elements_ok() {
+    let list = create_list_elements();
+    let mut ply =
+        Ply::from_default_elements("aList", list.payload["aList"].clone(), Encoding::Ascii)
+            .unwrap();
+    assert_eq!(
+        ply.header.elements[0].properties,
+        vec![PropertyDef::new(
+            "x",
+            PropertyType::List(ScalarType::UChar, ScalarType::Int)
+        )]
+    );
+    let read = read_write_ply(&ply);
+    assert_eq!(read.payload["aList"], list.payload["aList"]);
+    assert!(ply.make_consistent().is_ok());
+
+    let empty = Ply::from_default_elements("vertex", Vec::new(), Encoding::Ascii).unwrap();
+    assert_eq!(empty.header.elements[0].count, 0);
+    assert!(empty.header.elements[0].properties.is_empty());
+}
+#[test]
+fn from_default_elements_err() {
+    let point = |key: &str, property: Property| {
+        let mut e = DefaultElement::new();
+        e.insert(key.to_string(), property);
+        e
+    };
+    let mismatched = vec![
+        point("x", Property::Float(1.0)),
+        point("x", Property::ListFloat([1.0].into_iter().collect())),
+    ];
+    let missing = vec![
+        point("x", Property::Float(1.0)),
+        point("y", Property::Float(1.0)),
+    ];
+    let mut extra = point("x", Property::Float(1.0));
+    extra.insert("y".to_string(), Property::Float(2.0));
+    let extra = vec![point("x", Property::Float(1.0)), extra];
+    let custom = vec![point("x", Property::Custom(vec![1, 2].into()))];
+    for elements in [mismatched, missing, extra, custom] {
+        assert!(Ply::from_default_elements("point", elements, Encoding::Ascii).is_err());
+    }
+}
+#[test]
+fn point_cloud_roundtrip() {
+    let positions = [[0.0, 1.0, 2.0], [-1.5, 0.25, 8.0], [3.0, 3.0, 3.0]];
+    let colors = [[255, 0, 0], [0, 255, 0], [0, 0, 255]];
+    let normals = [[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]];
+    for (colors, normals) in [
+        (None, None),
+        (Some(&colors[..]), None),
+        (Some(&colors[..]), Some(&normals[..])),
+    ] {
+        let mut ply = builder::point_cloud(&positions, colors, normals).unwrap();
+        let mut buf = Vec::new();
+        writer::Writer::new().write_ply(&mut buf, &mut ply).unwrap();
+        let read = read_buff(&mut buf.as_slice());
+        let names: Vec<&str> = read.header.elements[0]
+            .properties
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        let mut expected = vec!["x", "y", "z"];
+        if normals.is_some() {
+            expected.extend(["nx", "ny", "nz"]);
+        }
+        if colors.is_some() {
+            expected.extend(["red", "green", "blue"]);
+        }
+        assert_eq!(names, expected);
+        let vertices = &read.payload["vertex"];
+        assert_eq!(vertices.len(), 3);
+        for (i, v) in vertices.iter().enumerate() {
+            let position = ["x", "y", "z"].map(|k| v.get_float(k).unwrap());
+            assert_eq!(position, positions[i]);
+            if let Some(colors) = colors {
+                let color = ["red", "green", "blue"].map(|k| v.get_uchar(k).unwrap());
+                assert_eq!(color, colors[i]);
+            }
+            if let Some(normals) = normals {
+                let normal = ["nx", "ny", "nz"].map(|k| v.get_float(k).unwrap());
+                assert_eq!(normal, normals[i]);
+            }
+        }
+    }
+}
+#[test]
+fn point_cloud_len_err() {
+    let positions = [[0.0, 1.0, 2.0], [-1.5, 0.25, 8.0]];
+    assert!(builder::point_cloud(&positions, Some(&[[0, 0, 0]]), None).is_err());
+    assert!(builder::point_cloud(&positions, None, Some(&[[0.0; 3]; 3])).is_err());
+}
+fn with_encoding(mut ply: Ply, encoding: Encoding) -> Ply {
+    ply.header.encoding = encoding;
+    ply
+}
+#[test]
+fn write_ply_multi_roundtrip() {
+    let mut plys = vec![
+        with_encoding(create_list_elements(), Encoding::Ascii),
+        with_encoding(create_list_elements(), Encoding::BinaryBigEndian),
+        create_min(),
+        with_encoding(create_single_elements(), Encoding::Ascii),
+        with_encoding(create_single_elements(), Encoding::BinaryLittleEndian),
+        with_encoding(create_list_elements(), Encoding::Ascii),
+    ];
+    for options in [
+        writer::WriteOptions::new(),
+        writer::WriteOptions::new().checksum(true),
+    ] {
+        let mut buf = Vec::new();
+        let w = writer::Writer::with_options(options);
+        let written = w.write_ply_multi(&mut buf, &mut plys).unwrap();
+        assert_eq!(written, buf.len());
+        let p = parser::Parser::<DefaultElement>::new();
+        let read = p.read_ply_multi(&mut buf.as_slice()).unwrap();
+        assert_eq!(read.len(), plys.len());
+        for (ply, read) in plys.iter().zip(&read) {
+            // The checksum option adds a comment, compare the rest.
+            assert_eq!(ply.header.encoding, read.header.encoding);
+            assert_eq!(ply.header.elements, read.header.elements);
+            assert_eq!(ply.payload, read.payload);
+        }
+    }
+    let p = parser::Parser::<DefaultElement>::new();
+    assert!(p.read_ply_multi(&mut [].as_slice()).unwrap().is_empty());
+}
+#[test]
+fn read_ply_multi_truncated_err() {
+    let mut plys = vec![
+        with_encoding(create_list_elements(), Encoding::Ascii),
+        with_encoding(create_list_elements(), Encoding::BinaryLittleEndian),
+    ];
+    let mut buf = Vec::new();
+    writer::Writer::new()
+        .write_ply_multi(&mut buf, &mut plys)
+        .unwrap();
+    buf.pop();
+    let p = parser::Parser::<DefaultElement>::new();
+    let err = p.read_ply_multi(&mut buf.as_slice()).unwrap_err();
+    assert!(err.to_string().starts_with("Document #1: "), "{}", err);
+}
+#[test]
+fn append_elements_unchecked() {
+    let mut ply = read_buff(&mut std::fs::File::open("example_plys/house_2_ok_ascii.ply").unwrap());
+    let mut vertex = ply.payload["vertex"][0].clone();
+    *vertex.get_mut("x").unwrap() = Property::Float(42.0);
+    assert_eq!(
+        ply.append_elements("vertex", vec![vertex.clone(); 2])
+            .unwrap(),
+        7
+    );
+    assert_eq!(ply.push_element("vertex", vertex).unwrap(), 8);
+    assert_eq!(ply.header.elements[0].count, 8);
+    assert!(ply.push_element("edge", DefaultElement::new()).is_err());
+    assert_eq!(ply.header.elements.len(), 2);
+
+    let buf = write_buff(&ply);
+    let read = read_buff(&mut buf.as_slice());
+    assert_eq!(read.payload["vertex"].len(), 8);
+    assert_eq!(read.payload["vertex"][7]["x"], Property::Float(42.0));
+    assert_eq!(read, ply);
+}
+#[test]
+fn split_multiline_comments_roundtrip() {
+    let mut ply = create_basic_header();
+    ply.header
+        .comments
+        .push("First paragraph.\r\nSecond paragraph.\r\n\r\nThird paragraph.\r\n".to_string());
+    ply.header.obj_infos.push("a\nb".to_string());
+    assert!(ply.clone().make_consistent().is_err());
+
+    let options = ConsistencyOptions::new().split_multiline_comments(true);
+    let report = ply.make_consistent_with(&options).unwrap();
+    assert_eq!(report.split_comments, 2);
+    assert_eq!(
+        ply.header.comments,
+        vec![
+            "Hi, I'm your friendly comment.",
+            "First paragraph.",
+            "Second paragraph.",
+            "Third paragraph.",
+        ]
+    );
+    assert_eq!(
+        ply.header.obj_infos,
+        vec!["And I'm your object information.", "a", "b"]
+    );
+    read_write_ply(&ply);
+}
+#[test]
+fn sanitize_names_colliding() {
+    let float = PropertyType::Scalar(ScalarType::Float);
+    let properties = ["point count", "point_count", "point/count", "2d"]
+        .into_iter()
+        .map(|name| PropertyDef::new(name, float.clone()))
+        .collect();
+    let mut point = DefaultElement::new();
+    for (i, name) in ["point count", "point_count", "point/count", "2d"]
+        .into_iter()
+        .enumerate()
+    {
+        point.insert(name.to_string(), Property::Float(i as f32));
+    }
+    let mut ply = Ply::from_elements("my points", properties, vec![point], Encoding::Ascii);
+    ply.header.elements.push(ElementDef::new("my_points"));
+    ply.payload.insert("my_points".to_string(), Vec::new());
+    assert!(ply.clone().make_consistent().is_err());
+
+    let options = ConsistencyOptions::new().sanitize_names(true);
+    let report = ply.make_consistent_with(&options).unwrap();
+    assert_eq!(
+        report.renamed_elements,
+        vec![("my points".to_string(), "my_points_2".to_string())]
+    );
+    let renamed: Vec<_> = report
+        .renamed_properties
+        .iter()
+        .map(|(e, old, new)| (e.as_str(), old.as_str(), new.as_str()))
+        .collect();
+    assert_eq!(
+        renamed,
+        vec![
+            ("my_points_2", "point count", "point_count_2"),
+            ("my_points_2", "point/count", "point_count_3"),
+            ("my_points_2", "2d", "_2d"),
+        ]
+    );
+    let point = &ply.payload["my_points_2"][0];
+    assert_eq!(point["point_count"], Property::Float(1.0));
+    assert_eq!(point["point_count_2"], Property::Float(0.0));
+    assert_eq!(point["point_count_3"], Property::Float(2.0));
+    assert_eq!(point["_2d"], Property::Float(3.0));
+
+    // Renamed properties moved to the end of the elements, the header keeps the order.
+    let read = read_buff(&mut write_buff(&ply).as_slice());
+    assert_eq!(read.header, ply.header);
+    assert_eq!(
+        read.payload["my_points_2"][0]["point_count_2"],
+        Property::Float(0.0)
+    );
+    assert_eq!(
+        read.payload["my_points_2"][0]["point_count_3"],
+        Property::Float(2.0)
+    );
+}
+#[test]
+fn write_payload_in_header_order() {
+    let mut ply = Ply::new();
+    for (name, ty) in [("vertex", ScalarType::Float), ("face", ScalarType::UChar)] {
+        let mut e = ElementDef::new(name);
+        e.properties
+            .push(PropertyDef::new("v", PropertyType::Scalar(ty)));
+        ply.header.elements.push(e);
+    }
+    // The payload map iterates in the opposite order of the header.
+    let mut face = DefaultElement::new();
+    face.insert("v".to_string(), Property::UChar(7));
+    ply.payload.insert("face".to_string(), vec![face]);
+    let mut vertex = DefaultElement::new();
+    vertex.insert("v".to_string(), Property::Float(0.5));
+    ply.payload
+        .insert("vertex".to_string(), vec![vertex.clone(), vertex]);
+    assert!(ply.make_consistent().is_ok());
+
+    for encoding in [Encoding::Ascii, Encoding::BinaryLittleEndian] {
+        ply.header.encoding = encoding;
+        let written = write_buff(&ply);
+        let measured = writer::Writer::new().measure(&ply).unwrap();
+        assert_eq!(measured, written.len() as u64);
+        let read = read_buff(&mut written.as_slice());
+        assert_eq!(read.header, ply.header);
+        assert_eq!(read.payload["vertex"], ply.payload["vertex"]);
+        assert_eq!(read.payload["face"], ply.payload["face"]);
+    }
+
+    // Undeclared payload is rejected instead of being written after the declared elements.
+    ply.payload.insert("edge".to_string(), Vec::new());
+    let w = writer::Writer::<DefaultElement>::new();
+    for err in [
+        w.write_ply_unchecked(&mut Vec::new(), &ply).unwrap_err(),
+        w.measure(&ply).unwrap_err(),
+    ] {
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            "Payload contains element 'edge' which is not declared in the header."
+        );
+    }
+}
+#[test]
+fn write_shared_ply_ok() {
+    let mut f = std::fs::File::open("example_plys/greg_turk_example2_ok_ascii.ply").unwrap();
+    let ply = read_buff(&mut f);
+    let expected = write_buff(&ply);
+    let shared = ply.clone().into_shared();
+    assert_eq!(shared.payload["vertex"][..], ply.payload["vertex"][..]);
+
+    let vertices = shared.elements("vertex").unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let seen = runtime.block_on(async {
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let vertices = std::sync::Arc::clone(&vertices);
+                tokio::spawn(async move {
+                    assert_eq!(vertices.len(), 8);
+                    vertices
+                })
+            })
+            .collect();
+        let mut seen = Vec::new();
+        for task in tasks {
+            seen.push(task.await.unwrap());
+        }
+        seen
+    });
+    // Both tasks saw the elements of the payload, not copies.
+    for vertices in &seen {
+        assert!(std::sync::Arc::ptr_eq(vertices, &shared.payload["vertex"]));
+    }
+    drop(seen);
+    assert_eq!(std::sync::Arc::strong_count(&vertices), 2);
+    assert!(shared.elements("missing").is_none());
+
+    let mut buf = Vec::new();
+    let w = writer::Writer::new();
+    w.write_shared_ply(&mut buf, &shared).unwrap();
+    assert_eq!(buf, expected);
+
+    let w = writer::Writer::with_options(writer::WriteOptions::new().checksum(true));
+    let mut from_ply = Vec::new();
+    w.write_ply_unchecked(&mut from_ply, &ply).unwrap();
+    let mut from_shared = Vec::new();
+    w.write_shared_ply(&mut from_shared, &shared).unwrap();
+    assert_eq!(from_shared, from_ply);
+}
+#[test]
+fn write_comments_verbatim() {
+    let path = "example_plys/fancy_comments_ok_ascii.ply";
+    let original = std::fs::read(path).unwrap();
+    let ply = read_buff(&mut original.as_slice());
+    assert_eq!(ply.header.comments[0], "   ASCII art  here");
+    assert_eq!(ply.header.obj_infos[1], "\tindented with tabs");
+    assert_eq!(
+        ply.header.elements[0].properties[0].comments,
+        vec!["  unit:    meters"]
+    );
+
+    let mut header = Vec::new();
+    writer::Writer::<DefaultElement>::new()
+        .write_header(&mut header, &ply.header)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(header).unwrap(),
+        String::from_utf8_lossy(&original[..original.len() - "0 0\n1 1\n2 0 1\n".len()])
+    );
+}
+#[test]
+fn write_dotted_names_round_trip() {
+    let float = PropertyType::Scalar(ScalarType::Float);
+    let properties = ["scale.x", "pos-x", "0ffset"]
+        .into_iter()
+        .map(|name| PropertyDef::new(name, float.clone()))
+        .collect();
+    let mut point = DefaultElement::new();
+    for (i, name) in ["scale.x", "pos-x", "0ffset"].into_iter().enumerate() {
+        point.insert(name.to_string(), Property::Float(i as f32));
+    }
+    let mut ply = Ply::from_elements("vertex.data", properties, vec![point], Encoding::Ascii);
+    // Names without whitespace are consistent and aren't changed.
+    ply.make_consistent().unwrap();
+    let text = String::from_utf8(write_buff(&ply)).unwrap();
+    assert!(
+        text.contains("element vertex.data 1\nproperty float scale.x\nproperty float pos-x\nproperty float 0ffset\n"),
+        "{}",
+        text
+    );
+    read_write_ply(&ply);
+}
+#[test]
+fn write_digit_names_round_trip() {
+    let path = "example_plys/digit_names_ok_ascii.ply";
+    let original = std::fs::read(path).unwrap();
+    let mut ply = read_buff(&mut original.as_slice());
+    let mut written = Vec::new();
+    writer::Writer::new()
+        .write_ply(&mut written, &mut ply)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(written).unwrap(),
+        String::from_utf8(original).unwrap()
+    );
+    ply.header.encoding = Encoding::BinaryLittleEndian;
+    read_write_ply(&ply);
+}
+#[test]
+fn write_ply_to_path_atomic() {
+    let dir = std::env::temp_dir().join(format!("ply_rs_atomic_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("points.ply");
+    let files = || std::fs::read_dir(&dir).unwrap().count();
+
+    let positions = vec![[0.0, 1.0, 2.0]; 1000];
+    let mut ply = builder::point_cloud(&positions, None, None).unwrap();
+    let w = writer::Writer::new();
+    let written = w.write_ply_to_path(&path, &mut ply).unwrap();
+    let old = std::fs::read(&path).unwrap();
+    assert_eq!(old.len(), written);
+    assert_eq!(read_buff(&mut old.as_slice()), ply);
+
+    // Fails after the header and part of the payload were written.
+    let mut broken = ply.clone();
+    broken.payload.get_mut("vertex").unwrap()[500].remove("z");
+    let err = w.write_ply_to_path(&path, &mut broken).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(std::fs::read(&path).unwrap(), old);
+    assert_eq!(files(), 1);
+
+    let absent = dir.join("absent.ply");
+    w.write_ply_to_path(&absent, &mut broken).unwrap_err();
+    assert!(!absent.exists());
+    assert_eq!(files(), 1);
+
+    // Without atomic writes, the partial file stays.
+    let direct = writer::Writer::with_options(writer::WriteOptions::new().atomic(false));
+    direct.write_ply_to_path(&path, &mut broken).unwrap_err();
+    let partial = std::fs::read(&path).unwrap();
+    assert!(!partial.is_empty() && partial.len() < old.len());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+#[test]
+fn write_metadata_round_trip() {
+    let f = std::fs::read("example_plys/greg_turk_example2_ok_ascii.ply").unwrap();
+    let mut ply = read_buff(&mut f.as_slice());
+    let options = writer::WriteOptions::new().embed_metadata(writer::MetadataOptions {
+        bbox: true,
+        counts: true,
+    });
+    let w = writer::Writer::with_options(options);
+    let mut buf = Vec::new();
+    let written = w.write_ply(&mut buf, &mut ply).unwrap();
+    assert_eq!(w.measure(&ply).unwrap(), written as u64);
+    let text = String::from_utf8(buf.clone()).unwrap();
+    assert!(
+        text.contains("comment bbox 0 0 0 1 1 1\ncomment count vertex 8\ncomment count face 7\ncomment count edge 5\n"),
+        "{}",
+        text
+    );
+    let read = read_buff(&mut buf.as_slice());
+    let metadata = read.header.parsed_metadata();
+    assert_eq!(metadata.bbox, Some(([0.0; 3], [1.0; 3])));
+    let counts: Vec<(&str, u64)> = metadata
+        .counts
+        .iter()
+        .map(|(n, c)| (n.as_str(), *c))
+        .collect();
+    assert_eq!(counts, vec![("vertex", 8), ("face", 7), ("edge", 5)]);
+    // The original comments are kept, outdated metadata is replaced.
+    assert_eq!(read.header.comments.len(), ply.header.comments.len() + 4);
+    let mut again = read.clone();
+    again.payload.get_mut("vertex").unwrap().truncate(1);
+    let mut buf = Vec::new();
+    w.write_ply(&mut buf, &mut again).unwrap();
+    let metadata = read_buff(&mut buf.as_slice()).header.parsed_metadata();
+    assert_eq!(metadata.bbox, Some(([0.0; 3], [0.0; 3])));
+    assert_eq!(metadata.counts[0], ("vertex".to_string(), 1));
+    assert_eq!(metadata.counts.len(), 3);
+
+    // Double precision coordinates are written exactly, no vertex element means no bbox.
+    let f = std::fs::read("example_plys/points_double_ok_big_endian.ply").unwrap();
+    let mut ply = read_buff(&mut f.as_slice());
+    let mut buf = Vec::new();
+    w.write_ply(&mut buf, &mut ply).unwrap();
+    let metadata = read_buff(&mut buf.as_slice()).header.parsed_metadata();
+    assert_eq!(metadata.bbox, Some(([-0.1, -1.25, 3.0], [1.0, 2.0, 1e10])));
+    let mut ply = Ply::new();
+    let mut buf = Vec::new();
+    w.write_ply(&mut buf, &mut ply).unwrap();
+    assert_eq!(
+        read_buff(&mut buf.as_slice()).header.parsed_metadata(),
+        Metadata::default()
+    );
+}
+#[test]
+fn write_example_plys_round_trip() {
+    for entry in std::fs::read_dir("example_plys").unwrap() {
+        let path = entry.unwrap().path();
+        let path = path.to_str().unwrap();
+        if !path.contains("_ok_") {
+            continue;
+        }
+        let mut f = std::fs::File::open(path).unwrap();
+        let ply = read_buff(&mut f);
+        let written = write_buff(&ply);
+        let new_ply = read_buff(&mut &written[..]);
+        assert_ply_eq!(ply, new_ply);
+    }
+}